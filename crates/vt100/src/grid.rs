@@ -129,6 +129,10 @@ impl Grid {
             )
     }
 
+    pub fn all_rows(&self) -> impl Iterator<Item = &crate::row::Row> {
+        self.scrollback.iter().chain(self.rows.iter())
+    }
+
     pub fn drawing_rows(&self) -> impl Iterator<Item = &crate::row::Row> {
         self.rows.iter()
     }
@@ -193,8 +197,20 @@ impl Grid {
     }
 
     pub fn write_contents(&self, contents: &mut String) {
+        self.write_rows_contents(self.visible_rows(), contents);
+    }
+
+    pub fn write_all_contents(&self, contents: &mut String) {
+        self.write_rows_contents(self.all_rows(), contents);
+    }
+
+    fn write_rows_contents<'a>(
+        &self,
+        rows: impl Iterator<Item = &'a crate::row::Row>,
+        contents: &mut String,
+    ) {
         let mut wrapping = false;
-        for row in self.visible_rows() {
+        for row in rows {
             row.write_contents(contents, 0, self.size.cols, wrapping);
             if !row.wrapped() {
                 contents.push('\n');
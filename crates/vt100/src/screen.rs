@@ -159,6 +159,17 @@ impl Screen {
         self.grid().write_contents(contents);
     }
 
+    /// Returns the text contents of the scrollback followed by the screen.
+    ///
+    /// Like [`Self::contents`], this is plain text, and it ignores the
+    /// current scrollback position.
+    #[must_use]
+    pub fn all_contents(&self) -> String {
+        let mut contents = String::new();
+        self.grid().write_all_contents(&mut contents);
+        contents
+    }
+
     /// Returns the text contents of the terminal by row, restricted to the
     /// given subset of columns.
     ///
@@ -178,6 +189,24 @@ impl Screen {
         })
     }
 
+    /// Returns the text contents of every retained row, oldest scrollback row
+    /// first, restricted to the given subset of columns.
+    ///
+    /// Unlike [`Self::rows`], this ignores the current scrollback position.
+    ///
+    /// Newlines will not be included.
+    pub fn all_rows(
+        &self,
+        start: u16,
+        width: u16,
+    ) -> impl Iterator<Item = String> + '_ {
+        self.grid().all_rows().map(move |row| {
+            let mut contents = String::new();
+            row.write_contents(&mut contents, start, width, false);
+            contents
+        })
+    }
+
     /// Returns the text contents of the terminal logically between two cells.
     /// This will include the remainder of the starting row after `start_col`,
     /// followed by the entire contents of the rows between `start_row` and
@@ -0,0 +1,136 @@
+//! Per-item outcomes of commands that act on many items at once, and the
+//! `3 succeeded, 1 failed, 0 skipped` line they end with.
+//!
+//! Bulk commands keep going past a failing item, then report every outcome
+//! together and exit non-zero if anything failed.
+
+use anyhow::{bail, Result};
+use crossterm::style::Stylize;
+use std::fmt::Display;
+
+/// What happened to one item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Succeeded,
+    /// The error, as it would be printed on its own.
+    Failed(String),
+    Skipped,
+}
+
+/// Outcomes in the order the items were handled.
+#[derive(Debug, Default)]
+pub struct BulkResult {
+    items: Vec<(String, Outcome)>,
+}
+
+impl BulkResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, name: impl Into<String>, outcome: Outcome) {
+        self.items.push((name.into(), outcome));
+    }
+
+    /// Record `result` as a success or, with its message, a failure.
+    pub fn record_result<E: Display>(
+        &mut self,
+        name: impl Into<String>,
+        result: std::result::Result<(), E>,
+    ) {
+        let outcome = match result {
+            Ok(()) => Outcome::Succeeded,
+            Err(err) => Outcome::Failed(format!("{err:#}")),
+        };
+        self.record(name, outcome);
+    }
+
+    /// The failed items' names and errors.
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.items
+            .iter()
+            .filter_map(|(name, outcome)| match outcome {
+                Outcome::Failed(err) => Some((name.as_str(), err.as_str())),
+                _ => None,
+            })
+    }
+
+    fn count(&self, wanted: fn(&Outcome) -> bool) -> usize {
+        self.items
+            .iter()
+            .filter(|(_, outcome)| wanted(outcome))
+            .count()
+    }
+
+    /// `3 succeeded, 1 failed, 0 skipped`, naming the failed items; the counts
+    /// are green and red when `color` is set and they are not zero.
+    pub fn render_summary(&self, color: bool) -> String {
+        let succeeded = self.count(|outcome| *outcome == Outcome::Succeeded);
+        let failed = self.count(|outcome| matches!(outcome, Outcome::Failed(_)));
+        let skipped = self.count(|outcome| *outcome == Outcome::Skipped);
+        let mut ok = format!("{succeeded} succeeded");
+        let mut bad = format!("{failed} failed");
+        if color && succeeded > 0 {
+            ok = ok.green().to_string();
+        }
+        if color && failed > 0 {
+            bad = bad.red().to_string();
+        }
+        let mut summary = format!("{ok}, {bad}, {skipped} skipped");
+        if failed > 0 {
+            let names: Vec<&str> = self.failures().map(|(name, _)| name).collect();
+            summary.push_str(&format!(" (failed: {})", names.join(", ")));
+        }
+        summary
+    }
+
+    /// An error when any item failed, so the command exits non-zero.
+    pub fn check(&self) -> Result<()> {
+        let failed = self.failures().count();
+        if failed > 0 {
+            bail!("{failed} of {} item(s) failed", self.items.len());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{exit_code, EXIT_FAILURE};
+
+    #[test]
+    fn summary_counts_outcomes_and_names_the_failures() {
+        let mut result = BulkResult::new();
+        result.record("a", Outcome::Succeeded);
+        result.record_result("b", Err(anyhow::anyhow!("permission denied")));
+        result.record_result::<String>("c", Ok(()));
+        result.record("d", Outcome::Skipped);
+        result.record_result("e", Err("busy"));
+
+        assert_eq!(
+            result.render_summary(false),
+            "2 succeeded, 2 failed, 1 skipped (failed: b, e)"
+        );
+        let colored = result.render_summary(true);
+        assert!(colored.contains("\u{1b}["), "{colored:?}");
+        assert!(colored.ends_with("1 skipped (failed: b, e)"));
+        assert_eq!(
+            result.failures().collect::<Vec<_>>(),
+            [("b", "permission denied"), ("e", "busy")]
+        );
+        let err = result.check().unwrap_err();
+        assert_eq!(err.to_string(), "2 of 5 item(s) failed");
+        assert_eq!(exit_code(&err), EXIT_FAILURE);
+
+        let mut clean = BulkResult::new();
+        clean.record("a", Outcome::Succeeded);
+        clean.record("b", Outcome::Skipped);
+        assert_eq!(
+            clean.render_summary(true),
+            format!("{}, 0 failed, 1 skipped", "1 succeeded".green())
+        );
+        assert!(clean.check().is_ok());
+        assert!(BulkResult::new().check().is_ok());
+    }
+}
@@ -0,0 +1,84 @@
+//! Stopping long-running work early.
+//!
+//! Walks and other slow loops take a [`CancellationToken`] and check it between
+//! steps, returning what they have so far once it is set. The CLI hands them
+//! [`CancellationToken::ctrl_c`]: while that scope is alive the first Ctrl+C
+//! ends the work with a partial result and a second one exits straight away.
+//! Outside of it Ctrl+C exits as it normally would.
+
+use std::{
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+};
+
+/// Exit status after Ctrl+C, as shells report for SIGINT.
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// How many [`CtrlCScope`]s are alive; Ctrl+C only cancels while there are any.
+static SCOPES: AtomicUsize = AtomicUsize::new(0);
+
+/// A flag shared between the code doing the work and whoever may stop it.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// A token nothing cancels unless [`Self::cancel`] is called.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide token set by Ctrl+C, for as long as the returned scope
+    /// lives. The handler is installed on the first call; later calls share
+    /// the same token, so work cancelled once stays cancelled.
+    pub fn ctrl_c() -> CtrlCScope {
+        static TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+        let token = TOKEN
+            .get_or_init(|| {
+                let token = Self::new();
+                let handler = token.clone();
+                let installed = ctrlc::set_handler(move || {
+                    if SCOPES.load(Ordering::SeqCst) == 0 || handler.is_cancelled() {
+                        std::process::exit(EXIT_INTERRUPTED);
+                    }
+                    handler.cancel();
+                });
+                if let Err(err) = installed {
+                    log::warn!("failed to install Ctrl+C handler: {err}");
+                }
+                token
+            })
+            .clone();
+        SCOPES.fetch_add(1, Ordering::SeqCst);
+        CtrlCScope(token)
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The Ctrl+C token while cancellable work runs; dropping it lets Ctrl+C exit
+/// the process again.
+#[derive(Debug)]
+pub struct CtrlCScope(CancellationToken);
+
+impl Deref for CtrlCScope {
+    type Target = CancellationToken;
+
+    fn deref(&self) -> &CancellationToken {
+        &self.0
+    }
+}
+
+impl Drop for CtrlCScope {
+    fn drop(&mut self) {
+        SCOPES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
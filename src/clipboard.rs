@@ -0,0 +1,31 @@
+//! System clipboard access, compiled in with the `clipboard` feature.
+
+use anyhow::Result;
+
+/// Put `text` on the system clipboard.
+#[cfg(feature = "clipboard")]
+pub fn set(text: &str) -> Result<()> {
+    use anyhow::Context;
+    use std::sync::Mutex;
+
+    // On X11 and Wayland the contents are served by this process, so the
+    // handle stays open for as long as wtm runs.
+    static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+    let mut clipboard = CLIPBOARD
+        .lock()
+        .unwrap_or_else(|poison| poison.into_inner());
+    if clipboard.is_none() {
+        *clipboard = Some(arboard::Clipboard::new().context("no clipboard available")?);
+    }
+    clipboard
+        .as_mut()
+        .expect("clipboard was just opened")
+        .set_text(text)
+        .context("failed to copy to the clipboard")
+}
+
+/// Put `text` on the system clipboard.
+#[cfg(not(feature = "clipboard"))]
+pub fn set(_text: &str) -> Result<()> {
+    anyhow::bail!("wtm was built without clipboard support")
+}
@@ -0,0 +1,203 @@
+use crate::{
+    git::{self, status, CommitLine, WorktreeInfo},
+    output::Output,
+    spinner::Spinner,
+    stale::parse_age,
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Turn `--since` into a value for `git log --since`.
+///
+/// Ages such as `12h` or `2w` (see [`parse_age`]) count back from now; anything
+/// else, e.g. `yesterday` or `2024-05-01`, is left for git to interpret.
+pub fn parse_since(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("expected an age such as 1d or a date git understands".into());
+    }
+    let digits = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let looks_like_age = digits > 0 && input[digits..].chars().all(|c| c.is_ascii_alphabetic());
+    if looks_like_age {
+        return parse_age(input).map(|age| format!("{} seconds ago", age.as_secs()));
+    }
+    Ok(input.to_string())
+}
+
+/// What happened in one worktree since the cutoff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorktreeActivity {
+    pub name: String,
+    pub path: PathBuf,
+    pub branch: Option<String>,
+    /// Commits reachable from `HEAD` made since the cutoff, newest first.
+    pub commits: Vec<CommitLine>,
+    /// Staged, unstaged, untracked, and conflicted entries right now.
+    pub changes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl WorktreeActivity {
+    fn is_idle(&self) -> bool {
+        self.commits.is_empty() && self.changes == 0 && self.error.is_none()
+    }
+}
+
+/// Gather each worktree's commits and current changes, in list order.
+pub fn collect_activity(
+    worktrees: &[WorktreeInfo],
+    commits: impl Fn(&Path) -> Result<Vec<CommitLine>>,
+    changes: impl Fn(&Path) -> Result<usize>,
+) -> Vec<WorktreeActivity> {
+    worktrees
+        .iter()
+        .map(|wt| {
+            let mut errors = Vec::new();
+            let commits = commits(&wt.path).unwrap_or_else(|err| {
+                errors.push(format!("{err:#}"));
+                Vec::new()
+            });
+            let changes = changes(&wt.path).unwrap_or_else(|err| {
+                errors.push(format!("{err:#}"));
+                0
+            });
+            WorktreeActivity {
+                name: wt.name(),
+                path: wt.path.clone(),
+                branch: wt.branch.clone(),
+                commits,
+                changes,
+                error: (!errors.is_empty()).then(|| errors.join("; ")),
+            }
+        })
+        .collect()
+}
+
+/// The text report: active worktrees with their commits, then the idle ones.
+pub fn report_lines(activity: &[WorktreeActivity]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for entry in activity.iter().filter(|entry| !entry.is_idle()) {
+        let branch = entry.branch.as_deref().unwrap_or("detached");
+        let mut summary = vec![plural(entry.commits.len(), "commit")];
+        if entry.changes > 0 {
+            summary.push(plural(entry.changes, "uncommitted change"));
+        }
+        lines.push(format!("{} ({branch}): {}", entry.name, summary.join(", ")));
+        lines.extend(
+            entry
+                .commits
+                .iter()
+                .map(|commit| format!("  {} {}", commit.hash, commit.subject)),
+        );
+        if let Some(error) = &entry.error {
+            lines.push(format!("  error: {error}"));
+        }
+    }
+    let idle: Vec<&str> = activity
+        .iter()
+        .filter(|entry| entry.is_idle())
+        .map(|entry| entry.name.as_str())
+        .collect();
+    if !idle.is_empty() {
+        lines.push(format!("No activity: {}", idle.join(", ")));
+    }
+    lines
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// `worktree activity`: commits since `since` and uncommitted changes per worktree.
+pub fn activity_command(
+    worktrees: &[WorktreeInfo],
+    since: &str,
+    json: bool,
+    out: Output,
+) -> Result<()> {
+    let activity = {
+        let _spinner = Spinner::start("Collecting activity…", json || !out.show_progress());
+        collect_activity(
+            worktrees,
+            |path| git::commits_since(path, since),
+            |path| {
+                let summary = status::status(path)?;
+                Ok(summary.staged + summary.unstaged + summary.untracked + summary.conflicts)
+            },
+        )
+    };
+    if json {
+        out.print_json(&activity)?;
+        return Ok(());
+    }
+    for line in report_lines(&activity) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn parse_since_turns_ages_into_seconds_and_passes_dates_through() {
+        assert_eq!(parse_since("1d").unwrap(), "86400 seconds ago");
+        assert_eq!(parse_since(" 12h ").unwrap(), "43200 seconds ago");
+        assert_eq!(parse_since("yesterday").unwrap(), "yesterday");
+        assert_eq!(parse_since("2024-05-01").unwrap(), "2024-05-01");
+        assert!(parse_since("3m").unwrap_err().contains("ambiguous"));
+        assert!(parse_since("").is_err());
+    }
+
+    #[test]
+    fn activity_is_grouped_per_worktree_and_idle_ones_are_summarised() {
+        let worktrees = [
+            WorktreeInfo::at("/repo").on("main"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/login").on("feature/login"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/spike"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/broken").on("broken"),
+        ];
+        let activity = collect_activity(
+            &worktrees,
+            |path| match path.file_name().and_then(|name| name.to_str()) {
+                Some("login") => Ok(vec![
+                    CommitLine {
+                        hash: "a1b2c3d".into(),
+                        subject: "Validate the form".into(),
+                    },
+                    CommitLine {
+                        hash: "0f0f0f0".into(),
+                        subject: "Add login page".into(),
+                    },
+                ]),
+                Some("broken") => Err(anyhow!("not a git repository")),
+                _ => Ok(Vec::new()),
+            },
+            |path| Ok(if path.ends_with("spike") { 1 } else { 0 }),
+        );
+        assert_eq!(activity[1].commits.len(), 2);
+        assert_eq!(activity[3].error.as_deref(), Some("not a git repository"));
+        assert_eq!(
+            report_lines(&activity),
+            [
+                "login (feature/login): 2 commits",
+                "  a1b2c3d Validate the form",
+                "  0f0f0f0 Add login page",
+                "spike (detached): 0 commits, 1 uncommitted change",
+                "broken (broken): 0 commits",
+                "  error: not a git repository",
+                "No activity: repo",
+            ]
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! `wtm worktree cd-script`: a `wtw` shell function for jumping between worktrees.
+//!
+//! The function lists candidates with `wtm completions suggest worktrees`,
+//! picks one with `fzf` when it is installed (else the first name containing
+//! the query), and changes into the directory `wtm worktree path` reports.
+
+use anyhow::{bail, Result};
+use clap_complete::Shell;
+
+const POSIX_SCRIPT: &str = r#"# wtm shell integration: `wtw [query]` jumps to a worktree.
+wtw() {
+    local name dir
+    if command -v fzf >/dev/null 2>&1; then
+        name=$(wtm completions suggest worktrees | fzf --delimiter='\t' --select-1 --query="$*" | cut -f1)
+    else
+        name=$(wtm completions suggest worktrees --description-style none | grep -F -- "$*" | head -n 1)
+    fi
+    [ -n "$name" ] || return 1
+    dir=$(wtm worktree path "$name") || return
+    cd -- "$dir"
+}
+"#;
+
+const FISH_SCRIPT: &str = r#"# wtm shell integration: `wtw [query]` jumps to a worktree.
+function wtw --description 'Jump to a wtm worktree'
+    set -l name
+    if type -q fzf
+        set name (wtm completions suggest worktrees --shell fish | fzf --delimiter='\t' --select-1 --query="$argv" | cut -f1)
+    else
+        set name (wtm completions suggest worktrees --shell fish --description-style none | grep -F -- "$argv" | head -n 1)
+    end
+    test -n "$name"; or return 1
+    set -l dir (wtm worktree path $name); or return
+    cd $dir
+end
+"#;
+
+/// The `wtw` function for `shell`, to be `eval`'d (or `source`d) at start-up.
+pub fn cd_script(shell: Shell) -> Result<&'static str> {
+    match shell {
+        Shell::Bash | Shell::Zsh => Ok(POSIX_SCRIPT),
+        Shell::Fish => Ok(FISH_SCRIPT),
+        other => bail!("No cd script for {other}; use bash, zsh, or fish."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_pick_from_worktree_suggestions() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = cd_script(shell).unwrap();
+            assert!(
+                script.contains("wtm completions suggest worktrees"),
+                "{shell}"
+            );
+            assert!(script.contains("wtm worktree path"), "{shell}");
+            assert!(script.contains("fzf"), "{shell}");
+        }
+        assert!(cd_script(Shell::PowerShell).is_err());
+    }
+}
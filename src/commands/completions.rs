@@ -0,0 +1,447 @@
+//! `wtm completions`: print or install shell completion scripts, and list
+//! dynamic suggestions (branches, worktrees) for custom completion functions.
+
+use crate::{
+    config,
+    git::{self, WorktreeInfo},
+    output::Output,
+    wtm_paths::{branch_dir_name, DEFAULT_MAX_SLUG_LENGTH},
+};
+use anyhow::{bail, Context, Result};
+use clap::{Command, ValueEnum};
+use clap_complete::{generate, Shell};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+const BIN_NAME: &str = "wtm";
+
+/// Worktree subcommands whose argument completion leaves the primary out
+/// unless `--include-primary` is given.
+const DESTRUCTIVE_COMMANDS: &[&str] = &["remove", "move"];
+
+/// What `wtm completions suggest` lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SuggestKind {
+    /// Local branches, then remote branches without a local counterpart
+    Branches,
+    /// Worktree names
+    Worktrees,
+}
+
+/// How much of a suggestion's description the shell is given.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DescriptionStyle {
+    /// Source and details, e.g. `remote branch origin/x ⇒ x`
+    #[default]
+    Full,
+    /// Only where the suggestion comes from, e.g. `remote branch`
+    SourceOnly,
+    /// Bare values
+    None,
+}
+
+/// How `wtm completions suggest` prints its candidates. `--json` and `--null`
+/// replace the shell formats rather than combine with them, so they refuse an
+/// explicit `--shell`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestFormat {
+    /// One line per candidate in the shell's syntax.
+    Shell(Shell),
+    /// A JSON array of `{value, source, detail}` objects.
+    Json,
+    /// Bare values, each terminated by a NUL byte, for `read -d ''`.
+    Null,
+}
+
+/// One completion candidate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Suggestion {
+    pub value: String,
+    /// Where the value comes from, e.g. `local branch`.
+    pub source: &'static str,
+    /// Extra context appended to `source` in [`DescriptionStyle::Full`].
+    pub detail: String,
+}
+
+impl Suggestion {
+    fn description(&self, style: DescriptionStyle) -> String {
+        let text = match style {
+            DescriptionStyle::Full if !self.detail.is_empty() => {
+                format!("{} {}", self.source, self.detail)
+            }
+            DescriptionStyle::Full | DescriptionStyle::SourceOnly => self.source.to_string(),
+            DescriptionStyle::None => String::new(),
+        };
+        single_line(&text)
+    }
+}
+
+/// Whether worktree suggestions leave the primary out: when asked to, or by
+/// default when completing one of [`DESTRUCTIVE_COMMANDS`].
+pub fn excludes_primary(for_command: Option<&str>, exclude: bool, include: bool) -> bool {
+    exclude || (!include && for_command.is_some_and(|name| DESTRUCTIVE_COMMANDS.contains(&name)))
+}
+
+/// Print suggestions of `kind` in `format`.
+pub fn suggest_command(
+    repo_root: &Path,
+    kind: SuggestKind,
+    exclude_primary: bool,
+    format: SuggestFormat,
+    style: DescriptionStyle,
+    out: Output,
+) -> Result<()> {
+    let suggestions = collect_suggestions(repo_root, kind, exclude_primary)?;
+    let text = match format {
+        SuggestFormat::Json => {
+            out.print_json(&suggestions)?;
+            return Ok(());
+        }
+        SuggestFormat::Null => format_null_delimited(&suggestions),
+        SuggestFormat::Shell(Shell::Bash | Shell::Fish) => format_for_bash(&suggestions, style),
+        SuggestFormat::Shell(Shell::Zsh) => format_for_zsh(&suggestions, style),
+        SuggestFormat::Shell(other) => {
+            bail!("Suggestions are not supported for {other}; use bash, zsh, or fish.")
+        }
+    };
+    print!("{text}");
+    io::stdout().flush().context("failed to write suggestions")
+}
+
+/// Gather every suggestion of `kind` for the repository at `repo_root`.
+///
+/// `exclude_primary` drops the main checkout from worktree suggestions, so
+/// completing a destructive command never offers it.
+pub fn collect_suggestions(
+    repo_root: &Path,
+    kind: SuggestKind,
+    exclude_primary: bool,
+) -> Result<Vec<Suggestion>> {
+    match kind {
+        SuggestKind::Branches => {
+            // Completion stays quiet, so a broken config just means the default.
+            let max_slug_length = config::load_config(&repo_root.join(".wtm"))
+                .map_or(DEFAULT_MAX_SLUG_LENGTH, |config| {
+                    config.path_layout().max_slug_length
+                });
+            Ok(branch_suggestions(
+                &git::list_branches(repo_root, None)?,
+                &git::list_remote_branches(repo_root, None)?,
+                max_slug_length,
+            ))
+        }
+        SuggestKind::Worktrees => {
+            let mut worktrees = git::list_worktrees_cached(repo_root)?;
+            if exclude_primary {
+                worktrees.retain(|wt| !wt.is_primary(repo_root));
+            }
+            Ok(worktree_suggestions(&worktrees))
+        }
+    }
+}
+
+fn branch_suggestions(
+    local: &[String],
+    remote: &[String],
+    max_slug_length: usize,
+) -> Vec<Suggestion> {
+    let mut seen: HashSet<&str> = local.iter().map(String::as_str).collect();
+    let mut suggestions: Vec<Suggestion> = local
+        .iter()
+        .map(|branch| Suggestion {
+            value: branch.clone(),
+            source: "local branch",
+            detail: format!("⇒ {}", branch_dir_name(branch, max_slug_length)),
+        })
+        .collect();
+    for reference in remote {
+        let Some((_, branch)) = reference.split_once('/') else {
+            continue;
+        };
+        if branch.is_empty() || !seen.insert(branch) {
+            continue;
+        }
+        suggestions.push(Suggestion {
+            value: branch.to_string(),
+            source: "remote branch",
+            detail: format!("{reference} ⇒ {}", branch_dir_name(branch, max_slug_length)),
+        });
+    }
+    suggestions
+}
+
+fn worktree_suggestions(worktrees: &[WorktreeInfo]) -> Vec<Suggestion> {
+    worktrees
+        .iter()
+        .map(|wt| Suggestion {
+            value: wt.name(),
+            source: "worktree",
+            detail: match &wt.branch {
+                Some(branch) => format!("on {branch}"),
+                None => "(detached)".into(),
+            },
+        })
+        .collect()
+}
+
+/// `value<TAB>description` lines, as bash helpers and fish expect; bare values
+/// for [`DescriptionStyle::None`].
+pub fn format_for_bash(suggestions: &[Suggestion], style: DescriptionStyle) -> String {
+    suggestions
+        .iter()
+        .map(|suggestion| {
+            let value = single_line(&suggestion.value);
+            match suggestion.description(style) {
+                description if description.is_empty() => format!("{value}\n"),
+                description => format!("{value}\t{description}\n"),
+            }
+        })
+        .collect()
+}
+
+/// `value:description` lines for zsh's `_describe`, with colons in values escaped.
+pub fn format_for_zsh(suggestions: &[Suggestion], style: DescriptionStyle) -> String {
+    suggestions
+        .iter()
+        .map(|suggestion| {
+            let value = single_line(&suggestion.value).replace(':', "\\:");
+            match suggestion.description(style) {
+                description if description.is_empty() => format!("{value}\n"),
+                description => format!("{value}:{description}\n"),
+            }
+        })
+        .collect()
+}
+
+/// Each value followed by a NUL byte, untouched: NUL cannot occur in a branch
+/// or worktree name, so nothing needs escaping.
+pub fn format_null_delimited(suggestions: &[Suggestion]) -> String {
+    suggestions
+        .iter()
+        .map(|suggestion| format!("{}\0", suggestion.value))
+        .collect()
+}
+
+/// Replace tabs and line breaks, which would split a suggestion line, with spaces.
+fn single_line(text: &str) -> String {
+    text.replace(['\t', '\n', '\r'], " ")
+}
+
+/// Write the completion script for `shell` to stdout.
+pub fn generate_command(shell: Shell, cmd: &mut Command) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    generate(shell, cmd, BIN_NAME, &mut stdout);
+    stdout.flush().context("failed to write completion script")
+}
+
+/// Write the completion script for `shell` into its conventional per-user location.
+pub fn install_command(shell: Shell, cmd: &mut Command, print_path: bool) -> Result<()> {
+    let home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .context("HOME is not set; cannot locate the completions directory")?;
+    let xdg_data_home = std::env::var_os("XDG_DATA_HOME").map(PathBuf::from);
+    let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from);
+    let path = install_path(
+        shell,
+        &home,
+        xdg_data_home.as_deref(),
+        xdg_config_home.as_deref(),
+    )?;
+    if print_path {
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    let mut script = Vec::new();
+    generate(shell, cmd, BIN_NAME, &mut script);
+    write_script(&path, &script)?;
+    println!(
+        "Installed {shell} completions to {}. {}",
+        path.display(),
+        reload_hint(shell, &path)
+    );
+    Ok(())
+}
+
+/// Conventional location for a user-level completion script.
+pub fn install_path(
+    shell: Shell,
+    home: &Path,
+    xdg_data_home: Option<&Path>,
+    xdg_config_home: Option<&Path>,
+) -> Result<PathBuf> {
+    let data_home = xdg_data_home
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| home.join(".local/share"));
+    let config_home = xdg_config_home
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| home.join(".config"));
+    match shell {
+        Shell::Bash => Ok(data_home.join("bash-completion/completions").join(BIN_NAME)),
+        Shell::Zsh => Ok(home.join(".zfunc").join(format!("_{BIN_NAME}"))),
+        Shell::Fish => Ok(config_home
+            .join("fish/completions")
+            .join(format!("{BIN_NAME}.fish"))),
+        other => bail!(
+            "Automatic installation is not supported for {other}; use `wtm completions generate {other}` instead."
+        ),
+    }
+}
+
+fn write_script(path: &Path, script: &[u8]) -> Result<()> {
+    let dir = path
+        .parent()
+        .context("completion path has no parent directory")?;
+    fs::create_dir_all(dir)
+        .map_err(|err| not_writable(dir, err))
+        .with_context(|| format!("failed to create {}", dir.display()))?;
+    fs::write(path, script)
+        .map_err(|err| not_writable(path, err))
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn not_writable(path: &Path, err: io::Error) -> anyhow::Error {
+    if err.kind() == io::ErrorKind::PermissionDenied {
+        anyhow::anyhow!(
+            "{} is not writable; run `wtm completions generate` and redirect the output to a location you own",
+            path.display()
+        )
+    } else {
+        err.into()
+    }
+}
+
+fn reload_hint(shell: Shell, path: &Path) -> String {
+    match shell {
+        Shell::Zsh => format!(
+            "Add `fpath=({} $fpath)` before `compinit` in ~/.zshrc, then restart your shell.",
+            path.parent().unwrap_or(path).display()
+        ),
+        Shell::Fish => "Open a new fish session to load them.".to_string(),
+        _ => format!(
+            "Restart your shell or run `source {}` to load them.",
+            path.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote_suggestion() -> Vec<Suggestion> {
+        branch_suggestions(&[], &["origin/feature/x".into()], DEFAULT_MAX_SLUG_LENGTH)
+    }
+
+    #[test]
+    fn destructive_commands_leave_the_primary_out_by_default() {
+        assert!(excludes_primary(Some("remove"), false, false));
+        assert!(excludes_primary(Some("move"), false, false));
+        assert!(!excludes_primary(Some("remove"), false, true));
+        assert!(!excludes_primary(Some("diff"), false, false));
+        assert!(!excludes_primary(None, false, false));
+        assert!(excludes_primary(None, true, false));
+    }
+
+    #[test]
+    fn branch_suggestions_skip_remotes_with_a_local_branch() {
+        let suggestions = branch_suggestions(
+            &["main".into()],
+            &["origin/main".into(), "origin/feature/x".into()],
+            DEFAULT_MAX_SLUG_LENGTH,
+        );
+        let values: Vec<_> = suggestions.iter().map(|s| s.value.as_str()).collect();
+        assert_eq!(values, ["main", "feature/x"]);
+        assert_eq!(suggestions[1].source, "remote branch");
+    }
+
+    #[test]
+    fn description_styles_control_what_the_shell_sees() {
+        let suggestions = remote_suggestion();
+        assert_eq!(
+            format_for_bash(&suggestions, DescriptionStyle::Full),
+            "feature/x\tremote branch origin/feature/x ⇒ feature-x\n"
+        );
+        assert_eq!(
+            format_for_bash(&suggestions, DescriptionStyle::SourceOnly),
+            "feature/x\tremote branch\n"
+        );
+        assert_eq!(
+            format_for_bash(&suggestions, DescriptionStyle::None),
+            "feature/x\n"
+        );
+        assert_eq!(
+            format_for_zsh(&suggestions, DescriptionStyle::Full),
+            "feature/x:remote branch origin/feature/x ⇒ feature-x\n"
+        );
+        assert_eq!(
+            format_for_zsh(&suggestions, DescriptionStyle::SourceOnly),
+            "feature/x:remote branch\n"
+        );
+        assert_eq!(
+            format_for_zsh(&suggestions, DescriptionStyle::None),
+            "feature/x\n"
+        );
+    }
+
+    #[test]
+    fn tabs_in_descriptions_do_not_split_the_line() {
+        let suggestions = [Suggestion {
+            value: "odd:name".into(),
+            source: "worktree",
+            detail: "on\tweird\nbranch".into(),
+        }];
+        assert_eq!(
+            format_for_bash(&suggestions, DescriptionStyle::Full),
+            "odd:name\tworktree on weird branch\n"
+        );
+        assert_eq!(
+            format_for_zsh(&suggestions, DescriptionStyle::Full),
+            "odd\\:name:worktree on weird branch\n"
+        );
+    }
+
+    #[test]
+    fn null_delimited_output_keeps_values_verbatim() {
+        let suggestions = [
+            Suggestion {
+                value: "feature/with space".into(),
+                source: "local branch",
+                detail: "⇒ feature-with-space".into(),
+            },
+            remote_suggestion().remove(0),
+        ];
+        assert_eq!(
+            format_null_delimited(&suggestions),
+            "feature/with space\0feature/x\0"
+        );
+        assert_eq!(format_null_delimited(&[]), "");
+    }
+
+    #[test]
+    fn install_path_follows_shell_conventions() {
+        let home = Path::new("/home/me");
+        assert_eq!(
+            install_path(Shell::Bash, home, None, None).unwrap(),
+            Path::new("/home/me/.local/share/bash-completion/completions/wtm")
+        );
+        assert_eq!(
+            install_path(Shell::Zsh, home, None, None).unwrap(),
+            Path::new("/home/me/.zfunc/_wtm")
+        );
+        assert_eq!(
+            install_path(Shell::Fish, home, None, Some(Path::new("/cfg"))).unwrap(),
+            Path::new("/cfg/fish/completions/wtm.fish")
+        );
+        assert_eq!(
+            install_path(Shell::Bash, home, Some(Path::new("/data")), None).unwrap(),
+            Path::new("/data/bash-completion/completions/wtm")
+        );
+        assert!(install_path(Shell::PowerShell, home, None, None).is_err());
+    }
+}
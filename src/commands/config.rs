@@ -0,0 +1,123 @@
+//! `wtm config show` and `wtm config edit`: the configuration in effect and the
+//! files behind it.
+
+use crate::{
+    commands::init::write_config_template,
+    config::{
+        config_path, global_config_dir, global_config_path, load_config, validate_config_file,
+    },
+    error::WtmError,
+    output::Output,
+};
+use anyhow::{bail, Context, Result};
+use std::{path::Path, process::Command};
+
+/// Print the merged, expanded configuration for `repo_root` as JSON.
+pub fn config_show_command(repo_root: &Path, out: Output) -> Result<()> {
+    let wtm_dir = repo_root.join(".wtm");
+    let mut effective = load_config(&wtm_dir)?.effective_json(repo_root);
+    effective["configFile"] = serde_json::to_value(config_path(&wtm_dir))?;
+    effective["globalConfigFile"] = serde_json::to_value(global_config_path())?;
+    out.print_json(&effective)?;
+    Ok(())
+}
+
+/// Open the repository's config file (or the global one when `repo_root` is
+/// `None`) in `$VISUAL`/`$EDITOR`, creating it first, and validate the result.
+pub fn config_edit_command(repo_root: Option<&Path>, out: Output) -> Result<()> {
+    let dir = match repo_root {
+        Some(repo_root) if !repo_root.join(".wtm").is_dir() => {
+            return Err(WtmError::NoWtmDir(repo_root.to_path_buf()).into())
+        }
+        Some(repo_root) => repo_root.join(".wtm"),
+        None => global_config_dir()
+            .context("cannot locate the global config directory; set XDG_CONFIG_HOME or HOME")?,
+    };
+    let path = match config_path(&dir) {
+        Some(path) => path,
+        None => {
+            let path = dir.join("config.json");
+            write_config_template(&path)?;
+            out.status(format!("Created {}", path.display()));
+            path
+        }
+    };
+
+    open_in_editor(&path)?;
+    validate_config_file(&path).with_context(|| {
+        let again = if repo_root.is_some() {
+            "wtm config edit"
+        } else {
+            "wtm config edit --global"
+        };
+        format!(
+            "{} is not valid; run `{again}` again to fix it",
+            path.display()
+        )
+    })?;
+    out.status(format!("{} is valid", path.display()));
+    Ok(())
+}
+
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Open `path` in the user's editor and wait for it to exit successfully.
+pub fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = editor_command();
+    let words = editor_words(&editor)?;
+    let (program, args) = words
+        .split_first()
+        .map_or((DEFAULT_EDITOR, &[][..]), |(program, args)| {
+            (program.as_str(), args)
+        });
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .with_context(|| format!("failed to start editor `{editor}`"))?;
+    if !status.success() {
+        bail!("editor `{editor}` exited with {status}");
+    }
+    Ok(())
+}
+
+/// Split an editor command into program and arguments the way a POSIX shell
+/// would, so quoted paths with spaces stay whole.
+fn editor_words(editor: &str) -> Result<Vec<String>> {
+    shell_words::split(editor).with_context(|| format!("cannot parse editor `{editor}`"))
+}
+
+/// `$VISUAL`, else `$EDITOR`, else the platform's basic editor; may include arguments.
+pub fn editor_command() -> String {
+    configured_editor().unwrap_or_else(|| DEFAULT_EDITOR.into())
+}
+
+/// `$VISUAL`, else `$EDITOR`, when the user set either.
+pub fn configured_editor() -> Option<String> {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .map(|value| value.to_string_lossy().trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editor_words_keep_quoted_paths_whole() {
+        assert_eq!(
+            editor_words(r#""/Applications/My Editor.app/bin/edit" --wait"#).unwrap(),
+            ["/Applications/My Editor.app/bin/edit", "--wait"]
+        );
+        assert_eq!(
+            editor_words(r"/opt/my\ editor/bin/edit -n").unwrap(),
+            ["/opt/my editor/bin/edit", "-n"]
+        );
+        assert!(editor_words("code \"--wait").is_err());
+    }
+}
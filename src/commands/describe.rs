@@ -0,0 +1,84 @@
+//! Hidden `wtm __describe`: a JSON model of the CLI for editor extensions and launchers.
+
+use crate::output::Output;
+use anyhow::Result;
+use clap::{Arg, Command};
+use serde_json::{json, Value};
+
+/// Print the JSON description of `cmd` and all of its visible subcommands.
+pub fn describe_command(cmd: &Command, out: Output) -> Result<()> {
+    out.print_json(&describe(cmd))?;
+    Ok(())
+}
+
+/// Recursively convert a clap command into `{name, about, args, subcommands}`.
+pub fn describe(cmd: &Command) -> Value {
+    let args: Vec<Value> = cmd
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set())
+        .map(describe_arg)
+        .collect();
+    let subcommands: Vec<Value> = cmd
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .map(describe)
+        .collect();
+    json!({
+        "name": cmd.get_name(),
+        "about": cmd.get_about().map(|about| about.to_string()),
+        "args": args,
+        "subcommands": subcommands,
+    })
+}
+
+fn describe_arg(arg: &Arg) -> Value {
+    let possible_values: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    json!({
+        "name": arg.get_id().as_str(),
+        "long": arg.get_long(),
+        "short": arg.get_short().map(String::from),
+        "help": arg.get_help().map(|help| help.to_string()),
+        "takes_value": arg.get_action().takes_values(),
+        "required": arg.is_required_set(),
+        "global": arg.is_global_set(),
+        "positional": arg.is_positional(),
+        "possible_values": possible_values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{ArgAction, Command};
+
+    #[test]
+    fn describe_walks_subcommands_and_args() {
+        let cmd = Command::new("tool")
+            .arg(
+                Arg::new("verbose")
+                    .long("verbose")
+                    .action(ArgAction::SetTrue),
+            )
+            .subcommand(
+                Command::new("run")
+                    .about("Run things")
+                    .arg(Arg::new("mode").value_parser(["fast", "slow"])),
+            )
+            .subcommand(Command::new("secret").hide(true));
+        let value = describe(&cmd);
+        assert_eq!(value["args"][0]["long"], "verbose");
+        assert_eq!(value["args"][0]["takes_value"], false);
+        let subcommands = value["subcommands"].as_array().unwrap();
+        assert_eq!(subcommands.len(), 1);
+        assert_eq!(subcommands[0]["about"], "Run things");
+        assert_eq!(
+            subcommands[0]["args"][0]["possible_values"],
+            json!(["fast", "slow"])
+        );
+    }
+}
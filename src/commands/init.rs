@@ -1,9 +1,130 @@
+use super::config::editor_command;
+use crate::{
+    config::{config_path, global_config_dir, validate_config_file, TerminalSession},
+    output::Output,
+    wtm_paths::atomic_write_json,
+};
 use anyhow::{bail, Context, Result};
-use serde_json::json;
-use std::{fs, path::Path};
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+    path::Path,
+};
 
 /// Create a `.wtm` scaffold within the provided root directory.
-pub fn init_command(root: &Path) -> Result<()> {
+pub fn init_command(root: &Path, out: Output) -> Result<()> {
+    let config = json!({
+        "version": 1,
+        "icon": "🤖",
+        "quickAccess": [],
+    });
+    scaffold(root, &config, out)
+}
+
+/// `init --interactive`: ask a few questions on stderr and scaffold `.wtm`
+/// with the resulting config. Without a terminal on stdin nothing is asked and
+/// the suggested answers are used.
+pub fn init_interactive_command(root: &Path, out: Output) -> Result<()> {
+    let defaults = SetupAnswers::defaults();
+    let answers = if io::stdin().is_terminal() {
+        ask_setup(&mut io::stdin().lock(), &mut io::stderr(), defaults)?
+    } else {
+        defaults
+    };
+    scaffold(root, &answers.config(), out)
+}
+
+/// Answers to the `init --interactive` questions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetupAnswers {
+    /// Command for the "Open in editor" quick action.
+    pub editor: String,
+    pub session: TerminalSession,
+    /// `jira.onCreateTransition`; `None` leaves tickets where they are.
+    pub jira_transition: Option<String>,
+}
+
+impl SetupAnswers {
+    /// `$VISUAL`/`$EDITOR` (or `vi`), plain shell tabs, no Jira transition.
+    fn defaults() -> Self {
+        Self {
+            editor: editor_command(),
+            session: TerminalSession::Shell,
+            jira_transition: None,
+        }
+    }
+
+    /// The config file for these answers, with a couple of example quick actions.
+    pub fn config(&self) -> Value {
+        let mut config = json!({
+            "version": 1,
+            "icon": "🤖",
+            "quickAccess": [
+                { "label": "Open in editor", "quickCommand": format!("{} .", self.editor) },
+                { "label": "Git status", "quickCommand": "git status" },
+            ],
+        });
+        if self.session != TerminalSession::Shell {
+            config["session"] = json!(self.session);
+        }
+        if let Some(status) = &self.jira_transition {
+            config["jira"] = json!({ "onCreateTransition": status });
+        }
+        config
+    }
+}
+
+/// Ask the setup questions, writing prompts to `prompts` and reading one line
+/// per answer from `input`; an empty answer or the end of input takes the
+/// default.
+pub fn ask_setup(
+    input: &mut dyn BufRead,
+    prompts: &mut dyn Write,
+    defaults: SetupAnswers,
+) -> Result<SetupAnswers> {
+    let mut ask = |question: &str, default: &str| -> Result<String> {
+        write!(prompts, "{question} [{default}]: ")?;
+        prompts.flush()?;
+        let mut line = String::new();
+        input
+            .read_line(&mut line)
+            .context("failed to read an answer")?;
+        let answer = line.trim();
+        Ok(if answer.is_empty() { default } else { answer }.to_string())
+    };
+
+    let editor = ask(
+        "Editor for the \"Open in editor\" quick action",
+        &defaults.editor,
+    )?;
+    let mut question = "Terminal tabs run a plain shell or a tmux session (shell/tmux)";
+    let session = loop {
+        match ask(question, "shell")?.to_lowercase().as_str() {
+            "shell" => break TerminalSession::Shell,
+            "tmux" => break TerminalSession::Tmux,
+            _ => question = "Please answer shell or tmux",
+        }
+    };
+    let jira = ask("Use Jira tickets (needs the acli CLI)? (y/n)", "n")?;
+    let jira_transition = if matches!(jira.to_lowercase().as_str(), "y" | "yes") {
+        let status = ask(
+            "Status to move a ticket to when its worktree is created (- for none)",
+            "In Progress",
+        )?;
+        (status != "-").then_some(status)
+    } else {
+        None
+    };
+    Ok(SetupAnswers {
+        editor,
+        session,
+        jira_transition,
+    })
+}
+
+/// Create `.wtm` under `root` with `config` as its config file.
+fn scaffold(root: &Path, config: &Value, out: Output) -> Result<()> {
     let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
     let wtm_dir = root.join(".wtm");
     if wtm_dir.exists() {
@@ -13,28 +134,92 @@ pub fn init_command(root: &Path) -> Result<()> {
     fs::create_dir_all(wtm_dir.join("workspaces"))
         .with_context(|| format!("failed to create {}", wtm_dir.display()))?;
 
-    let config = json!({
-        "version": 1,
-        "icon": "🤖",
-        "quickAccess": [],
-    });
-    write_json_file(&wtm_dir.join("config.json"), &config)?;
+    let config_file = wtm_dir.join("config.json");
+    write_json_file(&config_file, config)?;
+    validate_config_file(&config_file)?;
 
     let terminals = json!({
         "workspaces": {}
     });
     write_json_file(&wtm_dir.join("terminals.json"), &terminals)?;
 
-    let default_ws = root.join(".wtm/workspaces/default");
-    fs::create_dir_all(&default_ws)
-        .with_context(|| format!("failed to create {}", default_ws.display()))?;
+    out.status(format!(
+        "Initialised .wtm workspace scaffold at {}",
+        root.display()
+    ));
+    Ok(())
+}
 
-    println!("Initialised .wtm workspace scaffold at {}", root.display());
+/// Create the global config shared by every repository (see [`global_config_dir`]).
+pub fn init_global_command(out: Output) -> Result<()> {
+    let dir = global_config_dir()
+        .context("cannot locate the global config directory; set XDG_CONFIG_HOME or HOME")?;
+    if let Some(existing) = config_path(&dir) {
+        bail!("A global config already exists at {}", existing.display());
+    }
+    let path = dir.join("config.json");
+    write_config_template(&path)?;
+    out.status(format!("Created global config at {}", path.display()));
     Ok(())
 }
 
+/// Write a minimal config file to `path`, creating its directory.
+pub fn write_config_template(path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    write_json_file(path, &json!({ "quickAccess": [] }))
+}
+
 fn write_json_file(path: &Path, value: &serde_json::Value) -> Result<()> {
-    let data = serde_json::to_string_pretty(value)?;
-    fs::write(path, data).with_context(|| format!("failed to write {}", path.display()))?;
-    Ok(())
+    atomic_write_json(path, value).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn answers(script: &str) -> SetupAnswers {
+        let defaults = SetupAnswers {
+            editor: "vi".into(),
+            session: TerminalSession::Shell,
+            jira_transition: None,
+        };
+        let mut prompts = Vec::new();
+        ask_setup(&mut Cursor::new(script), &mut prompts, defaults).unwrap()
+    }
+
+    #[test]
+    fn scripted_answers_produce_a_valid_config() {
+        let setup = answers("code --wait\nscreen\nTMUX\ny\n\n");
+        assert_eq!(
+            setup,
+            SetupAnswers {
+                editor: "code --wait".into(),
+                session: TerminalSession::Tmux,
+                jira_transition: Some("In Progress".into()),
+            }
+        );
+        let config = setup.config();
+        assert_eq!(config["quickAccess"][0]["quickCommand"], "code --wait .");
+        assert_eq!(config["session"], "tmux");
+        assert_eq!(config["jira"]["onCreateTransition"], "In Progress");
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        write_json_file(&path, &config).unwrap();
+        validate_config_file(&path).unwrap();
+    }
+
+    #[test]
+    fn empty_input_keeps_the_defaults() {
+        let setup = answers("");
+        assert_eq!(setup.editor, "vi");
+        assert_eq!(setup.session, TerminalSession::Shell);
+        let config = setup.config();
+        assert!(config.get("session").is_none());
+        assert!(config.get("jira").is_none());
+        assert_eq!(answers("\n\ny\n-\n").jira_transition, None);
+    }
 }
@@ -0,0 +1,197 @@
+//! `wtm json-schema`: JSON Schema documents for the `--json` outputs.
+//!
+//! The schemas are written by hand; the tests below fail when a serialized type
+//! gains or loses a field the schema does not describe.
+
+use crate::output::Output;
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Which command's JSON output to describe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SchemaKind {
+    /// `wtm worktree list --json`
+    List,
+    /// `wtm worktree status --json`
+    Status,
+    /// `wtm worktree du --json`
+    Du,
+}
+
+/// Print the schema for `kind`; it describes the data inside `--envelope` too.
+pub fn json_schema_command(kind: SchemaKind, out: Output) -> Result<()> {
+    out.print_json(&schema(kind))?;
+    Ok(())
+}
+
+pub fn schema(kind: SchemaKind) -> Value {
+    let (title, body) = match kind {
+        SchemaKind::List => (
+            "wtm worktree list --json",
+            json!({ "type": "array", "items": worktree_info() }),
+        ),
+        SchemaKind::Status => (
+            "wtm worktree status --json",
+            json!({ "type": "array", "items": worktree_status() }),
+        ),
+        SchemaKind::Du => ("wtm worktree du --json", disk_usage()),
+    };
+    let mut schema = json!({ "$schema": DRAFT, "title": title });
+    if let (Some(schema), Value::Object(body)) = (schema.as_object_mut(), body) {
+        schema.extend(body);
+    }
+    schema
+}
+
+fn count() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn optional_string() -> Value {
+    json!({ "type": ["string", "null"] })
+}
+
+fn object(properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// `list_table::WorktreeSummary`: `git::WorktreeInfo` plus `path_exists`.
+fn worktree_info() -> Value {
+    object(
+        json!({
+            "path": { "type": "string" },
+            "head": optional_string(),
+            "branch": optional_string(),
+            "is_locked": { "type": "boolean" },
+            "is_prunable": { "type": "boolean" },
+            "path_exists": { "type": "boolean" },
+        }),
+        &[
+            "path",
+            "head",
+            "branch",
+            "is_locked",
+            "is_prunable",
+            "path_exists",
+        ],
+    )
+}
+
+/// A `status --json` entry: name and path, the flattened `GitStatusSummary`
+/// unless git status failed (then `error`), `bytes` when measured, and
+/// `services` (or `services_error`) with `--include-services`. Nothing is
+/// required because `--fields` may leave out any of them.
+fn worktree_status() -> Value {
+    object(
+        json!({
+            "name": { "type": "string" },
+            "path": { "type": "string" },
+            "branch": optional_string(),
+            "upstream": optional_string(),
+            "upstream_gone": { "type": "boolean" },
+            "ahead": count(),
+            "behind": count(),
+            "staged": count(),
+            "unstaged": count(),
+            "untracked": count(),
+            "conflicts": count(),
+            "bytes": count(),
+            "error": { "type": "string" },
+            "services": object(
+                json!({
+                    "running": count(),
+                    "total": count(),
+                    "by_state": { "type": "object", "additionalProperties": count() },
+                }),
+                &["running", "total", "by_state"],
+            ),
+            "services_error": { "type": "string" },
+        }),
+        &[],
+    )
+}
+
+/// `workspace::DiskUsage`.
+fn disk_usage() -> Value {
+    let usage = object(
+        json!({
+            "name": { "type": "string" },
+            "path": { "type": "string" },
+            "bytes": count(),
+            "missing": { "type": "boolean" },
+        }),
+        &["name", "path", "bytes", "missing"],
+    );
+    object(
+        json!({
+            "total_bytes": count(),
+            "workspaces": { "type": "array", "items": usage },
+            "cancelled": { "type": "boolean" },
+        }),
+        &["total_bytes", "workspaces", "cancelled"],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commands::{list_table::WorktreeSummary, status::STATUS_FIELDS},
+        git::{status::GitStatusSummary, WorktreeInfo},
+        workspace::{DiskUsage, WorkspaceUsage},
+    };
+    use std::path::PathBuf;
+
+    fn keys(value: &Value) -> Vec<String> {
+        let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn schemas_list_every_serialized_field() {
+        let info = WorktreeInfo::at("/repo");
+        let list = schema(SchemaKind::List);
+        assert_eq!(
+            keys(&serde_json::to_value(WorktreeSummary::new(&info)).unwrap()),
+            keys(&list["items"]["properties"])
+        );
+
+        let usage = DiskUsage {
+            total_bytes: 1,
+            workspaces: vec![WorkspaceUsage {
+                name: "repo".into(),
+                path: PathBuf::from("/repo"),
+                bytes: 1,
+                missing: false,
+            }],
+            cancelled: false,
+        };
+        let du = schema(SchemaKind::Du);
+        assert_eq!(
+            keys(&serde_json::to_value(&usage).unwrap()),
+            keys(&du["properties"])
+        );
+        assert_eq!(
+            keys(&serde_json::to_value(&usage.workspaces[0]).unwrap()),
+            keys(&du["properties"]["workspaces"]["items"]["properties"])
+        );
+
+        let status = schema(SchemaKind::Status);
+        let mut fields: Vec<String> = STATUS_FIELDS.iter().map(|f| f.to_string()).collect();
+        fields.sort();
+        assert_eq!(fields, keys(&status["items"]["properties"]));
+        for field in keys(&serde_json::to_value(GitStatusSummary::default()).unwrap()) {
+            assert!(fields.contains(&field), "{field} missing from the schema");
+        }
+        assert_eq!(status["$schema"], DRAFT);
+    }
+}
@@ -0,0 +1,267 @@
+//! `worktree list`: one row per worktree with aligned columns, styled by state,
+//! or [`WorktreeSummary`] entries for `--json`.
+//!
+//! Widths are measured on the plain cells and the colour is applied to the
+//! finished line, so escape codes never throw the alignment off. The state
+//! flags come last because the 🔒 icon is wider than one column.
+
+use crate::git::WorktreeInfo;
+use crossterm::style::Stylize;
+use serde::Serialize;
+
+/// Column separator, kept from the unaligned listing so scripts that split on
+/// it keep working.
+const SEPARATOR: &str = " | ";
+
+/// A `worktree list --json` entry.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeSummary<'a> {
+    #[serde(flatten)]
+    pub info: &'a WorktreeInfo,
+    /// False when git still lists the worktree but its directory is gone,
+    /// e.g. after it was deleted without `worktree remove`.
+    pub path_exists: bool,
+}
+
+impl<'a> WorktreeSummary<'a> {
+    pub fn new(info: &'a WorktreeInfo) -> Self {
+        Self {
+            info,
+            path_exists: info.path.exists(),
+        }
+    }
+}
+
+/// What decides a row's flags and style.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RowState {
+    /// The main checkout; printed bold.
+    pub primary: bool,
+    pub locked: bool,
+    /// Pinned with `worktree pin`, so bulk removals skip it.
+    pub pinned: bool,
+    /// Printed dimmed.
+    pub prunable: bool,
+    /// Created with `--no-checkout` and not populated since; only checked for
+    /// `--status`, as it reads every worktree's directory.
+    pub unpopulated: bool,
+    /// The directory no longer exists.
+    pub missing: bool,
+    pub upstream_gone: bool,
+    /// Uncommitted changes, printed yellow; `None` unless `--status` asked.
+    pub dirty: Option<bool>,
+}
+
+/// One worktree's cells, before alignment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListRow {
+    pub path: String,
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    /// Already formatted, e.g. `#reviewing #wip`.
+    pub tags: Option<String>,
+    pub state: RowState,
+}
+
+impl ListRow {
+    fn cells(&self) -> Vec<String> {
+        let mut flags = Vec::new();
+        if self.state.missing {
+            flags.push("missing");
+        }
+        if self.state.upstream_gone {
+            flags.push("⚠ upstream gone");
+        }
+        if self.state.dirty == Some(true) {
+            flags.push("dirty");
+        }
+        if self.state.pinned {
+            flags.push("📌 pinned");
+        }
+        if self.state.locked {
+            flags.push("🔒 locked");
+        }
+        if self.state.prunable {
+            flags.push("prunable");
+        }
+        if self.state.unpopulated {
+            flags.push("no-checkout");
+        }
+        vec![
+            self.path.clone(),
+            self.branch
+                .as_deref()
+                .map(|branch| format!("branch: {branch}"))
+                .unwrap_or_default(),
+            self.head
+                .as_deref()
+                .map(|head| format!("HEAD: {}", &head[..head.len().min(7)]))
+                .unwrap_or_default(),
+            self.tags
+                .as_deref()
+                .map(|tags| format!("tags: {tags}"))
+                .unwrap_or_default(),
+            flags.join(", "),
+        ]
+    }
+}
+
+/// Render `rows` as aligned lines, coloured when `color` is set.
+pub fn list_table(rows: &[ListRow], color: bool) -> Vec<String> {
+    let cells: Vec<Vec<String>> = rows.iter().map(ListRow::cells).collect();
+    let widths = column_widths(&cells);
+    rows.iter()
+        .zip(&cells)
+        .map(|(row, cells)| {
+            let line = format_row(cells, &widths);
+            if color {
+                style_line(line, row.state)
+            } else {
+                line
+            }
+        })
+        .collect()
+}
+
+/// The widest cell of each column, in characters.
+fn column_widths(rows: &[Vec<String>]) -> Vec<usize> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    (0..columns)
+        .map(|col| {
+            rows.iter()
+                .filter_map(|row| row.get(col))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// Pad each cell to its column's width, leaving out columns that are empty in
+/// every row and the row's trailing empty cells.
+fn format_row(cells: &[String], widths: &[usize]) -> String {
+    let used: Vec<(&String, usize)> = cells
+        .iter()
+        .zip(widths.iter().copied())
+        .filter(|(_, width)| *width > 0)
+        .collect();
+    let Some(last) = used.iter().rposition(|(cell, _)| !cell.is_empty()) else {
+        return String::new();
+    };
+    used[..=last]
+        .iter()
+        .enumerate()
+        .map(|(index, (cell, width))| {
+            if index == last {
+                cell.to_string()
+            } else {
+                format!("{cell:<width$}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(SEPARATOR)
+}
+
+fn style_line(line: String, state: RowState) -> String {
+    let mut styled = line.stylize();
+    if state.primary {
+        styled = styled.bold();
+    }
+    if state.prunable || state.missing {
+        styled = styled.dim();
+    }
+    if state.dirty == Some(true) {
+        styled = styled.yellow();
+    }
+    styled.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(path: &str, branch: &str) -> ListRow {
+        ListRow {
+            path: path.into(),
+            branch: Some(branch.into()),
+            head: Some("0123456789abcdef".into()),
+            ..ListRow::default()
+        }
+    }
+
+    #[test]
+    fn columns_line_up_across_names_of_any_length() {
+        let mut locked = row("feature-with-a-long-name", "feature/with-a-long-name");
+        locked.state.locked = true;
+        let mut tagged = row("x", "x");
+        tagged.tags = Some("#wip".into());
+        let lines = list_table(&[row("/repo", "main"), locked, tagged], false);
+        assert_eq!(
+            lines,
+            [
+                "/repo                    | branch: main                     | HEAD: 0123456",
+                "feature-with-a-long-name | branch: feature/with-a-long-name | HEAD: 0123456 |            | 🔒 locked",
+                "x                        | branch: x                        | HEAD: 0123456 | tags: #wip",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_columns_are_left_out() {
+        let lines = list_table(&[row("a", "a"), row("bb", "bb")], false);
+        assert_eq!(
+            lines,
+            [
+                "a  | branch: a  | HEAD: 0123456",
+                "bb | branch: bb | HEAD: 0123456"
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_reports_whether_the_directory_exists() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut info = WorktreeInfo::at(temp.path()).on("main");
+        assert!(WorktreeSummary::new(&info).path_exists);
+
+        info.path = temp.path().join("deleted");
+        let json = serde_json::to_value(WorktreeSummary::new(&info)).unwrap();
+        assert_eq!(json["path_exists"], false);
+        assert_eq!(
+            json["branch"], "main",
+            "the worktree's own fields stay flat"
+        );
+    }
+
+    #[test]
+    fn state_sets_flags_and_colour() {
+        let mut dirty = row("dirty", "dirty");
+        dirty.state = RowState {
+            dirty: Some(true),
+            upstream_gone: true,
+            ..RowState::default()
+        };
+        let plain = list_table(std::slice::from_ref(&dirty), false);
+        assert_eq!(
+            plain,
+            ["dirty | branch: dirty | HEAD: 0123456 | ⚠ upstream gone, dirty"]
+        );
+        let mut pinned = row("pinned", "pinned");
+        pinned.state.pinned = true;
+        pinned.state.locked = true;
+        assert_eq!(
+            list_table(&[pinned], false),
+            ["pinned | branch: pinned | HEAD: 0123456 | 📌 pinned, 🔒 locked"]
+        );
+        let colored = list_table(&[dirty], true);
+        assert!(colored[0].starts_with("\u{1b}["), "{:?}", colored[0]);
+        assert!(colored[0].contains(plain[0].as_str()));
+
+        let clean = row("clean", "clean");
+        assert_eq!(
+            list_table(std::slice::from_ref(&clean), true),
+            list_table(&[clean], false),
+            "nothing to highlight"
+        );
+    }
+}
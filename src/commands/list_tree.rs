@@ -0,0 +1,86 @@
+use crate::git::WorktreeInfo;
+use std::path::Path;
+
+/// `worktree list --tree`: worktrees grouped under their category, in list order.
+///
+/// A worktree's group is its first directory under `workspace_root` when it is
+/// nested (see `worktree add --category`), or else the part of its branch before
+/// the first `/`. Worktrees with neither are printed at the root.
+pub fn tree_lines(worktrees: &[WorktreeInfo], workspace_root: &Path) -> Vec<String> {
+    let mut entries: Vec<(Option<String>, Vec<&WorktreeInfo>)> = Vec::new();
+    for wt in worktrees {
+        match group_of(wt, workspace_root) {
+            Some(group) => match entries
+                .iter_mut()
+                .find(|(name, _)| name.as_deref() == Some(group.as_str()))
+            {
+                Some((_, members)) => members.push(wt),
+                None => entries.push((Some(group), vec![wt])),
+            },
+            None => entries.push((None, vec![wt])),
+        }
+    }
+    let mut lines = Vec::new();
+    for (group, members) in entries {
+        match group {
+            Some(group) => {
+                lines.push(format!("{group}/ ({})", members.len()));
+                lines.extend(members.into_iter().map(|wt| format!("  {}", leaf(wt))));
+            }
+            None => lines.extend(members.into_iter().map(leaf)),
+        }
+    }
+    lines
+}
+
+fn group_of(wt: &WorktreeInfo, workspace_root: &Path) -> Option<String> {
+    let nested = wt
+        .path
+        .strip_prefix(workspace_root)
+        .ok()
+        .filter(|relative| relative.components().count() > 1)
+        .and_then(|relative| relative.components().next())
+        .map(|first| first.as_os_str().to_string_lossy().into_owned());
+    nested.or_else(|| {
+        wt.branch
+            .as_deref()
+            .and_then(|branch| branch.split_once('/'))
+            .map(|(prefix, _)| prefix.to_string())
+    })
+}
+
+fn leaf(wt: &WorktreeInfo) -> String {
+    match wt.branch.as_deref() {
+        Some(branch) => format!("{} | branch: {branch}", wt.name()),
+        None => format!("{} | detached", wt.name()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_prefixes_are_grouped_and_the_rest_stay_at_the_root() {
+        let root = Path::new("/repo/.wtm/workspaces");
+        let worktrees = [
+            WorktreeInfo::at("/repo").on("master"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/feature-a").on("feature/a"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/bugs/fix-1").on("fix-1"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/feature-b").on("feature/b"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/scratch"),
+        ];
+        assert_eq!(
+            tree_lines(&worktrees, root),
+            [
+                "repo | branch: master",
+                "feature/ (2)",
+                "  feature-a | branch: feature/a",
+                "  feature-b | branch: feature/b",
+                "bugs/ (1)",
+                "  fix-1 | branch: fix-1",
+                "scratch | detached",
+            ]
+        );
+    }
+}
@@ -1 +1,17 @@
+pub mod activity;
+pub mod cd_script;
+pub mod completions;
+pub mod config;
+pub mod describe;
 pub mod init;
+pub mod json_schema;
+pub mod list_table;
+pub mod list_tree;
+pub mod open;
+pub mod quick;
+pub mod reset;
+pub mod run;
+pub mod status;
+pub mod sync;
+pub mod telemetry;
+pub mod version;
@@ -0,0 +1,58 @@
+//! `wtm worktree add --open`: go straight from creating a worktree to working in it.
+
+use super::config::{configured_editor, open_in_editor};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::path::Path;
+
+/// What `--open` does with the new worktree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenWith {
+    /// The editor when `$VISUAL` or `$EDITOR` is set, else `cd`
+    Auto,
+    /// Open the worktree directory in `$VISUAL`/`$EDITOR`
+    Editor,
+    /// Print the worktree's path last, for `cd "$(wtm worktree add … --open=cd | tail -n 1)"`
+    Cd,
+}
+
+impl OpenWith {
+    /// Settle [`OpenWith::Auto`] on a concrete action.
+    pub fn resolve(self, editor_configured: bool) -> Self {
+        match self {
+            OpenWith::Auto if editor_configured => OpenWith::Editor,
+            OpenWith::Auto => OpenWith::Cd,
+            other => other,
+        }
+    }
+}
+
+/// Open `path` as `with` asks and return the action taken. With `print_path`
+/// unset (`--json`, which reports the path itself), `cd` prints nothing.
+pub fn open_worktree(path: &Path, with: OpenWith, print_path: bool) -> Result<OpenWith> {
+    let with = with.resolve(configured_editor().is_some());
+    match with {
+        OpenWith::Editor => open_in_editor(path)?,
+        OpenWith::Cd | OpenWith::Auto => {
+            if print_path {
+                println!("{}", path.display());
+            }
+        }
+    }
+    Ok(with)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_prefers_a_configured_editor() {
+        assert_eq!(OpenWith::Auto.resolve(true), OpenWith::Editor);
+        assert_eq!(OpenWith::Auto.resolve(false), OpenWith::Cd);
+        assert_eq!(OpenWith::Cd.resolve(true), OpenWith::Cd);
+        assert_eq!(OpenWith::Editor.resolve(false), OpenWith::Editor);
+    }
+}
@@ -0,0 +1,108 @@
+//! `wtm quick run`: a configured quick action, outside the dashboards.
+
+use crate::{
+    config::{self, QuickAction},
+    output::Output,
+    tui::pty_tab::{default_shell, workspace_env},
+    workspace::{find_workspace, lookup_workspace},
+};
+use anyhow::{anyhow, bail, Context, Result};
+use std::path::Path;
+
+use super::run::run_in_pty;
+
+/// The quick action labelled `label`, ignoring case.
+pub fn find_quick_action<'a>(actions: &'a [QuickAction], label: &str) -> Result<&'a QuickAction> {
+    if actions.is_empty() {
+        bail!("no quick actions are configured (add them under `quickAccess` in .wtm/config.json)");
+    }
+    let label = label.trim();
+    actions
+        .iter()
+        .find(|action| action.label.eq_ignore_ascii_case(label))
+        .ok_or_else(|| {
+            let labels: Vec<&str> = actions.iter().map(|action| action.label.as_str()).collect();
+            anyhow!(
+                "no quick action labelled `{label}` (available: {})",
+                labels.join(", ")
+            )
+        })
+}
+
+/// `shell` invocation that runs `command` the way a dashboard tab would.
+pub fn shell_argv(shell: &str, command: &str) -> Vec<String> {
+    let name = shell.rsplit(['/', '\\']).next().unwrap_or(shell);
+    let is_cmd = name.eq_ignore_ascii_case("cmd") || name.eq_ignore_ascii_case("cmd.exe");
+    let flag = if is_cmd { "/C" } else { "-c" };
+    vec![shell.to_string(), flag.to_string(), command.to_string()]
+}
+
+/// Run the quick action `label` in `workspace` (a name or path; the repository
+/// root when `None`) and return its exit code.
+pub fn quick_run_command(
+    repo_root: &Path,
+    label: &str,
+    workspace: Option<&str>,
+    out: Output,
+) -> Result<i32> {
+    let config = config::load_config(&repo_root.join(".wtm"))?;
+    let action = find_quick_action(&config.quick_actions, label)?;
+    let worktree = match workspace {
+        Some(selector) => lookup_workspace(repo_root, selector)?,
+        None => find_workspace(repo_root, repo_root)?,
+    };
+    out.status(format!(
+        "Running `{}` in {}",
+        action.label,
+        worktree.path.display()
+    ));
+    let env = workspace_env(&worktree, &config.terminal_env);
+    run_in_pty(
+        &worktree.path,
+        &shell_argv(&default_shell(), &action.command),
+        &env,
+    )
+    .with_context(|| format!("quick action `{}`", action.label))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actions() -> Vec<QuickAction> {
+        vec![
+            QuickAction {
+                label: "Tests".into(),
+                command: "cargo test --workspace".into(),
+            },
+            QuickAction {
+                label: "Lint".into(),
+                command: "cargo clippy".into(),
+            },
+        ]
+    }
+
+    #[test]
+    fn quick_actions_are_found_by_label() {
+        let actions = actions();
+        let action = find_quick_action(&actions, "tests").unwrap();
+        assert_eq!(
+            shell_argv("/bin/zsh", &action.command),
+            ["/bin/zsh", "-c", "cargo test --workspace"]
+        );
+        assert_eq!(
+            shell_argv(r"C:\Windows\System32\cmd.exe", "dir"),
+            [r"C:\Windows\System32\cmd.exe", "/C", "dir"]
+        );
+
+        let err = find_quick_action(&actions, "deploy").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no quick action labelled `deploy` (available: Tests, Lint)"
+        );
+        assert!(find_quick_action(&[], "tests")
+            .unwrap_err()
+            .to_string()
+            .contains("no quick actions are configured"));
+    }
+}
@@ -0,0 +1,242 @@
+//! `wtm worktree reset`: make a throwaway worktree pristine and up to date by
+//! discarding its changes and fast-forwarding it to its upstream, or moving it
+//! to `--to`.
+//!
+//! Everything thrown away is listed first and needs `--yes` or a confirmation;
+//! a worktree with nothing to lose is reset without asking. A branch that has
+//! diverged from its upstream cannot be fast-forwarded, so it is refused before
+//! anything is discarded.
+
+use crate::{
+    git::{self, CommitLine},
+    runner::{CommandRunner, SystemRunner},
+};
+use anyhow::{bail, Result};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResetOptions<'a> {
+    /// Also delete untracked files and directories (`git clean -fd`).
+    pub clean: bool,
+    /// Reset to this ref instead of fast-forwarding to the upstream.
+    pub to: Option<&'a str>,
+}
+
+/// What a reset would throw away.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Discarded {
+    /// `git diff HEAD --stat` of the uncommitted changes.
+    pub diff_stat: String,
+    /// `Would remove <path>` lines from `git clean -nd`; only with `--clean`.
+    pub untracked: Vec<String>,
+    /// Commits that moving the branch to `--to` leaves behind.
+    pub commits: Vec<CommitLine>,
+}
+
+impl Discarded {
+    pub fn is_empty(&self) -> bool {
+        self.diff_stat.trim().is_empty() && self.untracked.is_empty() && self.commits.is_empty()
+    }
+
+    /// The preview printed before asking, one section per kind of loss.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if !self.diff_stat.trim().is_empty() {
+            lines.push("Uncommitted changes:".to_string());
+            lines.extend(
+                self.diff_stat
+                    .lines()
+                    .map(|line| format!("  {}", line.trim())),
+            );
+        }
+        if !self.untracked.is_empty() {
+            lines.push("Untracked files:".to_string());
+            lines.extend(self.untracked.iter().map(|path| format!("  {path}")));
+        }
+        if !self.commits.is_empty() {
+            lines.push("Commits left behind:".to_string());
+            lines.extend(
+                self.commits
+                    .iter()
+                    .map(|commit| format!("  {} {}", commit.hash, commit.subject)),
+            );
+        }
+        lines
+    }
+}
+
+/// Collect what resetting the worktree at `path` with `options` would discard,
+/// failing when the reset could not fast-forward to the upstream afterwards.
+pub fn preview(path: &Path, options: ResetOptions, has_upstream: bool) -> Result<Discarded> {
+    if options.to.is_none() && has_upstream {
+        let divergence = git::divergence(path)?;
+        if divergence.diverged {
+            bail!(
+                "the branch has {} commit(s) its upstream lacks and is {} behind, so it cannot \
+                 be fast-forwarded; pass `--to @{{upstream}}` to drop the local commits",
+                divergence.left,
+                divergence.right
+            );
+        }
+    }
+    let untracked = if options.clean {
+        git::clean(path, true)?
+            .lines()
+            .filter_map(|line| line.strip_prefix("Would remove "))
+            .map(str::to_string)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let commits = match options.to {
+        Some(to) => git::commits_not_in(path, to)?,
+        None => Vec::new(),
+    };
+    Ok(Discarded {
+        diff_stat: git::diff_stat(path)?,
+        untracked,
+        commits,
+    })
+}
+
+/// Whether to go ahead: at once when nothing would be lost or `yes` was
+/// passed, otherwise only if `ask` gets a yes.
+pub fn confirmed(
+    discarded: &Discarded,
+    yes: bool,
+    ask: impl FnOnce() -> Result<bool>,
+) -> Result<bool> {
+    if yes || discarded.is_empty() {
+        return Ok(true);
+    }
+    ask()
+}
+
+/// What [`reset_worktree`] did after resetting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Synced {
+    /// Fast-forwarded to the upstream.
+    Pulled,
+    /// Moved to `--to`, so the upstream was left alone.
+    MovedTo,
+    /// The branch has no upstream to fast-forward to.
+    NoUpstream,
+}
+
+/// Reset the worktree at `path`, clean it with `--clean`, then fast-forward
+/// it to its upstream as last fetched, unless `--to` picked the target or
+/// there is none.
+pub fn reset_worktree(path: &Path, options: ResetOptions, has_upstream: bool) -> Result<Synced> {
+    reset_worktree_with(&SystemRunner, path, options, has_upstream)
+}
+
+fn reset_worktree_with(
+    runner: &dyn CommandRunner,
+    path: &Path,
+    options: ResetOptions,
+    has_upstream: bool,
+) -> Result<Synced> {
+    git::reset_hard_with(runner, path, options.to)?;
+    if options.clean {
+        git::clean_with(runner, path, false)?;
+    }
+    if options.to.is_some() {
+        return Ok(Synced::MovedTo);
+    }
+    if !has_upstream {
+        return Ok(Synced::NoUpstream);
+    }
+    git::merge_upstream_with(runner, path, true)?;
+    Ok(Synced::Pulled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::testing::ScriptedRunner;
+
+    fn args(runner: &ScriptedRunner) -> Vec<Vec<String>> {
+        runner.calls().into_iter().map(|call| call.args).collect()
+    }
+
+    #[test]
+    fn reset_runs_reset_clean_and_fast_forward_in_the_worktree() {
+        let path = Path::new("/ws/scratch");
+        let runner = ScriptedRunner::default()
+            .succeed("")
+            .succeed("")
+            .succeed("");
+        let clean = ResetOptions {
+            clean: true,
+            to: None,
+        };
+        let synced = reset_worktree_with(&runner, path, clean, true).unwrap();
+        assert_eq!(synced, Synced::Pulled);
+        assert_eq!(
+            args(&runner),
+            [
+                vec!["reset", "--hard"],
+                vec!["clean", "-fd"],
+                vec!["merge", "--ff-only", "@{upstream}"]
+            ]
+        );
+        assert!(runner
+            .calls()
+            .iter()
+            .all(|call| call.cwd.as_deref() == Some(path)));
+
+        let runner = ScriptedRunner::default().succeed("");
+        let to = ResetOptions {
+            clean: false,
+            to: Some("origin/main"),
+        };
+        assert_eq!(
+            reset_worktree_with(&runner, path, to, true).unwrap(),
+            Synced::MovedTo
+        );
+        assert_eq!(args(&runner), [vec!["reset", "--hard", "origin/main"]]);
+
+        let runner = ScriptedRunner::default().succeed("");
+        assert_eq!(
+            reset_worktree_with(&runner, path, ResetOptions::default(), false).unwrap(),
+            Synced::NoUpstream
+        );
+        assert_eq!(args(&runner), [vec!["reset", "--hard"]]);
+    }
+
+    #[test]
+    fn only_a_reset_that_loses_something_asks() {
+        let nothing = Discarded::default();
+        let refuse = || Ok(false);
+        assert!(confirmed(&nothing, false, refuse).unwrap());
+
+        let dirty = Discarded {
+            diff_stat: " a.rs | 2 +-\n 1 file changed\n".into(),
+            ..Discarded::default()
+        };
+        assert!(!confirmed(&dirty, false, refuse).unwrap());
+        assert!(confirmed(&dirty, false, || Ok(true)).unwrap());
+        assert!(confirmed(&dirty, true, || panic!("--yes never asks")).unwrap());
+
+        let everything = Discarded {
+            untracked: vec!["scratch.txt".into()],
+            commits: vec![CommitLine {
+                hash: "0123abc".into(),
+                subject: "wip".into(),
+            }],
+            ..dirty
+        };
+        assert_eq!(
+            everything.lines(),
+            [
+                "Uncommitted changes:",
+                "  a.rs | 2 +-",
+                "  1 file changed",
+                "Untracked files:",
+                "  scratch.txt",
+                "Commits left behind:",
+                "  0123abc wip",
+            ]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! `wtm worktree run`: one command in a real PTY, attached to this terminal.
+//!
+//! Unlike piping a child's output, a PTY lets interactive tools (pagers,
+//! prompts, test watchers) behave as they would in a shell. Input is forwarded
+//! raw when stdin is a terminal, and the PTY follows the terminal's size
+//! (on `SIGWINCH` where there is one).
+
+use anyhow::{bail, Context, Result};
+use crossterm::terminal;
+use portable_pty::{native_pty_system, Child, CommandBuilder, ExitStatus, MasterPty, PtySize};
+use std::{
+    io::{self, IsTerminal, Read, Write},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+const FALLBACK_SIZE: (u16, u16) = (80, 24);
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// How long to keep copying output after the child exits (e.g. from a
+/// background process still holding the PTY open).
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Run `argv` in `cwd` under a new PTY and return its exit code.
+pub fn run_in_pty(cwd: &Path, argv: &[String], env: &[(String, String)]) -> Result<i32> {
+    let Some((program, args)) = argv.split_first() else {
+        bail!("no command given; pass it after `--`");
+    };
+    let pair = native_pty_system().openpty(pty_size(terminal_size()))?;
+    let mut command = CommandBuilder::new(program);
+    command.args(args);
+    command.cwd(cwd);
+    command.env("PWD", cwd);
+    for (key, value) in env {
+        command.env(key, value);
+    }
+    let mut child = pair
+        .slave
+        .spawn_command(command)
+        .with_context(|| format!("failed to run `{program}`"))?;
+    drop(pair.slave);
+
+    let _raw = RawMode::enable_if_terminal()?;
+    let mut reader = pair.master.try_clone_reader()?;
+    let output = thread::spawn(move || {
+        let mut stdout = io::stdout();
+        let mut buf = [0u8; 8192];
+        // The read fails with EIO once the child side of the PTY is closed.
+        while let Ok(n @ 1..) = reader.read(&mut buf) {
+            if stdout
+                .write_all(&buf[..n])
+                .and_then(|()| stdout.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    let mut writer = pair.master.take_writer()?;
+    // Not joined: a read from stdin cannot be interrupted, and the process
+    // exits right after the child does.
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut buf = [0u8; 1024];
+        while let Ok(n @ 1..) = stdin.read(&mut buf) {
+            if writer.write_all(&buf[..n]).is_err() {
+                break;
+            }
+        }
+    });
+
+    let status = wait_following_resizes(child.as_mut(), pair.master)?;
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    while !output.is_finished() && Instant::now() < deadline {
+        thread::sleep(POLL_INTERVAL);
+    }
+    Ok(status.exit_code() as i32)
+}
+
+/// Wait for `child`, resizing the PTY whenever the terminal sends `SIGWINCH`.
+#[cfg(unix)]
+fn wait_following_resizes(
+    child: &mut (dyn Child + Send + Sync),
+    master: Box<dyn MasterPty + Send>,
+) -> Result<ExitStatus> {
+    use signal_hook::{consts::SIGWINCH, iterator::Signals};
+
+    let mut signals = Signals::new([SIGWINCH]).context("failed to watch for terminal resizes")?;
+    let handle = signals.handle();
+    let resizer = thread::spawn(move || {
+        for _ in signals.forever() {
+            let _ = master.resize(pty_size(terminal_size()));
+        }
+    });
+    let status = child.wait();
+    handle.close();
+    let _ = resizer.join();
+    Ok(status?)
+}
+
+/// Wait for `child`, checking the terminal's size while it runs.
+#[cfg(not(unix))]
+fn wait_following_resizes(
+    child: &mut (dyn Child + Send + Sync),
+    master: Box<dyn MasterPty + Send>,
+) -> Result<ExitStatus> {
+    let mut size = terminal_size();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        let current = terminal_size();
+        if current != size {
+            size = current;
+            let _ = master.resize(pty_size(size));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// The controlling terminal's `(cols, rows)`, or 80×24 without one.
+fn terminal_size() -> (u16, u16) {
+    terminal::size()
+        .ok()
+        .filter(|&(cols, rows)| cols > 0 && rows > 0)
+        .unwrap_or(FALLBACK_SIZE)
+}
+
+fn pty_size((cols, rows): (u16, u16)) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// Raw mode for the duration of the run, so keys reach the child unprocessed.
+struct RawMode(bool);
+
+impl RawMode {
+    fn enable_if_terminal() -> Result<Self> {
+        if !io::stdin().is_terminal() {
+            return Ok(Self(false));
+        }
+        terminal::enable_raw_mode().context("failed to switch the terminal to raw mode")?;
+        Ok(Self(true))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if self.0 {
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use crate::{
+    cancel::CancellationToken,
+    config::PathDisplay,
+    docker::{self, DockerOptions, ServicesState},
+    git::{status, status::GitStatusSummary, WorktreeInfo},
+    output::Output,
+    spinner::Spinner,
+    workspace::{disk_usage_with, format_bytes},
+    wtm_paths::{format_path, home_dir},
+};
+use anyhow::{anyhow, Result};
+use crossterm::style::{Color, Stylize};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Fields of a `status --json` object, in the order `--fields` accepts them.
+pub const STATUS_FIELDS: &[&str] = &[
+    "name",
+    "path",
+    "branch",
+    "upstream",
+    "upstream_gone",
+    "ahead",
+    "behind",
+    "staged",
+    "unstaged",
+    "untracked",
+    "conflicts",
+    "bytes",
+    "error",
+    "services",
+    "services_error",
+];
+
+/// How [`status_command`] reports each worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusFormat {
+    /// The path followed by a `key=value` status line.
+    Lines,
+    /// One aligned row per worktree, including its size.
+    Compact,
+    /// A JSON array, optionally reduced to the given [`STATUS_FIELDS`], with
+    /// docker compose service counts when compose options are given.
+    Json(Option<Vec<&'static str>>, Option<DockerOptions>),
+}
+
+/// Validate `--fields` names against [`STATUS_FIELDS`].
+pub fn select_fields(requested: &[String]) -> Result<Vec<&'static str>> {
+    requested
+        .iter()
+        .map(|field| {
+            let field = field.trim();
+            STATUS_FIELDS
+                .iter()
+                .copied()
+                .find(|known| *known == field)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "unknown field `{field}` (valid fields: {})",
+                        STATUS_FIELDS.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct WorktreeStatus<'a> {
+    name: String,
+    path: &'a Path,
+    #[serde(flatten)]
+    status: Option<GitStatusSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    services: Option<ServicesState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    services_error: Option<String>,
+}
+
+/// Print each worktree's path followed by its git status summary. Once
+/// `cancel` is set the remaining worktrees are left out.
+pub fn status_command(
+    repo_root: &Path,
+    worktrees: &[WorktreeInfo],
+    path_display: PathDisplay,
+    format: StatusFormat,
+    cancel: &CancellationToken,
+    out: Output,
+) -> Result<()> {
+    match format {
+        StatusFormat::Lines => {}
+        StatusFormat::Compact => {
+            for line in compact_lines(worktrees, cancel, out) {
+                println!("{line}");
+            }
+            warn_if_cancelled(cancel, out);
+            return Ok(());
+        }
+        StatusFormat::Json(fields, services) => {
+            let value = json_status(worktrees, fields.as_deref(), services.as_ref(), cancel, out)?;
+            out.print_json(&value)?;
+            warn_if_cancelled(cancel, out);
+            return Ok(());
+        }
+    }
+    let home = home_dir();
+    for wt in worktrees.iter().take_while(|_| !cancel.is_cancelled()) {
+        println!(
+            "{}",
+            format_path(&wt.path, path_display, repo_root, home.as_deref())
+        );
+        match status::status(&wt.path) {
+            Ok(summary) => println!("  {}", format_status_line(&summary, out.color)),
+            Err(err) => println!("  status unavailable: {err}"),
+        }
+    }
+    warn_if_cancelled(cancel, out);
+    Ok(())
+}
+
+fn warn_if_cancelled(cancel: &CancellationToken, out: Output) {
+    if cancel.is_cancelled() {
+        out.warn("cancelled; only what was read before Ctrl+C is shown");
+    }
+}
+
+/// Disk usage per worktree path; empty when it could not be measured or the
+/// walk was cancelled.
+fn worktree_sizes(
+    worktrees: &[WorktreeInfo],
+    cancel: &CancellationToken,
+    out: Output,
+) -> HashMap<PathBuf, u64> {
+    let usage = {
+        let _spinner = Spinner::start("Measuring worktrees…", !out.show_progress());
+        disk_usage_with(worktrees, cancel, &|_| {})
+    };
+    match usage {
+        Ok(usage) if usage.cancelled => HashMap::new(),
+        Ok(usage) => usage
+            .workspaces
+            .into_iter()
+            .filter(|ws| !ws.missing)
+            .map(|ws| (ws.path, ws.bytes))
+            .collect(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Serialize every worktree, skipping the disk walk when `bytes` is not
+/// requested. `services` adds each worktree's compose service counts. Once
+/// `cancel` is set the remaining worktrees are left out.
+pub(super) fn json_status(
+    worktrees: &[WorktreeInfo],
+    fields: Option<&[&str]>,
+    services: Option<&DockerOptions>,
+    cancel: &CancellationToken,
+    out: Output,
+) -> Result<Value> {
+    let sizes = match fields {
+        Some(fields) if !fields.contains(&"bytes") => HashMap::new(),
+        _ => worktree_sizes(worktrees, cancel, out),
+    };
+    let entries = worktrees
+        .iter()
+        .take_while(|_| !cancel.is_cancelled())
+        .map(|wt| {
+            let (status, error) = match status::status(&wt.path) {
+                Ok(summary) => (Some(summary), None),
+                Err(err) => (None, Some(format!("{err:#}"))),
+            };
+            let (services, services_error) =
+                match services.map(|options| docker::services_state(&wt.path, options)) {
+                    Some(Ok(state)) => (Some(state), None),
+                    Some(Err(err)) => (None, Some(format!("{err:#}"))),
+                    None => (None, None),
+                };
+            let entry = serde_json::to_value(WorktreeStatus {
+                name: wt.name(),
+                path: &wt.path,
+                status,
+                bytes: sizes.get(&wt.path).copied(),
+                error,
+                services,
+                services_error,
+            })?;
+            Ok(match fields {
+                Some(fields) => project(entry, fields),
+                None => entry,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Value::Array(entries))
+}
+
+/// Keep only `fields` of a JSON object.
+fn project(value: Value, fields: &[&str]) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(key, _)| fields.contains(&key.as_str()))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+fn compact_lines(
+    worktrees: &[WorktreeInfo],
+    cancel: &CancellationToken,
+    out: Output,
+) -> Vec<String> {
+    let sizes = worktree_sizes(worktrees, cancel, out);
+    let rows: Vec<Vec<String>> = worktrees
+        .iter()
+        .take_while(|_| !cancel.is_cancelled())
+        .map(|wt| {
+            compact_row(
+                &wt.name(),
+                status::status(&wt.path).ok().as_ref(),
+                sizes.get(&wt.path).copied(),
+            )
+        })
+        .collect();
+    align_columns(&rows)
+}
+
+/// `name branch ↑ahead↓behind ✱changed ?untracked ‼conflicts size`; `?` marks unknowns.
+fn compact_row(name: &str, summary: Option<&GitStatusSummary>, bytes: Option<u64>) -> Vec<String> {
+    let size = bytes.map(format_bytes).unwrap_or_else(|| "?".into());
+    let Some(summary) = summary else {
+        return vec![
+            name.into(),
+            "?".into(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            size,
+        ];
+    };
+    vec![
+        name.to_string(),
+        summary.branch.clone().unwrap_or_else(|| "-".into()),
+        if summary.upstream_gone {
+            "⚠gone".into()
+        } else {
+            format!("↑{}↓{}", summary.ahead, summary.behind)
+        },
+        format!("✱{}", summary.staged + summary.unstaged),
+        format!("?{}", summary.untracked),
+        format!("‼{}", summary.conflicts),
+        size,
+    ]
+}
+
+/// Pad every column to its widest cell so the rows read as a table.
+fn align_columns(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|col| {
+            rows.iter()
+                .filter_map(|row| row.get(col))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    rows.iter()
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// `branch=…, upstream=…, ahead=…, …` with problem values highlighted when `color` is set.
+///
+/// Conflicts and commits behind are red, local-only work is yellow, and the
+/// branch is green when everything is committed and in sync.
+pub fn format_status_line(summary: &GitStatusSummary, color: bool) -> String {
+    let paint = |value: String, tint: Option<Color>| match (color, tint) {
+        (true, Some(tint)) => value.with(tint).to_string(),
+        _ => value,
+    };
+    let warn_if = |count: usize, tint: Color| paint(count.to_string(), (count > 0).then_some(tint));
+    let clean = !summary.upstream_gone
+        && summary.ahead == 0
+        && summary.behind == 0
+        && summary.staged == 0
+        && summary.unstaged == 0
+        && summary.untracked == 0
+        && summary.conflicts == 0;
+
+    let fields = [
+        (
+            "branch",
+            paint(
+                summary.branch.clone().unwrap_or_else(|| "-".into()),
+                clean.then_some(Color::Green),
+            ),
+        ),
+        (
+            "upstream",
+            match summary.upstream.clone() {
+                Some(upstream) if summary.upstream_gone => {
+                    paint(format!("{upstream} [gone]"), Some(Color::Red))
+                }
+                upstream => upstream.unwrap_or_else(|| "-".into()),
+            },
+        ),
+        ("ahead", warn_if(summary.ahead as usize, Color::Yellow)),
+        ("behind", warn_if(summary.behind as usize, Color::Red)),
+        ("staged", warn_if(summary.staged, Color::Yellow)),
+        ("unstaged", warn_if(summary.unstaged, Color::Yellow)),
+        ("untracked", warn_if(summary.untracked, Color::Yellow)),
+        ("conflicts", warn_if(summary.conflicts, Color::Red)),
+    ];
+    fields
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> GitStatusSummary {
+        GitStatusSummary {
+            branch: Some("feature/x".into()),
+            upstream: Some("origin/feature/x".into()),
+            ahead: 1,
+            behind: 2,
+            conflicts: 1,
+            ..GitStatusSummary::default()
+        }
+    }
+
+    #[test]
+    fn select_fields_rejects_unknown_names() {
+        let fields = select_fields(&["name".into(), " ahead".into()]).unwrap();
+        assert_eq!(fields, ["name", "ahead"]);
+        let err = select_fields(&["nmae".into()]).unwrap_err().to_string();
+        assert!(err.contains("unknown field `nmae`"), "{err}");
+        assert!(err.contains("name, path, branch"), "{err}");
+    }
+
+    #[test]
+    fn project_keeps_only_requested_fields() {
+        let entry = serde_json::to_value(WorktreeStatus {
+            name: "feature-x".into(),
+            path: Path::new("/ws/feature-x"),
+            status: Some(summary()),
+            bytes: Some(42),
+            error: None,
+            services: Some(ServicesState {
+                running: 1,
+                total: 1,
+                by_state: [("running".to_string(), 1)].into(),
+            }),
+            services_error: None,
+        })
+        .unwrap();
+        assert_eq!(entry["ahead"], 1, "status fields are flattened");
+        assert_eq!(entry["services"]["by_state"]["running"], 1);
+        assert!(entry.get("services_error").is_none());
+        assert_eq!(
+            project(entry, &["name", "behind"]),
+            serde_json::json!({ "name": "feature-x", "behind": 2 })
+        );
+    }
+
+    #[test]
+    fn compact_rows_are_aligned() {
+        let other = GitStatusSummary {
+            branch: Some("main".into()),
+            staged: 2,
+            unstaged: 1,
+            untracked: 4,
+            ..GitStatusSummary::default()
+        };
+        let rows = [
+            compact_row("feature-x", Some(&summary()), Some(2048)),
+            compact_row("repo", Some(&other), None),
+        ];
+        assert_eq!(
+            align_columns(&rows),
+            [
+                "feature-x  feature/x  ↑1↓2  ✱0  ?0  ‼1  2.0 KiB",
+                "repo       main       ↑0↓0  ✱3  ?4  ‼0  ?",
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_status_line_is_stable() {
+        assert_eq!(
+            format_status_line(&summary(), false),
+            "branch=feature/x, upstream=origin/feature/x, ahead=1, behind=2, \
+             staged=0, unstaged=0, untracked=0, conflicts=1"
+        );
+    }
+
+    #[test]
+    fn colored_status_line_highlights_problems() {
+        let line = format_status_line(&summary(), true);
+        assert!(line.contains(&"2".with(Color::Red).to_string()));
+        assert!(line.contains(&"1".with(Color::Yellow).to_string()));
+        assert!(
+            line.contains("branch=feature/x,"),
+            "dirty branch is not green"
+        );
+
+        let clean = GitStatusSummary {
+            branch: Some("main".into()),
+            ..GitStatusSummary::default()
+        };
+        assert!(format_status_line(&clean, true).contains(&"main".with(Color::Green).to_string()));
+    }
+
+    #[test]
+    fn gone_upstream_is_marked() {
+        let gone = GitStatusSummary {
+            upstream_gone: true,
+            ..summary()
+        };
+        assert!(format_status_line(&gone, false).contains("upstream=origin/feature/x [gone],"));
+        assert_eq!(compact_row("x", Some(&gone), None)[2], "⚠gone");
+    }
+}
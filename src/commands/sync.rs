@@ -0,0 +1,183 @@
+//! `wtm worktree sync`: fetch once, then bring every worktree's branch up to
+//! its upstream, so the day starts with everything current.
+//!
+//! Worktrees that cannot be updated safely are skipped with the reason rather
+//! than failed. After the single fetch each worktree only runs a local
+//! `git merge @{upstream}`, which writes its own index and branch and leaves
+//! the shared remote-tracking refs alone, so the merges run side by side.
+
+use crate::{
+    bulk::{BulkResult, Outcome},
+    cancel::CancellationToken,
+    git::{self, status::GitStatusSummary, WorktreeInfo},
+    output::Output,
+    parallel,
+};
+use anyhow::{anyhow, Result};
+use std::{fmt, path::Path};
+
+/// Why a worktree is left alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The directory is gone.
+    Missing,
+    Detached,
+    NoUpstream,
+    UpstreamGone,
+    /// Staged or unstaged changes, or conflicts; untracked files do not count,
+    /// since git refuses a merge that would overwrite them anyway.
+    Dirty,
+    /// A real merge hit conflicts and was aborted, leaving the worktree as it
+    /// was; the branch has diverged and needs merging by hand.
+    Conflicts,
+    /// Ctrl+C came before its turn.
+    Cancelled,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SkipReason::Missing => "directory missing",
+            SkipReason::Detached => "detached HEAD",
+            SkipReason::NoUpstream => "no upstream",
+            SkipReason::UpstreamGone => "upstream gone",
+            SkipReason::Dirty => "uncommitted changes",
+            SkipReason::Conflicts => "diverged, merge conflicts (merge aborted)",
+            SkipReason::Cancelled => "cancelled",
+        })
+    }
+}
+
+/// Why `status` rules out updating the branch, if it does.
+pub fn skip_reason(status: &GitStatusSummary) -> Option<SkipReason> {
+    match status.branch.as_deref() {
+        None | Some("(detached)") => return Some(SkipReason::Detached),
+        Some(_) => {}
+    }
+    if status.upstream.is_none() {
+        return Some(SkipReason::NoUpstream);
+    }
+    if status.upstream_gone {
+        return Some(SkipReason::UpstreamGone);
+    }
+    if status.staged + status.unstaged + status.conflicts > 0 {
+        return Some(SkipReason::Dirty);
+    }
+    None
+}
+
+/// Merge the upstream of the worktree at `path` unless [`skip_reason`] says
+/// otherwise; a merge that conflicts is aborted and skipped.
+fn sync_worktree(
+    path: &Path,
+    ff_only: bool,
+    cancel: &CancellationToken,
+) -> Result<Option<SkipReason>> {
+    if cancel.is_cancelled() {
+        return Ok(Some(SkipReason::Cancelled));
+    }
+    if !path.exists() {
+        return Ok(Some(SkipReason::Missing));
+    }
+    if let Some(reason) = skip_reason(&git::status::status(path)?) {
+        return Ok(Some(reason));
+    }
+    match git::merge_upstream(path, ff_only)? {
+        git::MergeOutcome::Merged => Ok(None),
+        git::MergeOutcome::Aborted => Ok(Some(SkipReason::Conflicts)),
+    }
+}
+
+/// Fetch all remotes from `repo_root`, then update each of `worktrees` from
+/// what was fetched. Worktrees not yet started when `cancel` is set are
+/// skipped.
+pub fn sync_command(
+    repo_root: &Path,
+    worktrees: &[WorktreeInfo],
+    ff_only: bool,
+    cancel: &CancellationToken,
+    out: Output,
+) -> BulkResult {
+    if let Err(err) = git::fetch_all(repo_root, cancel) {
+        out.warn(format!(
+            "fetch failed, merging what was fetched before: {err:#}"
+        ));
+    }
+    let outcomes = parallel::map(worktrees, |wt| sync_worktree(&wt.path, ff_only, cancel));
+
+    let mut result = BulkResult::new();
+    for (worktree, outcome) in worktrees.iter().zip(outcomes) {
+        let name = worktree.name();
+        match outcome.unwrap_or_else(|| Err(anyhow!("updating it failed unexpectedly"))) {
+            Ok(Some(reason)) => {
+                out.status(format!("Skipped {name}: {reason}"));
+                result.record(name, Outcome::Skipped);
+            }
+            Ok(None) => {
+                out.status(format!("Updated {name}"));
+                result.record(name, Outcome::Succeeded);
+            }
+            Err(err) => result.record_result(name, Err(err)),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracking(branch: &str) -> GitStatusSummary {
+        GitStatusSummary {
+            branch: Some(branch.into()),
+            upstream: Some(format!("origin/{branch}")),
+            ..GitStatusSummary::default()
+        }
+    }
+
+    #[test]
+    fn skip_reasons_cover_detached_dirty_and_upstreamless_worktrees() {
+        assert_eq!(skip_reason(&tracking("main")), None);
+
+        let mut behind = tracking("main");
+        behind.behind = 3;
+        behind.untracked = 1;
+        assert_eq!(skip_reason(&behind), None, "untracked files do not block");
+
+        let detached = GitStatusSummary {
+            branch: Some("(detached)".into()),
+            ..GitStatusSummary::default()
+        };
+        assert_eq!(skip_reason(&detached), Some(SkipReason::Detached));
+        assert_eq!(
+            skip_reason(&GitStatusSummary::default()),
+            Some(SkipReason::Detached)
+        );
+
+        let mut local = tracking("scratch");
+        local.upstream = None;
+        assert_eq!(skip_reason(&local), Some(SkipReason::NoUpstream));
+
+        let mut gone = tracking("merged");
+        gone.upstream_gone = true;
+        assert_eq!(skip_reason(&gone), Some(SkipReason::UpstreamGone));
+
+        for dirty in [
+            GitStatusSummary {
+                staged: 1,
+                ..tracking("a")
+            },
+            GitStatusSummary {
+                unstaged: 2,
+                ..tracking("b")
+            },
+            GitStatusSummary {
+                conflicts: 1,
+                ..tracking("c")
+            },
+        ] {
+            assert_eq!(skip_reason(&dirty), Some(SkipReason::Dirty));
+        }
+        assert_eq!(SkipReason::Dirty.to_string(), "uncommitted changes");
+    }
+}
@@ -0,0 +1,301 @@
+//! `wtm worktree status --snapshot/--compare`: save the status of every
+//! worktree and report what changed since an earlier save, so refreshing
+//! dashboards can consume status as a change feed.
+//!
+//! A snapshot is the full `status --json` array, so `status --json > file`
+//! (with or without `--envelope`) works as a baseline too.
+
+use super::status::json_status;
+use crate::{
+    cancel::CancellationToken, git::WorktreeInfo, output::Output, workspace::format_bytes,
+    wtm_paths::atomic_write_json,
+};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The parts of a `status --json` entry that a comparison looks at.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TelemetryEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub ahead: u32,
+    pub behind: u32,
+    pub staged: usize,
+    pub unstaged: usize,
+    pub untracked: usize,
+    pub conflicts: usize,
+    pub bytes: Option<u64>,
+}
+
+impl TelemetryEntry {
+    fn is_dirty(&self) -> bool {
+        self.staged + self.unstaged + self.untracked + self.conflicts > 0
+    }
+}
+
+/// What changed between two snapshots, matched up by worktree path.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TelemetryDiff {
+    /// Worktrees that only the newer snapshot has.
+    pub added: Vec<TelemetryWorktree>,
+    /// Worktrees that only the older snapshot has.
+    pub removed: Vec<TelemetryWorktree>,
+    pub changed: Vec<TelemetryChange>,
+}
+
+impl TelemetryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct TelemetryWorktree {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// A worktree present in both snapshots whose status moved.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TelemetryChange {
+    pub name: String,
+    pub path: PathBuf,
+    /// Clean before, uncommitted changes now.
+    pub newly_dirty: bool,
+    /// Uncommitted changes before, clean now.
+    pub newly_clean: bool,
+    pub ahead_delta: i64,
+    pub behind_delta: i64,
+    /// Growth in bytes; absent unless both snapshots measured the worktree.
+    pub bytes_delta: Option<i64>,
+}
+
+/// Compare `old` with `new`; changes keep the order of `new`.
+pub fn diff_telemetry(old: &[TelemetryEntry], new: &[TelemetryEntry]) -> TelemetryDiff {
+    let before: HashMap<&Path, &TelemetryEntry> = old
+        .iter()
+        .map(|entry| (entry.path.as_path(), entry))
+        .collect();
+    let after: HashMap<&Path, &TelemetryEntry> = new
+        .iter()
+        .map(|entry| (entry.path.as_path(), entry))
+        .collect();
+    let worktree = |entry: &TelemetryEntry| TelemetryWorktree {
+        name: entry.name.clone(),
+        path: entry.path.clone(),
+    };
+
+    let mut diff = TelemetryDiff::default();
+    for entry in new {
+        let Some(previous) = before.get(entry.path.as_path()) else {
+            diff.added.push(worktree(entry));
+            continue;
+        };
+        let change = TelemetryChange {
+            name: entry.name.clone(),
+            path: entry.path.clone(),
+            newly_dirty: entry.is_dirty() && !previous.is_dirty(),
+            newly_clean: !entry.is_dirty() && previous.is_dirty(),
+            ahead_delta: i64::from(entry.ahead) - i64::from(previous.ahead),
+            behind_delta: i64::from(entry.behind) - i64::from(previous.behind),
+            bytes_delta: entry
+                .bytes
+                .zip(previous.bytes)
+                .map(|(now, then)| now as i64 - then as i64)
+                .filter(|delta| *delta != 0),
+        };
+        let moved = change.newly_dirty
+            || change.newly_clean
+            || change.ahead_delta != 0
+            || change.behind_delta != 0
+            || change.bytes_delta.is_some();
+        if moved {
+            diff.changed.push(change);
+        }
+    }
+    diff.removed = old
+        .iter()
+        .filter(|entry| !after.contains_key(entry.path.as_path()))
+        .map(worktree)
+        .collect();
+    diff
+}
+
+/// Compare against the snapshot at `compare` and/or save the current status to
+/// `snapshot`. Comparing first lets one file serve as a rolling baseline.
+/// Nothing is compared or saved when `cancel` is set while reading the status,
+/// since a partial snapshot would show every missing worktree as removed.
+pub fn telemetry_command(
+    worktrees: &[WorktreeInfo],
+    compare: Option<&Path>,
+    snapshot: Option<&Path>,
+    json: bool,
+    cancel: &CancellationToken,
+    out: Output,
+) -> Result<()> {
+    let current = json_status(worktrees, None, None, cancel, out)?;
+    if cancel.is_cancelled() {
+        bail!("cancelled before every worktree was read; nothing was compared or saved");
+    }
+    if let Some(path) = compare {
+        let old = read_snapshot(path)?;
+        let new: Vec<TelemetryEntry> = serde_json::from_value(current.clone())?;
+        let diff = diff_telemetry(&old, &new);
+        if json {
+            out.print_json(&diff)?;
+        } else if diff.is_empty() {
+            println!("No changes since {}", path.display());
+        } else {
+            for line in diff_lines(&diff) {
+                println!("{line}");
+            }
+        }
+    }
+    if let Some(path) = snapshot {
+        atomic_write_json(path, &current)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        if !json {
+            out.status(format!(
+                "Saved the status of {} worktree(s) to {}",
+                worktrees.len(),
+                path.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Read a snapshot, unwrapping it if it was saved with `--envelope`.
+fn read_snapshot(path: &Path) -> Result<Vec<TelemetryEntry>> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut value: Value = serde_json::from_str(&text)
+        .with_context(|| format!("{} is not a status snapshot", path.display()))?;
+    if let Some(data) = value.get_mut("data") {
+        value = data.take();
+    }
+    serde_json::from_value(value)
+        .with_context(|| format!("{} is not a status snapshot", path.display()))
+}
+
+/// `+ name (new)`, `- name (removed)` and `~ name: now dirty, ahead +2, …` lines.
+pub fn diff_lines(diff: &TelemetryDiff) -> Vec<String> {
+    let mut lines: Vec<String> = diff
+        .added
+        .iter()
+        .map(|wt| format!("+ {} (new)", wt.name))
+        .collect();
+    lines.extend(
+        diff.removed
+            .iter()
+            .map(|wt| format!("- {} (removed)", wt.name)),
+    );
+    for change in &diff.changed {
+        let mut parts = Vec::new();
+        if change.newly_dirty {
+            parts.push("now dirty".to_string());
+        }
+        if change.newly_clean {
+            parts.push("now clean".to_string());
+        }
+        if change.ahead_delta != 0 {
+            parts.push(format!("ahead {:+}", change.ahead_delta));
+        }
+        if change.behind_delta != 0 {
+            parts.push(format!("behind {:+}", change.behind_delta));
+        }
+        if let Some(delta) = change.bytes_delta {
+            let sign = if delta < 0 { '-' } else { '+' };
+            parts.push(format!("size {sign}{}", format_bytes(delta.unsigned_abs())));
+        }
+        lines.push(format!("~ {}: {}", change.name, parts.join(", ")));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str) -> TelemetryEntry {
+        TelemetryEntry {
+            name: name.into(),
+            path: PathBuf::from("/repo/.wtm/workspaces").join(name),
+            bytes: Some(4096),
+            ..TelemetryEntry::default()
+        }
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_worktrees() {
+        let old = [entry("stays"), entry("gone"), entry("busy")];
+        let mut busy = entry("busy");
+        busy.unstaged = 2;
+        busy.ahead = 3;
+        busy.bytes = Some(4096 + 2048);
+        let new = [entry("stays"), busy, entry("fresh")];
+
+        let diff = diff_telemetry(&old, &new);
+        let names = |worktrees: &[TelemetryWorktree]| -> Vec<String> {
+            worktrees.iter().map(|wt| wt.name.clone()).collect()
+        };
+        assert_eq!(names(&diff.added), ["fresh"]);
+        assert_eq!(names(&diff.removed), ["gone"]);
+        assert_eq!(
+            diff.changed,
+            [TelemetryChange {
+                name: "busy".into(),
+                path: PathBuf::from("/repo/.wtm/workspaces/busy"),
+                newly_dirty: true,
+                ahead_delta: 3,
+                bytes_delta: Some(2048),
+                ..TelemetryChange::default()
+            }]
+        );
+        assert_eq!(
+            diff_lines(&diff),
+            [
+                "+ fresh (new)",
+                "- gone (removed)",
+                "~ busy: now dirty, ahead +3, size +2.0 KiB",
+            ]
+        );
+
+        let back = diff_telemetry(&new, &old);
+        assert!(back.changed[0].newly_clean);
+        assert_eq!(back.changed[0].ahead_delta, -3);
+        assert_eq!(
+            diff_lines(&back)[2],
+            "~ busy: now clean, ahead -3, size -2.0 KiB"
+        );
+        assert!(diff_telemetry(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn status_json_reads_as_a_snapshot() {
+        let json = serde_json::json!([{
+            "name": "x",
+            "path": "/x",
+            "branch": "x",
+            "upstream": null,
+            "upstream_gone": false,
+            "ahead": 1,
+            "behind": 0,
+            "staged": 0,
+            "unstaged": 0,
+            "untracked": 1,
+            "conflicts": 0,
+        }, { "name": "broken", "path": "/broken", "error": "not a git repository" }]);
+        let entries: Vec<TelemetryEntry> = serde_json::from_value(json).unwrap();
+        assert!(entries[0].is_dirty());
+        assert_eq!(entries[0].bytes, None);
+        assert!(!entries[1].is_dirty());
+    }
+}
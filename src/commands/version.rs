@@ -0,0 +1,141 @@
+//! `wtm version`: build metadata plus the external tools WTM shells out to.
+
+use crate::{
+    output::Output,
+    runner::{CommandRunner, SystemRunner},
+};
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use std::ffi::OsStr;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const GIT_COMMIT: &str = env!("WTM_GIT_COMMIT");
+const RUSTC_VERSION: &str = env!("WTM_RUSTC_VERSION");
+
+/// External tools probed by `wtm version --verbose`, with the flag that prints their version.
+const TOOLS: &[(&str, &[&str])] = &[
+    ("git", &["--version"]),
+    ("docker", &["--version"]),
+    ("acli", &["--version"]),
+];
+
+/// Version of an external tool, or `None` when it is missing or fails to report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolVersion {
+    pub name: &'static str,
+    pub version: Option<String>,
+}
+
+/// Print the WTM version, optionally with build and tool details.
+pub fn version_command(verbose: bool, json: bool, out: Output) -> Result<()> {
+    let tools = if verbose {
+        detect_tools(&SystemRunner)
+    } else {
+        Vec::new()
+    };
+    if json {
+        let report = version_json(verbose, &tools);
+        out.print_json(&report)?;
+    } else {
+        println!("wtm {VERSION}");
+        if verbose {
+            println!("commit: {GIT_COMMIT}");
+            println!("rustc: {RUSTC_VERSION}");
+            for tool in &tools {
+                println!(
+                    "{}: {}",
+                    tool.name,
+                    tool.version.as_deref().unwrap_or("not found")
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Query every entry in [`TOOLS`] for its version string.
+pub fn detect_tools(runner: &dyn CommandRunner) -> Vec<ToolVersion> {
+    TOOLS
+        .iter()
+        .map(|(name, args)| ToolVersion {
+            name,
+            version: tool_version(runner, name, args),
+        })
+        .collect()
+}
+
+fn tool_version(runner: &dyn CommandRunner, program: &str, args: &[&str]) -> Option<String> {
+    let args = args.iter().copied().map(OsStr::new).collect::<Vec<_>>();
+    let output = runner.run(program.as_ref(), &args, None, &[]).ok()?;
+    if !output.success {
+        return None;
+    }
+    output
+        .stdout
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+fn version_json(verbose: bool, tools: &[ToolVersion]) -> Value {
+    let mut report = json!({ "version": VERSION });
+    if verbose {
+        let tool_map: Map<String, Value> = tools
+            .iter()
+            .map(|tool| (tool.name.to_string(), json!(tool.version)))
+            .collect();
+        report["commit"] = json!(GIT_COMMIT);
+        report["rustc"] = json!(RUSTC_VERSION);
+        report["os"] = json!(std::env::consts::OS);
+        report["arch"] = json!(std::env::consts::ARCH);
+        report["tools"] = Value::Object(tool_map);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::testing::ScriptedRunner;
+
+    #[test]
+    fn detect_tools_reports_first_line_and_missing_tools() {
+        let runner = ScriptedRunner::default()
+            .succeed("git version 2.43.0\n")
+            .fail("docker: command not found")
+            .succeed("\nacli version 1.2.3\nextra\n");
+        let tools = detect_tools(&runner);
+        assert_eq!(
+            tools,
+            vec![
+                ToolVersion {
+                    name: "git",
+                    version: Some("git version 2.43.0".into()),
+                },
+                ToolVersion {
+                    name: "docker",
+                    version: None,
+                },
+                ToolVersion {
+                    name: "acli",
+                    version: Some("acli version 1.2.3".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn version_json_includes_tools_only_when_verbose() {
+        let tools = vec![ToolVersion {
+            name: "git",
+            version: None,
+        }];
+        let short = version_json(false, &tools);
+        assert_eq!(short, json!({ "version": VERSION }));
+
+        let full = version_json(true, &tools);
+        assert_eq!(full["tools"]["git"], Value::Null);
+        assert_eq!(full["commit"], GIT_COMMIT);
+    }
+}
@@ -1,6 +1,24 @@
-use anyhow::{Context, Result};
-use serde::Deserialize;
-use std::{fs, path::Path};
+use crate::{
+    docker::DockerOptions,
+    git::WorktreeInfo,
+    ignore::IgnoreList,
+    wtm_paths::{
+        validate_path_template, workspace_root, PathLayout, DEFAULT_MAX_SLUG_LENGTH,
+        MIN_SLUG_LENGTH,
+    },
+};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Config file names inside `.wtm/`, in precedence order; the first one present is used.
+pub const CONFIG_FILE_NAMES: &[&str] = &["config.toml", "config.yaml", "config.yml", "config.json"];
 
 #[derive(Clone, Debug)]
 pub struct QuickAction {
@@ -8,10 +26,232 @@ pub struct QuickAction {
     pub command: String,
 }
 
+/// Settings loaded from `.wtm/config.{toml,yaml,yml,json}`.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub quick_actions: Vec<QuickAction>,
+    /// How long finished terminal tabs linger before being closed.
+    ///
+    /// `None` closes them immediately; a zero duration keeps them open forever.
+    pub auto_close_finished_tabs_after: Option<Duration>,
+    /// Where the primary (repository root) worktree is listed.
+    pub primary_position: PrimaryPosition,
+    /// How worktree paths are shown in the dashboards and CLI listings.
+    pub path_display: PathDisplay,
+    /// Worktrees hidden from listings via `.wtm/ignore`.
+    pub ignore: IgnoreList,
+    /// Per-branch defaults for upstream tracking when creating from a remote branch.
+    pub tracking: TrackingRules,
+    /// Command run in every newly created worktree (a TUI tab, or inline for the CLI).
+    pub post_create_command: Option<String>,
+    /// Force-removing a dirty worktree in the TUI requires typing its name.
+    pub confirm_force_remove: bool,
+    /// `{ticket}`/`{branch}` layout for new worktrees under `.wtm/workspaces`.
+    pub workspace_path_template: Option<String>,
+    /// Longest directory name a branch is turned into; `None` uses
+    /// [`DEFAULT_MAX_SLUG_LENGTH`].
+    pub max_slug_length: Option<usize>,
+    /// Extra environment for every terminal tab, with `${VAR}` references expanded.
+    pub terminal_env: BTreeMap<String, String>,
+    /// Register new worktrees with `git worktree add --relative-paths` when git supports it.
+    pub relative_worktree_paths: bool,
+    /// What new terminal tabs attach to.
+    pub session: TerminalSession,
+    /// Git hooks directory set as `core.hooksPath` in new worktrees.
+    pub hooks_path: Option<String>,
+    /// What `worktree add --track-issue` does to the branch's Jira ticket.
+    pub jira: JiraSettings,
+    /// TUI status bar template with `{workspace}`, `{branch}`, `{ahead}`, … tokens.
+    pub status_line: Option<String>,
+    /// Compose files and project name for the `docker compose` commands.
+    pub docker: DockerOptions,
+    /// Start point for new branches created without `--from`, or `auto` for
+    /// the remote's default branch.
+    pub default_upstream: Option<String>,
+}
+
+impl Config {
+    /// Every setting as it is in effect, keyed like the config file.
+    ///
+    /// Includes are merged, `${VAR}` references expanded, and defaults filled in;
+    /// `workspacesDir` is where `repo_root`'s worktrees are created.
+    pub fn effective_json(&self, repo_root: &Path) -> Value {
+        json!({
+            "quickAccess": self
+                .quick_actions
+                .iter()
+                .map(|action| json!({ "label": action.label, "quickCommand": action.command }))
+                .collect::<Vec<_>>(),
+            "autoCloseFinishedTabsAfterSeconds": self
+                .auto_close_finished_tabs_after
+                .map(|after| after.as_secs()),
+            "primaryPosition": self.primary_position,
+            "pathDisplay": self.path_display,
+            "upstreamTracking": self
+                .tracking
+                .rules
+                .iter()
+                .map(|(pattern, track)| json!({ "pattern": pattern.as_str(), "track": track }))
+                .collect::<Vec<_>>(),
+            "postCreateCommand": self.post_create_command,
+            "confirmForceRemove": self.confirm_force_remove,
+            "workspacePathTemplate": self.workspace_path_template,
+            "maxSlugLength": self.path_layout().max_slug_length,
+            "workspacesDir": workspace_root(repo_root),
+            "terminalEnv": self.terminal_env,
+            "relativeWorktreePaths": self.relative_worktree_paths,
+            "session": self.session,
+            "hooksPath": self.hooks_path,
+            "jira": self.jira,
+            "statusLine": self.status_line,
+            "docker": self.docker,
+            "defaultUpstream": self.default_upstream,
+            "ignore": self.ignore.patterns().collect::<Vec<_>>(),
+        })
+    }
+
+    /// How new worktree directories are named: `workspacePathTemplate` and
+    /// `maxSlugLength`.
+    pub fn path_layout(&self) -> PathLayout<'_> {
+        PathLayout {
+            template: self.workspace_path_template.as_deref(),
+            max_slug_length: self.max_slug_length.unwrap_or(DEFAULT_MAX_SLUG_LENGTH),
+        }
+    }
+}
+
+/// Ordered `glob → track` rules from `upstreamTracking`; the first match wins.
+#[derive(Clone, Debug, Default)]
+pub struct TrackingRules {
+    rules: Vec<(glob::Pattern, bool)>,
+}
+
+impl TrackingRules {
+    /// Whether a new `branch` should track its upstream, or `None` to use git's default.
+    pub fn tracking_policy(&self, branch: &str) -> Option<bool> {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| pattern.matches(branch))
+            .map(|(_, track)| *track)
+    }
+
+    /// Combine an explicit `--track`/`--no-track` choice with the configured policy.
+    pub fn resolve(&self, branch: &str, explicit: Option<bool>) -> Option<bool> {
+        explicit.or_else(|| self.tracking_policy(branch))
+    }
+}
+
+/// The `jira` section of the config file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JiraSettings {
+    /// Status a ticket moves to when `--track-issue` creates its worktree.
+    #[serde(default, rename = "onCreateTransition")]
+    pub on_create_transition: Option<String>,
+}
+
+/// Rendering style for worktree paths shown to the user.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathDisplay {
+    /// Full absolute paths.
+    #[default]
+    Absolute,
+    /// Relative to the directory containing the repository root.
+    Relative,
+    /// Absolute, with `$HOME` abbreviated to `~`.
+    Tilde,
+}
+
+/// What a new terminal tab runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalSession {
+    /// A fresh shell that ends with the tab.
+    #[default]
+    Shell,
+    /// The worktree's `wtm-<name>-<repo hash>` tmux session, created on first
+    /// use, which survives wtm restarts.
+    Tmux,
+}
+
+/// Placement of the primary worktree in worktree lists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrimaryPosition {
+    /// Keep the order reported by `git worktree list`.
+    #[default]
+    Natural,
+    Top,
+    Bottom,
+}
+
+impl PrimaryPosition {
+    /// Move the worktree at `repo_root` to the configured end, keeping the rest in order.
+    pub fn apply(self, worktrees: &mut [WorktreeInfo], repo_root: &Path) {
+        let rank = |wt: &WorktreeInfo| match (self, wt.path == repo_root) {
+            (PrimaryPosition::Top, true) => 0,
+            (PrimaryPosition::Bottom, true) => 2,
+            _ => 1,
+        };
+        worktrees.sort_by_key(rank);
+    }
+}
+
 #[derive(Deserialize)]
 struct ConfigFile {
     #[serde(default, rename = "quickAccess")]
     quick_access: Vec<QuickAccessEntry>,
+    #[serde(default, rename = "autoCloseFinishedTabsAfterSeconds")]
+    auto_close_finished_tabs_after_seconds: Option<u64>,
+    #[serde(default, rename = "primaryPosition")]
+    primary_position: PrimaryPosition,
+    #[serde(default, rename = "pathDisplay")]
+    path_display: PathDisplay,
+    #[serde(default, rename = "upstreamTracking")]
+    upstream_tracking: Vec<TrackingEntry>,
+    #[serde(default, rename = "postCreateCommand")]
+    post_create_command: Option<String>,
+    #[serde(default, rename = "unresolvedEnv")]
+    unresolved_env: UnresolvedEnv,
+    #[serde(default, rename = "confirmForceRemove")]
+    confirm_force_remove: bool,
+    #[serde(default, rename = "workspacePathTemplate")]
+    workspace_path_template: Option<String>,
+    #[serde(default, rename = "maxSlugLength")]
+    max_slug_length: Option<usize>,
+    #[serde(default, rename = "terminalEnv")]
+    terminal_env: BTreeMap<String, String>,
+    #[serde(default, rename = "relativeWorktreePaths")]
+    relative_worktree_paths: bool,
+    #[serde(default)]
+    session: TerminalSession,
+    #[serde(default, rename = "hooksPath")]
+    hooks_path: Option<String>,
+    #[serde(default)]
+    jira: JiraSettings,
+    #[serde(default, rename = "statusLine")]
+    status_line: Option<String>,
+    #[serde(default)]
+    docker: DockerOptions,
+    #[serde(default, rename = "defaultUpstream")]
+    default_upstream: Option<String>,
+}
+
+/// What to do with a `${VAR}` reference whose variable is not set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum UnresolvedEnv {
+    /// Keep `${VAR}` as written, e.g. so the shell can expand it later.
+    #[default]
+    Literal,
+    /// Fail to load the config.
+    Error,
+}
+
+#[derive(Deserialize)]
+struct TrackingEntry {
+    pattern: String,
+    track: bool,
 }
 
 #[derive(Deserialize)]
@@ -24,39 +264,384 @@ struct QuickAccessEntry {
     entry_type: Option<String>,
 }
 
-/// Load quick-action definitions from `.wtm/config.json`.
-pub fn load_quick_actions(wtm_dir: &Path) -> Result<Vec<QuickAction>> {
-    let config_path = wtm_dir.join("config.json");
-    let data = match fs::read_to_string(&config_path) {
-        Ok(data) => data,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
-        Err(err) => {
-            return Err(err).with_context(|| format!("failed to read {}", config_path.display()))
+/// The config file that [`load_config`] reads, or `None` when there is none.
+pub fn config_path(wtm_dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| wtm_dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Per-user config directory shared by every repository: `$XDG_CONFIG_HOME/wtm`,
+/// else `%APPDATA%\wtm` on Windows or `~/.config/wtm` elsewhere.
+pub fn global_config_dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    let base = var("XDG_CONFIG_HOME").map(PathBuf::from).or_else(|| {
+        if cfg!(windows) {
+            var("APPDATA").map(PathBuf::from)
+        } else {
+            var("HOME").map(|home| PathBuf::from(home).join(".config"))
         }
-    };
+    })?;
+    Some(base.join("wtm"))
+}
+
+/// The global config file layered under every repository's, if there is one.
+pub fn global_config_path() -> Option<PathBuf> {
+    global_config_dir().and_then(|dir| config_path(&dir))
+}
+
+/// Load the global config, the `.wtm` config file over it, and `.wtm/ignore`,
+/// falling back to defaults when missing.
+pub fn load_config(wtm_dir: &Path) -> Result<Config> {
+    load_layered_config(global_config_path().as_deref(), wtm_dir)
+}
+
+/// [`load_config`] with `global` (if any) as the bottom layer; repository keys win.
+fn load_layered_config(global: Option<&Path>, wtm_dir: &Path) -> Result<Config> {
+    let ignore = IgnoreList::load(wtm_dir)?;
+    let layers: Vec<PathBuf> = [global.map(Path::to_path_buf), config_path(wtm_dir)]
+        .into_iter()
+        .flatten()
+        .collect();
+    config_from_layers(&layers, ignore)
+}
+
+/// Check that the config file at `path` loads on its own, e.g. after editing it.
+pub fn validate_config_file(path: &Path) -> Result<()> {
+    config_from_layers(&[path.to_path_buf()], IgnoreList::default()).map(drop)
+}
 
-    let parsed: ConfigFile = serde_json::from_str(&data)
+/// Merge `layers` in order (later files win) and build the [`Config`].
+fn config_from_layers(layers: &[PathBuf], ignore: IgnoreList) -> Result<Config> {
+    let Some(config_path) = layers.last() else {
+        return Ok(Config {
+            ignore,
+            ..Config::default()
+        });
+    };
+    let mut value = serde_json::Value::Object(Default::default());
+    let mut sources = HashMap::new();
+    for layer in layers {
+        let layer_value = load_config_value(layer, &mut Vec::new())?;
+        // Type errors are reported against the layer that contains them.
+        serde_json::from_value::<ConfigFile>(layer_value.clone())
+            .with_context(|| format!("failed to parse {}", layer.display()))?;
+        record_sources(&layer_value, layer, &mut sources);
+        merge_config_values(&mut value, layer_value);
+    }
+    let parsed: ConfigFile = serde_json::from_value(value)
         .with_context(|| format!("failed to parse {}", config_path.display()))?;
+    // The file that last set `key` (or `key.entry`), for errors about its value.
+    let source = |key: &str| -> String {
+        sources
+            .get(key)
+            .copied()
+            .unwrap_or(config_path.as_path())
+            .display()
+            .to_string()
+    };
+    let env_lookup = |name: &str| std::env::var(name).ok();
 
     let mut actions = Vec::new();
     for entry in parsed.quick_access {
         if let Some(command) = entry.quick_command.as_deref() {
             let ty = entry.entry_type.as_deref().unwrap_or("command");
             if ty == "command" {
+                // Fall back to the command as written so expanded secrets stay off screen.
                 let label = entry
                     .label
                     .filter(|s| !s.trim().is_empty())
                     .map(|s| s.trim().to_string())
                     .unwrap_or_else(|| command.to_string());
-                actions.push(QuickAction {
-                    label,
-                    command: command.to_string(),
-                });
+                let command = expand_env(command, &env_lookup, parsed.unresolved_env)
+                    .with_context(|| {
+                        format!("quick action `{label}` in {}", source("quickAccess"))
+                    })?;
+                actions.push(QuickAction { label, command });
+            }
+        }
+    }
+
+    let mut tracking = TrackingRules::default();
+    for entry in parsed.upstream_tracking {
+        let pattern = glob::Pattern::new(&entry.pattern).with_context(|| {
+            format!(
+                "invalid upstreamTracking pattern `{}` in {}",
+                entry.pattern,
+                source("upstreamTracking")
+            )
+        })?;
+        tracking.rules.push((pattern, entry.track));
+    }
+
+    let workspace_path_template = parsed
+        .workspace_path_template
+        .filter(|template| !template.trim().is_empty())
+        .map(|template| {
+            validate_path_template(&template)
+                .map(|()| template)
+                .map_err(|err| {
+                    anyhow!(
+                        "invalid workspacePathTemplate in {}: {err}",
+                        source("workspacePathTemplate")
+                    )
+                })
+        })
+        .transpose()?;
+
+    if let Some(max) = parsed.max_slug_length.filter(|&max| max < MIN_SLUG_LENGTH) {
+        bail!(
+            "invalid maxSlugLength in {}: {max} is shorter than the minimum of {MIN_SLUG_LENGTH}",
+            source("maxSlugLength")
+        );
+    }
+
+    let post_create_command = parsed
+        .post_create_command
+        .filter(|command| !command.trim().is_empty())
+        .map(|command| {
+            expand_env(&command, &env_lookup, parsed.unresolved_env)
+                .with_context(|| format!("postCreateCommand in {}", source("postCreateCommand")))
+        })
+        .transpose()?;
+
+    let terminal_env = parsed
+        .terminal_env
+        .into_iter()
+        .map(|(key, value)| {
+            let value =
+                expand_env(&value, &env_lookup, parsed.unresolved_env).with_context(|| {
+                    format!(
+                        "terminalEnv `{key}` in {}",
+                        source(&format!("terminalEnv.{key}"))
+                    )
+                })?;
+            Ok((key, value))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Config {
+        quick_actions: actions,
+        auto_close_finished_tabs_after: parsed
+            .auto_close_finished_tabs_after_seconds
+            .map(Duration::from_secs),
+        primary_position: parsed.primary_position,
+        path_display: parsed.path_display,
+        ignore,
+        tracking,
+        post_create_command,
+        confirm_force_remove: parsed.confirm_force_remove,
+        workspace_path_template,
+        max_slug_length: parsed.max_slug_length,
+        terminal_env,
+        relative_worktree_paths: parsed.relative_worktree_paths,
+        session: parsed.session,
+        hooks_path: parsed.hooks_path.filter(|path| !path.trim().is_empty()),
+        jira: JiraSettings {
+            on_create_transition: parsed
+                .jira
+                .on_create_transition
+                .filter(|status| !status.trim().is_empty()),
+        },
+        status_line: parsed.status_line.filter(|line| !line.trim().is_empty()),
+        docker: DockerOptions {
+            compose_files: parsed
+                .docker
+                .compose_files
+                .into_iter()
+                .filter(|file| !file.trim().is_empty())
+                .collect(),
+            project_name: parsed
+                .docker
+                .project_name
+                .filter(|name| !name.trim().is_empty()),
+        },
+        default_upstream: parsed
+            .default_upstream
+            .filter(|upstream| !upstream.trim().is_empty()),
+    })
+}
+
+/// Read `path` and the files named in its `include` array, merged in order.
+///
+/// Includes are resolved relative to the including file and applied before
+/// the file's own keys, so local values win. `stack` guards against cycles.
+fn load_config_value(path: &Path, stack: &mut Vec<PathBuf>) -> Result<serde_json::Value> {
+    if stack.iter().any(|seen| seen == path) {
+        bail!("config include cycle through {}", path.display());
+    }
+    let data =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut value = parse_config_file(path, &data)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+
+    let includes: Vec<String> = match value.as_object_mut().and_then(|obj| obj.remove("include")) {
+        Some(includes) => serde_json::from_value(includes)
+            .with_context(|| format!("`include` in {} must be a list of paths", path.display()))?,
+        None => Vec::new(),
+    };
+    if includes.is_empty() {
+        return Ok(value);
+    }
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut merged = serde_json::Value::Object(Default::default());
+    stack.push(path.to_path_buf());
+    for include in &includes {
+        let include_path = dir.join(include);
+        if !include_path.is_file() {
+            bail!(
+                "included config `{include}` not found (referenced from {})",
+                path.display()
+            );
+        }
+        merge_config_values(&mut merged, load_config_value(&include_path, stack)?);
+    }
+    stack.pop();
+    merge_config_values(&mut merged, value);
+    Ok(merged)
+}
+
+/// Note `layer` as the source of each top-level key in `value`, and of each
+/// entry of the keys that hold objects (`terminalEnv.NAME`).
+fn record_sources<'a>(
+    value: &serde_json::Value,
+    layer: &'a Path,
+    sources: &mut HashMap<String, &'a Path>,
+) {
+    let Some(object) = value.as_object() else {
+        return;
+    };
+    for (key, entry) in object {
+        sources.insert(key.clone(), layer);
+        for nested in entry
+            .as_object()
+            .into_iter()
+            .flat_map(|entries| entries.keys())
+        {
+            sources.insert(format!("{key}.{nested}"), layer);
+        }
+    }
+}
+
+/// Overlay `overlay` onto `base`: objects merge key by key, anything else is replaced.
+fn merge_config_values(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_config_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
             }
         }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Replace `${VAR}` references in `input`; `$${` produces a literal `${`.
+fn expand_env(
+    input: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    unresolved: UnresolvedEnv,
+) -> Result<String> {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(escaped) = tail.strip_prefix("$${") {
+            result.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let reference = tail
+            .strip_prefix("${")
+            .and_then(|body| body.find('}').map(|end| &body[..end]));
+        match reference {
+            Some(name) => {
+                match lookup(name) {
+                    Some(value) => result.push_str(&value),
+                    None if unresolved == UnresolvedEnv::Error => {
+                        bail!("environment variable `{name}` is not set")
+                    }
+                    None => result.push_str(&tail[..name.len() + 3]),
+                }
+                rest = &tail[name.len() + 3..];
+            }
+            None => {
+                result.push('$');
+                rest = &tail[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Parse `data` according to the extension of `path`.
+///
+/// TOML and YAML are converted to a JSON value so every format shares the
+/// `serde` definitions (and camelCase keys) of [`ConfigFile`].
+fn parse_config_file(path: &Path, data: &str) -> Result<serde_json::Value> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => {
+            let document = toml_edit::Document::parse(data.to_string())?;
+            Ok(toml_table_to_json(document.as_table()))
+        }
+        Some("yaml" | "yml") => match serde_yaml_ng::from_str(data)? {
+            // An empty (or comment-only) document sets nothing.
+            serde_json::Value::Null => Ok(serde_json::Value::Object(Default::default())),
+            value => Ok(value),
+        },
+        _ => Ok(serde_json::from_str(data)?),
+    }
+}
+
+fn toml_table_to_json<'a>(
+    entries: impl IntoIterator<Item = (&'a str, &'a toml_edit::Item)>,
+) -> serde_json::Value {
+    serde_json::Value::Object(
+        entries
+            .into_iter()
+            .map(|(key, item)| (key.to_string(), toml_item_to_json(item)))
+            .collect(),
+    )
+}
+
+fn toml_item_to_json(item: &toml_edit::Item) -> serde_json::Value {
+    use toml_edit::Item;
+    match item {
+        Item::None => serde_json::Value::Null,
+        Item::Value(value) => toml_value_to_json(value),
+        Item::Table(table) => toml_table_to_json(table.iter()),
+        Item::ArrayOfTables(tables) => tables
+            .iter()
+            .map(|table| toml_table_to_json(table.iter()))
+            .collect(),
     }
+}
 
-    Ok(actions)
+fn toml_value_to_json(value: &toml_edit::Value) -> serde_json::Value {
+    use serde_json::Value as Json;
+    use toml_edit::Value;
+    match value {
+        Value::String(s) => Json::String(s.value().clone()),
+        Value::Integer(i) => Json::from(*i.value()),
+        Value::Float(f) => Json::from(*f.value()),
+        Value::Boolean(b) => Json::Bool(*b.value()),
+        Value::Datetime(d) => Json::String(d.value().to_string()),
+        Value::Array(array) => array.iter().map(toml_value_to_json).collect(),
+        Value::InlineTable(table) => Json::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), toml_value_to_json(value)))
+                .collect(),
+        ),
+    }
 }
 
 #[cfg(test)]
@@ -67,8 +652,9 @@ mod tests {
     #[test]
     fn load_quick_actions_missing_file_returns_empty() {
         let dir = tempdir().unwrap();
-        let actions = load_quick_actions(dir.path()).unwrap();
-        assert!(actions.is_empty());
+        let config = load_config(dir.path()).unwrap();
+        assert!(config.quick_actions.is_empty());
+        assert_eq!(config.auto_close_finished_tabs_after, None);
     }
 
     #[test]
@@ -96,11 +682,441 @@ mod tests {
         "#;
         std::fs::write(dir.path().join("config.json"), config).unwrap();
 
-        let actions = load_quick_actions(dir.path()).unwrap();
+        let actions = load_config(dir.path()).unwrap().quick_actions;
         assert_eq!(actions.len(), 2);
         assert_eq!(actions[0].label, "Deploy");
         assert_eq!(actions[0].command, "deploy.sh");
         assert_eq!(actions[1].label, "status.sh");
         assert_eq!(actions[1].command, "status.sh");
     }
+
+    fn labels_and_commands(actions: &[QuickAction]) -> Vec<(String, String)> {
+        actions
+            .iter()
+            .map(|action| (action.label.clone(), action.command.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn toml_and_json_configs_load_identically() {
+        let json_dir = tempdir().unwrap();
+        std::fs::write(
+            json_dir.path().join("config.json"),
+            r#"{
+                "quickAccess": [
+                    {"label": "Deploy", "quickCommand": "deploy.sh"},
+                    {"quickCommand": "status.sh", "type": "command"}
+                ],
+                "autoCloseFinishedTabsAfterSeconds": 5,
+                "primaryPosition": "bottom"
+            }"#,
+        )
+        .unwrap();
+        let toml_dir = tempdir().unwrap();
+        std::fs::write(
+            toml_dir.path().join("config.toml"),
+            r#"
+            # Comments are the reason to prefer TOML.
+            autoCloseFinishedTabsAfterSeconds = 5
+            primaryPosition = "bottom"
+
+            [[quickAccess]]
+            label = "Deploy"
+            quickCommand = "deploy.sh"
+
+            [[quickAccess]]
+            quickCommand = "status.sh"
+            type = "command"
+            "#,
+        )
+        .unwrap();
+
+        let json = load_config(json_dir.path()).unwrap();
+        let toml = load_config(toml_dir.path()).unwrap();
+        assert_eq!(
+            labels_and_commands(&toml.quick_actions),
+            labels_and_commands(&json.quick_actions)
+        );
+        assert_eq!(toml.quick_actions.len(), 2);
+        assert_eq!(
+            toml.auto_close_finished_tabs_after,
+            json.auto_close_finished_tabs_after
+        );
+        assert_eq!(toml.primary_position, PrimaryPosition::Bottom);
+    }
+
+    #[test]
+    fn config_toml_takes_precedence_over_json() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"pathDisplay": "tilde"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("config.toml"),
+            r#"pathDisplay = "relative""#,
+        )
+        .unwrap();
+        assert_eq!(
+            config_path(dir.path()),
+            Some(dir.path().join("config.toml"))
+        );
+        assert_eq!(
+            load_config(dir.path()).unwrap().path_display,
+            PathDisplay::Relative
+        );
+    }
+
+    #[test]
+    fn yaml_config_matches_json() {
+        let json_dir = tempdir().unwrap();
+        std::fs::write(
+            json_dir.path().join("config.json"),
+            r#"{"primaryPosition": "bottom",
+                "quickAccess": [{"label": "Deploy", "quickCommand": "deploy.sh"}]}"#,
+        )
+        .unwrap();
+        let yaml_dir = tempdir().unwrap();
+        std::fs::write(
+            yaml_dir.path().join("config.yml"),
+            "# Comments work here too.\nprimaryPosition: bottom\nquickAccess:\n  - label: Deploy\n    quickCommand: deploy.sh\n",
+        )
+        .unwrap();
+
+        let json = load_config(json_dir.path()).unwrap();
+        let yaml = load_config(yaml_dir.path()).unwrap();
+        assert_eq!(
+            labels_and_commands(&yaml.quick_actions),
+            labels_and_commands(&json.quick_actions)
+        );
+        assert_eq!(yaml.primary_position, PrimaryPosition::Bottom);
+
+        std::fs::write(yaml_dir.path().join("config.yml"), "# nothing yet\n").unwrap();
+        assert!(load_config(yaml_dir.path()).is_ok());
+    }
+
+    #[test]
+    fn config_yaml_sits_between_toml_and_json() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("config.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("config.yaml"), "{}").unwrap();
+        assert_eq!(
+            config_path(dir.path()),
+            Some(dir.path().join("config.yaml"))
+        );
+        std::fs::write(dir.path().join("config.toml"), "").unwrap();
+        assert_eq!(
+            config_path(dir.path()),
+            Some(dir.path().join("config.toml"))
+        );
+    }
+
+    #[test]
+    fn global_config_sits_under_the_repository_config() {
+        let global = tempdir().unwrap();
+        let global_path = global.path().join("config.json");
+        std::fs::write(
+            &global_path,
+            r#"{"pathDisplay": "tilde",
+                "quickAccess": [{"label": "Global", "quickCommand": "global.sh"}]}"#,
+        )
+        .unwrap();
+        let repo = tempdir().unwrap();
+
+        let config = load_layered_config(Some(&global_path), repo.path()).unwrap();
+        assert_eq!(config.quick_actions[0].label, "Global");
+        assert_eq!(config.path_display, PathDisplay::Tilde);
+
+        std::fs::write(
+            repo.path().join("config.json"),
+            r#"{"quickAccess": [{"label": "Repo", "quickCommand": "repo.sh"}]}"#,
+        )
+        .unwrap();
+        let config = load_layered_config(Some(&global_path), repo.path()).unwrap();
+        let labels: Vec<_> = config.quick_actions.iter().map(|a| &a.label).collect();
+        assert_eq!(labels, ["Repo"]);
+        assert_eq!(config.path_display, PathDisplay::Tilde);
+    }
+
+    #[test]
+    fn global_config_errors_name_the_global_file() {
+        let global = tempdir().unwrap();
+        let global_path = global.path().join("config.json");
+        let repo = tempdir().unwrap();
+        std::fs::write(
+            repo.path().join("config.json"),
+            r#"{"pathDisplay": "tilde"}"#,
+        )
+        .unwrap();
+        let error = |global_config: &str| {
+            std::fs::write(&global_path, global_config).unwrap();
+            format!(
+                "{:#}",
+                load_layered_config(Some(&global_path), repo.path()).unwrap_err()
+            )
+        };
+
+        let message = error(r#"{"upstreamTracking": [{"pattern": "[", "track": true}]}"#);
+        assert!(
+            message.contains(&global_path.display().to_string()),
+            "{message}"
+        );
+        let message = error(r#"{"primaryPosition": 7}"#);
+        assert!(
+            message.contains(&global_path.display().to_string()),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn includes_merge_before_local_config() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("team.json"),
+            r#"{"primaryPosition": "top", "pathDisplay": "tilde",
+                "quickAccess": [{"label": "Team", "quickCommand": "team.sh"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("extra.toml"), r#"pathDisplay = "relative""#).unwrap();
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"include": ["team.json", "extra.toml"], "primaryPosition": "bottom"}"#,
+        )
+        .unwrap();
+
+        let config = load_config(dir.path()).unwrap();
+        assert_eq!(config.primary_position, PrimaryPosition::Bottom);
+        assert_eq!(config.path_display, PathDisplay::Relative);
+        assert_eq!(config.quick_actions[0].command, "team.sh");
+    }
+
+    #[test]
+    fn effective_json_reports_merged_values_and_defaults() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("team.json"), r#"{"pathDisplay": "tilde"}"#).unwrap();
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"include": ["team.json"], "autoCloseFinishedTabsAfterSeconds": 30,
+                "upstreamTracking": [{"pattern": "release/*", "track": false}],
+                "terminalEnv": {"PROJECT": "wtm", "ESCAPED": "$${HOME}"}}"#,
+        )
+        .unwrap();
+
+        let effective = load_config(dir.path())
+            .unwrap()
+            .effective_json(Path::new("/work/shop"));
+        assert_eq!(effective["pathDisplay"], "tilde");
+        assert_eq!(effective["autoCloseFinishedTabsAfterSeconds"], 30);
+        assert_eq!(
+            effective["upstreamTracking"],
+            json!([{ "pattern": "release/*", "track": false }])
+        );
+        assert_eq!(
+            effective["terminalEnv"],
+            json!({ "PROJECT": "wtm", "ESCAPED": "${HOME}" })
+        );
+        assert_eq!(effective["primaryPosition"], "natural");
+        assert_eq!(effective["confirmForceRemove"], false);
+        assert_eq!(effective["session"], "shell");
+        assert_eq!(effective["postCreateCommand"], Value::Null);
+        assert_eq!(
+            effective["workspacesDir"],
+            json!(Path::new("/work/shop/.wtm/workspaces"))
+        );
+    }
+
+    #[test]
+    fn missing_include_is_reported() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"include": ["missing.json"]}"#,
+        )
+        .unwrap();
+        let err = format!("{:#}", load_config(dir.path()).unwrap_err());
+        assert!(
+            err.contains("included config `missing.json` not found"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn expand_env_substitutes_and_honours_policy() {
+        let lookup = |name: &str| (name == "TOKEN").then(|| "s3cret".to_string());
+        assert_eq!(
+            expand_env(
+                "deploy --token ${TOKEN} $HOME",
+                &lookup,
+                UnresolvedEnv::Error
+            )
+            .unwrap(),
+            "deploy --token s3cret $HOME"
+        );
+        assert_eq!(
+            expand_env("echo ${UNSET} $${TOKEN}", &lookup, UnresolvedEnv::Literal).unwrap(),
+            "echo ${UNSET} ${TOKEN}"
+        );
+        let err = expand_env("echo ${UNSET}", &lookup, UnresolvedEnv::Error).unwrap_err();
+        assert!(err.to_string().contains("`UNSET` is not set"));
+    }
+
+    #[test]
+    fn load_config_reads_post_create_command() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"postCreateCommand": "npm install"}"#,
+        )
+        .unwrap();
+        let config = load_config(dir.path()).unwrap();
+        assert_eq!(config.post_create_command.as_deref(), Some("npm install"));
+        assert_eq!(Config::default().post_create_command, None);
+
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"hooksPath": ".wtm/githooks"}"#,
+        )
+        .unwrap();
+        let config = load_config(dir.path()).unwrap();
+        assert_eq!(config.hooks_path.as_deref(), Some(".wtm/githooks"));
+        assert_eq!(
+            config.effective_json(Path::new("/repo"))["hooksPath"],
+            ".wtm/githooks"
+        );
+
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"jira": {"onCreateTransition": "In Progress"}}"#,
+        )
+        .unwrap();
+        let config = load_config(dir.path()).unwrap();
+        assert_eq!(
+            config.jira.on_create_transition.as_deref(),
+            Some("In Progress")
+        );
+        assert_eq!(
+            config.effective_json(Path::new("/repo"))["jira"]["onCreateTransition"],
+            "In Progress"
+        );
+    }
+
+    #[test]
+    fn load_config_reads_auto_close_delay() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"autoCloseFinishedTabsAfterSeconds": 30}"#,
+        )
+        .unwrap();
+        let config = load_config(dir.path()).unwrap();
+        assert_eq!(
+            config.auto_close_finished_tabs_after,
+            Some(Duration::from_secs(30))
+        );
+        assert!(!config.confirm_force_remove);
+    }
+
+    #[test]
+    fn load_config_rejects_bad_path_template() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"workspacePathTemplate": "{ticket}/{brnch}"}"#,
+        )
+        .unwrap();
+        let err = format!("{:#}", load_config(dir.path()).unwrap_err());
+        assert!(err.contains("invalid workspacePathTemplate"), "{err}");
+        assert!(err.contains("{brnch}"), "{err}");
+    }
+
+    #[test]
+    fn max_slug_length_defaults_and_rejects_tiny_limits() {
+        let dir = tempdir().unwrap();
+        assert_eq!(
+            Config::default().path_layout().max_slug_length,
+            DEFAULT_MAX_SLUG_LENGTH
+        );
+        std::fs::write(dir.path().join("config.json"), r#"{"maxSlugLength": 32}"#).unwrap();
+        let config = load_config(dir.path()).unwrap();
+        assert_eq!(config.path_layout().max_slug_length, 32);
+        assert_eq!(
+            config.effective_json(Path::new("/repo"))["maxSlugLength"],
+            32
+        );
+
+        std::fs::write(dir.path().join("config.json"), r#"{"maxSlugLength": 4}"#).unwrap();
+        let err = format!("{:#}", load_config(dir.path()).unwrap_err());
+        assert!(err.contains("invalid maxSlugLength"), "{err}");
+    }
+
+    fn paths(worktrees: &[WorktreeInfo]) -> Vec<&str> {
+        worktrees
+            .iter()
+            .map(|wt| wt.path.to_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn primary_position_reorders_primary_worktree() {
+        let repo = Path::new("/repo");
+        let mut worktrees = vec![
+            WorktreeInfo::at("/ws/a"),
+            WorktreeInfo::at("/repo"),
+            WorktreeInfo::at("/ws/b"),
+        ];
+
+        PrimaryPosition::Natural.apply(&mut worktrees, repo);
+        assert_eq!(paths(&worktrees), ["/ws/a", "/repo", "/ws/b"]);
+
+        PrimaryPosition::Top.apply(&mut worktrees, repo);
+        assert_eq!(paths(&worktrees), ["/repo", "/ws/a", "/ws/b"]);
+
+        PrimaryPosition::Bottom.apply(&mut worktrees, repo);
+        assert_eq!(paths(&worktrees), ["/ws/a", "/ws/b", "/repo"]);
+    }
+
+    #[test]
+    fn load_config_reads_primary_position() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"primaryPosition": "top"}"#,
+        )
+        .unwrap();
+        let config = load_config(dir.path()).unwrap();
+        assert_eq!(config.primary_position, PrimaryPosition::Top);
+        assert_eq!(config.path_display, PathDisplay::Absolute);
+        assert_eq!(Config::default().primary_position, PrimaryPosition::Natural);
+    }
+
+    #[test]
+    fn tracking_policy_uses_first_matching_rule() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("config.json"),
+            r#"{"upstreamTracking": [
+                {"pattern": "release/legacy-*", "track": false},
+                {"pattern": "release/*", "track": true},
+                {"pattern": "tmp/*", "track": false}
+            ]}"#,
+        )
+        .unwrap();
+        let tracking = load_config(dir.path()).unwrap().tracking;
+
+        assert_eq!(tracking.tracking_policy("release/1.2"), Some(true));
+        assert_eq!(tracking.tracking_policy("release/legacy-1"), Some(false));
+        assert_eq!(tracking.tracking_policy("tmp/spike"), Some(false));
+        assert_eq!(tracking.tracking_policy("feature/x"), None);
+    }
+
+    #[test]
+    fn explicit_tracking_flag_overrides_policy() {
+        let tracking = TrackingRules {
+            rules: vec![(glob::Pattern::new("tmp/*").unwrap(), false)],
+        };
+        assert_eq!(tracking.resolve("tmp/spike", Some(true)), Some(true));
+        assert_eq!(tracking.resolve("tmp/spike", None), Some(false));
+        assert_eq!(tracking.resolve("feature/x", None), None);
+    }
 }
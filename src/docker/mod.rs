@@ -1,6 +1,11 @@
+use crate::{
+    git::WorktreeInfo,
+    parallel,
+    runner::{CommandRunner, SystemRunner},
+};
 use anyhow::{anyhow, Context, Result};
-use serde::Deserialize;
-use std::{path::Path, process::Command};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, ffi::OsStr, path::Path};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DockerContainer {
@@ -9,36 +14,284 @@ pub struct DockerContainer {
     pub status: String,
 }
 
-pub fn compose_ps(worktree_path: &Path) -> Result<Vec<DockerContainer>> {
-    let output = Command::new("docker")
-        .current_dir(worktree_path)
-        .args(["compose", "ps", "--format", "json"])
-        .output()
-        .with_context(|| {
-            format!(
-                "failed to execute docker compose ps in {}",
-                worktree_path.display()
+impl DockerContainer {
+    /// The compose state (`running`, `exited`, …) without the health suffix.
+    pub fn state(&self) -> &str {
+        self.status.split_whitespace().next().unwrap_or("unknown")
+    }
+
+    /// Whether the container is up, whatever its health check says.
+    pub fn is_running(&self) -> bool {
+        self.state() == "running"
+    }
+}
+
+/// How many of a worktree's compose services are up.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ServicesState {
+    pub running: usize,
+    pub total: usize,
+    /// Container count per compose state.
+    pub by_state: BTreeMap<String, usize>,
+}
+
+impl ServicesState {
+    pub fn from_containers(containers: &[DockerContainer]) -> Self {
+        let mut by_state = BTreeMap::new();
+        for container in containers {
+            *by_state.entry(container.state().to_string()).or_insert(0) += 1;
+        }
+        Self {
+            running: containers.iter().filter(|c| c.is_running()).count(),
+            total: containers.len(),
+            by_state,
+        }
+    }
+
+    pub fn any_running(&self) -> bool {
+        self.running > 0
+    }
+}
+
+/// The `docker` section of the config file, applied to every compose command
+/// wtm runs for a worktree.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DockerOptions {
+    /// Compose files passed as `-f`, relative to the worktree unless absolute.
+    #[serde(default, rename = "composeFiles")]
+    pub compose_files: Vec<String>,
+    /// Project name passed as `-p`, e.g. to share one project across worktrees.
+    #[serde(default, rename = "projectName")]
+    pub project_name: Option<String>,
+}
+
+impl DockerOptions {
+    /// `-f <file>` for each compose file and `-p <name>`, to go between
+    /// `docker compose` and its subcommand.
+    pub fn compose_args(&self, worktree_path: &Path) -> Vec<String> {
+        let mut args = Vec::new();
+        for file in &self.compose_files {
+            args.push("-f".to_string());
+            args.push(worktree_path.join(file).to_string_lossy().into_owned());
+        }
+        if let Some(name) = &self.project_name {
+            args.push("-p".to_string());
+            args.push(name.clone());
+        }
+        args
+    }
+}
+
+/// The compose CLI that answered `ps`, newest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComposeBackend {
+    /// `docker compose` with `ps --format json`.
+    Plugin,
+    /// A `docker compose` too old for `--format json`; its table is parsed.
+    PluginTable,
+    /// The standalone v1 `docker-compose`, which only prints a table.
+    Standalone,
+}
+
+impl ComposeBackend {
+    /// The program and the arguments that select compose, ahead of
+    /// [`DockerOptions::compose_args`].
+    fn program(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            ComposeBackend::Plugin | ComposeBackend::PluginTable => ("docker", &["compose"]),
+            ComposeBackend::Standalone => ("docker-compose", &[]),
+        }
+    }
+
+    /// The program and arguments for `ps`, with `compose_args` from
+    /// [`DockerOptions::compose_args`] ahead of the subcommand.
+    fn ps_command(self, compose_args: &[String]) -> (&'static str, Vec<String>) {
+        let (program, prefix) = self.program();
+        let suffix: &[&str] = match self {
+            ComposeBackend::Plugin => &["ps", "--format", "json"],
+            ComposeBackend::PluginTable | ComposeBackend::Standalone => &["ps"],
+        };
+        let args = prefix
+            .iter()
+            .map(|arg| arg.to_string())
+            .chain(compose_args.iter().cloned())
+            .chain(suffix.iter().map(|arg| arg.to_string()))
+            .collect();
+        (program, args)
+    }
+
+    /// The backend to try next when this one failed with `stderr` because it
+    /// does not support what was asked, rather than for a real problem such as
+    /// a missing compose file.
+    pub fn fallback(self, stderr: &str) -> Option<Self> {
+        let no_plugin = stderr.contains("'compose' is not a docker command")
+            || stderr.contains("unknown command \"compose\"");
+        let no_json = stderr.contains("unknown flag: --format")
+            || stderr.contains("unknown shorthand flag")
+            || stderr.contains("No such option: --format");
+        match self {
+            ComposeBackend::Plugin if no_json => Some(ComposeBackend::PluginTable),
+            ComposeBackend::Plugin | ComposeBackend::PluginTable if no_plugin => {
+                Some(ComposeBackend::Standalone)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `docker compose ps` in `worktree_path`, falling back to the table output of
+/// older `docker compose` releases and of `docker-compose` when JSON or the
+/// compose plugin is unavailable.
+pub fn compose_ps_with(
+    runner: &dyn CommandRunner,
+    worktree_path: &Path,
+    options: &DockerOptions,
+) -> Result<Vec<DockerContainer>> {
+    compose_ps_backend_with(runner, worktree_path, options).map(|(_, containers)| containers)
+}
+
+/// [`compose_ps_with`], along with the backend that answered, so later compose
+/// commands for the worktree can use the same CLI.
+pub fn compose_ps_backend(
+    worktree_path: &Path,
+    options: &DockerOptions,
+) -> Result<(ComposeBackend, Vec<DockerContainer>)> {
+    compose_ps_backend_with(&SystemRunner, worktree_path, options)
+}
+
+pub fn compose_ps_backend_with(
+    runner: &dyn CommandRunner,
+    worktree_path: &Path,
+    options: &DockerOptions,
+) -> Result<(ComposeBackend, Vec<DockerContainer>)> {
+    let compose_args = options.compose_args(worktree_path);
+    let mut backend = ComposeBackend::Plugin;
+    loop {
+        let (program, args) = backend.ps_command(&compose_args);
+        let output = runner
+            .run(
+                program.as_ref(),
+                &args.iter().map(OsStr::new).collect::<Vec<_>>(),
+                Some(worktree_path),
+                &[],
             )
-        })?;
+            .with_context(|| {
+                format!(
+                    "failed to execute {program} {} in {}",
+                    args.join(" "),
+                    worktree_path.display()
+                )
+            })?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!(stderr.trim().to_string()));
+        if output.success {
+            let containers = match backend {
+                ComposeBackend::Plugin => parse_ps_output(&output.stdout)?,
+                ComposeBackend::PluginTable | ComposeBackend::Standalone => {
+                    parse_ps_table(&output.stdout)
+                }
+            };
+            return Ok((backend, containers));
+        }
+        match backend.fallback(&output.stderr) {
+            Some(next) => {
+                log::debug!("{program} {} unsupported; trying {next:?}", args.join(" "));
+                backend = next;
+            }
+            None => return Err(anyhow!(output.stderr.trim().to_string())),
+        }
     }
+}
+
+/// `docker compose [-f …] [-p …] logs [--follow] <service>`, or the same
+/// through `docker-compose` for that `backend`, to run in the worktree.
+/// Compose files are left as configured, so relative ones resolve against the
+/// directory the command runs in.
+pub fn logs_command(
+    backend: ComposeBackend,
+    options: &DockerOptions,
+    service: &str,
+    follow: bool,
+) -> Vec<String> {
+    let (program, prefix) = backend.program();
+    let mut command = vec![program.to_string()];
+    command.extend(prefix.iter().map(|arg| arg.to_string()));
+    command.extend(options.compose_args(Path::new("")));
+    command.push("logs".to_string());
+    if follow {
+        command.push("--follow".to_string());
+    }
+    command.push(service.to_string());
+    command
+}
+
+/// Count the running compose services in `worktree_path`. A worktree without a
+/// compose project, or a machine without docker, has none.
+pub fn services_summary(worktree_path: &Path, options: &DockerOptions) -> ServicesState {
+    services_summary_with(&SystemRunner, worktree_path, options)
+}
+
+pub fn services_summary_with(
+    runner: &dyn CommandRunner,
+    worktree_path: &Path,
+    options: &DockerOptions,
+) -> ServicesState {
+    match services_state_with(runner, worktree_path, options) {
+        Ok(state) => state,
+        Err(err) => {
+            log::debug!(
+                "no compose services in {}: {err:#}",
+                worktree_path.display()
+            );
+            ServicesState::default()
+        }
+    }
+}
+
+/// Count the compose services in `worktree_path` by state, failing when docker
+/// or the compose project is unavailable.
+pub fn services_state(worktree_path: &Path, options: &DockerOptions) -> Result<ServicesState> {
+    services_state_with(&SystemRunner, worktree_path, options)
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_ps_output(&stdout)
+pub fn services_state_with(
+    runner: &dyn CommandRunner,
+    worktree_path: &Path,
+    options: &DockerOptions,
+) -> Result<ServicesState> {
+    compose_ps_with(runner, worktree_path, options)
+        .map(|containers| ServicesState::from_containers(&containers))
 }
 
+/// Keep the worktrees with at least one running service. `summary` is asked
+/// about the worktrees in parallel, since each `docker compose ps` takes a
+/// while; one whose check panics counts as not running.
+pub fn retain_with_running_services(
+    worktrees: &mut Vec<WorktreeInfo>,
+    summary: impl Fn(&Path) -> ServicesState + Sync,
+) {
+    let keep = parallel::map(worktrees, |wt| summary(&wt.path).any_running());
+    let mut keep = keep.into_iter();
+    worktrees.retain(|_| keep.next().flatten().unwrap_or(false));
+}
+
+/// Parse `ps --format json`: one object per line, or a single array from
+/// compose releases before 2.21.
 fn parse_ps_output(output: &str) -> Result<Vec<DockerContainer>> {
+    let entries: Vec<ComposePsEntry> = if output.trim_start().starts_with('[') {
+        serde_json::from_str(output.trim()).context("failed to parse docker compose ps output")?
+    } else {
+        output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("failed to parse docker compose ps entry: {line}"))
+            })
+            .collect::<Result<_>>()?
+    };
     let mut containers = Vec::new();
-    for line in output
-        .lines()
-        .map(str::trim)
-        .filter(|line| !line.is_empty())
-    {
-        let entry: ComposePsEntry = serde_json::from_str(line)
-            .with_context(|| format!("failed to parse docker compose ps entry: {line}"))?;
+    for entry in entries {
         let label = entry
             .service
             .clone()
@@ -66,6 +319,95 @@ fn parse_ps_output(output: &str) -> Result<Vec<DockerContainer>> {
     Ok(containers)
 }
 
+/// Parse the table printed by `docker compose ps` without `--format json`, or
+/// by `docker-compose ps`, into the same shape as the JSON output.
+///
+/// Cells are separated by two or more spaces, since v1 centres its header
+/// titles and both versions pad every column. Without a `SERVICE` column
+/// (v1), the service comes from the `<project>_<service>_<n>` name, and
+/// `Up`/`Exit 0` statuses read as `running`/`exited`.
+pub fn parse_ps_table(output: &str) -> Vec<DockerContainer> {
+    let mut lines = output.lines().filter(|line| !line.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let titles = table_cells(header);
+    let column = |name: &str| {
+        titles
+            .iter()
+            .position(|title| title.eq_ignore_ascii_case(name))
+    };
+    let (name_col, service_col) = (column("name"), column("service"));
+    let state_col = column("state").or_else(|| column("status"));
+
+    lines
+        .filter(|line| !line.trim_start().starts_with("---"))
+        .map(|line| {
+            let cells = table_cells(line);
+            let cell = |index: Option<usize>| -> String {
+                index
+                    .and_then(|index| cells.get(index))
+                    .map_or(String::new(), |cell| cell.to_string())
+            };
+            let name = cell(name_col);
+            let service = Some(cell(service_col))
+                .filter(|service| !service.is_empty())
+                .unwrap_or_else(|| service_from_v1_name(&name));
+            DockerContainer {
+                service,
+                status: table_status(&cell(state_col)),
+                name,
+            }
+        })
+        .collect()
+}
+
+/// The cells of a table line: text separated by runs of two or more spaces.
+fn table_cells(line: &str) -> Vec<&str> {
+    line.split("  ")
+        .map(str::trim)
+        .filter(|cell| !cell.is_empty())
+        .collect()
+}
+
+/// `web` from v1's `project_web_1`; v1 project names have no underscores.
+fn service_from_v1_name(name: &str) -> String {
+    let trimmed = name
+        .rsplit_once('_')
+        .filter(|(_, n)| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        .map_or(name, |(rest, _)| rest);
+    trimmed
+        .split_once('_')
+        .map_or(trimmed, |(_, service)| service)
+        .to_string()
+}
+
+/// A table status (`Up 2 minutes (healthy)`, `Exit 0`, `exited (1)`) in the
+/// `state (health)` form of the JSON output.
+fn table_status(raw: &str) -> String {
+    let mut state = match raw.split_whitespace().next() {
+        None => return String::new(),
+        Some(word) => match word.to_ascii_lowercase().as_str() {
+            "up" => "running".to_string(),
+            "exit" => "exited".to_string(),
+            other => other.to_string(),
+        },
+    };
+    let health = raw
+        .split('(')
+        .skip(1)
+        .filter_map(|part| part.split_once(')'))
+        .map(|(inside, _)| inside.trim())
+        .find_map(|inside| match inside {
+            "healthy" | "unhealthy" => Some(inside),
+            _ => inside.strip_prefix("health: "),
+        });
+    if let Some(health) = health {
+        state.push_str(&format!(" ({health})"));
+    }
+    state
+}
+
 #[derive(Debug, Deserialize)]
 struct ComposePsEntry {
     #[serde(rename = "Name")]
@@ -81,6 +423,183 @@ struct ComposePsEntry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::runner::testing::ScriptedRunner;
+
+    #[test]
+    fn compose_ps_with_runs_in_worktree_and_parses_output() {
+        let runner = ScriptedRunner::default()
+            .succeed(r#"{"Service":"web","Name":"project-web-1","State":"running"}"#);
+        let containers =
+            compose_ps_with(&runner, Path::new("/work/tree"), &DockerOptions::default()).unwrap();
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].service, "web");
+
+        let calls = runner.calls();
+        assert_eq!(calls[0].program, "docker");
+        assert_eq!(calls[0].args, vec!["compose", "ps", "--format", "json"]);
+        assert_eq!(calls[0].cwd.as_deref(), Some(Path::new("/work/tree")));
+    }
+
+    #[test]
+    fn compose_files_and_project_name_go_before_the_subcommand() {
+        let options = DockerOptions {
+            compose_files: vec!["docker-compose.yml".into(), "/shared/dev.yml".into()],
+            project_name: Some("shop".into()),
+        };
+        let expected = [
+            "-f",
+            "/work/tree/docker-compose.yml",
+            "-f",
+            "/shared/dev.yml",
+            "-p",
+            "shop",
+        ];
+        assert_eq!(options.compose_args(Path::new("/work/tree")), expected);
+        assert!(DockerOptions::default()
+            .compose_args(Path::new("/work/tree"))
+            .is_empty());
+
+        let runner = ScriptedRunner::default()
+            .fail("docker: 'compose' is not a docker command.\n")
+            .succeed("");
+        compose_ps_with(&runner, Path::new("/work/tree"), &options).unwrap();
+        let calls = runner.calls();
+        assert_eq!(calls[0].args[0], "compose");
+        assert_eq!(calls[0].args[1..7], expected);
+        assert_eq!(calls[0].args[7..], ["ps", "--format", "json"]);
+        assert_eq!(calls[1].program, "docker-compose");
+        assert_eq!(calls[1].args[..6], expected);
+        assert_eq!(calls[1].args[6..], ["ps"]);
+    }
+
+    #[test]
+    fn logs_command_follows_one_service() {
+        let options = DockerOptions {
+            compose_files: vec!["compose.dev.yml".into()],
+            project_name: Some("shop".into()),
+        };
+        assert_eq!(
+            logs_command(ComposeBackend::Plugin, &options, "web", true),
+            [
+                "docker",
+                "compose",
+                "-f",
+                "compose.dev.yml",
+                "-p",
+                "shop",
+                "logs",
+                "--follow",
+                "web"
+            ]
+        );
+        assert_eq!(
+            logs_command(
+                ComposeBackend::Plugin,
+                &DockerOptions::default(),
+                "db",
+                false
+            ),
+            ["docker", "compose", "logs", "db"]
+        );
+        assert_eq!(
+            logs_command(ComposeBackend::Standalone, &options, "web", true),
+            [
+                "docker-compose",
+                "-f",
+                "compose.dev.yml",
+                "-p",
+                "shop",
+                "logs",
+                "--follow",
+                "web"
+            ]
+        );
+    }
+
+    #[test]
+    fn compose_ps_reports_the_backend_that_answered() {
+        let runner = ScriptedRunner::default()
+            .fail("docker: 'compose' is not a docker command.\n")
+            .succeed("NAME  SERVICE  STATUS\nshop-web-1  web  running\n");
+        let (backend, _) =
+            compose_ps_backend_with(&runner, Path::new("/work/tree"), &DockerOptions::default())
+                .unwrap();
+        assert_eq!(backend, ComposeBackend::Standalone);
+        assert_eq!(
+            logs_command(backend, &DockerOptions::default(), "web", true)[0],
+            "docker-compose"
+        );
+
+        let runner = ScriptedRunner::default().succeed("");
+        let (backend, _) =
+            compose_ps_backend_with(&runner, Path::new("/work/tree"), &DockerOptions::default())
+                .unwrap();
+        assert_eq!(backend, ComposeBackend::Plugin);
+    }
+
+    #[test]
+    fn compose_ps_with_surfaces_stderr_on_failure() {
+        let runner = ScriptedRunner::default().fail("no configuration file provided\n");
+        let err = compose_ps_with(&runner, Path::new("/work/tree"), &DockerOptions::default())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no configuration file provided");
+    }
+
+    #[test]
+    fn only_worktrees_with_a_running_service_pass_the_filter() {
+        let api = concat!(
+            r#"{"Service":"web","Name":"api-web-1","State":"running","Health":"healthy"}"#,
+            "\n",
+            r#"{"Service":"db","Name":"api-db-1","State":"exited"}"#,
+        );
+        let mut worktrees = vec![
+            WorktreeInfo::at("/ws/api"),
+            WorktreeInfo::at("/ws/docs"),
+            WorktreeInfo::at("/ws/old"),
+            WorktreeInfo::at("/ws/broken"),
+        ];
+        retain_with_running_services(&mut worktrees, |path| {
+            let output = match path.to_str().unwrap() {
+                "/ws/api" => api,
+                "/ws/broken" => panic!("compose check failed"),
+                "/ws/old" => r#"{"Service":"web","Name":"old-web-1","State":"exited"}"#,
+                _ => "",
+            };
+            services_summary_with(
+                &ScriptedRunner::default().succeed(output),
+                path,
+                &DockerOptions::default(),
+            )
+        });
+        let paths: Vec<&Path> = worktrees.iter().map(|wt| wt.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("/ws/api")]);
+
+        let summary = services_summary_with(
+            &ScriptedRunner::default().succeed(api),
+            Path::new("/ws/api"),
+            &DockerOptions::default(),
+        );
+        assert_eq!(
+            summary,
+            ServicesState {
+                running: 1,
+                total: 2,
+                by_state: BTreeMap::from([("exited".into(), 1), ("running".into(), 1)]),
+            }
+        );
+        let missing = ScriptedRunner::default().fail("no configuration file provided\n");
+        assert_eq!(
+            services_summary_with(&missing, Path::new("/ws/docs"), &DockerOptions::default()),
+            ServicesState::default()
+        );
+        let err = services_state_with(
+            &ScriptedRunner::default().fail("no configuration file provided\n"),
+            Path::new("/ws/docs"),
+            &DockerOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "no configuration file provided");
+    }
 
     #[test]
     fn parse_ps_output_extracts_name_and_status() {
@@ -98,6 +617,75 @@ mod tests {
         assert_eq!(containers[1].status, "exited");
     }
 
+    #[test]
+    fn legacy_tables_parse_like_json() {
+        let v1 = "\
+     Name                   Command               State                Ports
+-----------------------------------------------------------------------------------------
+shop_db_1       docker-entrypoint.sh postgres    Up                    5432/tcp
+shop_web_1      python app.py                    Up (healthy)          0.0.0.0:8000->8000/tcp
+shop_worker_1   celery worker                    Exit 0
+";
+        let json = concat!(
+            r#"{"Service":"db","Name":"shop_db_1","State":"running"}"#,
+            "\n",
+            r#"{"Service":"web","Name":"shop_web_1","State":"running","Health":"healthy"}"#,
+            "\n",
+            r#"{"Service":"worker","Name":"shop_worker_1","State":"exited"}"#,
+        );
+        assert_eq!(parse_ps_table(v1), parse_ps_output(json).unwrap());
+
+        let v2 = "\
+NAME                IMAGE     COMMAND                  SERVICE   CREATED         STATUS                       PORTS
+shop-web-1          nginx     \"/docker-entrypoint.…\"   web       2 minutes ago   Up 2 minutes (unhealthy)     80/tcp
+shop-db-1           postgres  \"docker-entrypoint.s…\"   db        2 minutes ago   Exited (1) 1 minute ago
+";
+        let containers = parse_ps_table(v2);
+        assert_eq!(
+            containers,
+            [
+                DockerContainer {
+                    service: "web".into(),
+                    name: "shop-web-1".into(),
+                    status: "running (unhealthy)".into(),
+                },
+                DockerContainer {
+                    service: "db".into(),
+                    name: "shop-db-1".into(),
+                    status: "exited".into(),
+                },
+            ]
+        );
+        assert!(parse_ps_table("").is_empty());
+    }
+
+    #[test]
+    fn unsupported_compose_falls_back_to_older_backends() {
+        let table = "NAME   SERVICE   STATUS\nx-web-1   web   running\n";
+        let runner = ScriptedRunner::default()
+            .fail("unknown flag: --format\n")
+            .succeed(table);
+        let containers =
+            compose_ps_with(&runner, Path::new("/work/tree"), &DockerOptions::default()).unwrap();
+        assert_eq!(containers[0].service, "web");
+        assert!(containers[0].is_running());
+        assert_eq!(runner.calls()[1].args, ["compose", "ps"]);
+
+        let runner = ScriptedRunner::default()
+            .fail("docker: 'compose' is not a docker command.\n")
+            .succeed(table);
+        compose_ps_with(&runner, Path::new("/work/tree"), &DockerOptions::default()).unwrap();
+        assert_eq!(runner.calls()[1].program, "docker-compose");
+        assert_eq!(runner.calls()[1].args, ["ps"]);
+        assert_eq!(
+            runner.calls()[1].cwd.as_deref(),
+            Some(Path::new("/work/tree"))
+        );
+
+        let array = r#"[{"Service":"web","Name":"x-web-1","State":"running"}]"#;
+        assert_eq!(parse_ps_output(array).unwrap(), containers);
+    }
+
     #[test]
     fn parse_ps_output_handles_missing_fields() {
         let sample = r#"{"Name":"orphan","State":"running"}"#;
@@ -0,0 +1,104 @@
+//! Top-level error categories and the process exit codes they map to.
+
+use crate::git::GitCommandError;
+use crate::workspace::WorkspaceError;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// The command, and any child it ran, succeeded.
+pub const EXIT_SUCCESS: i32 = 0;
+/// Unclassified failure.
+pub const EXIT_FAILURE: i32 = 1;
+// 2 is reserved for command-line usage errors reported by clap.
+/// The target directory is not inside a git repository.
+pub const EXIT_NOT_IN_REPO: i32 = 3;
+/// No `.wtm` directory was found; run `wtm init` first.
+pub const EXIT_NO_WTM_DIR: i32 = 4;
+/// The requested worktree does not exist.
+pub const EXIT_NO_MATCH: i32 = 5;
+/// A git command ran but failed.
+pub const EXIT_GIT_FAILURE: i32 = 6;
+/// The given name matches several worktrees.
+pub const EXIT_AMBIGUOUS: i32 = 7;
+
+/// Failures detected while locating the repository and its `.wtm` folder.
+#[derive(Debug, Error)]
+pub enum WtmError {
+    #[error(
+        "{} is not inside a git repository; run `git init` there, or pass `-C <repo>`",
+        path.display()
+    )]
+    NotInRepo {
+        path: PathBuf,
+        #[source]
+        source: anyhow::Error,
+    },
+    #[error("No .wtm directory found in {}. Run `wtm init` first.", .0.display())]
+    NoWtmDir(PathBuf),
+}
+
+/// Map an error to the exit code documented in the README.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    if let Some(err) = err.downcast_ref::<WtmError>() {
+        return match err {
+            WtmError::NotInRepo { .. } => EXIT_NOT_IN_REPO,
+            WtmError::NoWtmDir(_) => EXIT_NO_WTM_DIR,
+        };
+    }
+    if let Some(err) = err.downcast_ref::<WorkspaceError>() {
+        return match err {
+            WorkspaceError::NoMatch(_) | WorkspaceError::NotInTrash(_) => EXIT_NO_MATCH,
+            WorkspaceError::Ambiguous { .. } => EXIT_AMBIGUOUS,
+            WorkspaceError::Git(_) => EXIT_GIT_FAILURE,
+            _ => EXIT_FAILURE,
+        };
+    }
+    if err.chain().any(|cause| cause.is::<GitCommandError>()) {
+        return EXIT_GIT_FAILURE;
+    }
+    EXIT_FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn exit_code_distinguishes_error_categories() {
+        let not_in_repo = WtmError::NotInRepo {
+            path: PathBuf::from("/tmp"),
+            source: anyhow!("fatal: not a git repository"),
+        };
+        assert_eq!(exit_code(&not_in_repo.into()), EXIT_NOT_IN_REPO);
+        assert_eq!(
+            exit_code(&WtmError::NoWtmDir(PathBuf::from("/tmp")).into()),
+            EXIT_NO_WTM_DIR
+        );
+        assert_eq!(
+            exit_code(&WorkspaceError::NoMatch(PathBuf::from("/tmp/x")).into()),
+            EXIT_NO_MATCH
+        );
+        let ambiguous = WorkspaceError::Ambiguous {
+            name: "hot-fix".into(),
+            candidates: vec![PathBuf::from("/a/hot-fix"), PathBuf::from("/b/hot-fix")],
+        };
+        assert_eq!(exit_code(&ambiguous.into()), EXIT_AMBIGUOUS);
+        assert_eq!(
+            exit_code(&WorkspaceError::Git(anyhow!("boom")).into()),
+            EXIT_GIT_FAILURE
+        );
+        assert_eq!(exit_code(&anyhow!("something else")), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn exit_code_finds_git_failures_behind_context() {
+        let err = anyhow::Error::new(GitCommandError {
+            command: "git log nope".into(),
+            dir: PathBuf::from("/repo"),
+            stderr: "fatal: bad revision".into(),
+        })
+        .context("failed to list worktrees");
+        assert_eq!(exit_code(&err), EXIT_GIT_FAILURE);
+    }
+}
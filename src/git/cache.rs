@@ -0,0 +1,64 @@
+//! Per-process cache of `git worktree list`.
+//!
+//! Listing worktrees can be slow on network filesystems, and one command or
+//! dashboard refresh may ask several times. Every worktree mutation in this
+//! module's parent drops the cache, and entries expire after
+//! [`WORKTREE_CACHE_TTL`] so changes made outside wtm show up eventually.
+
+use super::WorktreeInfo;
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// How long a cached listing is trusted.
+pub(super) const WORKTREE_CACHE_TTL: Duration = Duration::from_secs(2);
+
+static WORKTREE_CACHE: OnceLock<WorktreeCache> = OnceLock::new();
+
+/// Worktree listings keyed by repository root.
+#[derive(Debug, Default)]
+pub(super) struct WorktreeCache {
+    entries: Mutex<HashMap<PathBuf, (Instant, Vec<WorktreeInfo>)>>,
+}
+
+impl WorktreeCache {
+    /// The process-wide cache.
+    pub(super) fn global() -> &'static Self {
+        WORKTREE_CACHE.get_or_init(Self::default)
+    }
+
+    /// The cached listing for `repo_root` if it is younger than the TTL at `now`,
+    /// otherwise the result of `load`, which is cached when it succeeds.
+    pub(super) fn get_or_load(
+        &self,
+        repo_root: &Path,
+        now: Instant,
+        load: impl FnOnce() -> Result<Vec<WorktreeInfo>>,
+    ) -> Result<Vec<WorktreeInfo>> {
+        if let Some((loaded_at, worktrees)) = self.lock().get(repo_root) {
+            if now.saturating_duration_since(*loaded_at) < WORKTREE_CACHE_TTL {
+                return Ok(worktrees.clone());
+            }
+        }
+        let worktrees = load()?;
+        self.lock()
+            .insert(repo_root.to_path_buf(), (now, worktrees.clone()));
+        Ok(worktrees)
+    }
+
+    /// Forget the listing for `repo_root`.
+    pub(super) fn invalidate(&self, repo_root: &Path) {
+        self.lock().remove(repo_root);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, (Instant, Vec<WorktreeInfo>)>> {
+        // A panic while holding the lock cannot leave a half-written entry behind.
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -1,8 +1,9 @@
 use super::run_git;
 use anyhow::Result;
+use serde::Serialize;
 use std::path::Path;
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
 pub struct GitStatusSummary {
     pub branch: Option<String>,
     pub upstream: Option<String>,
@@ -12,18 +13,35 @@ pub struct GitStatusSummary {
     pub unstaged: usize,
     pub untracked: usize,
     pub conflicts: usize,
+    /// The branch tracks an upstream that no longer exists (`[gone]`).
+    pub upstream_gone: bool,
 }
 
 pub fn status(worktree_path: &Path) -> Result<GitStatusSummary> {
-    let output = run_git(["status", "--porcelain=v2", "--branch"], worktree_path)?;
+    let output = crate::timings::measure(
+        || format!("status {}", worktree_path.display()),
+        || {
+            run_git(
+                ["status", "--porcelain=v2", "--branch", "-z"],
+                worktree_path,
+            )
+        },
+    )?;
     Ok(parse_status_output(&output))
 }
 
+/// Summarise `git status --porcelain=v2 --branch -z` output.
+///
+/// Records are NUL-terminated, so paths containing newlines cannot be
+/// mistaken for further entries.
 pub fn parse_status_output(output: &str) -> GitStatusSummary {
     let mut summary = GitStatusSummary::default();
+    let mut has_ab = false;
 
-    for line in output.lines() {
+    let mut records = output.split('\0');
+    while let Some(line) = records.next() {
         if let Some(rest) = line.strip_prefix("# ") {
+            has_ab |= rest.starts_with("branch.ab ");
             parse_branch_line(rest, &mut summary);
             continue;
         }
@@ -42,6 +60,10 @@ pub fn parse_status_output(output: &str) -> GitStatusSummary {
                     }
                 }
             }
+            if line.starts_with('2') {
+                // Renames and copies are followed by their original path.
+                records.next();
+            }
             continue;
         }
 
@@ -56,6 +78,8 @@ pub fn parse_status_output(output: &str) -> GitStatusSummary {
         }
     }
 
+    // git names a configured upstream but omits `branch.ab` when the upstream ref is gone.
+    summary.upstream_gone = summary.upstream.is_some() && !has_ab;
     summary
 }
 
@@ -88,23 +112,36 @@ mod tests {
     #[test]
     fn parse_status_output_tracks_branch_metadata() {
         let sample =
-            "# branch.head feature/context\n# branch.upstream origin/main\n# branch.ab +2 -1\n";
+            "# branch.head feature/context\0# branch.upstream origin/main\0# branch.ab +2 -1\0";
         let summary = parse_status_output(sample);
         assert_eq!(summary.branch.as_deref(), Some("feature/context"));
         assert_eq!(summary.upstream.as_deref(), Some("origin/main"));
         assert_eq!(summary.ahead, 2);
         assert_eq!(summary.behind, 1);
+        assert!(!summary.upstream_gone);
+    }
+
+    #[test]
+    fn parse_status_output_flags_gone_upstream() {
+        let gone = parse_status_output(
+            "# branch.head feature/merged\0# branch.upstream origin/feature/merged\0",
+        );
+        assert!(gone.upstream_gone);
+        assert_eq!(gone.ahead, 0);
+
+        let untracked = parse_status_output("# branch.head scratch\0");
+        assert!(!untracked.upstream_gone);
     }
 
     #[test]
     fn parse_status_output_counts_file_states() {
         let sample = "\
-# branch.head main\n\
-1 M. N... 100644 100644 100644 abcdef1234567890abcdef1234567890abcdef12 file1\n\
-1 .M N... 100644 100644 100644 abcdef1234567890abcdef1234567890abcdef12 file2\n\
-2 AA N... 100644 100644 100644 100644 abcdef1234567890abcdef1234567890abcdef12 file3\n\
-? new_file\n\
-u UU N... 100644 100644 100644 100644 abcdef1234567890abcdef1234567890abcdef12 file4\n";
+# branch.head main\0\
+1 M. N... 100644 100644 100644 abcdef1234567890abcdef1234567890abcdef12 file1\0\
+1 .M N... 100644 100644 100644 abcdef1234567890abcdef1234567890abcdef12 file2\0\
+2 RM N... 100644 100644 100644 abcdef1234567890abcdef1234567890abcdef12 abcdef1234567890abcdef1234567890abcdef12 R100 file3\0? old name\0\
+? new\nfile\0\
+u UU N... 100644 100644 100644 100644 abcdef1234567890abcdef1234567890abcdef12 file4\0";
         let summary = parse_status_output(sample);
         assert_eq!(summary.staged, 2);
         assert_eq!(summary.unstaged, 2);
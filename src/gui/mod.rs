@@ -1,50 +1,127 @@
-use std::path::{Path, PathBuf};
+mod resize;
+mod screen;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{Receiver, TryRecvError},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 use anyhow::{anyhow, Context, Result};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use eframe::{egui, App};
+use resize::{settle_cells, ResizeThrottle, RESIZE_DEBOUNCE};
+use screen::ScreenCache;
 
 use crate::{
-    config::QuickAction,
+    config::{Config, PathDisplay, PrimaryPosition, QuickAction, TerminalSession},
     git::{self, WorktreeInfo},
-    tui::{pty_tab::PtyTab, size::TerminalSize},
-    wtm_paths::{branch_dir_name, ensure_workspace_root, next_available_workspace_path},
+    hooks::{self, Hook, HookContext, HookOutcome},
+    ignore::IgnoreList,
+    lock::WtmLock,
+    parallel,
+    tui::{
+        pty_tab::{usable_session, workspace_env, PtyTab, ScreenWaker, TabProgram},
+        size::TerminalSize,
+    },
+    workspace,
+    wtm_paths::{ensure_workspace_root, format_path, home_dir, reserve_target_path, PathLayout},
 };
 
 const INITIAL_TERMINAL_SIZE: TerminalSize = TerminalSize { rows: 24, cols: 90 };
 const MIN_TERMINAL_ROWS: u16 = 12;
 const MIN_TERMINAL_COLS: u16 = 48;
+/// How often to look for finished hooks and removals while any are running.
+const BACKGROUND_POLL: Duration = Duration::from_millis(100);
 
-pub fn run_gui(
-    repo_root: PathBuf,
-    worktrees: Vec<WorktreeInfo>,
-    quick_actions: Vec<QuickAction>,
-) -> Result<()> {
+pub fn run_gui(repo_root: PathBuf, worktrees: Vec<WorktreeInfo>, config: Config) -> Result<()> {
+    let max_slug_length = config.path_layout().max_slug_length;
     let init = GuiInitState {
+        add_options: git::AddOptions::for_repo(&repo_root, config.relative_worktree_paths),
+        bare: git::is_bare_repository(&repo_root).unwrap_or(false),
         repo_root,
         worktrees,
-        quick_actions,
+        quick_actions: config.quick_actions,
+        primary_position: config.primary_position,
+        path_display: config.path_display,
+        ignore: config.ignore,
+        workspace_path_template: config.workspace_path_template,
+        max_slug_length,
+        hooks_path: config.hooks_path,
+        terminal_env: config.terminal_env,
+        session: config.session,
+        waker: None,
     };
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
         "WTM Worktree Manager",
         native_options,
-        Box::new(move |_cc| Box::new(WtmGui::new(init, DefaultBackend::default()))),
+        Box::new(move |cc| {
+            // Frames are drawn on input or when a terminal reports new output,
+            // not continuously.
+            let ctx = cc.egui_ctx.clone();
+            let waker: ScreenWaker = Arc::new(move || ctx.request_repaint());
+            let init = GuiInitState {
+                waker: Some(waker),
+                ..init
+            };
+            Box::new(WtmGui::new(init, DefaultBackend))
+        }),
     )
     .map_err(|err| anyhow!("failed to launch GUI: {err}"))
 }
 
 struct GuiInitState {
     repo_root: PathBuf,
+    /// The repository is bare, so every worktree is a linked one.
+    bare: bool,
     worktrees: Vec<WorktreeInfo>,
     quick_actions: Vec<QuickAction>,
+    primary_position: PrimaryPosition,
+    path_display: PathDisplay,
+    ignore: IgnoreList,
+    add_options: git::AddOptions,
+    workspace_path_template: Option<String>,
+    max_slug_length: usize,
+    hooks_path: Option<String>,
+    terminal_env: BTreeMap<String, String>,
+    session: TerminalSession,
+    waker: Option<ScreenWaker>,
 }
 
 trait GuiBackend {
     fn list_worktrees(&mut self, repo_root: &Path) -> Result<Vec<WorktreeInfo>>;
-    fn add_worktree(&mut self, repo_root: &Path, path: &Path, branch: Option<&str>) -> Result<()>;
-    fn remove_worktree(&mut self, repo_root: &Path, path: &Path, force: bool) -> Result<()>;
+    fn add_worktree(
+        &mut self,
+        repo_root: &Path,
+        path: &Path,
+        branch: &str,
+        options: &git::AddOptions,
+    ) -> Result<()>;
+    /// Remove the worktree, pre-remove hook included, off the UI thread.
+    fn remove_worktree(
+        &mut self,
+        repo_root: &Path,
+        path: &Path,
+        force: bool,
+    ) -> Receiver<Result<()>>;
     fn spawn_quick_command(&mut self, repo_root: &Path, command: &str) -> Result<()>;
+    fn install_hooks_path(
+        &mut self,
+        repo_root: &Path,
+        worktree_path: &Path,
+        hooks_path: &str,
+    ) -> Result<()>;
+    /// Run `hook` for the worktree off the UI thread.
+    fn run_hook(
+        &mut self,
+        hook: Hook,
+        context: HookContext<'_>,
+    ) -> Receiver<Result<Option<HookOutcome>>>;
 }
 
 #[derive(Default)]
@@ -55,33 +132,94 @@ impl GuiBackend for DefaultBackend {
         git::list_worktrees(repo_root)
     }
 
-    fn add_worktree(&mut self, repo_root: &Path, path: &Path, branch: Option<&str>) -> Result<()> {
-        git::add_worktree(repo_root, path, branch)
+    fn add_worktree(
+        &mut self,
+        repo_root: &Path,
+        path: &Path,
+        branch: &str,
+        options: &git::AddOptions,
+    ) -> Result<()> {
+        let _lock = WtmLock::acquire(repo_root)?;
+        git::add_worktree_new_branch(repo_root, path, branch, options)
     }
 
-    fn remove_worktree(&mut self, repo_root: &Path, path: &Path, force: bool) -> Result<()> {
-        git::remove_worktree(repo_root, path, force)
+    fn remove_worktree(
+        &mut self,
+        repo_root: &Path,
+        path: &Path,
+        force: bool,
+    ) -> Receiver<Result<()>> {
+        let (repo_root, path) = (repo_root.to_path_buf(), path.to_path_buf());
+        parallel::background(move || {
+            workspace::delete_workspace(&repo_root, &path, force, true)?;
+            Ok(())
+        })
     }
 
     fn spawn_quick_command(&mut self, repo_root: &Path, command: &str) -> Result<()> {
         spawn_quick_command(repo_root, command)
     }
+
+    fn install_hooks_path(
+        &mut self,
+        repo_root: &Path,
+        worktree_path: &Path,
+        hooks_path: &str,
+    ) -> Result<()> {
+        hooks::install_hooks_path(repo_root, worktree_path, hooks_path)?;
+        Ok(())
+    }
+
+    fn run_hook(
+        &mut self,
+        hook: Hook,
+        context: HookContext<'_>,
+    ) -> Receiver<Result<Option<HookOutcome>>> {
+        let repo_root = context.repo_root.to_path_buf();
+        let worktree_path = context.worktree_path.to_path_buf();
+        let branch = context.branch.to_string();
+        parallel::background(move || {
+            hooks::run_hook(
+                hook,
+                HookContext {
+                    repo_root: &repo_root,
+                    worktree_path: &worktree_path,
+                    branch: &branch,
+                },
+            )
+        })
+    }
 }
 
 struct GuiWorkspace {
     info: WorktreeInfo,
+    /// Repository the worktree belongs to, which scopes its tmux session.
+    repo_root: PathBuf,
     tabs: Vec<PtyTab>,
     active_tab: usize,
     next_tab_id: usize,
+    terminal_env: BTreeMap<String, String>,
+    session: TerminalSession,
+    waker: Option<ScreenWaker>,
 }
 
 impl GuiWorkspace {
-    fn new(info: WorktreeInfo) -> Result<Self> {
+    fn new(
+        info: WorktreeInfo,
+        repo_root: &Path,
+        terminal_env: &BTreeMap<String, String>,
+        session: TerminalSession,
+        waker: Option<&ScreenWaker>,
+    ) -> Result<Self> {
         let mut workspace = Self {
             info,
+            repo_root: repo_root.to_path_buf(),
             tabs: Vec::new(),
             active_tab: 0,
             next_tab_id: 1,
+            terminal_env: terminal_env.clone(),
+            session,
+            waker: waker.cloned(),
         };
         workspace.ensure_primary_tab()?;
         Ok(workspace)
@@ -108,7 +246,21 @@ impl GuiWorkspace {
     }
 
     fn push_tab(&mut self, title: String, bootstrap: Option<&str>) -> Result<()> {
-        let tab = PtyTab::new(&title, &self.info.path, INITIAL_TERMINAL_SIZE)?;
+        // Quick actions run in a shell of their own rather than a shared session.
+        let program = match bootstrap {
+            Some(_) => TabProgram::Shell,
+            None => TabProgram::for_worktree(self.session, &self.repo_root, &self.info),
+        };
+        let tab = PtyTab::new(
+            &title,
+            &self.info.path,
+            INITIAL_TERMINAL_SIZE,
+            &workspace_env(&self.info, &self.terminal_env),
+            &program,
+        )?;
+        if let Some(waker) = &self.waker {
+            tab.set_waker(Arc::clone(waker));
+        }
         if let Some(command) = bootstrap {
             tab.send_command(command)?;
         }
@@ -140,14 +292,19 @@ impl GuiWorkspace {
     }
 
     fn close_active_tab(&mut self) -> Result<()> {
-        if self.tabs.len() <= 1 {
+        self.close_tab(self.active_tab)
+    }
+
+    /// Close the tab at `index`, keeping the same tab active where possible.
+    ///
+    /// The last remaining tab is never closed.
+    fn close_tab(&mut self, index: usize) -> Result<()> {
+        if self.tabs.len() <= 1 || index >= self.tabs.len() {
             return Ok(());
         }
-        if self.active_tab < self.tabs.len() {
-            let _ = self.tabs.remove(self.active_tab);
-            if self.active_tab >= self.tabs.len() && !self.tabs.is_empty() {
-                self.active_tab = self.tabs.len() - 1;
-            }
+        let _ = self.tabs.remove(index);
+        if index < self.active_tab || self.active_tab >= self.tabs.len() {
+            self.active_tab = self.active_tab.saturating_sub(1);
         }
         Ok(())
     }
@@ -169,8 +326,8 @@ impl GuiWorkspace {
         label
     }
 
-    fn display_path(&self) -> String {
-        self.info.path.display().to_string()
+    fn display_path(&self, style: PathDisplay, repo_root: &Path) -> String {
+        format_path(&self.info.path, style, repo_root, home_dir().as_deref())
     }
 
     fn info(&self) -> &WorktreeInfo {
@@ -186,7 +343,7 @@ impl GuiWorkspace {
     }
 
     fn is_primary(&self, repo_root: &Path) -> bool {
-        self.info.path == repo_root
+        self.info.is_primary(repo_root)
     }
 
     fn reap_finished(&mut self) {
@@ -195,10 +352,6 @@ impl GuiWorkspace {
             self.active_tab = self.tabs.len() - 1;
         }
     }
-
-    fn needs_repaint(&self) -> bool {
-        self.tabs.iter().any(|tab| !tab.is_terminated())
-    }
 }
 
 #[derive(Clone)]
@@ -229,6 +382,24 @@ impl StatusMessage {
     }
 }
 
+/// How a reload changed the set of worktrees.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct SyncDelta {
+    added: usize,
+    removed: usize,
+}
+
+impl SyncDelta {
+    fn summary(self) -> String {
+        match (self.added, self.removed) {
+            (0, 0) => "Refreshed worktrees (no changes)".into(),
+            (added, 0) => format!("Refreshed worktrees (+{added} new)"),
+            (0, removed) => format!("Refreshed worktrees (-{removed} removed)"),
+            (added, removed) => format!("Refreshed worktrees (+{added} new, -{removed} removed)"),
+        }
+    }
+}
+
 impl StatusKind {
     fn color(&self) -> egui::Color32 {
         match self {
@@ -241,21 +412,47 @@ impl StatusKind {
 struct WtmGui<B: GuiBackend> {
     backend: B,
     repo_root: PathBuf,
+    bare: bool,
     quick_actions: Vec<QuickAction>,
+    primary_position: PrimaryPosition,
+    path_display: PathDisplay,
+    ignore: IgnoreList,
+    add_options: git::AddOptions,
+    workspace_path_template: Option<String>,
+    max_slug_length: usize,
+    hooks_path: Option<String>,
+    terminal_env: BTreeMap<String, String>,
+    session: TerminalSession,
+    waker: Option<ScreenWaker>,
     workspaces: Vec<GuiWorkspace>,
     selected_workspace: usize,
+    /// Give the selected workspace's terminal keyboard focus on the next frame.
+    focus_terminal: bool,
+    resize: ResizeThrottle,
+    screen: ScreenCache,
     new_branch: String,
     status: Option<StatusMessage>,
     pending_removal: Option<PathBuf>,
     force_remove: bool,
+    /// Post-create hooks still running.
+    running_hooks: Vec<Receiver<Result<Option<HookOutcome>>>>,
+    /// Removals still running, by worktree path.
+    running_removals: Vec<(PathBuf, Receiver<Result<()>>)>,
 }
 
 impl<B: GuiBackend> WtmGui<B> {
     fn new(init: GuiInitState, backend: B) -> Self {
-        let mut status = None;
+        let (session, session_warning) = usable_session(init.session);
+        let mut status = session_warning.map(StatusMessage::error);
         let mut workspaces = Vec::new();
         for info in init.worktrees {
-            match GuiWorkspace::new(info) {
+            match GuiWorkspace::new(
+                info,
+                &init.repo_root,
+                &init.terminal_env,
+                session,
+                init.waker.as_ref(),
+            ) {
                 Ok(workspace) => workspaces.push(workspace),
                 Err(err) => {
                     status = Some(StatusMessage::error(format!(
@@ -267,13 +464,29 @@ impl<B: GuiBackend> WtmGui<B> {
         Self {
             backend,
             repo_root: init.repo_root,
+            bare: init.bare,
             quick_actions: init.quick_actions,
+            primary_position: init.primary_position,
+            path_display: init.path_display,
+            ignore: init.ignore,
+            add_options: init.add_options,
+            workspace_path_template: init.workspace_path_template,
+            max_slug_length: init.max_slug_length,
+            hooks_path: init.hooks_path,
+            terminal_env: init.terminal_env,
+            session,
+            waker: init.waker,
             workspaces,
             selected_workspace: 0,
+            focus_terminal: false,
+            resize: ResizeThrottle::default(),
+            screen: ScreenCache::default(),
             new_branch: String::new(),
             status,
             pending_removal: None,
             force_remove: false,
+            running_hooks: Vec::new(),
+            running_removals: Vec::new(),
         }
     }
 
@@ -296,18 +509,46 @@ impl<B: GuiBackend> WtmGui<B> {
             }
         };
 
-        let dir_name = branch_dir_name(branch);
-        let worktree_path = next_available_workspace_path(&workspace_root, &dir_name);
+        let layout = PathLayout {
+            template: self.workspace_path_template.as_deref(),
+            max_slug_length: self.max_slug_length,
+        };
+        let worktree_path = match reserve_target_path(&workspace_root, branch, layout) {
+            Ok(path) => path,
+            Err(err) => {
+                self.status = Some(StatusMessage::error(format!(
+                    "Failed to reserve worktree directory: {err}"
+                )));
+                return;
+            }
+        };
 
         match self
             .backend
-            .add_worktree(&self.repo_root, &worktree_path, Some(branch))
+            .add_worktree(&self.repo_root, &worktree_path, branch, &self.add_options)
         {
             Ok(_) => {
-                self.status = Some(StatusMessage::info(format!(
-                    "Created worktree at {}",
-                    worktree_path.display()
-                )));
+                let hooks_path_error = self.hooks_path.as_deref().and_then(|hooks_path| {
+                    self.backend
+                        .install_hooks_path(&self.repo_root, &worktree_path, hooks_path)
+                        .err()
+                });
+                let context = HookContext {
+                    repo_root: &self.repo_root,
+                    worktree_path: &worktree_path,
+                    branch,
+                };
+                let hook = self.backend.run_hook(Hook::PostCreate, context);
+                self.running_hooks.push(hook);
+                self.status = Some(match hooks_path_error {
+                    Some(err) => {
+                        StatusMessage::error(format!("Failed to set core.hooksPath: {err:#}"))
+                    }
+                    None => StatusMessage::info(format!(
+                        "Created worktree at {}",
+                        worktree_path.display()
+                    )),
+                });
                 self.new_branch.clear();
                 self.pending_removal = None;
                 if let Err(err) = self.reload_worktrees() {
@@ -315,6 +556,7 @@ impl<B: GuiBackend> WtmGui<B> {
                 }
             }
             Err(err) => {
+                let _ = std::fs::remove_dir(&worktree_path);
                 self.status = Some(StatusMessage::error(format!(
                     "Failed to create worktree: {err}"
                 )));
@@ -323,11 +565,65 @@ impl<B: GuiBackend> WtmGui<B> {
     }
 
     fn remove_worktree(&mut self, path: &Path) {
-        match self
+        let removal = self
             .backend
-            .remove_worktree(&self.repo_root, path, self.force_remove)
-        {
-            Ok(_) => {
+            .remove_worktree(&self.repo_root, path, self.force_remove);
+        self.running_removals.push((path.to_path_buf(), removal));
+        self.status = Some(StatusMessage::info(format!(
+            "Removing worktree {}…",
+            path.display()
+        )));
+    }
+
+    /// Report the hooks and removals that have finished since the last frame.
+    fn finish_background_work(&mut self) {
+        let mut hook_results = Vec::new();
+        self.running_hooks.retain(|hook| match hook.try_recv() {
+            Ok(result) => {
+                hook_results.push(result);
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => {
+                hook_results.push(Err(anyhow!("it stopped unexpectedly")));
+                false
+            }
+        });
+        for result in hook_results {
+            match result {
+                Ok(Some(outcome)) if !outcome.success => {
+                    self.status = Some(StatusMessage::error(outcome.summary()));
+                }
+                Err(err) => {
+                    self.status = Some(StatusMessage::error(format!(
+                        "Failed to run post-create hook: {err}"
+                    )));
+                }
+                Ok(_) => {}
+            }
+        }
+
+        let mut removals = Vec::new();
+        self.running_removals
+            .retain(|(path, removal)| match removal.try_recv() {
+                Ok(result) => {
+                    removals.push((path.clone(), result));
+                    false
+                }
+                Err(TryRecvError::Empty) => true,
+                Err(TryRecvError::Disconnected) => {
+                    removals.push((path.clone(), Err(anyhow!("it stopped unexpectedly"))));
+                    false
+                }
+            });
+        for (path, result) in removals {
+            self.removal_finished(&path, result);
+        }
+    }
+
+    fn removal_finished(&mut self, path: &Path, result: Result<()>) {
+        match result {
+            Ok(()) => {
                 self.status = Some(StatusMessage::info(format!(
                     "Removed worktree {}",
                     path.display()
@@ -345,13 +641,28 @@ impl<B: GuiBackend> WtmGui<B> {
         }
     }
 
-    fn reload_worktrees(&mut self) -> Result<()> {
-        let worktrees = self.backend.list_worktrees(&self.repo_root)?;
-        self.sync_workspaces(worktrees);
-        Ok(())
+    fn reload_worktrees(&mut self) -> Result<SyncDelta> {
+        let mut worktrees = self.backend.list_worktrees(&self.repo_root)?;
+        self.ignore.retain_visible(&mut worktrees, &self.repo_root);
+        self.primary_position.apply(&mut worktrees, &self.repo_root);
+        Ok(self.sync_workspaces(worktrees))
     }
 
-    fn sync_workspaces(&mut self, infos: Vec<WorktreeInfo>) {
+    /// Reload the worktree list and report what changed in the status bar.
+    fn refresh(&mut self) {
+        self.status = None;
+        match self.reload_worktrees() {
+            // Keep a terminal start-up error reported by the sync instead.
+            Ok(delta) if self.status.is_none() => {
+                self.status = Some(StatusMessage::info(delta.summary()));
+            }
+            Ok(_) => {}
+            Err(err) => self.status = Some(StatusMessage::error(err.to_string())),
+        }
+    }
+
+    fn sync_workspaces(&mut self, infos: Vec<WorktreeInfo>) -> SyncDelta {
+        let mut delta = SyncDelta::default();
         let mut updated = Vec::with_capacity(infos.len());
         for info in infos {
             if let Some(index) = self
@@ -363,8 +674,17 @@ impl<B: GuiBackend> WtmGui<B> {
                 workspace.update_info(info);
                 updated.push(workspace);
             } else {
-                match GuiWorkspace::new(info) {
-                    Ok(workspace) => updated.push(workspace),
+                match GuiWorkspace::new(
+                    info,
+                    &self.repo_root,
+                    &self.terminal_env,
+                    self.session,
+                    self.waker.as_ref(),
+                ) {
+                    Ok(workspace) => {
+                        delta.added += 1;
+                        updated.push(workspace);
+                    }
                     Err(err) => {
                         self.status = Some(StatusMessage::error(format!(
                             "Failed to start terminal: {err}"
@@ -374,6 +694,8 @@ impl<B: GuiBackend> WtmGui<B> {
             }
         }
 
+        // Whatever was not matched above has disappeared from git's list.
+        delta.removed = self.workspaces.len();
         self.workspaces = updated;
         if self.selected_workspace >= self.workspaces.len() {
             self.selected_workspace = self.workspaces.len().saturating_sub(1);
@@ -383,6 +705,7 @@ impl<B: GuiBackend> WtmGui<B> {
                 self.pending_removal = None;
             }
         }
+        delta
     }
 
     fn run_quick_action(&mut self, action: &QuickAction) {
@@ -408,12 +731,12 @@ impl<B: GuiBackend> WtmGui<B> {
                 ui.heading("WTM Worktree Manager");
                 ui.separator();
                 ui.label(self.repo_root.display().to_string());
-                if ui.button("Refresh").clicked() {
-                    if let Err(err) = self.reload_worktrees() {
-                        self.status = Some(StatusMessage::error(err.to_string()));
-                    } else {
-                        self.status = Some(StatusMessage::info("Refreshed worktrees"));
-                    }
+                if ui
+                    .button("Refresh")
+                    .on_hover_text("F5 or Ctrl+R while the terminal is not focused")
+                    .clicked()
+                {
+                    self.refresh();
                 }
             });
 
@@ -437,7 +760,11 @@ impl<B: GuiBackend> WtmGui<B> {
             .resizable(true)
             .default_width(260.0)
             .show(ctx, |ui| {
-                ui.heading("Worktrees");
+                ui.heading(if self.bare {
+                    "Worktrees (bare repo)"
+                } else {
+                    "Worktrees"
+                });
                 if self.workspaces.is_empty() {
                     ui.label("No worktrees available. Create one to start a terminal.");
                     return;
@@ -454,7 +781,13 @@ impl<B: GuiBackend> WtmGui<B> {
                             if ui.selectable_label(selected, label).clicked() {
                                 action = Some(WorkspaceAction::Select(index));
                             }
-                            ui.label(egui::RichText::new(workspace.display_path()).small().weak());
+                            ui.label(
+                                egui::RichText::new(
+                                    workspace.display_path(self.path_display, &self.repo_root),
+                                )
+                                .small()
+                                .weak(),
+                            );
 
                             ui.horizontal(|row| {
                                 let pending = self.pending_removal.as_ref();
@@ -532,15 +865,32 @@ impl<B: GuiBackend> WtmGui<B> {
         let workspace = &mut self.workspaces[workspace_idx];
 
         ui.heading(workspace.info().name());
-        ui.label(egui::RichText::new(workspace.display_path()).small().weak());
+        ui.label(
+            egui::RichText::new(workspace.display_path(self.path_display, &self.repo_root))
+                .small()
+                .weak(),
+        );
 
         let mut tab_action = None;
+        let closable = workspace.tabs_len() > 1;
         ui.horizontal(|ui| {
             for (index, title) in workspace.tab_titles().into_iter().enumerate() {
                 let selected = index == workspace.active_tab_index();
-                if ui.selectable_label(selected, title).clicked() {
+                let label = ui.selectable_label(selected, title);
+                if label.clicked() {
                     tab_action = Some(TabAction::Select(index));
                 }
+                if closable && label.middle_clicked() {
+                    tab_action = Some(TabAction::Close(index));
+                }
+                if closable
+                    && ui
+                        .small_button("×")
+                        .on_hover_text("Close tab (Ctrl+Shift+W closes the active one)")
+                        .clicked()
+                {
+                    tab_action = Some(TabAction::Close(index));
+                }
             }
             if ui.button("+").clicked() {
                 tab_action = Some(TabAction::Spawn);
@@ -563,6 +913,11 @@ impl<B: GuiBackend> WtmGui<B> {
                         self.status = Some(StatusMessage::error(err.to_string()));
                     }
                 }
+                TabAction::Close(index) => {
+                    if let Err(err) = workspace.close_tab(index) {
+                        self.status = Some(StatusMessage::error(err.to_string()));
+                    }
+                }
             }
         }
 
@@ -589,34 +944,37 @@ impl<B: GuiBackend> WtmGui<B> {
         let desired = egui::vec2(available.x.max(1.0), available.y.max(1.0));
         let (rect, focus_response) = ui.allocate_at_least(desired, egui::Sense::click());
         let area_size = rect.size();
-        let rows = (area_size.y / char_height)
-            .floor()
-            .max(f32::from(MIN_TERMINAL_ROWS)) as u16;
-        let cols = (area_size.x / char_width)
-            .floor()
-            .max(f32::from(MIN_TERMINAL_COLS)) as u16;
-        tab.resize_to(TerminalSize::new(rows, cols));
-
-        let text = {
-            let parser_handle = tab.parser_handle();
-            let text = match parser_handle.read() {
+        let current = tab.size();
+        let target = TerminalSize::new(
+            settle_cells(current.rows, area_size.y / char_height, MIN_TERMINAL_ROWS),
+            settle_cells(current.cols, area_size.x / char_width, MIN_TERMINAL_COLS),
+        );
+        let dragging = ui.input(|input| input.pointer.primary_down());
+        if let Some(size) = self.resize.poll(current, target, dragging, Instant::now()) {
+            tab.resize_to(size);
+        }
+        if self.resize.is_pending() {
+            ui.ctx().request_repaint_after(RESIZE_DEBOUNCE);
+        }
+
+        self.screen
+            .refresh(tab.generation(), || match tab.parser_handle().read() {
                 Ok(parser) => screen_to_string(&parser),
                 Err(_) => "[terminal busy]".to_string(),
-            };
-            text
-        };
+            });
+        let text = self.screen.text();
         let mut child_ui = ui.child_ui(rect, egui::Layout::top_down(egui::Align::LEFT));
         egui::ScrollArea::both()
             .id_source(terminal_id)
             .stick_to_bottom(true)
             .show(&mut child_ui, |ui| {
                 ui.add(
-                    egui::Label::new(egui::RichText::new(&text).monospace())
+                    egui::Label::new(egui::RichText::new(text).monospace())
                         .sense(egui::Sense::click()),
                 );
             });
 
-        if focus_response.clicked() {
+        if focus_response.clicked() || std::mem::take(&mut self.focus_terminal) {
             focus_response.request_focus();
         }
         if focus_response.has_focus() {
@@ -641,6 +999,76 @@ impl<B: GuiBackend> WtmGui<B> {
         );
     }
 
+    /// Select the workspace `step` places away, wrapping at either end.
+    fn cycle_workspace(&mut self, step: isize) {
+        let len = self.workspaces.len() as isize;
+        if len == 0 {
+            return;
+        }
+        let index = (self.selected_workspace as isize + step).rem_euclid(len);
+        self.handle_workspace_action(WorkspaceAction::Select(index as usize));
+        self.focus_terminal = true;
+    }
+
+    /// Ctrl+PageUp/PageDown cycle workspaces and Alt+1…9 pick one; the keys are
+    /// consumed so the focused terminal never sees them.
+    fn handle_workspace_keys(&mut self, ctx: &egui::Context) {
+        const NUMBER_KEYS: [egui::Key; 9] = [
+            egui::Key::Num1,
+            egui::Key::Num2,
+            egui::Key::Num3,
+            egui::Key::Num4,
+            egui::Key::Num5,
+            egui::Key::Num6,
+            egui::Key::Num7,
+            egui::Key::Num8,
+            egui::Key::Num9,
+        ];
+        let (previous, next, number) = ctx.input_mut(|input| {
+            let previous = input.consume_key(egui::Modifiers::CTRL, egui::Key::PageUp);
+            let next = input.consume_key(egui::Modifiers::CTRL, egui::Key::PageDown);
+            let number = NUMBER_KEYS
+                .iter()
+                .position(|key| input.consume_key(egui::Modifiers::ALT, *key));
+            (previous, next, number)
+        });
+        if previous {
+            self.cycle_workspace(-1);
+        }
+        if next {
+            self.cycle_workspace(1);
+        }
+        if let Some(index) = number.filter(|index| *index < self.workspaces.len()) {
+            self.handle_workspace_action(WorkspaceAction::Select(index));
+            self.focus_terminal = true;
+        }
+    }
+
+    /// Ctrl+Shift+T opens and Ctrl+Shift+W closes a tab in the selected
+    /// workspace; plain Ctrl+T/Ctrl+W (e.g. the shell's delete-word) still reach
+    /// the focused terminal.
+    fn handle_tab_keys(&mut self, ctx: &egui::Context) {
+        let chord = egui::Modifiers::CTRL | egui::Modifiers::SHIFT;
+        let (spawn, close) = ctx.input_mut(|input| {
+            (
+                input.consume_key(chord, egui::Key::T),
+                input.consume_key(chord, egui::Key::W),
+            )
+        });
+        let Some(workspace) = self.workspaces.get_mut(self.selected_workspace) else {
+            return;
+        };
+        let result = match (spawn, close) {
+            (true, _) => workspace.spawn_blank_tab(),
+            (_, true) => workspace.close_active_tab(),
+            _ => return,
+        };
+        match result {
+            Ok(()) => self.focus_terminal = true,
+            Err(err) => self.status = Some(StatusMessage::error(err.to_string())),
+        }
+    }
+
     fn handle_workspace_action(&mut self, action: WorkspaceAction) {
         match action {
             WorkspaceAction::Select(index) => {
@@ -672,8 +1100,21 @@ where
         for workspace in &mut self.workspaces {
             workspace.reap_finished();
         }
-        if self.workspaces.iter().any(|ws| ws.needs_repaint()) {
-            ctx.request_repaint();
+        self.finish_background_work();
+        if !self.running_hooks.is_empty() || !self.running_removals.is_empty() {
+            ctx.request_repaint_after(BACKGROUND_POLL);
+        }
+
+        self.handle_workspace_keys(ctx);
+        self.handle_tab_keys(ctx);
+        // A focused terminal or text field receives these keys itself.
+        let refresh_pressed = ctx.memory(|mem| mem.focused().is_none())
+            && ctx.input(|input| {
+                input.key_pressed(egui::Key::F5)
+                    || (input.modifiers.command && input.key_pressed(egui::Key::R))
+            });
+        if refresh_pressed {
+            self.refresh();
         }
 
         self.render_top_panel(ctx);
@@ -722,10 +1163,8 @@ fn forward_events_to_tab(response: &egui::Response, tab: &mut PtyTab) -> Result<
                     tab.handle_key_event(event)?;
                 }
             }
-            egui::Event::Scroll(delta) => {
-                if delta.y.abs() > f32::EPSILON {
-                    tab.scroll_scrollback(delta.y.round() as isize);
-                }
+            egui::Event::Scroll(delta) if delta.y.abs() > f32::EPSILON => {
+                tab.scroll_scrollback(delta.y.round() as isize);
             }
             _ => {}
         }
@@ -792,6 +1231,7 @@ enum TabAction {
     Select(usize),
     Spawn,
     CloseActive,
+    Close(usize),
 }
 
 fn spawn_quick_command(repo_root: &Path, command: &str) -> Result<()> {
@@ -826,7 +1266,8 @@ fn spawn_quick_command(repo_root: &Path, command: &str) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{collections::VecDeque, path::PathBuf};
+    use crate::wtm_paths::DEFAULT_MAX_SLUG_LENGTH;
+    use std::{collections::VecDeque, path::PathBuf, sync::mpsc};
     use tempfile::tempdir;
 
     #[derive(Default)]
@@ -838,12 +1279,14 @@ mod tests {
         add_calls: Vec<AddCall>,
         remove_calls: Vec<RemoveCall>,
         quick_calls: Vec<QuickCall>,
+        hooks_path_calls: Vec<(PathBuf, String)>,
+        hook_results: VecDeque<Result<Option<HookOutcome>>>,
     }
 
     struct AddCall {
         repo_root: PathBuf,
         path: PathBuf,
-        branch: Option<String>,
+        branch: String,
     }
 
     struct RemoveCall {
@@ -868,23 +1311,29 @@ mod tests {
             &mut self,
             repo_root: &Path,
             path: &Path,
-            branch: Option<&str>,
+            branch: &str,
+            _options: &git::AddOptions,
         ) -> Result<()> {
             self.add_calls.push(AddCall {
                 repo_root: repo_root.to_path_buf(),
                 path: path.to_path_buf(),
-                branch: branch.map(|b| b.to_string()),
+                branch: branch.to_string(),
             });
             self.add_results.pop_front().unwrap_or_else(|| Ok(()))
         }
 
-        fn remove_worktree(&mut self, repo_root: &Path, path: &Path, force: bool) -> Result<()> {
+        fn remove_worktree(
+            &mut self,
+            repo_root: &Path,
+            path: &Path,
+            force: bool,
+        ) -> Receiver<Result<()>> {
             self.remove_calls.push(RemoveCall {
                 _repo_root: repo_root.to_path_buf(),
                 _path: path.to_path_buf(),
                 _force: force,
             });
-            self.remove_results.pop_front().unwrap_or_else(|| Ok(()))
+            finished(self.remove_results.pop_front().unwrap_or_else(|| Ok(())))
         }
 
         fn spawn_quick_command(&mut self, repo_root: &Path, command: &str) -> Result<()> {
@@ -894,14 +1343,51 @@ mod tests {
             });
             self.quick_results.pop_front().unwrap_or_else(|| Ok(()))
         }
+
+        fn install_hooks_path(
+            &mut self,
+            _repo_root: &Path,
+            worktree_path: &Path,
+            hooks_path: &str,
+        ) -> Result<()> {
+            self.hooks_path_calls
+                .push((worktree_path.to_path_buf(), hooks_path.to_string()));
+            Ok(())
+        }
+
+        fn run_hook(
+            &mut self,
+            _hook: Hook,
+            _context: HookContext<'_>,
+        ) -> Receiver<Result<Option<HookOutcome>>> {
+            finished(self.hook_results.pop_front().unwrap_or(Ok(None)))
+        }
+    }
+
+    /// A job that has already finished with `result`.
+    fn finished<T>(result: T) -> Receiver<T> {
+        let (sender, receiver) = mpsc::channel();
+        sender.send(result).unwrap();
+        receiver
     }
 
     fn build_gui(backend: MockBackend, repo_root: PathBuf) -> WtmGui<MockBackend> {
         WtmGui::new(
             GuiInitState {
                 repo_root,
+                bare: false,
                 worktrees: Vec::new(),
                 quick_actions: Vec::new(),
+                primary_position: PrimaryPosition::default(),
+                path_display: PathDisplay::default(),
+                ignore: IgnoreList::default(),
+                add_options: git::AddOptions::default(),
+                workspace_path_template: None,
+                max_slug_length: DEFAULT_MAX_SLUG_LENGTH,
+                hooks_path: None,
+                terminal_env: BTreeMap::new(),
+                session: TerminalSession::Shell,
+                waker: None,
             },
             backend,
         )
@@ -947,7 +1433,65 @@ mod tests {
         let call = &gui.backend.add_calls[0];
         assert_eq!(call.repo_root, repo_root);
         assert_eq!(call.path, expected_path);
-        assert_eq!(call.branch.as_deref(), Some("feature/test"));
+        assert_eq!(call.branch, "feature/test");
+        assert!(gui.backend.hooks_path_calls.is_empty());
+    }
+
+    #[test]
+    fn create_worktree_applies_hooks_path() {
+        let temp_repo = tempdir().unwrap();
+        let repo_root = temp_repo.path().to_path_buf();
+        let mut gui = build_gui(MockBackend::default(), repo_root.clone());
+        gui.hooks_path = Some(".wtm/githooks".into());
+        gui.new_branch = "hooked".into();
+
+        gui.create_worktree();
+
+        assert_eq!(
+            gui.backend.hooks_path_calls,
+            [(
+                repo_root.join(".wtm/workspaces/hooked"),
+                ".wtm/githooks".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn hooks_and_removals_report_once_they_finish() {
+        let temp_repo = tempdir().unwrap();
+        let mut backend = MockBackend::default();
+        backend.hook_results.push_back(Ok(Some(HookOutcome {
+            hook: Hook::PostCreate,
+            success: false,
+            output: "npm install failed".into(),
+        })));
+        backend
+            .remove_results
+            .push_back(Err(anyhow!("pre-remove hook refused")));
+        let mut gui = build_gui(backend, temp_repo.path().to_path_buf());
+        gui.new_branch = "hooked".into();
+
+        gui.create_worktree();
+        assert!(gui
+            .status
+            .as_ref()
+            .unwrap()
+            .text
+            .starts_with("Created worktree"));
+        gui.finish_background_work();
+        assert_eq!(
+            gui.status.as_ref().unwrap().text,
+            "post-create hook failed: npm install failed"
+        );
+        assert!(gui.running_hooks.is_empty());
+
+        gui.remove_worktree(Path::new("/ws/hooked"));
+        assert!(gui.status.as_ref().unwrap().text.starts_with("Removing"));
+        gui.finish_background_work();
+        let status = gui.status.as_ref().unwrap();
+        assert!(matches!(status.kind, StatusKind::Error));
+        assert!(status.text.contains("pre-remove hook refused"));
+        assert!(gui.running_removals.is_empty());
     }
 
     #[test]
@@ -967,6 +1511,98 @@ mod tests {
             .unwrap_or(false));
     }
 
+    fn info(path: &Path) -> WorktreeInfo {
+        std::fs::create_dir_all(path).unwrap();
+        WorktreeInfo::at(path)
+    }
+
+    #[test]
+    fn close_tab_keeps_the_active_tab_selected() {
+        let temp = tempdir().unwrap();
+        let mut workspace = GuiWorkspace::new(
+            info(temp.path()),
+            temp.path(),
+            &BTreeMap::new(),
+            TerminalSession::Shell,
+            None,
+        )
+        .unwrap();
+        workspace.spawn_blank_tab().unwrap();
+        workspace.spawn_blank_tab().unwrap();
+        workspace.set_active_tab(1);
+
+        workspace.close_tab(0).unwrap();
+        assert_eq!(workspace.tabs_len(), 2);
+        assert_eq!(workspace.active_tab_index(), 0, "the same tab stays active");
+
+        workspace.close_tab(1).unwrap();
+        assert_eq!(workspace.tabs_len(), 1);
+        assert_eq!(workspace.active_tab_index(), 0);
+
+        workspace.close_tab(0).unwrap();
+        assert_eq!(workspace.tabs_len(), 1, "the last tab stays open");
+
+        workspace.spawn_blank_tab().unwrap();
+        assert_eq!(workspace.active_tab_index(), 1);
+        workspace.close_tab(1).unwrap();
+        assert_eq!(
+            workspace.active_tab_index(),
+            0,
+            "closing the active last tab steps back"
+        );
+    }
+
+    #[test]
+    fn cycle_workspace_wraps_around() {
+        let temp_repo = tempdir().unwrap();
+        let repo = temp_repo.path();
+        let mut backend = MockBackend::default();
+        let worktrees = ["a", "b", "c"].map(|name| info(&repo.join(name)));
+        backend.list_results.push_back(Ok(worktrees.to_vec()));
+        let mut gui = build_gui(backend, repo.to_path_buf());
+        gui.refresh();
+
+        gui.cycle_workspace(-1);
+        assert_eq!(gui.selected_workspace, 2);
+        assert!(gui.focus_terminal, "the new terminal takes focus");
+        gui.cycle_workspace(1);
+        assert_eq!(gui.selected_workspace, 0);
+        gui.cycle_workspace(4);
+        assert_eq!(gui.selected_workspace, 1);
+    }
+
+    #[test]
+    fn refresh_reports_added_and_removed_worktrees() {
+        let temp_repo = tempdir().unwrap();
+        let repo = temp_repo.path();
+        let mut backend = MockBackend::default();
+        let first = vec![info(repo), info(&repo.join("old"))];
+        let second = vec![info(repo), info(&repo.join("new"))];
+        backend.list_results.push_back(Ok(first));
+        backend.list_results.push_back(Ok(second));
+        let mut gui = build_gui(backend, repo.to_path_buf());
+
+        gui.refresh();
+        let status = gui.status.clone().expect("status set");
+        assert_eq!(status.text, "Refreshed worktrees (+2 new)");
+
+        gui.refresh();
+        let status = gui.status.clone().expect("status set");
+        assert_eq!(status.text, "Refreshed worktrees (+1 new, -1 removed)");
+        assert!(matches!(status.kind, StatusKind::Info));
+        let paths: Vec<_> = gui
+            .workspaces
+            .iter()
+            .map(|ws| ws.path().to_path_buf())
+            .collect();
+        assert_eq!(paths, [repo.to_path_buf(), repo.join("new")]);
+
+        assert_eq!(
+            SyncDelta::default().summary(),
+            "Refreshed worktrees (no changes)"
+        );
+    }
+
     #[test]
     fn run_quick_action_records_backend_invocation() {
         let temp_repo = tempdir().unwrap();
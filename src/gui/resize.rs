@@ -0,0 +1,105 @@
+//! Deciding when the GUI terminal resizes its pseudoterminal.
+//!
+//! The grid is recomputed from pixel sizes every frame; resizing the PTY on each
+//! tiny change makes full-screen programs redraw constantly, so changes must clear
+//! a deadband and then hold still for a moment before they are applied.
+
+use crate::tui::size::TerminalSize;
+use std::time::{Duration, Instant};
+
+/// How far, in cells, the available space must pass a cell boundary to count.
+const DEADBAND: f32 = 0.25;
+
+/// How long a new grid size must stay put before the PTY is resized.
+pub(super) const RESIZE_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Whole cells for `available` (fractional) cells, keeping `current` near a boundary.
+pub(super) fn settle_cells(current: u16, available: f32, min: u16) -> u16 {
+    let current_cells = f32::from(current);
+    let moved = available >= current_cells + 1.0 + DEADBAND || available < current_cells - DEADBAND;
+    let cells = if moved {
+        available.floor().clamp(0.0, f32::from(u16::MAX)) as u16
+    } else {
+        current
+    };
+    cells.max(min)
+}
+
+/// Holds a grid size back until it has been stable for [`RESIZE_DEBOUNCE`].
+#[derive(Debug, Default)]
+pub(super) struct ResizeThrottle {
+    pending: Option<(TerminalSize, Instant)>,
+}
+
+impl ResizeThrottle {
+    /// The size to resize to now, if any; nothing is applied while `dragging`.
+    pub(super) fn poll(
+        &mut self,
+        current: TerminalSize,
+        target: TerminalSize,
+        dragging: bool,
+        now: Instant,
+    ) -> Option<TerminalSize> {
+        if target == current {
+            self.pending = None;
+            return None;
+        }
+        match self.pending {
+            Some((size, since)) if size == target => {
+                if dragging || now.duration_since(since) < RESIZE_DEBOUNCE {
+                    return None;
+                }
+                self.pending = None;
+                Some(target)
+            }
+            _ => {
+                self.pending = Some((target, now));
+                None
+            }
+        }
+    }
+
+    /// Whether a size is waiting, so the caller should repaint again soon.
+    pub(super) fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settle_cells_ignores_jitter_around_a_boundary() {
+        assert_eq!(settle_cells(80, 79.9, 10), 80);
+        assert_eq!(settle_cells(80, 81.1, 10), 80);
+        assert_eq!(settle_cells(80, 81.3, 10), 81);
+        assert_eq!(settle_cells(80, 79.7, 10), 79);
+        assert_eq!(settle_cells(80, 4.0, 10), 10, "minimum applies");
+    }
+
+    #[test]
+    fn throttle_waits_for_a_stable_size_and_the_end_of_a_drag() {
+        let start = Instant::now();
+        let current = TerminalSize::new(24, 80);
+        let target = TerminalSize::new(30, 100);
+        let mut throttle = ResizeThrottle::default();
+
+        assert_eq!(throttle.poll(current, target, false, start), None);
+        assert!(throttle.is_pending());
+        let later = start + RESIZE_DEBOUNCE;
+        assert_eq!(throttle.poll(current, target, true, later), None);
+        assert_eq!(throttle.poll(current, target, false, later), Some(target));
+        assert!(!throttle.is_pending());
+
+        let moved = TerminalSize::new(31, 100);
+        assert_eq!(throttle.poll(current, target, false, start), None);
+        assert_eq!(
+            throttle.poll(current, moved, false, later),
+            None,
+            "restarts"
+        );
+        assert_eq!(throttle.poll(current, current, false, later), None);
+        assert!(!throttle.is_pending());
+    }
+}
@@ -0,0 +1,56 @@
+//! Reusing the terminal text between frames.
+//!
+//! Turning the vt100 screen into a string on every frame keeps the GUI busy even
+//! when nothing happens, so the text is rebuilt only when the tab's screen
+//! generation moves.
+
+/// The text of the terminal shown last, and the generation it was built from.
+#[derive(Debug, Default)]
+pub(super) struct ScreenCache {
+    generation: Option<u64>,
+    text: String,
+}
+
+impl ScreenCache {
+    /// Rebuild the text with `render` if `generation` differs from the cached
+    /// one. Returns whether the content changed.
+    pub(super) fn refresh(&mut self, generation: u64, render: impl FnOnce() -> String) -> bool {
+        if self.generation == Some(generation) {
+            return false;
+        }
+        self.generation = Some(generation);
+        self.text = render();
+        true
+    }
+
+    pub(super) fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_renders_only_when_the_generation_moves() {
+        let mut cache = ScreenCache::default();
+        let mut renders = 0;
+        let mut render = |text: &str| {
+            renders += 1;
+            text.to_string()
+        };
+
+        assert!(cache.refresh(7, || render("$ ")));
+        assert_eq!(cache.text(), "$ ");
+        assert!(!cache.refresh(7, || render("ignored")));
+        assert_eq!(cache.text(), "$ ");
+        assert!(cache.refresh(9, || render("$ ls")));
+        assert_eq!(cache.text(), "$ ls");
+        assert!(
+            cache.refresh(3, || render("other tab")),
+            "any other generation counts"
+        );
+        assert_eq!(renders, 3);
+    }
+}
@@ -0,0 +1,101 @@
+//! Refusing to start the dashboard where nobody can use it: without a terminal,
+//! under CI, or when `--no-tui` asks for that.
+
+use anyhow::{bail, Result};
+use std::{
+    ffi::OsStr,
+    io::{self, IsTerminal},
+};
+
+/// The full-screen frontends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frontend {
+    Tui,
+    Gui,
+}
+
+/// Fail with a pointer to the CLI when `frontend` should not start here.
+///
+/// The GUI opens its own window and needs no terminal, but `--no-tui` and `CI`
+/// stop it as well.
+pub fn ensure_interactive(frontend: Frontend, no_tui: bool) -> Result<()> {
+    let reason = headless_reason(
+        frontend,
+        no_tui,
+        std::env::var_os("CI").as_deref(),
+        io::stdin().is_terminal(),
+        io::stdout().is_terminal(),
+    );
+    match reason {
+        Some(reason) => bail!(
+            "not starting the {}: {reason}. Use a subcommand instead, e.g. `wtm worktree list` (see `wtm --help`)",
+            match frontend {
+                Frontend::Tui => "dashboard",
+                Frontend::Gui => "GUI",
+            }
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Why `frontend` should not start, if it should not.
+fn headless_reason(
+    frontend: Frontend,
+    no_tui: bool,
+    ci_env: Option<&OsStr>,
+    stdin_is_terminal: bool,
+    stdout_is_terminal: bool,
+) -> Option<&'static str> {
+    if no_tui {
+        return Some("--no-tui was given");
+    }
+    if ci_env.is_some_and(|value| !value.is_empty() && value != "false" && value != "0") {
+        return Some("the CI environment variable is set");
+    }
+    if frontend == Frontend::Gui {
+        return None;
+    }
+    if !stdin_is_terminal {
+        return Some("stdin is not a terminal");
+    }
+    if !stdout_is_terminal {
+        return Some("stdout is not a terminal");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frontends_start_only_when_interactive() {
+        let ci = |value: &str| Some(OsStr::new(value).to_os_string());
+        let reason = |frontend, no_tui, env: Option<std::ffi::OsString>, stdin, stdout| {
+            headless_reason(frontend, no_tui, env.as_deref(), stdin, stdout)
+        };
+
+        assert_eq!(reason(Frontend::Tui, false, None, true, true), None);
+        assert_eq!(reason(Frontend::Tui, false, ci("false"), true, true), None);
+        assert_eq!(
+            reason(Frontend::Tui, false, None, true, false),
+            Some("stdout is not a terminal")
+        );
+        assert_eq!(
+            reason(Frontend::Tui, false, None, false, true),
+            Some("stdin is not a terminal")
+        );
+        assert_eq!(
+            reason(Frontend::Tui, false, ci("true"), true, true),
+            Some("the CI environment variable is set")
+        );
+        assert_eq!(
+            reason(Frontend::Tui, true, None, true, true),
+            Some("--no-tui was given")
+        );
+
+        assert_eq!(reason(Frontend::Gui, false, None, false, false), None);
+        assert!(reason(Frontend::Gui, false, ci("1"), true, true).is_some());
+        assert!(reason(Frontend::Gui, true, None, true, true).is_some());
+    }
+}
@@ -0,0 +1,196 @@
+//! Executable hooks in `.wtm/hooks/` run around worktree lifecycle events.
+//!
+//! A hook receives the worktree path and branch as arguments and as
+//! `WTM_WORKTREE_PATH`/`WTM_BRANCH`/`WTM_REPO_ROOT`, runs inside the worktree,
+//! and has its combined output captured for the caller to report.
+
+use crate::{
+    git,
+    runner::{CommandRunner, SystemRunner},
+};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+/// Directory inside `.wtm/` holding hook executables.
+pub const HOOKS_DIR: &str = "hooks";
+
+/// Lifecycle points that can run a hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// After a worktree has been created.
+    PostCreate,
+    /// Before a worktree is removed; a non-zero exit vetoes the removal.
+    PreRemove,
+}
+
+impl Hook {
+    /// File name of the hook inside `.wtm/hooks/`.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Hook::PostCreate => "post-create",
+            Hook::PreRemove => "pre-remove",
+        }
+    }
+}
+
+/// What a hook run produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookOutcome {
+    pub hook: Hook,
+    pub success: bool,
+    /// Trimmed stdout followed by stderr.
+    pub output: String,
+}
+
+impl HookOutcome {
+    /// One-line summary suitable for a status bar.
+    pub fn summary(&self) -> String {
+        let last_line = self.output.lines().last().unwrap_or_default();
+        match (self.success, last_line.is_empty()) {
+            (true, _) => format!("{} hook finished.", self.hook.file_name()),
+            (false, true) => format!("{} hook failed.", self.hook.file_name()),
+            (false, false) => format!("{} hook failed: {last_line}", self.hook.file_name()),
+        }
+    }
+}
+
+/// Point a new worktree's own `core.hooksPath` at `hooks_path` (the `hooksPath`
+/// setting), so its git hooks work straight away without touching the other
+/// worktrees. A relative path is taken from the repository root; the absolute
+/// path configured is returned.
+pub fn install_hooks_path(
+    repo_root: &Path,
+    worktree_path: &Path,
+    hooks_path: &str,
+) -> Result<PathBuf> {
+    let path = repo_root.join(hooks_path);
+    git::set_worktree_config(worktree_path, "core.hooksPath", &path.to_string_lossy())?;
+    Ok(path)
+}
+
+/// Worktree details passed to a hook.
+#[derive(Debug, Clone, Copy)]
+pub struct HookContext<'a> {
+    pub repo_root: &'a Path,
+    pub worktree_path: &'a Path,
+    pub branch: &'a str,
+}
+
+/// Path of `hook` for the repository at `repo_root`.
+pub fn hook_path(repo_root: &Path, hook: Hook) -> PathBuf {
+    repo_root
+        .join(".wtm")
+        .join(HOOKS_DIR)
+        .join(hook.file_name())
+}
+
+/// Run `hook` if it exists; `Ok(None)` means there was nothing to run.
+pub fn run_hook(hook: Hook, context: HookContext<'_>) -> Result<Option<HookOutcome>> {
+    run_hook_with(&SystemRunner, hook, context)
+}
+
+fn run_hook_with(
+    runner: &dyn CommandRunner,
+    hook: Hook,
+    context: HookContext<'_>,
+) -> Result<Option<HookOutcome>> {
+    let path = hook_path(context.repo_root, hook);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let worktree = context.worktree_path.as_os_str();
+    let output = runner.run(
+        path.as_os_str(),
+        &[worktree, context.branch.as_ref()],
+        Some(context.worktree_path),
+        &[
+            ("WTM_WORKTREE_PATH", worktree),
+            ("WTM_BRANCH", context.branch.as_ref()),
+            ("WTM_REPO_ROOT", context.repo_root.as_os_str()),
+        ],
+    )?;
+    let combined = [output.stdout.trim(), output.stderr.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    log::debug!("{} hook output: {combined}", hook.file_name());
+    Ok(Some(HookOutcome {
+        hook,
+        success: output.success,
+        output: combined,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::testing::ScriptedRunner;
+    use tempfile::tempdir;
+
+    #[test]
+    fn missing_hook_is_skipped() {
+        let repo = tempdir().unwrap();
+        let runner = ScriptedRunner::default();
+        let context = HookContext {
+            repo_root: repo.path(),
+            worktree_path: repo.path(),
+            branch: "main",
+        };
+        assert_eq!(
+            run_hook_with(&runner, Hook::PostCreate, context).unwrap(),
+            None
+        );
+        assert!(runner.calls().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn post_create_hook_receives_worktree_env() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let repo = tempdir().unwrap();
+        let worktree = tempdir().unwrap();
+        let hooks = repo.path().join(".wtm").join(HOOKS_DIR);
+        std::fs::create_dir_all(&hooks).unwrap();
+        let script = hooks.join("post-create");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\necho \"$WTM_BRANCH|$WTM_WORKTREE_PATH|$1|$(pwd)\" > hook.out\necho done\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let context = HookContext {
+            repo_root: repo.path(),
+            worktree_path: worktree.path(),
+            branch: "feature/x",
+        };
+        let outcome = run_hook(Hook::PostCreate, context).unwrap().unwrap();
+        assert!(outcome.success);
+        assert_eq!(outcome.output, "done");
+
+        let recorded = std::fs::read_to_string(worktree.path().join("hook.out")).unwrap();
+        let path = worktree.path().display().to_string();
+        let cwd = worktree
+            .path()
+            .canonicalize()
+            .unwrap()
+            .display()
+            .to_string();
+        assert_eq!(recorded.trim(), format!("feature/x|{path}|{path}|{cwd}"));
+    }
+
+    #[test]
+    fn summary_reports_last_line_of_failures() {
+        let outcome = HookOutcome {
+            hook: Hook::PostCreate,
+            success: false,
+            output: "installing\nnpm ERR! missing script".into(),
+        };
+        assert_eq!(
+            outcome.summary(),
+            "post-create hook failed: npm ERR! missing script"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! `.wtm/ignore`: glob patterns for worktrees hidden from listings.
+//!
+//! Each non-empty, non-`#` line is matched against a worktree's absolute path,
+//! its path relative to the repository root, its directory name, and its branch.
+
+use crate::git::WorktreeInfo;
+use anyhow::{Context, Result};
+use glob::Pattern;
+use std::{fs, path::Path};
+
+/// File inside `.wtm/` listing the patterns.
+pub const IGNORE_FILE: &str = "ignore";
+
+/// Parsed ignore patterns; empty when no ignore file exists.
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreList {
+    patterns: Vec<Pattern>,
+}
+
+impl IgnoreList {
+    /// Read `<wtm_dir>/ignore`, treating a missing file as "hide nothing".
+    pub fn load(wtm_dir: &Path) -> Result<Self> {
+        let path = wtm_dir.join(IGNORE_FILE);
+        match fs::read_to_string(&path) {
+            Ok(data) => Self::parse(&data).with_context(|| format!("invalid {}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    fn parse(data: &str) -> Result<Self> {
+        let mut patterns = Vec::new();
+        for (index, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let pattern = Pattern::new(line)
+                .with_context(|| format!("line {}: bad pattern `{line}`", index + 1))?;
+            patterns.push(pattern);
+        }
+        Ok(Self { patterns })
+    }
+
+    /// The patterns as written, in file order.
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(Pattern::as_str)
+    }
+
+    /// Whether `worktree` matches any pattern and should be left out of listings.
+    pub fn should_hide(&self, worktree: &WorktreeInfo, repo_root: &Path) -> bool {
+        if self.patterns.is_empty() {
+            return false;
+        }
+        let mut candidates = vec![
+            worktree.path.to_string_lossy().into_owned(),
+            worktree.name(),
+        ];
+        if let Ok(relative) = worktree.path.strip_prefix(repo_root) {
+            candidates.push(relative.to_string_lossy().into_owned());
+        }
+        if let Some(branch) = worktree.branch.as_deref() {
+            candidates.push(branch.to_string());
+        }
+        self.patterns.iter().any(|pattern| {
+            candidates
+                .iter()
+                .any(|candidate| pattern.matches(candidate))
+        })
+    }
+
+    /// Drop hidden worktrees from `worktrees`.
+    pub fn retain_visible(&self, worktrees: &mut Vec<WorktreeInfo>, repo_root: &Path) {
+        worktrees.retain(|wt| !self.should_hide(wt, repo_root));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_hide_matches_path_name_and_branch() {
+        let ignore = IgnoreList::parse(
+            "# generated worktrees\n\n.wtm/workspaces/gen-*\nrenovate/*\nscratch\n",
+        )
+        .unwrap();
+        let repo = Path::new("/repo");
+
+        assert!(ignore.should_hide(&WorktreeInfo::at("/repo/.wtm/workspaces/gen-1"), repo));
+        assert!(ignore.should_hide(
+            &WorktreeInfo::at("/repo/.wtm/workspaces/deps").on("renovate/serde"),
+            repo
+        ));
+        assert!(ignore.should_hide(&WorktreeInfo::at("/elsewhere/scratch"), repo));
+        assert!(!ignore.should_hide(
+            &WorktreeInfo::at("/repo/.wtm/workspaces/feature").on("feature/login"),
+            repo
+        ));
+        assert!(!ignore.should_hide(&WorktreeInfo::at("/repo").on("main"), repo));
+    }
+
+    #[test]
+    fn load_without_file_hides_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let ignore = IgnoreList::load(dir.path()).unwrap();
+        assert!(!ignore.should_hide(&WorktreeInfo::at("/repo"), Path::new("/repo")));
+    }
+
+    #[test]
+    fn parse_reports_bad_patterns_with_line_numbers() {
+        let err = IgnoreList::parse("ok\n[unclosed\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+}
@@ -2,28 +2,65 @@ use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
+    ffi::OsStr,
     fs,
     path::{Path, PathBuf},
-    process::Command,
 };
 
-use crate::wtm_paths::branch_dir_name;
+use crate::{
+    runner::{is_program_missing, CommandRunner, SystemRunner},
+    wtm_paths::{atomic_write_json, branch_slug},
+};
 
 const CACHE_FILE: &str = "jira_cache.json";
 const DEFAULT_JQL: &str = "assignee = currentUser() AND statusCategory != Done";
-const DEFAULT_FIELDS: &str = "key,summary";
+const DEFAULT_FIELDS: &str = "key,summary,status";
 const DEFAULT_LIMIT: &str = "200";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JiraTicket {
     pub key: String,
     pub summary: String,
+    /// Workflow status (e.g. `In Progress`); absent in caches written before it was fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
 }
 
 impl JiraTicket {
     pub fn slug(&self) -> String {
-        branch_dir_name(&format!("{} {}", self.key, self.summary))
+        branch_slug(&format!("{} {}", self.key, self.summary))
     }
+
+    /// `PROJ-123 — Summary (Status)`.
+    pub fn headline(&self) -> String {
+        match self.status.as_deref() {
+            Some(status) => format!("{} — {} ({status})", self.key, self.summary),
+            None => format!("{} — {}", self.key, self.summary),
+        }
+    }
+}
+
+/// [`ticket_key_in`], also accepting lowercase keys (`proj-123-fix`), which are
+/// returned uppercased. Only for looking tickets up: `feature-2` reads as a key.
+pub fn ticket_key_in_any_case(branch: &str) -> Option<String> {
+    ticket_key_in(&branch.to_ascii_uppercase())
+}
+
+/// The first Jira-style key (`ABC-123`) starting a `/`-separated segment of `branch`.
+pub fn ticket_key_in(branch: &str) -> Option<String> {
+    branch.split('/').find_map(|segment| {
+        let (project, rest) = segment.split_once('-')?;
+        let mut project_chars = project.chars();
+        let valid_project = project_chars.next().is_some_and(|c| c.is_ascii_uppercase())
+            && project_chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let ends_cleanly = !rest[digits..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphanumeric());
+        (valid_project && digits > 0 && ends_cleanly)
+            .then(|| format!("{project}-{}", &rest[..digits]))
+    })
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,15 +68,29 @@ struct JiraCacheFile {
     tickets: Vec<JiraTicket>,
 }
 
+/// The cached tickets, without fetching when there is no cache.
+pub fn cached_tickets_offline(repo_root: &Path) -> Result<Option<Vec<JiraTicket>>> {
+    load_cache(repo_root)
+}
+
+/// The cached tickets, fetched and cached first when there is no usable cache.
 pub fn cached_tickets(repo_root: &Path) -> Result<Vec<JiraTicket>> {
+    cached_tickets_with(&SystemRunner, repo_root)
+}
+
+fn cached_tickets_with(runner: &dyn CommandRunner, repo_root: &Path) -> Result<Vec<JiraTicket>> {
     if let Some(tickets) = load_cache(repo_root)? {
         return Ok(tickets);
     }
-    refresh_cache(repo_root)
+    refresh_cache_with(runner, repo_root)
 }
 
 pub fn refresh_cache(repo_root: &Path) -> Result<Vec<JiraTicket>> {
-    let tickets = fetch_tickets()?;
+    refresh_cache_with(&SystemRunner, repo_root)
+}
+
+fn refresh_cache_with(runner: &dyn CommandRunner, repo_root: &Path) -> Result<Vec<JiraTicket>> {
+    let tickets = fetch_tickets_with(runner)?;
     write_cache(repo_root, &tickets)?;
     Ok(tickets)
 }
@@ -53,6 +104,9 @@ pub fn invalidate_cache(repo_root: &Path) -> Result<()> {
     Ok(())
 }
 
+/// The cached tickets, or `None` when there is no cache. A cache that does not
+/// parse (e.g. cut short by a crash) is moved aside to `jira_cache.json.bak`
+/// and treated as missing, so the next lookup fetches afresh.
 fn load_cache(repo_root: &Path) -> Result<Option<Vec<JiraTicket>>> {
     let cache_path = cache_path(repo_root);
     if !cache_path.exists() {
@@ -60,13 +114,24 @@ fn load_cache(repo_root: &Path) -> Result<Option<Vec<JiraTicket>>> {
     }
     let data = fs::read_to_string(&cache_path)
         .with_context(|| format!("failed to read Jira cache from {}", cache_path.display()))?;
-    let cache: JiraCacheFile = serde_json::from_str(&data).with_context(|| {
-        format!(
-            "failed to parse Jira cache stored at {}",
-            cache_path.display()
-        )
-    })?;
-    Ok(Some(cache.tickets))
+    match serde_json::from_str::<JiraCacheFile>(&data) {
+        Ok(cache) => Ok(Some(cache.tickets)),
+        Err(err) => {
+            let backup = cache_path.with_file_name(format!("{CACHE_FILE}.bak"));
+            log::warn!(
+                "ignoring corrupt Jira cache at {} ({err}); moved it to {}",
+                cache_path.display(),
+                backup.display()
+            );
+            fs::rename(&cache_path, &backup).with_context(|| {
+                format!(
+                    "failed to move corrupt Jira cache {} aside",
+                    cache_path.display()
+                )
+            })?;
+            Ok(None)
+        }
+    }
 }
 
 fn write_cache(repo_root: &Path, tickets: &[JiraTicket]) -> Result<()> {
@@ -83,8 +148,7 @@ fn write_cache(repo_root: &Path, tickets: &[JiraTicket]) -> Result<()> {
     let cache = JiraCacheFile {
         tickets: tickets.to_vec(),
     };
-    let data = serde_json::to_string_pretty(&cache).context("failed to serialize Jira cache")?;
-    fs::write(cache_path(repo_root), data).with_context(|| {
+    atomic_write_json(&cache_path(repo_root), &cache).with_context(|| {
         format!(
             "failed to write Jira cache to {}",
             cache_path(repo_root).display()
@@ -97,9 +161,26 @@ fn cache_path(repo_root: &Path) -> PathBuf {
     repo_root.join(".wtm").join(CACHE_FILE)
 }
 
-fn fetch_tickets() -> Result<Vec<JiraTicket>> {
-    fetch_tickets_new_cli().or_else(|primary_err| {
-        fetch_tickets_legacy_cli().map_err(|legacy_err| {
+/// `acli` is not installed, so Jira tickets cannot be fetched at all.
+#[derive(Debug, thiserror::Error)]
+#[error("acli is not installed")]
+pub struct AcliMissing;
+
+/// One-line explanation of why Jira tickets are unavailable, for the add overlay.
+pub fn unavailable_hint(err: &anyhow::Error) -> String {
+    if err.is::<AcliMissing>() {
+        "Jira unavailable: install acli".into()
+    } else {
+        format!("Jira unavailable: {err}")
+    }
+}
+
+fn fetch_tickets_with(runner: &dyn CommandRunner) -> Result<Vec<JiraTicket>> {
+    fetch_tickets_new_cli(runner).or_else(|primary_err| {
+        if is_program_missing(&primary_err) {
+            return Err(AcliMissing.into());
+        }
+        fetch_tickets_legacy_cli(runner).map_err(|legacy_err| {
             anyhow!(
                 "failed to fetch Jira tickets via acli: {primary_err} (legacy fallback error: {legacy_err})"
             )
@@ -107,45 +188,103 @@ fn fetch_tickets() -> Result<Vec<JiraTicket>> {
     })
 }
 
-fn fetch_tickets_new_cli() -> Result<Vec<JiraTicket>> {
-    let output = Command::new("acli")
-        .args([
-            "jira",
-            "workitem",
-            "search",
-            "--jql",
-            DEFAULT_JQL,
-            "--fields",
-            DEFAULT_FIELDS,
-            "--limit",
-            DEFAULT_LIMIT,
-            "--json",
-        ])
-        .output()
+fn fetch_tickets_new_cli(runner: &dyn CommandRunner) -> Result<Vec<JiraTicket>> {
+    let output = runner
+        .run(
+            "acli".as_ref(),
+            &[
+                "jira",
+                "workitem",
+                "search",
+                "--jql",
+                DEFAULT_JQL,
+                "--fields",
+                DEFAULT_FIELDS,
+                "--limit",
+                DEFAULT_LIMIT,
+                "--json",
+            ]
+            .map(OsStr::new),
+            None,
+            &[],
+        )
         .context("failed to execute acli workitem search for Jira tickets")?;
-    if !output.status.success() {
+    if !output.success {
         return Err(anyhow!(
             "acli workitem search command failed: {}",
-            String::from_utf8_lossy(&output.stderr).trim()
+            output.stderr.trim()
         ));
     }
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_acli_output(stdout.trim())
+    parse_acli_output(output.stdout.trim())
 }
 
-fn fetch_tickets_legacy_cli() -> Result<Vec<JiraTicket>> {
-    let output = Command::new("acli")
-        .args(["jira", "issues", "--format", "json"])
-        .output()
+fn fetch_tickets_legacy_cli(runner: &dyn CommandRunner) -> Result<Vec<JiraTicket>> {
+    let output = runner
+        .run(
+            "acli".as_ref(),
+            &["jira", "issues", "--format", "json"].map(OsStr::new),
+            None,
+            &[],
+        )
         .context("failed to execute legacy acli issues command for Jira tickets")?;
-    if !output.status.success() {
+    if !output.success {
         return Err(anyhow!(
             "legacy acli issues command failed: {}",
-            String::from_utf8_lossy(&output.stderr).trim()
+            output.stderr.trim()
         ));
     }
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    parse_acli_output(stdout.trim())
+    parse_acli_output(output.stdout.trim())
+}
+
+/// Move the ticket `key` to `status` (e.g. `In Progress`).
+pub fn transition(key: &str, status: &str) -> Result<()> {
+    transition_with(&SystemRunner, key, status)
+}
+
+fn transition_with(runner: &dyn CommandRunner, key: &str, status: &str) -> Result<()> {
+    run_acli(
+        runner,
+        &[
+            "jira",
+            "workitem",
+            "transition",
+            "--key",
+            key,
+            "--status",
+            status,
+            "--yes",
+        ],
+    )
+    .with_context(|| format!("failed to move {key} to {status}"))
+}
+
+/// Add a comment with `text` to the ticket `key`.
+pub fn comment(key: &str, text: &str) -> Result<()> {
+    comment_with(&SystemRunner, key, text)
+}
+
+fn comment_with(runner: &dyn CommandRunner, key: &str, text: &str) -> Result<()> {
+    run_acli(
+        runner,
+        &[
+            "jira", "workitem", "comment", "create", "--key", key, "--body", text,
+        ],
+    )
+    .with_context(|| format!("failed to comment on {key}"))
+}
+
+/// Run `acli` with `args`, failing with its stderr (or [`AcliMissing`]).
+fn run_acli(runner: &dyn CommandRunner, args: &[&str]) -> Result<()> {
+    let args: Vec<&OsStr> = args.iter().map(OsStr::new).collect();
+    let output = match runner.run("acli".as_ref(), &args, None, &[]) {
+        Ok(output) => output,
+        Err(err) if is_program_missing(&err) => return Err(AcliMissing.into()),
+        Err(err) => return Err(err.context("failed to execute acli")),
+    };
+    if !output.success {
+        return Err(anyhow!("{}", output.stderr.trim()));
+    }
+    Ok(())
 }
 
 fn parse_acli_output(output: &str) -> Result<Vec<JiraTicket>> {
@@ -176,6 +315,7 @@ fn parse_acli_output(output: &str) -> Result<Vec<JiraTicket>> {
         tickets.push(JiraTicket {
             key: key.to_string(),
             summary,
+            status: None,
         });
     }
     Ok(tickets)
@@ -193,17 +333,163 @@ fn value_to_ticket(value: &Value) -> Option<JiraTicket> {
                 .and_then(Value::as_str)
         })
         .unwrap_or("");
+    let fields = value.get("fields");
+    let status = [value.get("status"), fields.and_then(|f| f.get("status"))]
+        .into_iter()
+        .flatten()
+        .find_map(|status| {
+            status
+                .as_str()
+                .or_else(|| status.get("name").and_then(Value::as_str))
+        })
+        .filter(|status| !status.is_empty());
     Some(JiraTicket {
         key: key.to_string(),
         summary: summary.to_string(),
+        status: status.map(str::to_string),
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::runner::testing::ScriptedRunner;
     use serde_json::json;
 
+    #[test]
+    fn ticket_key_in_finds_keys_at_segment_starts() {
+        assert_eq!(
+            ticket_key_in("PROJ-123-fix-login").as_deref(),
+            Some("PROJ-123")
+        );
+        assert_eq!(ticket_key_in("feature/AB2-7").as_deref(), Some("AB2-7"));
+        assert_eq!(ticket_key_in("fix-login"), None);
+        assert_eq!(ticket_key_in("proj-123-lowercase"), None);
+        assert_eq!(ticket_key_in("PROJ-12abc"), None);
+        assert_eq!(ticket_key_in("release/2-0"), None);
+    }
+
+    #[test]
+    fn ticket_keys_are_found_in_any_case_for_lookups() {
+        for (branch, key) in [
+            ("PROJ-123-fix-login", Some("PROJ-123")),
+            ("proj-123-fix-login", Some("PROJ-123")),
+            ("feature/Proj-9", Some("PROJ-9")),
+            ("user/abc2-77/retry", Some("ABC2-77")),
+            ("proj-12abc", None),
+            ("fix-login", None),
+            ("release/2-0", None),
+        ] {
+            assert_eq!(ticket_key_in_any_case(branch).as_deref(), key, "{branch}");
+        }
+    }
+
+    #[test]
+    fn transition_and_comment_build_acli_commands() {
+        let runner = ScriptedRunner::default().succeed("").succeed("");
+        transition_with(&runner, "PROJ-7", "In Progress").unwrap();
+        comment_with(&runner, "PROJ-7", "Started on branch PROJ-7-login").unwrap();
+        let calls = runner.calls();
+        assert_eq!(calls[0].program, "acli");
+        assert_eq!(
+            calls[0].args,
+            [
+                "jira",
+                "workitem",
+                "transition",
+                "--key",
+                "PROJ-7",
+                "--status",
+                "In Progress",
+                "--yes"
+            ]
+        );
+        assert_eq!(
+            calls[1].args,
+            [
+                "jira",
+                "workitem",
+                "comment",
+                "create",
+                "--key",
+                "PROJ-7",
+                "--body",
+                "Started on branch PROJ-7-login"
+            ]
+        );
+
+        let err = transition_with(
+            &ScriptedRunner::default().fail("no transition to Done\n"),
+            "PROJ-7",
+            "Done",
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "failed to move PROJ-7 to Done: no transition to Done"
+        );
+        let err = comment_with(&ScriptedRunner::default().missing(), "PROJ-7", "hi").unwrap_err();
+        assert!(err.is::<AcliMissing>(), "{err:#}");
+    }
+
+    #[test]
+    fn missing_acli_asks_for_an_install() {
+        let err = fetch_tickets_with(&ScriptedRunner::default().missing()).unwrap_err();
+        assert_eq!(unavailable_hint(&err), "Jira unavailable: install acli");
+
+        let runner = ScriptedRunner::default()
+            .fail("not logged in")
+            .fail("not logged in");
+        let hint = unavailable_hint(&fetch_tickets_with(&runner).unwrap_err());
+        assert!(
+            hint.starts_with("Jira unavailable: failed to fetch"),
+            "{hint}"
+        );
+        assert!(hint.contains("not logged in"), "{hint}");
+    }
+
+    #[test]
+    fn fetch_tickets_with_falls_back_to_legacy_cli() {
+        let runner = ScriptedRunner::default()
+            .fail("unknown command workitem")
+            .succeed(r#"[{"key":"ABC-7","summary":"Legacy path"}]"#);
+        let tickets = fetch_tickets_with(&runner).unwrap();
+        assert_eq!(tickets.len(), 1);
+        assert_eq!(tickets[0].key, "ABC-7");
+
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].args[..3], ["jira", "workitem", "search"]);
+        assert_eq!(calls[1].args, vec!["jira", "issues", "--format", "json"]);
+    }
+
+    #[test]
+    fn corrupt_cache_is_moved_aside_and_refetched() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let wtm_dir = repo.path().join(".wtm");
+        fs::create_dir_all(&wtm_dir).unwrap();
+        fs::write(wtm_dir.join(CACHE_FILE), r#"{"tickets": [{"key": "ABC-1""#).unwrap();
+
+        let runner = ScriptedRunner::default().succeed(r#"[{"key":"ABC-2","summary":"Fresh"}]"#);
+        let tickets = cached_tickets_with(&runner, repo.path()).unwrap();
+        assert_eq!(tickets[0].key, "ABC-2");
+        assert_eq!(runner.calls().len(), 1, "fetched once");
+        assert!(wtm_dir.join("jira_cache.json.bak").exists());
+
+        let cached = cached_tickets_offline(repo.path()).unwrap().unwrap();
+        assert_eq!(cached[0].summary, "Fresh");
+    }
+
+    #[test]
+    fn fetch_tickets_with_reports_both_failures() {
+        let runner = ScriptedRunner::default()
+            .fail("primary broke")
+            .fail("legacy broke");
+        let err = fetch_tickets_with(&runner).unwrap_err().to_string();
+        assert!(err.contains("primary broke"));
+        assert!(err.contains("legacy broke"));
+    }
+
     #[test]
     fn parse_acli_output_handles_json_array() {
         let output = r#"[
@@ -222,7 +508,7 @@ mod tests {
             "issues": [
                 {
                     "key": "ABC-3",
-                    "fields": {"summary": "Nested summary"}
+                    "fields": {"summary": "Nested summary", "status": {"name": "In Review"}}
                 }
             ]
         })
@@ -231,6 +517,7 @@ mod tests {
         assert_eq!(tickets.len(), 1);
         assert_eq!(tickets[0].key, "ABC-3");
         assert_eq!(tickets[0].summary, "Nested summary");
+        assert_eq!(tickets[0].headline(), "ABC-3 — Nested summary (In Review)");
     }
 
     #[test]
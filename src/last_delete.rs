@@ -0,0 +1,52 @@
+//! The most recently removed worktree, remembered so `wtm worktree undo` can restore it.
+
+use crate::wtm_paths::atomic_write_json;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// File inside `.wtm/` holding the last removal.
+pub const LAST_DELETE_FILE: &str = "last_delete.json";
+
+/// What [`crate::workspace::delete_workspace`] removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastDelete {
+    /// Branch that was checked out; `None` for a detached worktree.
+    pub branch: Option<String>,
+    pub path: PathBuf,
+    pub force: bool,
+}
+
+/// Replace the remembered removal with `record`.
+pub fn save(wtm_dir: &Path, record: &LastDelete) -> Result<()> {
+    let path = wtm_dir.join(LAST_DELETE_FILE);
+    atomic_write_json(&path, record).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// The remembered removal, if there is one.
+pub fn load(wtm_dir: &Path) -> Result<Option<LastDelete>> {
+    let path = wtm_dir.join(LAST_DELETE_FILE);
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).with_context(|| format!("failed to read {}", path.display())),
+    };
+    serde_json::from_str(&data)
+        .map(Some)
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Forget the remembered removal.
+pub fn clear(wtm_dir: &Path) -> Result<()> {
+    let path = wtm_dir.join(LAST_DELETE_FILE);
+    match fs::remove_file(&path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            Err(err).with_context(|| format!("failed to remove {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
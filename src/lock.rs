@@ -0,0 +1,101 @@
+//! Advisory lock serialising commands that change worktrees.
+//!
+//! Creating, adopting, moving, and removing worktrees hold `.wtm/.lock` so two
+//! `wtm` processes (or the TUI and a CLI command) cannot race on
+//! `.wtm/workspaces` and git's worktree metadata. Read-only commands skip it.
+
+use std::{
+    fs::{self, File, TryLockError},
+    io,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+/// Lock file inside `.wtm/`.
+pub const LOCK_FILE: &str = ".lock";
+
+/// How long a mutating command waits for another one to finish.
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Error)]
+pub enum LockError {
+    #[error(
+        "another wtm operation is in progress (waited {}s for {})",
+        waited.as_secs(),
+        path.display()
+    )]
+    Busy { path: PathBuf, waited: Duration },
+    #[error("failed to lock {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Held lock on `.wtm/.lock`; released when dropped.
+#[derive(Debug)]
+pub struct WtmLock {
+    _file: File,
+}
+
+impl WtmLock {
+    /// Lock the repository's `.wtm` directory, waiting up to [`LOCK_TIMEOUT`].
+    pub fn acquire(repo_root: &Path) -> Result<Self, LockError> {
+        Self::acquire_with_timeout(&repo_root.join(".wtm"), LOCK_TIMEOUT)
+    }
+
+    /// Lock `wtm_dir`, retrying until `timeout` has passed.
+    pub fn acquire_with_timeout(wtm_dir: &Path, timeout: Duration) -> Result<Self, LockError> {
+        let path = wtm_dir.join(LOCK_FILE);
+        let io_error = |source| LockError::Io {
+            path: path.clone(),
+            source,
+        };
+        fs::create_dir_all(wtm_dir).map_err(io_error)?;
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(io_error)?;
+        let start = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(Self { _file: file }),
+                Err(TryLockError::WouldBlock) if start.elapsed() < timeout => {
+                    thread::sleep(RETRY_INTERVAL);
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(LockError::Busy {
+                        path,
+                        waited: start.elapsed(),
+                    })
+                }
+                Err(TryLockError::Error(source)) => return Err(io_error(source)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn second_acquisition_waits_for_the_first() {
+        let dir = tempdir().unwrap();
+        let held = WtmLock::acquire_with_timeout(dir.path(), Duration::ZERO).unwrap();
+
+        let err =
+            WtmLock::acquire_with_timeout(dir.path(), Duration::from_millis(120)).unwrap_err();
+        assert!(matches!(err, LockError::Busy { .. }), "{err}");
+        assert!(err
+            .to_string()
+            .contains("another wtm operation is in progress"));
+
+        drop(held);
+        WtmLock::acquire_with_timeout(dir.path(), Duration::ZERO).unwrap();
+    }
+}
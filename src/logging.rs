@@ -0,0 +1,70 @@
+//! Diagnostic logging controlled by `--verbose` and `RUST_LOG`.
+
+use anyhow::{Context, Result};
+use log::LevelFilter;
+use std::{
+    fs::{self, OpenOptions},
+    path::Path,
+};
+
+/// File (inside `.wtm/`) that receives logs while a full-screen frontend is active.
+pub const LOG_FILE: &str = "wtm.log";
+
+/// Where log records should be written.
+pub enum LogTarget<'a> {
+    /// Standard error; only safe before a TUI/GUI takes over the terminal.
+    Stderr,
+    /// Append to `<wtm_dir>/wtm.log` so the alternate screen is never corrupted.
+    File(&'a Path),
+}
+
+/// Install the global logger when `--verbose` or `RUST_LOG` request it.
+pub fn init(verbose: bool, target: LogTarget<'_>) -> Result<()> {
+    let env_configured = std::env::var_os("RUST_LOG").is_some();
+    if !verbose && !env_configured {
+        return Ok(());
+    }
+
+    let mut builder = env_logger::Builder::new();
+    if verbose {
+        builder.filter_module("wtm", LevelFilter::Debug);
+    }
+    builder.parse_default_env();
+
+    if let LogTarget::File(wtm_dir) = target {
+        fs::create_dir_all(wtm_dir)
+            .with_context(|| format!("failed to create {}", wtm_dir.display()))?;
+        let path = wtm_dir.join(LOG_FILE);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open log file {}", path.display()))?;
+        builder.target(env_logger::Target::Pipe(Box::new(file)));
+        builder.write_style(env_logger::WriteStyle::Never);
+    }
+
+    builder.try_init().context("failed to initialise logging")?;
+    Ok(())
+}
+
+/// Shorten command output for log records, keeping the first `max_chars` characters.
+pub fn truncate_for_log(text: &str, max_chars: usize) -> String {
+    let trimmed = text.trim();
+    let mut result: String = trimmed.chars().take(max_chars).collect();
+    if trimmed.chars().count() > max_chars {
+        result.push('…');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_for_log_limits_length() {
+        assert_eq!(truncate_for_log("  short\n", 10), "short");
+        assert_eq!(truncate_for_log("abcdefghij", 4), "abcd…");
+    }
+}
@@ -1,21 +1,83 @@
+mod bulk;
+mod cancel;
+mod clipboard;
 mod commands;
 mod config;
 mod docker;
+mod error;
 mod git;
 mod gui;
+mod headless;
+mod hooks;
+mod ignore;
 mod jira;
+mod last_delete;
+mod lock;
+mod logging;
+mod notes;
+mod output;
+mod parallel;
+mod pins;
+mod runner;
+mod spinner;
+mod stale;
+mod tags;
+mod timings;
+mod trash;
 mod tui;
+mod workspace;
 mod wtm_paths;
 
-use anyhow::{bail, Context, Result};
-use clap::{Parser, Subcommand};
-use commands::init::init_command;
-use config::QuickAction;
-use git::{add_worktree, find_repo_root, list_worktrees, remove_worktree, WorktreeInfo};
-use std::path::PathBuf;
-use wtm_paths::{
-    branch_dir_name, ensure_workspace_root, next_available_workspace_path, sanitize_branch_name,
+use anyhow::{anyhow, bail, Context, Result};
+use bulk::{BulkResult, Outcome};
+use cancel::CancellationToken;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use commands::{
+    activity::{activity_command, parse_since},
+    cd_script::cd_script,
+    completions::{self, DescriptionStyle, SuggestFormat, SuggestKind},
+    config::{config_edit_command, config_show_command, open_in_editor},
+    describe::describe_command,
+    init::{init_command, init_global_command, init_interactive_command},
+    json_schema::{json_schema_command, SchemaKind},
+    list_table::{list_table, ListRow, RowState, WorktreeSummary},
+    list_tree::tree_lines,
+    open::{open_worktree, OpenWith},
+    quick::quick_run_command,
+    reset::{self, ResetOptions, Synced},
+    run::run_in_pty,
+    status::{select_fields, status_command, StatusFormat},
+    sync::sync_command,
+    telemetry::telemetry_command,
+    version::version_command,
 };
+use config::Config;
+use error::{exit_code, WtmError, EXIT_FAILURE, EXIT_SUCCESS};
+use git::{find_repo_root, list_worktrees_cached, AddOptions, WorktreeInfo};
+use headless::Frontend;
+use hooks::{Hook, HookContext};
+use logging::LogTarget;
+use output::{Output, Page};
+use pins::Pins;
+use spinner::Spinner;
+use stale::AgeSelector;
+use std::{
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Instant,
+};
+use tags::Tags;
+use tui::pty_tab::workspace_env;
+use workspace::{
+    adopt_workspace, attach_workspace, audit_workspaces, checkout_workspace, create_workspace,
+    delete_workspace, detach_shared_branches, disk_usage_with, find_workspace, format_bytes,
+    is_unpopulated, lookup_workspace, move_workspace, plan_clone_current, plan_duplicate,
+    plan_move, remove_orphans, restore_trashed, retain_linked, stray_directories_warning,
+    trash_workspace, undo_delete, AttachSource, AuditReport, Upstream, WorkspaceError,
+};
+use wtm_paths::{format_path, home_dir, sanitize_branch_name, PathLayout};
 
 /// WTM command line interface.
 #[derive(Parser, Debug)]
@@ -25,6 +87,38 @@ use wtm_paths::{
     about = "WTM worktree manager (Rust CLI prototype)"
 )]
 struct Cli {
+    /// Log executed commands at debug level (also honours `RUST_LOG`)
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Only print command results and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Do not print warnings (e.g. about an unreadable config)
+    #[arg(long, global = true)]
+    no_warnings: bool,
+
+    /// Never colour output (also honours `NO_COLOR`)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Print how long each phase (repo discovery, git calls, disk walk) took to stderr
+    #[arg(long, global = true)]
+    timings: bool,
+
+    /// Wrap JSON output in `{"schemaVersion": 1, "data": …}` so scripts can detect format changes
+    #[arg(long, global = true)]
+    envelope: bool,
+
+    /// Fail instead of starting the dashboard or GUI (also implied by a set `CI`)
+    #[arg(long)]
+    no_tui: bool,
+
+    /// Run as if wtm was started in PATH instead of the current directory
+    #[arg(short = 'C', long = "repo", value_name = "PATH", global = true)]
+    repo: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -36,6 +130,16 @@ enum Commands {
         /// Root directory where `.wtm` should be created (defaults to the current directory)
         #[arg(default_value = ".")]
         path: PathBuf,
+        /// Create the global config in `$XDG_CONFIG_HOME/wtm` instead
+        #[arg(long, conflicts_with = "path")]
+        global: bool,
+        /// Also create a first worktree on a new BRANCH (like `wtm worktree add`)
+        #[arg(long, value_name = "BRANCH", conflicts_with = "global")]
+        with_worktree: Option<String>,
+        /// Ask for an editor, terminal session, and Jira setup, and seed example
+        /// quick actions (uses the defaults when stdin is not a terminal)
+        #[arg(long, short = 'i', conflicts_with = "global")]
+        interactive: bool,
     },
     /// Manage git worktrees via the CLI
     Worktree {
@@ -44,65 +148,701 @@ enum Commands {
     },
     /// Launch the experimental desktop GUI
     Gui,
+    /// Generate or install shell completion scripts
+    Completions {
+        #[command(subcommand)]
+        command: CompletionCommands,
+    },
+    /// Inspect or edit the `.wtm` and global configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Print version information (add `--verbose` for build and tool details)
+    Version {
+        /// Emit machine-readable JSON, e.g. for bug reports
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run configured quick actions from the command line
+    Quick {
+        #[command(subcommand)]
+        command: QuickCommands,
+    },
+    /// Print the JSON Schema of a command's `--json` output
+    JsonSchema {
+        /// The output to describe
+        #[arg(value_enum)]
+        kind: SchemaKind,
+    },
+    /// Print a JSON description of every command for external tools
+    #[command(name = "__describe", hide = true)]
+    Describe,
+}
+
+#[derive(Subcommand, Debug)]
+enum QuickCommands {
+    /// Run the quick action with this label (case-insensitive) and exit with its status
+    Run {
+        /// The action's `label` from `quickAccess`
+        label: String,
+        /// Worktree to run in, by directory name or path (default: the repository root)
+        #[arg(long, short = 'w')]
+        workspace: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    /// Print the effective configuration (includes merged, `${VAR}`s expanded, defaults filled in) as JSON
+    Show,
+    /// Open the config file in `$VISUAL`/`$EDITOR`, creating it if needed, then validate it
+    Edit {
+        /// Edit the global config shared by every repository
+        #[arg(long)]
+        global: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CompletionCommands {
+    /// Print the completion script for a shell to stdout
+    Generate {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
+    /// Write the completion script to the shell's per-user completions directory
+    Install {
+        /// Shell to install completions for (bash, zsh, or fish)
+        shell: Shell,
+        /// Only print where the script would be installed
+        #[arg(long)]
+        print_path: bool,
+    },
+    /// Print dynamic suggestions (`value<TAB>description` per line) for custom completions
+    Suggest {
+        /// What to suggest
+        #[arg(value_enum)]
+        kind: SuggestKind,
+        /// The worktree subcommand being completed; `remove` and `move` leave the
+        /// primary worktree out
+        #[arg(long = "for", value_name = "COMMAND")]
+        for_command: Option<String>,
+        /// Leave the primary worktree out
+        #[arg(long, conflicts_with = "include_primary")]
+        exclude_primary: bool,
+        /// Offer the primary worktree even for `--for remove` or `--for move`
+        #[arg(long)]
+        include_primary: bool,
+        /// Output format; bash and fish use tabs, zsh uses `_describe` colons [default: bash]
+        #[arg(long)]
+        shell: Option<Shell>,
+        /// Print a JSON array of `{value, source, detail}` instead of shell lines
+        #[arg(long, conflicts_with_all = ["shell", "null"])]
+        json: bool,
+        /// Print bare values, each terminated by a NUL byte (for `read -d ''`)
+        #[arg(long, conflicts_with = "shell")]
+        null: bool,
+        /// How much description to show next to each value
+        #[arg(long, value_enum, default_value_t)]
+        description_style: DescriptionStyle,
+    },
 }
 
 #[derive(Subcommand, Debug)]
 enum WorktreeCommands {
     /// List discovered worktrees
-    List,
+    List {
+        /// Include worktrees hidden by `.wtm/ignore`
+        #[arg(long)]
+        all: bool,
+        /// Only show linked worktrees with no commits for this long (e.g. 7d, 2w, 3mo)
+        #[arg(long, value_name = "AGE", value_parser = stale::parse_age)]
+        stale: Option<std::time::Duration>,
+        /// Only the N linked worktrees with the oldest last commit, oldest first
+        #[arg(long, value_name = "N", conflicts_with = "newest")]
+        oldest: Option<usize>,
+        /// Only the N linked worktrees with the newest last commit, newest first
+        #[arg(long, value_name = "N")]
+        newest: Option<usize>,
+        /// Leave out the primary worktree
+        #[arg(long, aliases = ["exclude-primary", "no-primary"])]
+        only_linked: bool,
+        /// Show at most N worktrees
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+        /// Skip the first M worktrees
+        #[arg(long, value_name = "M", default_value_t = 0)]
+        offset: usize,
+        /// Only show worktrees tagged TAG (see `worktree tag`)
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+        /// Group worktrees by category or branch prefix in an indented tree
+        #[arg(long, conflicts_with = "json")]
+        tree: bool,
+        /// Only worktrees with at least one running docker compose service (slow)
+        #[arg(long)]
+        with_services: bool,
+        /// Run git status in each worktree to flag (and colour) dirty ones
+        #[arg(long, conflicts_with_all = ["json", "tree"])]
+        status: bool,
+        /// Emit the worktrees as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show git status (ahead/behind, changes, conflicts) for each worktree
+    Status {
+        /// Include worktrees hidden by `.wtm/ignore`
+        #[arg(long)]
+        all: bool,
+        /// Only worktrees tagged TAG (see `worktree tag`)
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+        /// One aligned line per worktree: name, branch, ↑ahead↓behind, ✱changes, ?untracked, ‼conflicts, size
+        #[arg(long, conflicts_with = "json")]
+        compact: bool,
+        /// Print one JSON object per worktree instead
+        #[arg(long)]
+        json: bool,
+        /// Only include these comma-separated JSON fields (e.g. name,branch,ahead,behind)
+        #[arg(long, value_delimiter = ',', requires = "json")]
+        fields: Option<Vec<String>>,
+        /// Add docker compose service counts (running, total, by_state) to the JSON
+        #[arg(long, requires = "json")]
+        include_services: bool,
+        /// Leave out the primary worktree
+        #[arg(long)]
+        no_primary: bool,
+        /// Save every worktree's status to FILE as a baseline for `--compare`
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["compact", "fields"])]
+        snapshot: Option<PathBuf>,
+        /// Print what changed since the snapshot in FILE (runs before `--snapshot`)
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["compact", "fields"])]
+        compare: Option<PathBuf>,
+        /// Only worktrees with at least one running docker compose service (slow)
+        #[arg(long)]
+        with_services: bool,
+    },
     /// Add a new worktree for the specified branch
     Add {
         /// Branch name to create for the worktree
         branch: String,
+        /// Start the new branch from this (usually remote) reference instead of HEAD
+        #[arg(long, value_name = "REF")]
+        from: Option<String>,
+        /// Set the new branch to track `--from` (or `defaultUpstream`), overriding `upstreamTracking`
+        #[arg(long, conflicts_with = "no_track")]
+        track: bool,
+        /// Do not track `--from` (or `defaultUpstream`), overriding `upstreamTracking`
+        #[arg(long)]
+        no_track: bool,
+        /// Run this shell command in the new worktree (overrides `postCreateCommand`)
+        #[arg(long, value_name = "CMD")]
+        run: Option<String>,
+        /// Skip `.wtm/hooks/post-create` and the `hooksPath` setting
+        #[arg(long)]
+        no_hooks: bool,
+        /// Reuse the target directory if it already exists and is empty
+        #[arg(long)]
+        adopt: bool,
+        /// Lock the new worktree against pruning, e.g. `--lock="on USB drive"`
+        #[arg(long, value_name = "REASON", num_args = 0..=1, require_equals = true, default_missing_value = "")]
+        lock: Option<String>,
+        /// Nest the worktree under `.wtm/workspaces/<NAME>/`
+        #[arg(long, value_name = "NAME", value_parser = wtm_paths::parse_category)]
+        category: Option<String>,
+        /// Register the worktree without checking out any files, to populate it later
+        #[arg(long)]
+        no_checkout: bool,
+        /// Then open it: `editor`, `cd` (print its path last), or `auto` (the default)
+        #[arg(long, value_enum, value_name = "WITH", num_args = 0..=1, require_equals = true, default_missing_value = "auto")]
+        open: Option<OpenWith>,
+        /// Comment on the Jira ticket named in the branch, and move it to `jira.onCreateTransition`
+        #[arg(long)]
+        track_issue: bool,
+        /// Print `{path, branch, open}` as JSON; hook and command output is silenced
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add a worktree on a new `<branch>-wt-<n>` branch from the current branch's upstream
+    CloneCurrent {
+        /// Run this shell command in the new worktree (overrides `postCreateCommand`)
+        #[arg(long, value_name = "CMD")]
+        run: Option<String>,
+        /// Skip `.wtm/hooks/post-create` and the `hooksPath` setting
+        #[arg(long)]
+        no_hooks: bool,
+    },
+    /// Add a worktree for an existing branch
+    Attach {
+        /// Branch to check out
+        branch: String,
+        /// Start a missing branch from a matching remote branch, or else from HEAD
+        #[arg(long)]
+        create_if_missing: bool,
+        /// Lock the new worktree against pruning, e.g. `--lock="on USB drive"`
+        #[arg(long, value_name = "REASON", num_args = 0..=1, require_equals = true, default_missing_value = "")]
+        lock: Option<String>,
+        /// Register the worktree without checking out any files, to populate it later
+        #[arg(long)]
+        no_checkout: bool,
+        /// Run this shell command in the new worktree (overrides `postCreateCommand`)
+        #[arg(long, value_name = "CMD")]
+        run: Option<String>,
+        /// Skip `.wtm/hooks/post-create` and the `hooksPath` setting
+        #[arg(long)]
+        no_hooks: bool,
+    },
+    /// Add a worktree for a local branch, or else track `<remote>/<branch>` like `git checkout`
+    Checkout {
+        /// Branch to check out; `origin` is preferred when several remotes have it
+        branch: String,
+        /// Lock the new worktree against pruning, e.g. `--lock="on USB drive"`
+        #[arg(long, value_name = "REASON", num_args = 0..=1, require_equals = true, default_missing_value = "")]
+        lock: Option<String>,
+        /// Run this shell command in the new worktree (overrides `postCreateCommand`)
+        #[arg(long, value_name = "CMD")]
+        run: Option<String>,
+        /// Skip `.wtm/hooks/post-create` and the `hooksPath` setting
+        #[arg(long)]
+        no_hooks: bool,
+    },
+    /// Check out a worktree's branch a second time as `<branch>-copy-<n>` at its HEAD
+    Duplicate {
+        /// Path to the worktree to duplicate (relative paths are under `.wtm/workspaces`)
+        path: PathBuf,
+        /// Run this shell command in the new worktree (overrides `postCreateCommand`)
+        #[arg(long, value_name = "CMD")]
+        run: Option<String>,
+        /// Skip `.wtm/hooks/post-create` and the `hooksPath` setting
+        #[arg(long)]
+        no_hooks: bool,
+    },
+    /// Compare `.wtm/workspaces` with git's worktree list and report mismatches
+    Audit {
+        /// Delete directories under `.wtm/workspaces` that git does not track, and
+        /// detach HEAD in all but the first worktree of a branch checked out twice
+        #[arg(long)]
+        fix: bool,
+        /// Skip the confirmation prompt for `--fix`
+        #[arg(long, requires = "fix")]
+        yes: bool,
+        /// Emit the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarise commits since a cutoff and uncommitted changes per worktree
+    Activity {
+        /// An age (e.g. 12h, 1d, 2w) or any date `git log --since` accepts (e.g. yesterday)
+        #[arg(long, value_name = "WHEN", default_value = "1d", value_parser = parse_since)]
+        since: String,
+        /// Include worktrees hidden by `.wtm/ignore`
+        #[arg(long)]
+        all: bool,
+        /// Leave out the primary worktree
+        #[arg(long)]
+        no_primary: bool,
+        /// Emit `[{name, path, branch, commits: [{hash, subject}], changes}]` as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show disk usage per worktree and in total, largest first
+    Du {
+        /// Leave out the primary worktree
+        #[arg(long)]
+        no_primary: bool,
+        /// Emit `{total_bytes, workspaces: [{name, path, bytes}]}` as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Move a worktree to another path (relative paths are under `.wtm/workspaces`)
+    Move {
+        /// Path to the worktree to move
+        path: PathBuf,
+        /// New path; one ending in `/` or naming an existing directory receives
+        /// the worktree inside it, keeping its name
+        to: PathBuf,
+        /// Print the resolved source and destination without moving anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Allow a destination outside `.wtm/workspaces`
+        #[arg(long)]
+        allow_external: bool,
+        /// Passed on to `git worktree move`; twice also moves a locked worktree
+        #[arg(long, action = clap::ArgAction::Count)]
+        force: u8,
     },
     /// Remove an existing worktree by its path
     Remove {
-        /// Path to the worktree to remove
-        path: PathBuf,
-        /// Force removal even if there are unmerged changes
+        /// Path to the worktree to remove, or a glob matching worktree names
+        /// (e.g. 'feature-*') to remove every match
+        #[arg(required_unless_present_any = ["oldest", "newest", "tag"])]
+        path: Option<PathBuf>,
+        /// Remove even with uncommitted changes, unpushed commits, or from inside
+        /// the worktree; with --trash, only the last applies
         #[arg(long)]
         force: bool,
+        /// Move the directory to `.wtm/trash` instead of deleting it
+        #[arg(long)]
+        trash: bool,
+        /// Skip `.wtm/hooks/pre-remove`
+        #[arg(long)]
+        no_hooks: bool,
+        /// With a glob or selector, also remove pinned worktrees
+        #[arg(long)]
+        include_pinned: bool,
+        /// Remove the N linked worktrees with the oldest last commit, not
+        /// counting pinned ones unless --include-pinned
+        #[arg(long, value_name = "N", conflicts_with_all = ["path", "newest"])]
+        oldest: Option<usize>,
+        /// Remove the N linked worktrees with the newest last commit, not
+        /// counting pinned ones unless --include-pinned
+        #[arg(long, value_name = "N", conflicts_with = "path")]
+        newest: Option<usize>,
+        /// Remove every linked worktree tagged TAG (see `worktree tag`)
+        #[arg(long, value_name = "TAG", conflicts_with_all = ["path", "oldest", "newest"])]
+        tag: Option<String>,
+        /// Remove the worktrees picked by a glob, --oldest/--newest, or --tag
+        /// without asking
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+    /// Discard a worktree's changes and fast-forward it to its upstream,
+    /// after showing what would be lost
+    Reset {
+        /// Directory name (as in `list`) or path relative to `.wtm/workspaces`
+        name: String,
+        /// Also delete untracked files and directories (`git clean -fd`)
+        #[arg(long)]
+        clean: bool,
+        /// Reset to this ref (e.g. `origin/main`) instead of fast-forwarding to the upstream
+        #[arg(long, value_name = "REF")]
+        to: Option<String>,
+        /// Discard without asking
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+    /// Fetch once, then merge every worktree's upstream into its branch;
+    /// detached, dirty, and upstream-less worktrees are skipped with the reason
+    Sync {
+        /// Only fast-forward (`git merge --ff-only`)
+        #[arg(long)]
+        ff_only: bool,
+        /// Only worktrees tagged TAG (see `worktree tag`)
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+    },
+    /// Show `git diff` between the branches checked out in two worktrees
+    Diff {
+        /// Directory name (as in `list`) or path relative to `.wtm/workspaces`
+        from: String,
+        /// Worktree whose branch is compared against FROM's
+        to: String,
+        /// Only print the diffstat
+        #[arg(long)]
+        stat: bool,
+    },
+    /// Print a worktree's note from `.wtm/notes/`, or edit it with `--edit`
+    Note {
+        /// Directory name (as in `list`) or path relative to `.wtm/workspaces`
+        name: String,
+        /// Open the note in `$VISUAL`/`$EDITOR` (TUI: m)
+        #[arg(long)]
+        edit: bool,
+    },
+    /// Show, add, or remove free-form tags on a worktree
+    Tag {
+        /// Directory name (as in `list`) or path relative to `.wtm/workspaces`
+        name: String,
+        /// Tag to add (repeatable)
+        #[arg(long, value_name = "TAG", value_parser = tags::parse_tag)]
+        add: Vec<String>,
+        /// Tag to remove (repeatable)
+        #[arg(long, value_name = "TAG", value_parser = tags::parse_tag)]
+        remove: Vec<String>,
+    },
+    /// Pin a worktree so removals of several worktrees at once skip it
+    Pin {
+        /// Directory name (as in `list`) or path relative to `.wtm/workspaces`
+        name: String,
+    },
+    /// Unpin a worktree pinned with `pin`
+    Unpin {
+        /// Directory name (as in `list`) or path relative to `.wtm/workspaces`
+        name: String,
+    },
+    /// Print the directory of a worktree, found by name or path
+    Path {
+        /// Directory name (as in `list`) or path relative to `.wtm/workspaces`
+        name: String,
+    },
+    /// Print a `wtw` shell function that jumps to a worktree (uses fzf when installed)
+    CdScript {
+        /// Shell to generate the function for (bash, zsh, or fish)
+        shell: Shell,
+    },
+    /// Run a command in a worktree under a real terminal and exit with its status
+    Run {
+        /// Path to the worktree (relative paths are under `.wtm/workspaces`)
+        path: PathBuf,
+        /// Command and arguments, after `--`
+        #[arg(last = true, required = true, value_name = "CMD")]
+        command: Vec<String>,
+    },
+    /// Recreate the most recently removed worktree for its branch
+    Undo,
+    /// List, restore, or empty worktrees removed with `remove --trash`
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommands,
     },
 }
 
-fn main() -> Result<()> {
+#[derive(Subcommand, Debug)]
+enum TrashCommands {
+    /// List trashed worktrees, most recent first
+    List {
+        /// Emit `[{id, originalPath, branch, head, trashedAt}]` as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Put a trashed worktree back where it was
+    Restore {
+        /// Entry id as shown by `trash list`
+        id: String,
+    },
+    /// Permanently delete everything in the trash
+    Empty,
+}
+
+fn main() -> ExitCode {
+    let started = Instant::now();
     let cli = Cli::parse();
-    match cli.command {
-        Some(Commands::Init { path }) => init_command(&path),
-        Some(Commands::Worktree { command }) => run_worktree_cli(command),
-        Some(Commands::Gui) => run_gui_frontend(),
-        None => run_dashboard(),
+    if cli.timings {
+        timings::enable();
+    }
+    let result = run(cli);
+    timings::report(started.elapsed());
+    let code = result.unwrap_or_else(|err| {
+        eprintln!("Error: {err:?}");
+        exit_code(&err)
+    });
+    ExitCode::from(u8::try_from(code).unwrap_or(EXIT_FAILURE as u8))
+}
+
+/// Run the parsed command and return the process exit code, which is not
+/// [`EXIT_SUCCESS`] only when a child command (`worktree run`, `quick run`)
+/// failed.
+fn run(cli: Cli) -> Result<i32> {
+    let base_dir = base_dir(cli.repo.as_deref())?;
+    // `version --verbose` asks for more output, not for command logging.
+    let log_commands = cli.verbose && !matches!(cli.command, Some(Commands::Version { .. }));
+    init_logging(log_commands, cli.command.as_ref(), &base_dir)?;
+    let out = Output {
+        quiet: cli.quiet,
+        no_warnings: cli.no_warnings,
+        color: Output::color_enabled(
+            cli.no_color,
+            std::env::var_os("NO_COLOR").as_deref(),
+            std::io::stdout().is_terminal(),
+        ),
+        envelope: cli.envelope,
+    };
+    let result: Result<()> = match cli.command {
+        Some(Commands::Init { global: true, .. }) => init_global_command(out),
+        Some(Commands::Init {
+            path,
+            with_worktree: None,
+            interactive,
+            ..
+        }) => init_scaffold(&base_dir.join(path), interactive, out),
+        Some(Commands::Init {
+            path,
+            with_worktree: Some(branch),
+            interactive,
+            ..
+        }) => init_with_worktree(&base_dir.join(path), &branch, interactive, out),
+        Some(Commands::Worktree { command }) => return run_worktree_cli(command, &base_dir, out),
+        Some(Commands::Gui) => run_gui_frontend(&base_dir, cli.no_tui, out),
+        Some(Commands::Completions { command }) => match command {
+            CompletionCommands::Generate { shell } => {
+                completions::generate_command(shell, &mut Cli::command())
+            }
+            CompletionCommands::Install { shell, print_path } => {
+                completions::install_command(shell, &mut Cli::command(), print_path)
+            }
+            CompletionCommands::Suggest {
+                kind,
+                for_command,
+                exclude_primary,
+                include_primary,
+                shell,
+                json,
+                null,
+                description_style,
+            } => {
+                let format = if json {
+                    SuggestFormat::Json
+                } else if null {
+                    SuggestFormat::Null
+                } else {
+                    SuggestFormat::Shell(shell.unwrap_or(Shell::Bash))
+                };
+                completions::suggest_command(
+                    &repo_root_for(&base_dir)?,
+                    kind,
+                    completions::excludes_primary(
+                        for_command.as_deref(),
+                        exclude_primary,
+                        include_primary,
+                    ),
+                    format,
+                    description_style,
+                    out,
+                )
+            }
+        },
+        Some(Commands::Config {
+            command: ConfigCommands::Show,
+        }) => config_show_command(&repo_root_for(&base_dir)?, out),
+        Some(Commands::Config {
+            command: ConfigCommands::Edit { global: true },
+        }) => config_edit_command(None, out),
+        Some(Commands::Config {
+            command: ConfigCommands::Edit { global: false },
+        }) => config_edit_command(Some(&repo_root_for(&base_dir)?), out),
+        Some(Commands::Quick {
+            command: QuickCommands::Run { label, workspace },
+        }) => {
+            let repo_root = repo_root_for(&base_dir)?;
+            return quick_run_command(&repo_root, &label, workspace.as_deref(), out);
+        }
+        Some(Commands::Version { json }) => version_command(cli.verbose, json, out),
+        Some(Commands::Describe) => describe_command(&Cli::command(), out),
+        Some(Commands::JsonSchema { kind }) => json_schema_command(kind, out),
+        None => run_dashboard(&base_dir, cli.no_tui, out),
+    };
+    result.map(|()| EXIT_SUCCESS)
+}
+
+/// `git worktree add` options from the config and `--lock[=REASON]`; an empty
+/// reason means none.
+fn add_options(repo_root: &Path, config: &Config, lock: Option<String>, out: Output) -> AddOptions {
+    if let Some(warning) = git::relative_paths_warning(repo_root, config.relative_worktree_paths) {
+        out.warn(warning);
+    }
+    AddOptions {
+        lock: lock.is_some(),
+        lock_reason: lock.filter(|reason| !reason.trim().is_empty()),
+        ..AddOptions::for_repo(repo_root, config.relative_worktree_paths)
+    }
+}
+
+/// `locked ` (with the reason) and `no-checkout ` for summaries of a worktree
+/// created with `--lock` or `--no-checkout`.
+fn options_label(options: &AddOptions) -> String {
+    let mut label = match (options.lock, options.lock_reason.as_deref()) {
+        (false, _) => String::new(),
+        (true, None) => "locked ".into(),
+        (true, Some(reason)) => format!("locked ({reason}) "),
+    };
+    if options.no_checkout {
+        label.push_str("no-checkout ");
     }
+    label
 }
 
-fn run_dashboard() -> Result<()> {
-    let context = load_workspace_context()?;
-    tui::run_tui(context.repo_root, context.worktrees, context.quick_actions)
+/// `wtm init --with-worktree`: scaffold `.wtm`, then add a worktree for `branch`.
+fn init_scaffold(root: &Path, interactive: bool, out: Output) -> Result<()> {
+    if interactive {
+        init_interactive_command(root, out)
+    } else {
+        init_command(root, out)
+    }
 }
 
-fn run_gui_frontend() -> Result<()> {
-    let context = load_workspace_context()?;
-    gui::run_gui(context.repo_root, context.worktrees, context.quick_actions)
+fn init_with_worktree(root: &Path, branch: &str, interactive: bool, out: Output) -> Result<()> {
+    // Check for a repository first so a failure leaves no scaffold behind.
+    let repo_root = repo_root_for(root)?;
+    init_scaffold(root, interactive, out)?;
+    let config = load_config_or_default(&repo_root.join(".wtm"), out);
+    let options = add_options(&repo_root, &config, None, out);
+    let worktree_path = create_workspace(&repo_root, branch, None, config.path_layout(), &options)?;
+    out.status(format!(
+        "Created worktree for branch {} at {}",
+        sanitize_branch_name(branch),
+        worktree_path.display()
+    ));
+    Ok(())
+}
+
+/// Directory every command resolves against: `--repo` if given, else the cwd.
+fn base_dir(repo: Option<&Path>) -> Result<PathBuf> {
+    let cwd = std::env::current_dir().context("unable to determine current directory")?;
+    match repo {
+        Some(path) => {
+            let path = cwd.join(path);
+            if !path.is_dir() {
+                bail!("--repo path {} is not a directory", path.display());
+            }
+            Ok(path)
+        }
+        None => Ok(cwd),
+    }
+}
+
+/// Full-screen frontends log into `.wtm/wtm.log`; everything else logs to stderr.
+fn init_logging(verbose: bool, command: Option<&Commands>, base_dir: &Path) -> Result<()> {
+    let fullscreen = matches!(command, None | Some(Commands::Gui));
+    let wtm_dir = fullscreen
+        .then(|| find_repo_root(base_dir).ok())
+        .flatten()
+        .map(|repo_root| repo_root.join(".wtm"))
+        .filter(|wtm_dir| wtm_dir.is_dir());
+    let target = match &wtm_dir {
+        Some(wtm_dir) => LogTarget::File(wtm_dir),
+        None => LogTarget::Stderr,
+    };
+    logging::init(verbose, target)
+}
+
+fn run_dashboard(base_dir: &Path, no_tui: bool, out: Output) -> Result<()> {
+    let context = load_workspace_context(base_dir, out)?;
+    headless::ensure_interactive(Frontend::Tui, no_tui)?;
+    tui::run_tui(context.repo_root, context.worktrees, context.config)
+}
+
+fn run_gui_frontend(base_dir: &Path, no_tui: bool, out: Output) -> Result<()> {
+    let context = load_workspace_context(base_dir, out)?;
+    headless::ensure_interactive(Frontend::Gui, no_tui)?;
+    gui::run_gui(context.repo_root, context.worktrees, context.config)
 }
 
 struct WorkspaceContext {
     repo_root: PathBuf,
     worktrees: Vec<WorktreeInfo>,
-    quick_actions: Vec<QuickAction>,
+    config: Config,
 }
 
-fn load_workspace_context() -> Result<WorkspaceContext> {
-    let cwd = std::env::current_dir().context("unable to determine current directory")?;
-    let wtm_dir = cwd.join(".wtm");
-    if !wtm_dir.exists() {
-        bail!(
-            "No .wtm directory found in {}. Run `wtm init` first.",
-            cwd.display()
-        );
+fn load_workspace_context(base_dir: &Path, out: Output) -> Result<WorkspaceContext> {
+    let repo_root = find_repo_root(base_dir).map_err(|source| {
+        // Outside any repository, a missing `.wtm` is the likelier mistake.
+        if base_dir.join(".wtm").is_dir() {
+            WtmError::NotInRepo {
+                path: base_dir.to_path_buf(),
+                source,
+            }
+        } else {
+            WtmError::NoWtmDir(base_dir.to_path_buf())
+        }
+    })?;
+    let wtm_dir = repo_root.join(".wtm");
+    if !wtm_dir.is_dir() {
+        return Err(WtmError::NoWtmDir(base_dir.to_path_buf()).into());
     }
-
-    let repo_root = find_repo_root(&cwd)?;
-    let worktrees = list_worktrees(&repo_root)?;
+    warn_about_git_setup(&repo_root, out);
+    warn_about_stray_directories(&repo_root, out);
+    let mut worktrees = list_worktrees_cached(&repo_root)?;
     if worktrees.is_empty() {
         bail!(
             "No git worktrees found for {}. Use `wtm worktree add` to create one.",
@@ -110,73 +850,1269 @@ fn load_workspace_context() -> Result<WorkspaceContext> {
         );
     }
 
-    let quick_actions = match config::load_quick_actions(&wtm_dir) {
-        Ok(actions) => actions,
-        Err(err) => {
-            eprintln!(
-                "warning: failed to load quick actions from {}: {err}",
-                wtm_dir.join("config.json").display()
-            );
-            Vec::new()
-        }
-    };
+    let config = load_config_or_default(&wtm_dir, out);
+    if let Some(warning) = git::relative_paths_warning(&repo_root, config.relative_worktree_paths) {
+        out.warn(warning);
+    }
+    config.ignore.retain_visible(&mut worktrees, &repo_root);
+    config.primary_position.apply(&mut worktrees, &repo_root);
 
     Ok(WorkspaceContext {
         repo_root,
         worktrees,
-        quick_actions,
+        config,
     })
 }
 
-fn run_worktree_cli(command: WorktreeCommands) -> Result<()> {
-    let cwd = std::env::current_dir().context("unable to determine current directory")?;
-    let repo_root = find_repo_root(&cwd)?;
-    match command {
-        WorktreeCommands::List => {
-            let worktrees = list_worktrees(&repo_root)?;
-            for wt in worktrees {
-                let mut columns = vec![wt.path.display().to_string()];
-                if let Some(branch) = wt.branch.as_deref() {
-                    columns.push(format!("branch: {branch}"));
+/// Load the `.wtm` config file, warning and using defaults if it cannot be read.
+fn load_config_or_default(wtm_dir: &Path, out: Output) -> Config {
+    match config::load_config(wtm_dir) {
+        Ok(config) => config,
+        Err(err) => {
+            out.warn(format!("failed to load configuration: {err:#}"));
+            Config::default()
+        }
+    }
+}
+
+fn repo_root_for(path: &Path) -> Result<PathBuf> {
+    find_repo_root(path).map_err(|source| {
+        WtmError::NotInRepo {
+            path: path.to_path_buf(),
+            source,
+        }
+        .into()
+    })
+}
+
+fn warn_about_git_setup(repo_root: &Path, out: Output) {
+    if !out.show_warnings() {
+        return;
+    }
+    let warnings = [
+        git::minimum_version_warning(repo_root),
+        git::submodule_warning(repo_root),
+    ];
+    for warning in warnings.into_iter().flatten() {
+        out.warn(warning);
+    }
+}
+
+/// Point at `worktree audit` when `.wtm/workspaces` has directories git does not know.
+fn warn_about_stray_directories(repo_root: &Path, out: Output) {
+    if !out.show_warnings() {
+        return;
+    }
+    if let Some(warning) = stray_directories_warning(repo_root) {
+        out.warn(warning);
+    }
+}
+
+fn run_worktree_cli(command: WorktreeCommands, base_dir: &Path, out: Output) -> Result<i32> {
+    let repo_root = repo_root_for(base_dir)?;
+    warn_about_git_setup(&repo_root, out);
+    if !matches!(command, WorktreeCommands::Audit { .. }) {
+        warn_about_stray_directories(&repo_root, out);
+    }
+    let result: Result<()> = match command {
+        WorktreeCommands::List {
+            all,
+            stale,
+            oldest,
+            newest,
+            only_linked,
+            limit,
+            offset,
+            tag,
+            tree,
+            with_services,
+            status,
+            json,
+        } => {
+            let mut worktrees = list_worktrees_cached(&repo_root)?;
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            let tags = Tags::load(&repo_root.join(".wtm")).unwrap_or_else(|err| {
+                out.warn(format!("could not read worktree tags: {err:#}"));
+                Tags::default()
+            });
+            if let Some(tag) = tag.as_deref() {
+                worktrees.retain(|wt| tags.has(&wt.path, tag));
+            }
+            let pins = Pins::load(&repo_root.join(".wtm")).unwrap_or_else(|err| {
+                out.warn(format!("could not read worktree pins: {err:#}"));
+                Pins::default()
+            });
+            if !all {
+                config.ignore.retain_visible(&mut worktrees, &repo_root);
+            }
+            if only_linked {
+                retain_linked(&mut worktrees, &repo_root);
+            }
+            if let Some(max_age) = stale {
+                stale::retain_stale(
+                    &mut worktrees,
+                    &repo_root,
+                    std::time::SystemTime::now(),
+                    max_age,
+                    stale::last_activity,
+                );
+            }
+            if let Some(selector) = AgeSelector::from_flags(oldest, newest) {
+                stale::select_by_age(&mut worktrees, &repo_root, selector, stale::last_activity);
+            }
+            if with_services {
+                docker::retain_with_running_services(&mut worktrees, |path| {
+                    docker::services_summary(path, &config.docker)
+                });
+            }
+            config.primary_position.apply(&mut worktrees, &repo_root);
+            let page = Page::apply(&mut worktrees, offset, limit);
+            if json {
+                let summaries: Vec<WorktreeSummary> =
+                    worktrees.iter().map(WorktreeSummary::new).collect();
+                out.print_json_page(&summaries, &page)?;
+                return Ok(EXIT_SUCCESS);
+            }
+            let footer = page.footer(worktrees.len());
+            if tree {
+                let workspace_root = wtm_paths::workspace_root(&repo_root);
+                for line in tree_lines(&worktrees, &workspace_root) {
+                    println!("{line}");
                 }
-                if let Some(head) = wt.head.as_deref() {
-                    columns.push(format!("HEAD: {}", &head[..std::cmp::min(7, head.len())]));
+                if let Some(footer) = footer {
+                    out.status(footer);
                 }
-                if wt.is_locked {
-                    columns.push("locked".into());
+                return Ok(EXIT_SUCCESS);
+            }
+            let home = home_dir();
+            let gone = git::gone_branches(&repo_root).unwrap_or_else(|err| {
+                out.warn(format!("could not check upstreams: {err:#}"));
+                Default::default()
+            });
+            let rows: Vec<ListRow> = worktrees
+                .iter()
+                .map(|wt| {
+                    let wt_tags = tags.get(&wt.path);
+                    ListRow {
+                        path: format_path(
+                            &wt.path,
+                            config.path_display,
+                            &repo_root,
+                            home.as_deref(),
+                        ),
+                        branch: wt.branch.clone(),
+                        head: wt.head.clone(),
+                        tags: (!wt_tags.is_empty()).then(|| tags::label(&wt_tags)),
+                        state: RowState {
+                            primary: wt.is_primary(&repo_root),
+                            locked: wt.is_locked,
+                            pinned: pins.is_pinned(&wt.path),
+                            prunable: wt.is_prunable,
+                            unpopulated: status && is_unpopulated(&wt.path),
+                            missing: !wt.path.exists(),
+                            upstream_gone: wt
+                                .branch
+                                .as_deref()
+                                .is_some_and(|branch| gone.contains(branch)),
+                            dirty: status.then(|| {
+                                git::status::status(&wt.path).is_ok_and(|summary| {
+                                    summary.staged
+                                        + summary.unstaged
+                                        + summary.untracked
+                                        + summary.conflicts
+                                        > 0
+                                })
+                            }),
+                        },
+                    }
+                })
+                .collect();
+            if status && git::is_bare_repository(&repo_root).unwrap_or(false) {
+                out.status("Bare repository: every worktree is a linked one");
+            }
+            for line in list_table(&rows, out.color) {
+                println!("{line}");
+            }
+            if let Some(footer) = footer {
+                out.status(footer);
+            }
+            Ok(())
+        }
+        WorktreeCommands::Status {
+            all,
+            tag,
+            compact,
+            json,
+            fields,
+            include_services,
+            no_primary,
+            snapshot,
+            compare,
+            with_services,
+        } => {
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            let format = if json {
+                StatusFormat::Json(
+                    fields.map(|fields| select_fields(&fields)).transpose()?,
+                    include_services.then(|| config.docker.clone()),
+                )
+            } else if compact {
+                StatusFormat::Compact
+            } else {
+                StatusFormat::Lines
+            };
+            let mut worktrees = list_worktrees_cached(&repo_root)?;
+            if !all {
+                config.ignore.retain_visible(&mut worktrees, &repo_root);
+            }
+            if no_primary {
+                retain_linked(&mut worktrees, &repo_root);
+            }
+            if let Some(tag) = tag.as_deref() {
+                retain_tagged(&mut worktrees, &repo_root, tag)?;
+            }
+            if with_services {
+                docker::retain_with_running_services(&mut worktrees, |path| {
+                    docker::services_summary(path, &config.docker)
+                });
+            }
+            config.primary_position.apply(&mut worktrees, &repo_root);
+            if snapshot.is_some() || compare.is_some() {
+                return telemetry_command(
+                    &worktrees,
+                    compare.as_deref(),
+                    snapshot.as_deref(),
+                    json,
+                    &CancellationToken::ctrl_c(),
+                    out,
+                )
+                .map(|()| EXIT_SUCCESS);
+            }
+            status_command(
+                &repo_root,
+                &worktrees,
+                config.path_display,
+                format,
+                &CancellationToken::ctrl_c(),
+                out,
+            )
+        }
+        WorktreeCommands::Add {
+            branch,
+            from,
+            track,
+            no_track,
+            run,
+            no_hooks,
+            adopt,
+            lock,
+            category,
+            no_checkout,
+            open,
+            track_issue,
+            json,
+        } => {
+            let branch_name = sanitize_branch_name(&branch);
+            let explicit = match (track, no_track) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            };
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            let options = AddOptions {
+                no_checkout,
+                ..add_options(&repo_root, &config, lock, out)
+            };
+            let template = match &category {
+                Some(category) => Some(wtm_paths::categorized_template(
+                    category,
+                    config.workspace_path_template.as_deref(),
+                )),
+                None => config.workspace_path_template.clone(),
+            };
+            let default_start = match (&from, config.default_upstream.as_deref()) {
+                (None, Some(configured)) => {
+                    let start = git::default_start_point(&repo_root, configured);
+                    if start.is_none() {
+                        out.warn(format!(
+                            "defaultUpstream `{configured}` does not resolve; starting from HEAD"
+                        ));
+                    }
+                    start
                 }
-                if wt.is_prunable {
-                    columns.push("prunable".into());
+                _ => None,
+            };
+            // Only an explicit --from is tracked by default; a branch started
+            // from defaultUpstream tracks it only with --track or a rule.
+            let upstream = match (&from, &default_start) {
+                (Some(reference), _) => Some(Upstream {
+                    reference,
+                    track: config.tracking.resolve(&branch_name, explicit),
+                }),
+                (None, Some(reference)) => Some(Upstream {
+                    reference,
+                    track: config
+                        .tracking
+                        .resolve(&branch_name, explicit)
+                        .or(Some(false)),
+                }),
+                (None, None) => None,
+            };
+            if upstream.is_none() && explicit.is_some() {
+                bail!(
+                    "--track and --no-track need a start point; pass --from or set defaultUpstream"
+                );
+            }
+            let worktree_path = {
+                let _spinner = Spinner::start(
+                    format!("Creating worktree for {branch_name}…"),
+                    !out.show_progress(),
+                );
+                let create = if adopt {
+                    adopt_workspace
+                } else {
+                    create_workspace
+                };
+                let layout = PathLayout {
+                    template: template.as_deref(),
+                    ..config.path_layout()
+                };
+                create(&repo_root, &branch, upstream, layout, &options)?
+            };
+            if out.quiet && !json {
+                // The new path is the command's result, so scripts still get it.
+                println!("{}", worktree_path.display());
+            } else if !json {
+                println!(
+                    "Created {}worktree for branch {branch_name} at {}",
+                    options_label(&options),
+                    worktree_path.display()
+                );
+            }
+            // Keep hook and command output out of the JSON on stdout.
+            let hook_out = Output {
+                quiet: out.quiet || json,
+                ..out
+            };
+            if !no_hooks {
+                run_post_create_hook(
+                    &repo_root,
+                    &worktree_path,
+                    &branch_name,
+                    config.hooks_path.as_deref(),
+                    hook_out,
+                );
+            }
+            if track_issue {
+                track_jira_issue(
+                    &branch_name,
+                    config.jira.on_create_transition.as_deref(),
+                    hook_out,
+                );
+            }
+            run_post_create(&worktree_path, run.or(config.post_create_command), hook_out)?;
+            let opened = open
+                .map(|with| open_worktree(&worktree_path, with, !json && !out.quiet))
+                .transpose()?;
+            if json {
+                out.print_json(&serde_json::json!({
+                    "path": worktree_path,
+                    "branch": branch_name,
+                    "open": opened,
+                }))?;
+            }
+            Ok(())
+        }
+        WorktreeCommands::Attach {
+            branch,
+            create_if_missing,
+            lock,
+            no_checkout,
+            run,
+            no_hooks,
+        } => {
+            let branch = branch.trim();
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            let options = AddOptions {
+                no_checkout,
+                ..add_options(&repo_root, &config, lock, out)
+            };
+            let (worktree_path, source) = {
+                let _spinner = Spinner::start(
+                    format!("Attaching worktree to {branch}…"),
+                    !out.show_progress(),
+                );
+                attach_workspace(
+                    &repo_root,
+                    branch,
+                    create_if_missing,
+                    config.tracking.tracking_policy(branch),
+                    config.path_layout(),
+                    &options,
+                )?
+            };
+            if out.quiet {
+                println!("{}", worktree_path.display());
+            } else {
+                let how = match source {
+                    AttachSource::Local => "existing branch".to_string(),
+                    AttachSource::Remote(upstream) => format!("new branch from {upstream}"),
+                    AttachSource::Created => "new branch from HEAD".to_string(),
+                };
+                println!(
+                    "Attached {}worktree at {} to {how} {branch}",
+                    options_label(&options),
+                    worktree_path.display()
+                );
+            }
+            if !no_hooks {
+                run_post_create_hook(
+                    &repo_root,
+                    &worktree_path,
+                    branch,
+                    config.hooks_path.as_deref(),
+                    out,
+                );
+            }
+            run_post_create(&worktree_path, run.or(config.post_create_command), out)
+        }
+        WorktreeCommands::Checkout {
+            branch,
+            lock,
+            run,
+            no_hooks,
+        } => {
+            let branch = branch.trim();
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            let options = add_options(&repo_root, &config, lock, out);
+            let (worktree_path, source) = {
+                let _spinner =
+                    Spinner::start(format!("Checking out {branch}…"), !out.show_progress());
+                checkout_workspace(&repo_root, branch, config.path_layout(), &options)?
+            };
+            if out.quiet {
+                println!("{}", worktree_path.display());
+            } else {
+                let how = match source {
+                    AttachSource::Remote(upstream) => {
+                        format!("new branch {branch} tracking {upstream}")
+                    }
+                    _ => format!("existing branch {branch}"),
+                };
+                println!(
+                    "Checked out {how} in {}worktree at {}",
+                    options_label(&options),
+                    worktree_path.display()
+                );
+            }
+            if !no_hooks {
+                run_post_create_hook(
+                    &repo_root,
+                    &worktree_path,
+                    branch,
+                    config.hooks_path.as_deref(),
+                    out,
+                );
+            }
+            run_post_create(&worktree_path, run.or(config.post_create_command), out)
+        }
+        WorktreeCommands::CloneCurrent { run, no_hooks } => {
+            let plan = plan_clone_current(&repo_root, base_dir)?;
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            let upstream = Upstream {
+                reference: &plan.start_point,
+                track: config.tracking.resolve(&plan.branch, None),
+            };
+            let options = add_options(&repo_root, &config, None, out);
+            let worktree_path = {
+                let _spinner = Spinner::start(
+                    format!(
+                        "Creating worktree for {} from {}…",
+                        plan.branch, plan.start_point
+                    ),
+                    !out.show_progress(),
+                );
+                create_workspace(
+                    &repo_root,
+                    &plan.branch,
+                    Some(upstream),
+                    config.path_layout(),
+                    &options,
+                )?
+            };
+            if out.quiet {
+                println!("{}", worktree_path.display());
+            } else {
+                println!(
+                    "Created worktree for branch {} from {} at {}",
+                    plan.branch,
+                    plan.start_point,
+                    worktree_path.display()
+                );
+            }
+            if !no_hooks {
+                run_post_create_hook(
+                    &repo_root,
+                    &worktree_path,
+                    &plan.branch,
+                    config.hooks_path.as_deref(),
+                    out,
+                );
+            }
+            run_post_create(&worktree_path, run.or(config.post_create_command), out)
+        }
+        WorktreeCommands::Duplicate {
+            path,
+            run,
+            no_hooks,
+        } => {
+            let plan = plan_duplicate(&repo_root, &path)?;
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            let start = Upstream {
+                reference: &plan.head,
+                track: Some(false),
+            };
+            let options = add_options(&repo_root, &config, None, out);
+            let worktree_path = {
+                let _spinner = Spinner::start(
+                    format!("Creating worktree for {}…", plan.branch),
+                    !out.show_progress(),
+                );
+                create_workspace(
+                    &repo_root,
+                    &plan.branch,
+                    Some(start),
+                    config.path_layout(),
+                    &options,
+                )?
+            };
+            if out.quiet {
+                println!("{}", worktree_path.display());
+            } else {
+                println!(
+                    "Created worktree for branch {} at {} (copy of {} at {})",
+                    plan.branch,
+                    worktree_path.display(),
+                    plan.source.display(),
+                    &plan.head[..plan.head.len().min(12)]
+                );
+            }
+            if !no_hooks {
+                run_post_create_hook(
+                    &repo_root,
+                    &worktree_path,
+                    &plan.branch,
+                    config.hooks_path.as_deref(),
+                    out,
+                );
+            }
+            run_post_create(&worktree_path, run.or(config.post_create_command), out)
+        }
+        WorktreeCommands::Audit { fix, yes, json } => {
+            let report = audit_workspaces(&repo_root)?;
+            if json {
+                out.print_json(&report)?;
+            } else {
+                print_audit(&report);
+            }
+            let removable = report.removable_orphans().count();
+            if fix && removable > 0 {
+                let prompt = format!(
+                    "Remove {removable} orphaned director{}?",
+                    if removable == 1 { "y" } else { "ies" }
+                );
+                if yes || confirm(&prompt)? {
+                    let result = remove_orphans(&repo_root, &report)?;
+                    report_bulk(&result, out);
+                    result.check()?;
+                } else if !out.quiet {
+                    eprintln!("Left orphaned directories in place.");
                 }
-                println!("{}", columns.join(" | "));
+            }
+            if report.shared_branches.is_empty() {
+                return Ok(EXIT_SUCCESS);
+            }
+            if !fix {
+                bail!(
+                    "{} branch(es) are checked out in more than one worktree; run `wtm worktree audit --fix` to detach the extra worktrees",
+                    report.shared_branches.len()
+                );
+            }
+            let prompt = "Detach HEAD in the extra worktrees of shared branches?";
+            if !(yes || confirm(prompt)?) {
+                bail!("left branches checked out in more than one worktree");
+            }
+            for path in detach_shared_branches(&repo_root, &report)? {
+                out.status(format!("Detached HEAD in {}.", path.display()));
             }
             Ok(())
         }
-        WorktreeCommands::Add { branch } => {
-            let branch = sanitize_branch_name(&branch);
-            if branch.is_empty() {
-                bail!("Branch name is required.");
+        WorktreeCommands::Activity {
+            since,
+            all,
+            no_primary,
+            json,
+        } => {
+            let mut worktrees = list_worktrees_cached(&repo_root)?;
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            if !all {
+                config.ignore.retain_visible(&mut worktrees, &repo_root);
+            }
+            if no_primary {
+                retain_linked(&mut worktrees, &repo_root);
+            }
+            config.primary_position.apply(&mut worktrees, &repo_root);
+            activity_command(&worktrees, &since, json, out)
+        }
+        WorktreeCommands::Du { no_primary, json } => {
+            let mut worktrees = list_worktrees_cached(&repo_root)?;
+            if no_primary {
+                retain_linked(&mut worktrees, &repo_root);
+            }
+            let usage = {
+                let spinner = Spinner::start("Measuring worktrees…", json || !out.show_progress());
+                disk_usage_with(&worktrees, &CancellationToken::ctrl_c(), &|progress| {
+                    spinner.set_message(format!(
+                        "Measuring worktrees… {} in {} files",
+                        format_bytes(progress.bytes),
+                        progress.files
+                    ))
+                })
             }
-            let workspace_root = ensure_workspace_root(&repo_root)?;
-            let dir_name = branch_dir_name(&branch);
-            let worktree_path = next_available_workspace_path(&workspace_root, &dir_name);
-            add_worktree(&repo_root, &worktree_path, Some(branch.as_str()))?;
-            println!(
-                "Created worktree for branch {branch} at {}",
-                worktree_path.display()
+            .context("failed to measure worktree sizes")?;
+            if usage.cancelled {
+                out.warn("measuring was cancelled; sizes only cover what was counted so far");
+            }
+            if json {
+                out.print_json(&usage)?;
+            } else {
+                for ws in &usage.workspaces {
+                    let size = if ws.missing {
+                        "missing".to_string()
+                    } else {
+                        format_bytes(ws.bytes)
+                    };
+                    println!("{size:>10}  {}", ws.name);
+                }
+                println!("{:>10}  total", format_bytes(usage.total_bytes));
+            }
+            Ok(())
+        }
+        WorktreeCommands::Move {
+            path,
+            to,
+            dry_run,
+            allow_external,
+            force,
+        } => {
+            let plan = plan_move(&repo_root, &path, &to, allow_external)?;
+            if dry_run {
+                println!("source:      {}", plan.source.display());
+                println!(
+                    "destination: {} ({})",
+                    plan.destination.display(),
+                    if plan.destination_free {
+                        "free"
+                    } else {
+                        "already exists"
+                    }
+                );
+                return Ok(EXIT_SUCCESS);
+            }
+            move_workspace(&repo_root, &plan, force)?;
+            out.status(format!(
+                "Moved worktree {} to {}",
+                plan.source.display(),
+                plan.destination.display()
+            ));
+            Ok(())
+        }
+        WorktreeCommands::Remove {
+            path: None,
+            force,
+            trash,
+            no_hooks,
+            include_pinned,
+            tag: Some(tag),
+            yes,
+            ..
+        } => {
+            let mut worktrees = list_worktrees_cached(&repo_root)?;
+            retain_linked(&mut worktrees, &repo_root);
+            retain_tagged(&mut worktrees, &repo_root, &tag)?;
+            if worktrees.is_empty() {
+                bail!("no linked worktree is tagged `{tag}`");
+            }
+            let pins = Pins::load(&repo_root.join(".wtm"))?;
+            let heading = format!("Tagged #{tag}:");
+            if !confirm_removal(&heading, &worktrees, &pins, include_pinned, yes, out)? {
+                return Ok(EXIT_SUCCESS);
+            }
+            remove_each(
+                &repo_root,
+                worktrees,
+                RemoveMatching {
+                    force,
+                    trash,
+                    run_hooks: !no_hooks,
+                    include_pinned,
+                },
+                out,
+            )
+        }
+        WorktreeCommands::Remove {
+            path: None,
+            force,
+            trash,
+            no_hooks,
+            include_pinned,
+            oldest,
+            newest,
+            yes,
+            ..
+        } => {
+            let selector = AgeSelector::from_flags(oldest, newest)
+                .context("pass a worktree, a glob, --oldest, or --newest")?;
+            let mut worktrees = list_worktrees_cached(&repo_root)?;
+            let pins = Pins::load(&repo_root.join(".wtm"))?;
+            if !include_pinned {
+                worktrees.retain(|wt| !pins.is_pinned(&wt.path));
+            }
+            stale::select_by_age(&mut worktrees, &repo_root, selector, stale::last_activity);
+            if worktrees.is_empty() {
+                out.status("No worktrees to remove.");
+                return Ok(EXIT_SUCCESS);
+            }
+            let heading = "Selected by last commit:";
+            if !confirm_removal(heading, &worktrees, &pins, include_pinned, yes, out)? {
+                return Ok(EXIT_SUCCESS);
+            }
+            remove_each(
+                &repo_root,
+                worktrees,
+                RemoveMatching {
+                    force,
+                    trash,
+                    run_hooks: !no_hooks,
+                    include_pinned,
+                },
+                out,
+            )
+        }
+        WorktreeCommands::Remove {
+            path: Some(path),
+            force,
+            trash,
+            no_hooks,
+            include_pinned,
+            yes,
+            ..
+        } if is_glob(&path) => remove_matching(
+            &repo_root,
+            &path.to_string_lossy(),
+            RemoveMatching {
+                force,
+                trash,
+                run_hooks: !no_hooks,
+                include_pinned,
+            },
+            yes,
+            out,
+        ),
+        WorktreeCommands::Remove {
+            path: Some(path),
+            force,
+            trash: true,
+            no_hooks,
+            ..
+        } => {
+            let entry = {
+                let _spinner = Spinner::start("Moving worktree to trash…", !out.show_progress());
+                trash_workspace(&repo_root, &path, force, !no_hooks)?
+            };
+            out.status(format!(
+                "Moved worktree {} to trash as {} (restore with `wtm worktree trash restore {}`)",
+                entry.original_path.display(),
+                entry.id,
+                entry.id
+            ));
+            Ok(())
+        }
+        WorktreeCommands::Remove {
+            path: Some(path),
+            force,
+            trash: false,
+            no_hooks,
+            ..
+        } => {
+            let full_path = {
+                let _spinner = Spinner::start("Removing worktree…", !out.show_progress());
+                delete_workspace(&repo_root, &path, force, !no_hooks)?
+            };
+            out.status(format!("Removed worktree {}", full_path.display()));
+            Ok(())
+        }
+        WorktreeCommands::Trash { command } => run_trash_cli(command, &repo_root, out),
+        WorktreeCommands::Reset {
+            name,
+            clean,
+            to,
+            yes,
+        } => {
+            let worktree = lookup_workspace(&repo_root, &name)?;
+            let name = worktree.name();
+            let options = ResetOptions {
+                clean,
+                to: to.as_deref(),
+            };
+            let cancel = CancellationToken::ctrl_c();
+            if let Err(err) = git::fetch_all(&worktree.path, &cancel) {
+                if cancel.is_cancelled() {
+                    bail!("cancelled while fetching; {name} was left as it was");
+                }
+                out.warn(format!(
+                    "fetch failed, using the refs fetched before: {err:#}"
+                ));
+            }
+            drop(cancel);
+            let upstream = git::status::status(&worktree.path)?.upstream;
+            let discarded = reset::preview(&worktree.path, options, upstream.is_some())
+                .with_context(|| format!("cannot reset {name}"))?;
+            // Shown with the prompt when there is one, or else as a status
+            // line that --quiet hides.
+            let asking = !yes && !discarded.is_empty();
+            for line in discarded.lines() {
+                if asking {
+                    eprintln!("{line}");
+                } else {
+                    out.status(line);
+                }
+            }
+            let prompt = format!("Discard all of this in {name}?");
+            if !reset::confirmed(&discarded, yes, || confirm(&prompt))? {
+                out.status(format!("Left {name} as it was."));
+                return Ok(EXIT_SUCCESS);
+            }
+            match reset::reset_worktree(&worktree.path, options, upstream.is_some())? {
+                Synced::Pulled => out.status(format!(
+                    "Reset {name} and fast-forwarded it to {}",
+                    upstream.unwrap_or_default()
+                )),
+                Synced::MovedTo => {
+                    out.status(format!("Reset {name} to {}", to.unwrap_or_default()))
+                }
+                Synced::NoUpstream => {
+                    out.status(format!("Reset {name}; its branch has no upstream to pull"))
+                }
+            }
+            Ok(())
+        }
+        WorktreeCommands::Sync { ff_only, tag } => {
+            let mut worktrees = list_worktrees_cached(&repo_root)?;
+            if let Some(tag) = tag.as_deref() {
+                retain_tagged(&mut worktrees, &repo_root, tag)?;
+            }
+            let result = sync_command(
+                &repo_root,
+                &worktrees,
+                ff_only,
+                &CancellationToken::ctrl_c(),
+                out,
             );
+            report_bulk(&result, out);
+            result.check()
+        }
+        WorktreeCommands::Diff { from, to, stat } => {
+            let from = diff_operand(&lookup_workspace(&repo_root, &from)?)?;
+            let to = diff_operand(&lookup_workspace(&repo_root, &to)?)?;
+            git::diff_refs(&repo_root, &from, &to, stat)
+        }
+        WorktreeCommands::Note { name, edit } => {
+            let worktree = lookup_workspace(&repo_root, &name)?;
+            let path = notes::note_path(&repo_root, &worktree.path);
+            if edit {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)
+                        .with_context(|| format!("failed to create {}", dir.display()))?;
+                }
+                open_in_editor(&path)?;
+                // Saving again drops a note that was left blank.
+                return notes::save(&path, &notes::load(&path)?).map(|()| EXIT_SUCCESS);
+            }
+            let text = notes::load(&path)?;
+            if text.trim().is_empty() {
+                out.status(format!("{} has no note", worktree.name()));
+            } else {
+                print!("{text}");
+            }
             Ok(())
         }
-        WorktreeCommands::Remove { path, force } => {
-            let workspace_root = ensure_workspace_root(&repo_root)?;
-            let full_path = if path.is_absolute() {
-                path
+        WorktreeCommands::Tag { name, add, remove } => {
+            let worktree = lookup_workspace(&repo_root, &name)?;
+            let wtm_dir = repo_root.join(".wtm");
+            let tags = if add.is_empty() && remove.is_empty() {
+                Tags::load(&wtm_dir)?
             } else {
-                workspace_root.join(path)
+                tags::update(&wtm_dir, &worktree.path, &add, &remove)?
             };
-            remove_worktree(&repo_root, &full_path, force)?;
-            println!("Removed worktree {}", full_path.display());
+            let current = tags.get(&worktree.path);
+            if current.is_empty() {
+                println!("{}: no tags", worktree.name());
+            } else {
+                println!("{}: {}", worktree.name(), tags::label(&current));
+            }
+            Ok(())
+        }
+        WorktreeCommands::Pin { name } => {
+            let worktree = lookup_workspace(&repo_root, &name)?;
+            if pins::update(&repo_root.join(".wtm"), &worktree.path, true)? {
+                out.status(format!("Pinned {}", worktree.name()));
+            } else {
+                out.status(format!("{} is already pinned", worktree.name()));
+            }
+            Ok(())
+        }
+        WorktreeCommands::Unpin { name } => {
+            let worktree = lookup_workspace(&repo_root, &name)?;
+            if !pins::update(&repo_root.join(".wtm"), &worktree.path, false)? {
+                bail!("{} is not pinned", worktree.name());
+            }
+            out.status(format!("Unpinned {}", worktree.name()));
+            Ok(())
+        }
+        WorktreeCommands::Path { name } => {
+            println!("{}", lookup_workspace(&repo_root, &name)?.path.display());
+            Ok(())
+        }
+        WorktreeCommands::CdScript { shell } => {
+            print!("{}", cd_script(shell)?);
+            Ok(())
+        }
+        WorktreeCommands::Run { path, command } => {
+            let worktree = find_workspace(&repo_root, &path)?;
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            let env = workspace_env(&worktree, &config.terminal_env);
+            return run_in_pty(&worktree.path, &command, &env);
+        }
+        WorktreeCommands::Undo => {
+            let config = load_config_or_default(&repo_root.join(".wtm"), out);
+            let restored = undo_delete(&repo_root, &add_options(&repo_root, &config, None, out))?;
+            out.status(format!(
+                "Restored worktree {} for branch {}",
+                restored.path.display(),
+                restored.branch.unwrap_or_default()
+            ));
+            Ok(())
+        }
+    };
+    result.map(|()| EXIT_SUCCESS)
+}
+
+fn run_trash_cli(command: TrashCommands, repo_root: &Path, out: Output) -> Result<()> {
+    match command {
+        TrashCommands::List { json } => {
+            let entries = trash::list(repo_root)?;
+            if json {
+                let entries: Vec<_> = entries
+                    .iter()
+                    .map(|entry| {
+                        let mut value = serde_json::to_value(entry)?;
+                        value["id"] = entry.id.clone().into();
+                        Ok(value)
+                    })
+                    .collect::<Result<_>>()?;
+                out.print_json(&entries)?;
+                return Ok(());
+            }
+            if entries.is_empty() {
+                out.status("Trash is empty.");
+            }
+            for entry in entries {
+                println!(
+                    "{}  {}  {}",
+                    entry.id,
+                    entry.branch.as_deref().unwrap_or("(detached)"),
+                    entry.original_path.display()
+                );
+            }
             Ok(())
         }
+        TrashCommands::Restore { id } => {
+            let entry = restore_trashed(repo_root, &id)?;
+            out.status(format!(
+                "Restored worktree {}",
+                entry.original_path.display()
+            ));
+            Ok(())
+        }
+        TrashCommands::Empty => {
+            let _lock = lock::WtmLock::acquire(repo_root)?;
+            let result = trash::empty(repo_root)?;
+            report_bulk(&result, out);
+            result.check()
+        }
+    }
+}
+
+/// Keep only the worktrees tagged `tag`, for the `--tag` selector.
+fn retain_tagged(worktrees: &mut Vec<WorktreeInfo>, repo_root: &Path, tag: &str) -> Result<()> {
+    let tags = Tags::load(&repo_root.join(".wtm"))?;
+    worktrees.retain(|wt| tags.has(&wt.path, tag));
+    Ok(())
+}
+
+/// Whether a `worktree remove` argument is a glob rather than a path.
+fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// How [`remove_each`] removes each worktree.
+struct RemoveMatching {
+    force: bool,
+    trash: bool,
+    run_hooks: bool,
+    include_pinned: bool,
+}
+
+/// Remove every linked worktree whose name matches `pattern`, after listing
+/// them and asking unless `yes`.
+fn remove_matching(
+    repo_root: &Path,
+    pattern: &str,
+    how: RemoveMatching,
+    yes: bool,
+    out: Output,
+) -> Result<()> {
+    let glob = glob::Pattern::new(pattern).with_context(|| format!("bad pattern `{pattern}`"))?;
+    let mut worktrees = list_worktrees_cached(repo_root)?;
+    retain_linked(&mut worktrees, repo_root);
+    worktrees.retain(|wt| glob.matches(&wt.name()));
+    if worktrees.is_empty() {
+        return Err(WorkspaceError::NoMatch(PathBuf::from(pattern)).into());
+    }
+    let pins = Pins::load(&repo_root.join(".wtm"))?;
+    let heading = format!("Matching `{pattern}`:");
+    if !confirm_removal(&heading, &worktrees, &pins, how.include_pinned, yes, out)? {
+        return Ok(());
+    }
+    remove_each(repo_root, worktrees, how, out)
+}
+
+/// Remove `worktrees`, skipping pinned ones unless `include_pinned`, and
+/// carrying on past failures.
+fn remove_each(
+    repo_root: &Path,
+    worktrees: Vec<WorktreeInfo>,
+    how: RemoveMatching,
+    out: Output,
+) -> Result<()> {
+    let pins = Pins::load(&repo_root.join(".wtm"))?;
+    let mut result = BulkResult::new();
+    for worktree in worktrees {
+        let name = worktree.name();
+        if pins.is_pinned(&worktree.path) && !how.include_pinned {
+            out.status(format!("Skipped pinned worktree {name}"));
+            result.record(name, Outcome::Skipped);
+            continue;
+        }
+        let removed = if how.trash {
+            trash_workspace(repo_root, &worktree.path, how.force, how.run_hooks).map(|_| ())
+        } else {
+            delete_workspace(repo_root, &worktree.path, how.force, how.run_hooks).map(|_| ())
+        };
+        if removed.is_ok() {
+            out.status(format!("Removed worktree {name}"));
+        }
+        result.record_result(name, removed);
     }
+    report_bulk(&result, out);
+    result.check()
+}
+
+/// Print why each failed item failed, then the bulk command's summary line,
+/// to stderr so it stays out of `--json` output.
+fn report_bulk(result: &BulkResult, out: Output) {
+    for (name, err) in result.failures() {
+        eprintln!("error: {name}: {err}");
+    }
+    if !out.quiet {
+        let color = out.color && std::io::stderr().is_terminal();
+        eprintln!("{}", result.render_summary(color));
+    }
+}
+
+fn print_audit(report: &AuditReport) {
+    if report.is_clean() {
+        println!("No problems found.");
+        return;
+    }
+    let sections = [
+        ("Directories unknown to git", &report.orphan_directories),
+        (
+            "Checkouts of another repository (left in place by --fix)",
+            &report.foreign_worktrees,
+        ),
+        (
+            "Worktrees outside .wtm/workspaces",
+            &report.external_worktrees,
+        ),
+        ("Prunable worktrees", &report.prunable_worktrees),
+    ];
+    for (title, paths) in sections {
+        if paths.is_empty() {
+            continue;
+        }
+        println!("{title}:");
+        for path in paths {
+            println!("  {}", path.display());
+        }
+    }
+    if !report.shared_branches.is_empty() {
+        println!("Branches checked out in more than one worktree (error):");
+        for shared in &report.shared_branches {
+            println!("  {}", shared.branch);
+            for path in &shared.worktrees {
+                println!("    {}", path.display());
+            }
+        }
+    }
+}
+
+/// The branch checked out in `worktree`, or its commit when detached.
+fn diff_operand(worktree: &WorktreeInfo) -> Result<String> {
+    worktree
+        .branch
+        .clone()
+        .or_else(|| worktree.head.clone())
+        .ok_or_else(|| anyhow!("{} has no commit to compare", worktree.name()))
+}
+
+/// `--track-issue`: comment on the Jira ticket named in `branch` and move it to
+/// `transition` when set. Failures only warn; the worktree exists either way.
+fn track_jira_issue(branch: &str, transition: Option<&str>, out: Output) {
+    let Some(key) = jira::ticket_key_in(branch) else {
+        out.warn(format!(
+            "not tracking an issue: branch {branch} names no Jira ticket (e.g. PROJ-123)"
+        ));
+        return;
+    };
+    if let Some(status) = transition {
+        match jira::transition(&key, status) {
+            Ok(()) => out.status(format!("Moved {key} to {status}.")),
+            Err(err) => out.warn(format!("{err:#}")),
+        }
+    }
+    match jira::comment(&key, &format!("Work started on branch {branch}.")) {
+        Ok(()) => out.status(format!("Commented on {key}.")),
+        Err(err) => out.warn(format!("{err:#}")),
+    }
+}
+
+/// Point a new worktree's `core.hooksPath` at `hooksPath` and run
+/// `.wtm/hooks/post-create`; failures of either only warn.
+fn run_post_create_hook(
+    repo_root: &Path,
+    worktree_path: &Path,
+    branch: &str,
+    hooks_path: Option<&str>,
+    out: Output,
+) {
+    if let Some(hooks_path) = hooks_path {
+        if let Err(err) = hooks::install_hooks_path(repo_root, worktree_path, hooks_path) {
+            out.warn(format!("failed to set core.hooksPath: {err:#}"));
+        }
+    }
+    let context = HookContext {
+        repo_root,
+        worktree_path,
+        branch,
+    };
+    match hooks::run_hook(Hook::PostCreate, context) {
+        Ok(Some(outcome)) => {
+            if !outcome.output.is_empty() && !out.quiet {
+                eprintln!("{}", outcome.output);
+            }
+            if !outcome.success {
+                out.warn(outcome.summary());
+            }
+        }
+        Ok(None) => {}
+        Err(err) => out.warn(format!("failed to run post-create hook: {err:#}")),
+    }
+}
+
+/// Run the post-create command in a new worktree, failing if it exits unsuccessfully.
+fn run_post_create(worktree_path: &Path, command: Option<String>, out: Output) -> Result<()> {
+    let Some(command) = command else {
+        return Ok(());
+    };
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .current_dir(worktree_path)
+        .stdout(if out.quiet {
+            std::process::Stdio::null()
+        } else {
+            std::process::Stdio::inherit()
+        })
+        .status()
+        .with_context(|| format!("failed to run post-create command `{command}`"))?;
+    if !status.success() {
+        bail!(
+            "post-create command `{command}` failed in {} ({status})",
+            worktree_path.display()
+        );
+    }
+    out.status(format!("Post-create command `{command}` finished."));
+    Ok(())
+}
+
+/// List the worktrees a bulk removal picked under `heading` and, unless `yes`,
+/// ask before going ahead. The list goes to stderr beside the prompt, or
+/// through `out` when nothing is asked, so `--quiet --yes` prints nothing.
+fn confirm_removal(
+    heading: &str,
+    worktrees: &[WorktreeInfo],
+    pins: &Pins,
+    include_pinned: bool,
+    yes: bool,
+    out: Output,
+) -> Result<bool> {
+    let mut lines = vec![heading.to_string()];
+    let mut removing = 0;
+    for worktree in worktrees {
+        let pinned = pins.is_pinned(&worktree.path);
+        let note = match (pinned, include_pinned) {
+            (false, _) => "",
+            (true, true) => " (pinned)",
+            (true, false) => " (pinned, kept)",
+        };
+        if !pinned || include_pinned {
+            removing += 1;
+        }
+        lines.push(format!("  {}{note}", worktree.name()));
+    }
+    if yes {
+        lines.iter().for_each(|line| out.status(line));
+        return Ok(true);
+    }
+    lines.iter().for_each(|line| eprintln!("{line}"));
+    if confirm(&format!("Remove these {removing} worktree(s)?"))? {
+        return Ok(true);
+    }
+    out.status("Left the worktrees in place.");
+    Ok(false)
+}
+
+/// Ask a yes/no question on stderr, defaulting to "no".
+fn confirm(prompt: &str) -> Result<bool> {
+    use std::io::{BufRead, Write};
+    eprint!("{prompt} [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .context("failed to read confirmation")?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "YES" | "Yes"))
 }
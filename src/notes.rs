@@ -0,0 +1,203 @@
+//! Free-form Markdown notes per worktree, kept under `.wtm/notes/`.
+
+use crate::wtm_paths::{branch_slug, fnv1a, workspace_root};
+use anyhow::{Context, Result};
+use std::{
+    collections::BTreeSet,
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Directory inside `.wtm/` holding the notes.
+pub const NOTES_DIR: &str = "notes";
+
+/// Note file for the worktree at `worktree_path`.
+///
+/// Worktrees under `.wtm/workspaces` keep their path there, so
+/// `features/login` → `workspaces/features/login.md`. Any other worktree,
+/// the primary checkout included, gets `<directory name>-<hash of its path>.md`
+/// next to that directory; git reports worktree paths already resolved, so the
+/// hash stays put for as long as the worktree does.
+pub fn note_path(repo_root: &Path, worktree_path: &Path) -> PathBuf {
+    let notes_dir = repo_root.join(".wtm").join(NOTES_DIR);
+    let relative = worktree_path
+        .strip_prefix(workspace_root(repo_root))
+        .ok()
+        .filter(|relative| !relative.as_os_str().is_empty());
+    if let Some(relative) = relative {
+        let mut path = notes_dir.join("workspaces").join(relative);
+        path.as_mut_os_string().push(".md");
+        return path;
+    }
+    let name = worktree_path
+        .file_name()
+        .map(|name| branch_slug(&name.to_string_lossy()))
+        .unwrap_or_else(|| "worktree".to_string());
+    let hash = fnv1a(worktree_path.as_os_str().as_encoded_bytes());
+    notes_dir.join(format!("{name}-{hash:08x}.md"))
+}
+
+/// Carry the note of the worktree at `from` over to `to` after a move.
+pub fn rename(repo_root: &Path, from: &Path, to: &Path) -> Result<()> {
+    let (from, to) = (note_path(repo_root, from), note_path(repo_root, to));
+    if !from.exists() {
+        return Ok(());
+    }
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    fs::rename(&from, &to).with_context(|| format!("failed to move {}", from.display()))?;
+    remove_empty_dirs(repo_root, &from);
+    Ok(())
+}
+
+/// Delete the notes of worktrees not in `existing`; returns whether any were dropped.
+pub fn reconcile<'a>(
+    repo_root: &Path,
+    existing: impl IntoIterator<Item = &'a Path>,
+) -> Result<bool> {
+    let keep: BTreeSet<PathBuf> = existing
+        .into_iter()
+        .map(|path| note_path(repo_root, path))
+        .collect();
+    let mut found = Vec::new();
+    collect_notes(&repo_root.join(".wtm").join(NOTES_DIR), &mut found)?;
+    let mut dropped = false;
+    for note in found.into_iter().filter(|note| !keep.contains(note)) {
+        fs::remove_file(&note).with_context(|| format!("failed to remove {}", note.display()))?;
+        remove_empty_dirs(repo_root, &note);
+        dropped = true;
+    }
+    Ok(dropped)
+}
+
+/// Every `.md` file under `dir`, recursively.
+fn collect_notes(dir: &Path, found: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err).with_context(|| format!("failed to read {}", dir.display())),
+    };
+    for entry in entries {
+        let entry = entry.with_context(|| format!("failed to read {}", dir.display()))?;
+        let path = entry.path();
+        if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            collect_notes(&path, found)?;
+        } else if path.extension() == Some(OsStr::new("md")) {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Remove the directories left empty above `note`, up to `.wtm/notes`.
+fn remove_empty_dirs(repo_root: &Path, note: &Path) {
+    let notes_dir = repo_root.join(".wtm").join(NOTES_DIR);
+    for dir in note.ancestors().skip(1) {
+        if dir == notes_dir || !dir.starts_with(&notes_dir) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
+
+/// The note at `path`, or an empty string when there is none.
+pub fn load(path: &Path) -> Result<String> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(text),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Write `text` to `path`, deleting the file when the note is blank.
+pub fn save(path: &Path, text: &str) -> Result<()> {
+    if text.trim().is_empty() {
+        return match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                Err(err).with_context(|| format!("failed to remove {}", path.display()))
+            }
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// First non-blank line, for one-line previews.
+pub fn preview(text: &str) -> Option<&str> {
+    text.lines().map(str::trim).find(|line| !line.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn note_paths_keep_distinct_worktrees_apart() {
+        let repo = Path::new("/repo");
+        let notes = repo.join(".wtm/notes");
+        let workspaces = repo.join(".wtm/workspaces");
+        assert_eq!(
+            note_path(repo, &workspaces.join("features/login")),
+            notes.join("workspaces/features/login.md")
+        );
+        assert_ne!(
+            note_path(repo, &workspaces.join("features/login")),
+            note_path(repo, &workspaces.join("features-login"))
+        );
+        assert_ne!(
+            note_path(repo, repo),
+            note_path(repo, &workspaces.join("repo"))
+        );
+        let (a, b) = (Path::new("/a/hotfix"), Path::new("/b/hotfix"));
+        assert_ne!(note_path(repo, a), note_path(repo, b));
+        assert!(note_path(repo, a).starts_with(&notes));
+        assert!(note_path(repo, a)
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("hotfix-"));
+    }
+
+    #[test]
+    fn notes_move_with_their_worktree_and_go_with_it() {
+        let repo = tempdir().unwrap();
+        let workspaces = repo.path().join(".wtm/workspaces");
+        let (old, new, kept) = (
+            workspaces.join("features/login"),
+            workspaces.join("login"),
+            workspaces.join("kept"),
+        );
+        save(&note_path(repo.path(), &old), "wip").unwrap();
+        save(&note_path(repo.path(), &kept), "keep me").unwrap();
+
+        rename(repo.path(), &old, &new).unwrap();
+        assert_eq!(load(&note_path(repo.path(), &new)).unwrap(), "wip");
+        assert!(!repo.path().join(".wtm/notes/workspaces/features").exists());
+
+        assert!(reconcile(repo.path(), [kept.as_path()]).unwrap());
+        assert!(!note_path(repo.path(), &new).exists());
+        assert_eq!(load(&note_path(repo.path(), &kept)).unwrap(), "keep me");
+        assert!(!reconcile(repo.path(), [kept.as_path()]).unwrap());
+    }
+
+    #[test]
+    fn notes_roundtrip_and_blank_notes_are_deleted() {
+        let repo = tempdir().unwrap();
+        let path = note_path(repo.path(), &repo.path().join(".wtm/workspaces/x"));
+        assert_eq!(load(&path).unwrap(), "");
+
+        save(&path, "\n  Fix flaky login test\n- TODO: ask QA\n").unwrap();
+        let text = load(&path).unwrap();
+        assert_eq!(text, "\n  Fix flaky login test\n- TODO: ask QA\n");
+        assert_eq!(preview(&text), Some("Fix flaky login test"));
+
+        save(&path, "  \n").unwrap();
+        assert!(!path.exists());
+        assert_eq!(preview(""), None);
+    }
+}
@@ -0,0 +1,218 @@
+//! How chatty the CLI is, as selected by `--quiet` and `--no-warnings`,
+//! whether it may use colour (`--no-color`, `NO_COLOR`), and how JSON results
+//! are framed (`--envelope`).
+
+use serde::Serialize;
+use serde_json::json;
+use std::ffi::OsStr;
+
+/// Version of the JSON output formats, reported as `schemaVersion` by `--envelope`.
+///
+/// Bump it when a JSON result changes in a way that can break consumers.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Output policy threaded through command dispatch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Output {
+    /// Suppress confirmations, progress, and warnings; keep results and errors.
+    pub quiet: bool,
+    /// Suppress warnings only.
+    pub no_warnings: bool,
+    /// Style stdout with ANSI colours.
+    pub color: bool,
+    /// Wrap JSON results in `{ "schemaVersion": …, "data": … }`.
+    pub envelope: bool,
+}
+
+impl Output {
+    /// Print a non-essential confirmation line to stdout.
+    pub fn status(&self, message: impl AsRef<str>) {
+        if !self.quiet {
+            println!("{}", message.as_ref());
+        }
+    }
+
+    /// Print `warning: …` to stderr unless warnings are silenced.
+    pub fn warn(&self, message: impl AsRef<str>) {
+        if self.show_warnings() {
+            eprintln!("warning: {}", message.as_ref());
+        }
+    }
+
+    pub fn show_warnings(&self) -> bool {
+        !self.quiet && !self.no_warnings
+    }
+
+    /// Whether progress indicators such as the spinner may be shown.
+    pub fn show_progress(&self) -> bool {
+        !self.quiet
+    }
+
+    /// Print a JSON result to stdout, in the envelope when `--envelope` is set.
+    pub fn print_json(&self, data: &impl Serialize) -> serde_json::Result<()> {
+        println!("{}", self.json_string(data, None)?);
+        Ok(())
+    }
+
+    /// Like [`Output::print_json`] for one page of a list; the envelope also
+    /// reports the window as `pagination`.
+    pub fn print_json_page(&self, data: &impl Serialize, page: &Page) -> serde_json::Result<()> {
+        println!("{}", self.json_string(data, Some(page))?);
+        Ok(())
+    }
+
+    fn json_string(
+        &self,
+        data: &impl Serialize,
+        page: Option<&Page>,
+    ) -> serde_json::Result<String> {
+        if !self.envelope {
+            return serde_json::to_string_pretty(data);
+        }
+        let mut envelope = json!({
+            "schemaVersion": JSON_SCHEMA_VERSION,
+            "data": data,
+        });
+        if let Some(page) = page {
+            envelope["pagination"] = serde_json::to_value(page)?;
+        }
+        serde_json::to_string_pretty(&envelope)
+    }
+
+    /// Colour only a terminal, and only when neither `--no-color` nor a
+    /// non-empty `NO_COLOR` (see no-color.org) opts out.
+    pub fn color_enabled(
+        no_color_flag: bool,
+        no_color_env: Option<&OsStr>,
+        stdout_is_terminal: bool,
+    ) -> bool {
+        let env_opt_out = no_color_env.is_some_and(|value| !value.is_empty());
+        stdout_is_terminal && !no_color_flag && !env_opt_out
+    }
+}
+
+/// The window of a list selected by `--offset` and `--limit`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page {
+    pub offset: usize,
+    pub limit: Option<usize>,
+    /// Length of the list before paging.
+    pub total: usize,
+}
+
+impl Page {
+    /// Keep only the window of `items` starting at `offset`, at most `limit` long.
+    pub fn apply<T>(items: &mut Vec<T>, offset: usize, limit: Option<usize>) -> Self {
+        let total = items.len();
+        items.drain(..offset.min(total));
+        if let Some(limit) = limit {
+            items.truncate(limit);
+        }
+        Self {
+            offset,
+            limit,
+            total,
+        }
+    }
+
+    /// `showing X–Y of Z` when the window hides part of the list.
+    pub fn footer(&self, shown: usize) -> Option<String> {
+        if shown == self.total {
+            return None;
+        }
+        if shown == 0 {
+            return Some(format!("showing 0 of {}", self.total));
+        }
+        Some(format!(
+            "showing {}–{} of {}",
+            self.offset + 1,
+            self.offset + shown,
+            self.total
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_implies_no_warnings_or_progress() {
+        let quiet = Output {
+            quiet: true,
+            ..Output::default()
+        };
+        assert!(!quiet.show_warnings());
+        assert!(!quiet.show_progress());
+
+        let no_warnings = Output {
+            no_warnings: true,
+            ..Output::default()
+        };
+        assert!(!no_warnings.show_warnings());
+        assert!(no_warnings.show_progress());
+        assert!(Output::default().show_warnings());
+    }
+
+    #[test]
+    fn envelope_wraps_json_with_the_schema_version() {
+        let data = json!([{ "name": "feature-x" }]);
+        let bare = Output::default().json_string(&data, None).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&bare).unwrap(),
+            data
+        );
+
+        let enveloped = Output {
+            envelope: true,
+            ..Output::default()
+        };
+        let wrapped: serde_json::Value =
+            serde_json::from_str(&enveloped.json_string(&data, None).unwrap()).unwrap();
+        assert_eq!(
+            wrapped,
+            json!({ "schemaVersion": JSON_SCHEMA_VERSION, "data": data })
+        );
+    }
+
+    #[test]
+    fn page_selects_a_window_and_describes_it() {
+        let mut items: Vec<u32> = (1..=5).collect();
+        let page = Page::apply(&mut items, 1, Some(2));
+        assert_eq!(items, [2, 3]);
+        assert_eq!(
+            page.footer(items.len()).as_deref(),
+            Some("showing 2–3 of 5")
+        );
+
+        let mut all: Vec<u32> = (1..=3).collect();
+        let page = Page::apply(&mut all, 0, None);
+        assert_eq!(page.footer(all.len()), None);
+
+        let mut past_end: Vec<u32> = (1..=3).collect();
+        let page = Page::apply(&mut past_end, 7, Some(2));
+        assert!(past_end.is_empty());
+        assert_eq!(page.footer(0).as_deref(), Some("showing 0 of 3"));
+
+        let paged = Output {
+            envelope: true,
+            ..Output::default()
+        };
+        let wrapped: serde_json::Value =
+            serde_json::from_str(&paged.json_string(&items, Some(&page)).unwrap()).unwrap();
+        assert_eq!(
+            wrapped["pagination"],
+            json!({ "offset": 7, "limit": 2, "total": 3 })
+        );
+    }
+
+    #[test]
+    fn color_requires_terminal_and_no_opt_out() {
+        assert!(Output::color_enabled(false, None, true));
+        assert!(Output::color_enabled(false, Some(OsStr::new("")), true));
+        assert!(!Output::color_enabled(false, None, false));
+        assert!(!Output::color_enabled(true, None, true));
+        assert!(!Output::color_enabled(false, Some(OsStr::new("1")), true));
+    }
+}
@@ -0,0 +1,85 @@
+//! Run jobs off the calling thread: one job over many items on a bounded
+//! number of threads, or a single job in the background.
+//!
+//! Bulk commands mostly wait on git or docker processes; capping the threads
+//! keeps a repository with many worktrees from starting all of them at once.
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+/// Most threads a single [`map`] uses, whatever the core count.
+const MAX_THREADS: usize = 8;
+
+/// Call `job` on every item on at most [`MAX_THREADS`] threads (fewer on small
+/// machines), returning the results in the items' order. An item whose job
+/// panicked gets `None`, so the caller can report it and keep the rest.
+pub fn map<T: Sync, R: Send>(items: &[T], job: impl Fn(&T) -> R + Sync) -> Vec<Option<R>> {
+    let threads = thread::available_parallelism()
+        .map_or(1, |cores| cores.get())
+        .clamp(1, MAX_THREADS)
+        .min(items.len());
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            return done;
+                        };
+                        if let Ok(result) = panic::catch_unwind(AssertUnwindSafe(|| job(item))) {
+                            done.push((index, result));
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            for (index, result) in worker.join().unwrap_or_default() {
+                results[index] = Some(result);
+            }
+        }
+    });
+    results
+}
+
+/// Run `job` on its own thread, so an event loop can keep drawing; the result
+/// arrives on the returned channel, which disconnects instead if `job` panics.
+pub fn background<T: Send + 'static>(job: impl FnOnce() -> T + Send + 'static) -> Receiver<T> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(job());
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_keep_their_order_and_panics_become_none() {
+        let items: Vec<u32> = (0..50).collect();
+        let results = map(&items, |&n| {
+            assert_ne!(n, 7, "job for 7 fails");
+            n * 2
+        });
+        assert_eq!(results.len(), 50);
+        assert_eq!(results[7], None);
+        assert!(results
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 7)
+            .all(|(i, result)| *result == Some(i as u32 * 2)));
+        assert!(map(&[] as &[u32], |&n| n).is_empty());
+    }
+}
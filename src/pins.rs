@@ -0,0 +1,154 @@
+//! Pinned worktrees, kept in `.wtm/pins.json`.
+//!
+//! A pin is wtm's own guard, independent of `git worktree lock`: commands that
+//! remove several worktrees at once (e.g. `worktree remove 'feature-*'`) skip
+//! pinned ones unless asked to include them. Like tags, pins are keyed by path
+//! and [`Pins::reconcile`] forgets worktrees that no longer exist.
+
+use crate::{
+    lock::{WtmLock, LOCK_TIMEOUT},
+    wtm_paths::atomic_write_json,
+};
+use anyhow::{Context, Result};
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// File inside `.wtm/` listing the pinned worktree paths.
+pub const PINS_FILE: &str = "pins.json";
+
+/// Shown next to pinned worktrees.
+pub const PIN_MARKER: &str = "📌";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pins {
+    paths: BTreeSet<PathBuf>,
+}
+
+impl Pins {
+    /// The saved pins, or none when the file does not exist yet.
+    pub fn load(wtm_dir: &Path) -> Result<Self> {
+        let path = wtm_dir.join(PINS_FILE);
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()))
+            }
+        };
+        let paths = serde_json::from_str(&data)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        Ok(Self { paths })
+    }
+
+    /// Write the pins back, removing the file once nothing is pinned.
+    pub fn save(&self, wtm_dir: &Path) -> Result<()> {
+        let path = wtm_dir.join(PINS_FILE);
+        if self.paths.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                    Err(err).with_context(|| format!("failed to remove {}", path.display()))
+                }
+                _ => Ok(()),
+            };
+        }
+        atomic_write_json(&path, &self.paths)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn is_pinned(&self, path: &Path) -> bool {
+        self.paths.contains(path)
+    }
+
+    /// Pin `path`; returns whether it was not pinned before.
+    pub fn pin(&mut self, path: &Path) -> bool {
+        self.paths.insert(path.to_path_buf())
+    }
+
+    /// Unpin `path`; returns whether it was pinned.
+    pub fn unpin(&mut self, path: &Path) -> bool {
+        self.paths.remove(path)
+    }
+
+    /// Carry the pin of the worktree at `from` over to `to` after a move;
+    /// returns whether it was pinned.
+    pub fn rename(&mut self, from: &Path, to: &Path) -> bool {
+        let pinned = self.paths.remove(from);
+        if pinned {
+            self.paths.insert(to.to_path_buf());
+        }
+        pinned
+    }
+
+    /// Forget pins of worktrees not in `existing`; returns whether any were dropped.
+    pub fn reconcile<'a>(&mut self, existing: impl IntoIterator<Item = &'a Path>) -> bool {
+        let existing: BTreeSet<&Path> = existing.into_iter().collect();
+        let before = self.paths.len();
+        self.paths.retain(|path| existing.contains(path.as_path()));
+        self.paths.len() != before
+    }
+}
+
+/// Pin or unpin `path` and save, holding the `.wtm` lock so a concurrent
+/// command cannot lose the change; returns whether anything changed.
+pub fn update(wtm_dir: &Path, path: &Path, pinned: bool) -> Result<bool> {
+    let _lock = WtmLock::acquire_with_timeout(wtm_dir, LOCK_TIMEOUT)?;
+    let mut pins = Pins::load(wtm_dir)?;
+    let changed = if pinned {
+        pins.pin(path)
+    } else {
+        pins.unpin(path)
+    };
+    if changed {
+        pins.save(wtm_dir)?;
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn pins_round_trip_and_are_reconciled() {
+        let wtm = tempdir().unwrap();
+        let kept = Path::new("/ws/kept");
+        let removed = Path::new("/ws/removed");
+        let mut pins = Pins::default();
+        assert!(pins.pin(kept));
+        assert!(!pins.pin(kept));
+        pins.pin(removed);
+        pins.save(wtm.path()).unwrap();
+
+        let mut loaded = Pins::load(wtm.path()).unwrap();
+        assert_eq!(loaded, pins);
+        assert!(loaded.reconcile([kept]));
+        assert!(loaded.is_pinned(kept));
+        assert!(!loaded.is_pinned(removed));
+
+        assert!(loaded.unpin(kept));
+        assert!(!loaded.unpin(kept));
+        loaded.save(wtm.path()).unwrap();
+        assert!(!wtm.path().join(PINS_FILE).exists());
+    }
+
+    #[test]
+    fn pins_follow_a_moved_worktree() {
+        let wtm = tempdir().unwrap();
+        let (from, to) = (Path::new("/ws/old"), Path::new("/ws/archive/old"));
+        assert!(update(wtm.path(), from, true).unwrap());
+        assert!(!update(wtm.path(), from, true).unwrap());
+
+        let mut pins = Pins::load(wtm.path()).unwrap();
+        assert!(pins.rename(from, to));
+        assert!(pins.is_pinned(to) && !pins.is_pinned(from));
+        assert!(!pins.rename(from, to), "nothing left at the old path");
+
+        pins.save(wtm.path()).unwrap();
+        assert!(update(wtm.path(), to, false).unwrap());
+        assert!(!wtm.path().join(PINS_FILE).exists());
+    }
+}
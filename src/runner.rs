@@ -0,0 +1,247 @@
+//! External process execution shared by the git, docker, and Jira helpers.
+//!
+//! Integrations accept a [`CommandRunner`] so their argument construction and
+//! output handling can be exercised without the real binaries installed.
+//! Programs, arguments, and environment values are `OsStr`s so paths reach the
+//! child unchanged even when they are not valid UTF-8.
+
+use crate::logging::truncate_for_log;
+use anyhow::{Context, Result};
+use log::debug;
+use std::{ffi::OsStr, path::Path, process::Command};
+
+const LOGGED_STDERR_CHARS: usize = 500;
+
+/// Captured result of running an external program.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub success: bool,
+    /// Stdout decoded as UTF-8, with invalid sequences replaced.
+    pub stdout: String,
+    pub stderr: String,
+    /// Stdout as the program wrote it, for output that contains paths.
+    pub raw_stdout: Vec<u8>,
+}
+
+/// Executes external programs on behalf of the integration modules.
+pub trait CommandRunner {
+    fn run(
+        &self,
+        program: &OsStr,
+        args: &[&OsStr],
+        cwd: Option<&Path>,
+        env: &[(&str, &OsStr)],
+    ) -> Result<CommandOutput>;
+}
+
+/// Whether `err` means the program could not be started because it does not
+/// exist, as opposed to running and failing.
+pub fn is_program_missing(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|io| io.kind() == std::io::ErrorKind::NotFound)
+    })
+}
+
+/// `args` as one POSIX shell command line. Anything outside a small set of
+/// characters a shell never treats specially is single-quoted, so `$`, `;`,
+/// globs and the like stay literal.
+pub fn shell_join<S: AsRef<str>>(args: &[S]) -> String {
+    args.iter()
+        .map(|arg| shell_quote(arg.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Runner backed by `std::process::Command`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(
+        &self,
+        program: &OsStr,
+        args: &[&OsStr],
+        cwd: Option<&Path>,
+        env: &[(&str, &OsStr)],
+    ) -> Result<CommandOutput> {
+        let name = program.to_string_lossy();
+        let mut command = Command::new(program);
+        command.args(args);
+        if let Some(dir) = cwd {
+            command.current_dir(dir);
+        }
+        for (key, value) in env {
+            command.env(key, value);
+        }
+        debug!(
+            "running {name} {args:?} in {}",
+            cwd.map(|dir| dir.display().to_string())
+                .unwrap_or_else(|| ".".to_string())
+        );
+        let output = command
+            .output()
+            .with_context(|| format!("failed to spawn {name}"))?;
+        debug!(
+            "{name} finished with {}; stderr: {}",
+            output.status,
+            truncate_for_log(
+                &String::from_utf8_lossy(&output.stderr),
+                LOGGED_STDERR_CHARS
+            )
+        );
+        Ok(CommandOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            raw_stdout: output.stdout,
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::{CommandOutput, CommandRunner};
+    use anyhow::{anyhow, Context, Result};
+    use std::{
+        cell::RefCell,
+        collections::VecDeque,
+        ffi::OsStr,
+        io,
+        path::{Path, PathBuf},
+    };
+
+    /// A single invocation recorded by [`ScriptedRunner`].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct RecordedCall {
+        pub program: String,
+        pub args: Vec<String>,
+        pub cwd: Option<PathBuf>,
+    }
+
+    /// Runner that replays queued outputs and records every invocation.
+    #[derive(Default)]
+    pub struct ScriptedRunner {
+        /// `None` stands for a program that is not installed.
+        responses: RefCell<VecDeque<Option<CommandOutput>>>,
+        calls: RefCell<Vec<RecordedCall>>,
+    }
+
+    impl ScriptedRunner {
+        pub fn succeed(self, stdout: &str) -> Self {
+            self.succeed_bytes(stdout.as_bytes())
+        }
+
+        /// Queue a successful run whose stdout need not be valid UTF-8.
+        pub fn succeed_bytes(self, stdout: &[u8]) -> Self {
+            self.responses.borrow_mut().push_back(Some(CommandOutput {
+                success: true,
+                stdout: String::from_utf8_lossy(stdout).into_owned(),
+                stderr: String::new(),
+                raw_stdout: stdout.to_vec(),
+            }));
+            self
+        }
+
+        pub fn fail(self, stderr: &str) -> Self {
+            self.fail_with_stdout("", stderr)
+        }
+
+        /// Queue a failed run that printed `stdout` before failing.
+        pub fn fail_with_stdout(self, stdout: &str, stderr: &str) -> Self {
+            self.responses.borrow_mut().push_back(Some(CommandOutput {
+                success: false,
+                stdout: stdout.to_string(),
+                stderr: stderr.to_string(),
+                raw_stdout: stdout.as_bytes().to_vec(),
+            }));
+            self
+        }
+
+        /// Fail to spawn the next program as if it were not installed.
+        pub fn missing(self) -> Self {
+            self.responses.borrow_mut().push_back(None);
+            self
+        }
+
+        pub fn calls(&self) -> Vec<RecordedCall> {
+            self.calls.borrow().clone()
+        }
+    }
+
+    impl CommandRunner for ScriptedRunner {
+        fn run(
+            &self,
+            program: &OsStr,
+            args: &[&OsStr],
+            cwd: Option<&Path>,
+            _env: &[(&str, &OsStr)],
+        ) -> Result<CommandOutput> {
+            let program = program.to_string_lossy().into_owned();
+            self.calls.borrow_mut().push(RecordedCall {
+                program: program.clone(),
+                args: args
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().into_owned())
+                    .collect(),
+                cwd: cwd.map(Path::to_path_buf),
+            });
+            let response = self
+                .responses
+                .borrow_mut()
+                .pop_front()
+                .ok_or_else(|| anyhow!("no scripted response for {program}"))?;
+            response
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+                .with_context(|| format!("failed to spawn {program}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_join_quotes_everything_a_shell_would_interpret() {
+        assert_eq!(
+            shell_join(&["docker", "compose", "logs", "web-1", "--tail=50"]),
+            "docker compose logs web-1 --tail=50"
+        );
+        assert_eq!(
+            shell_join(&["echo", "$HOME", "a;b", "*", "", "it's"]),
+            r"echo '$HOME' 'a;b' '*' '' 'it'\''s'"
+        );
+    }
+
+    #[test]
+    fn system_runner_captures_stdout_and_status() {
+        let output = SystemRunner
+            .run("git".as_ref(), &["--version".as_ref()], None, &[])
+            .expect("git should be installed");
+        assert!(output.success);
+        assert!(output.stdout.starts_with("git version"));
+    }
+
+    #[test]
+    fn system_runner_errors_for_missing_program() {
+        let err = SystemRunner
+            .run("wtm-definitely-missing-binary".as_ref(), &[], None, &[])
+            .unwrap_err();
+        assert!(err.to_string().contains("failed to spawn"));
+        assert!(is_program_missing(&err));
+    }
+}
@@ -0,0 +1,109 @@
+//! Stderr spinner for CLI operations that may take a while.
+
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Operations finishing faster than this never show the spinner.
+const SHOW_AFTER: Duration = Duration::from_millis(200);
+const FRAME_INTERVAL: Duration = Duration::from_millis(80);
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Animates `message` on stderr until dropped, then clears the line.
+pub struct Spinner {
+    done: Arc<AtomicBool>,
+    message: Arc<Mutex<String>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    /// Start a spinner unless output is machine-readable or stderr is not a terminal.
+    pub fn start(message: impl Into<String>, machine_output: bool) -> Self {
+        let enabled = spinner_enabled(io::stderr().is_terminal(), machine_output);
+        let done = Arc::new(AtomicBool::new(false));
+        let message = Arc::new(Mutex::new(message.into()));
+        let worker = enabled.then(|| {
+            let done = Arc::clone(&done);
+            let message = Arc::clone(&message);
+            thread::spawn(move || animate(&message, &done))
+        });
+        Self {
+            done,
+            message,
+            worker,
+        }
+    }
+
+    /// Replace the text shown next to the spinner, e.g. with progress so far.
+    pub fn set_message(&self, message: impl Into<String>) {
+        *self
+            .message
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner()) = message.into();
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Whether a spinner may be drawn without corrupting piped or JSON output.
+fn spinner_enabled(stderr_is_terminal: bool, machine_output: bool) -> bool {
+    stderr_is_terminal && !machine_output
+}
+
+fn animate(message: &Mutex<String>, done: &AtomicBool) {
+    let started = Instant::now();
+    let mut frame = 0;
+    let mut drawn = false;
+    while !done.load(Ordering::SeqCst) {
+        if started.elapsed() >= SHOW_AFTER {
+            let message = message.lock().unwrap_or_else(|poison| poison.into_inner());
+            let mut stderr = io::stderr().lock();
+            let _ = write!(
+                stderr,
+                "\r\x1b[2K{} {message}",
+                FRAMES[frame % FRAMES.len()]
+            );
+            let _ = stderr.flush();
+            frame += 1;
+            drawn = true;
+        }
+        thread::sleep(FRAME_INTERVAL);
+    }
+    if drawn {
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[2K");
+        let _ = stderr.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spinner_is_suppressed_without_a_terminal_or_for_json() {
+        assert!(spinner_enabled(true, false));
+        assert!(!spinner_enabled(false, false));
+        assert!(!spinner_enabled(true, true));
+    }
+
+    #[test]
+    fn spinner_stops_on_drop() {
+        let spinner = Spinner::start("working", true);
+        assert!(spinner.worker.is_none());
+        drop(spinner);
+    }
+}
@@ -0,0 +1,197 @@
+//! Finding worktrees that have not been touched for a while.
+
+use crate::{
+    git::{self, WorktreeInfo},
+    workspace::retain_linked,
+};
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// Parse a human age such as `12h`, `7d`, `2w`, `3mo`, or `1y`.
+///
+/// Months are 30 days and years 365 days; `m` is rejected as ambiguous.
+pub fn parse_age(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (digits, unit) = input.split_at(split);
+    let count: u64 = digits
+        .parse()
+        .map_err(|_| format!("expected a number followed by a unit, got `{input}`"))?;
+    let unit_secs = match unit {
+        "h" => HOUR,
+        "d" => DAY,
+        "w" => 7 * DAY,
+        "mo" => 30 * DAY,
+        "y" => 365 * DAY,
+        "m" => return Err("`m` is ambiguous; use `mo` for months".into()),
+        "" => return Err(format!("missing unit in `{input}` (use h, d, w, mo, or y)")),
+        other => return Err(format!("unknown unit `{other}` (use h, d, w, mo, or y)")),
+    };
+    count
+        .checked_mul(unit_secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("`{input}` is too large"))
+}
+
+/// When the worktree was last active: its last commit, else the directory's mtime.
+pub fn last_activity(worktree: &WorktreeInfo) -> Option<SystemTime> {
+    git::last_commit_time(&worktree.path)
+        .ok()
+        .flatten()
+        .and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)))
+        .or_else(|| fs::metadata(&worktree.path).and_then(|m| m.modified()).ok())
+}
+
+/// Keep linked worktrees whose last activity is at least `max_age` before `now`.
+///
+/// The primary worktree is never stale, and worktrees with unknown activity are kept
+/// out so that `--stale` never suggests something it could not check.
+pub fn retain_stale(
+    worktrees: &mut Vec<WorktreeInfo>,
+    repo_root: &Path,
+    now: SystemTime,
+    max_age: Duration,
+    activity: impl Fn(&WorktreeInfo) -> Option<SystemTime>,
+) {
+    retain_linked(worktrees, repo_root);
+    worktrees.retain(|wt| {
+        activity(wt)
+            .and_then(|at| now.duration_since(at).ok())
+            .is_some_and(|age| age >= max_age)
+    });
+}
+
+/// `--oldest N` / `--newest N`: a fixed number of worktrees by last activity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgeSelector {
+    Oldest(usize),
+    Newest(usize),
+}
+
+impl AgeSelector {
+    /// The selector from the two flags, which clap keeps mutually exclusive.
+    pub fn from_flags(oldest: Option<usize>, newest: Option<usize>) -> Option<Self> {
+        oldest.map(Self::Oldest).or(newest.map(Self::Newest))
+    }
+}
+
+/// Keep the linked worktrees `selector` picks, oldest (or newest) first.
+///
+/// As with [`retain_stale`], the primary worktree and worktrees with unknown
+/// activity are never picked.
+pub fn select_by_age(
+    worktrees: &mut Vec<WorktreeInfo>,
+    repo_root: &Path,
+    selector: AgeSelector,
+    activity: impl Fn(&WorktreeInfo) -> Option<SystemTime>,
+) {
+    retain_linked(worktrees, repo_root);
+    let mut dated: Vec<(SystemTime, WorktreeInfo)> = worktrees
+        .drain(..)
+        .filter_map(|wt| activity(&wt).map(|at| (at, wt)))
+        .collect();
+    dated.sort_by_key(|(at, _)| *at);
+    let count = match selector {
+        AgeSelector::Oldest(count) => count,
+        AgeSelector::Newest(count) => {
+            dated.reverse();
+            count
+        }
+    };
+    worktrees.extend(dated.into_iter().take(count).map(|(_, wt)| wt));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_age_accepts_common_units() {
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * HOUR)));
+        assert_eq!(parse_age("7d"), Ok(Duration::from_secs(7 * DAY)));
+        assert_eq!(parse_age("2w"), Ok(Duration::from_secs(14 * DAY)));
+        assert_eq!(parse_age("3mo"), Ok(Duration::from_secs(90 * DAY)));
+        assert_eq!(parse_age(" 1y "), Ok(Duration::from_secs(365 * DAY)));
+    }
+
+    #[test]
+    fn parse_age_rejects_bad_input() {
+        assert!(parse_age("30").unwrap_err().contains("missing unit"));
+        assert!(parse_age("5m").unwrap_err().contains("ambiguous"));
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3fortnights")
+            .unwrap_err()
+            .contains("unknown unit"));
+    }
+
+    #[test]
+    fn retain_stale_keeps_old_linked_worktrees() {
+        let now = UNIX_EPOCH + Duration::from_secs(100 * DAY);
+        let days_ago = |days: u64| Some(now - Duration::from_secs(days * DAY));
+        let mut worktrees = vec![
+            WorktreeInfo::at("/repo"),
+            WorktreeInfo::at("/ws/old"),
+            WorktreeInfo::at("/ws/fresh"),
+            WorktreeInfo::at("/ws/unknown"),
+            WorktreeInfo::at("/ws/exact"),
+        ];
+        retain_stale(
+            &mut worktrees,
+            Path::new("/repo"),
+            now,
+            Duration::from_secs(30 * DAY),
+            |wt| match wt.path.to_str().unwrap() {
+                "/repo" | "/ws/old" => days_ago(60),
+                "/ws/fresh" => days_ago(2),
+                "/ws/exact" => days_ago(30),
+                _ => None,
+            },
+        );
+        let paths: Vec<_> = worktrees.iter().map(|wt| wt.path.clone()).collect();
+        assert_eq!(paths, [Path::new("/ws/old"), Path::new("/ws/exact")]);
+    }
+
+    #[test]
+    fn oldest_and_newest_pick_by_last_commit() {
+        let now = UNIX_EPOCH + Duration::from_secs(100 * DAY);
+        let all = || {
+            ["/repo", "/ws/a", "/ws/b", "/ws/c", "/ws/d", "/ws/unknown"]
+                .map(WorktreeInfo::at)
+                .to_vec()
+        };
+        let activity = |wt: &WorktreeInfo| {
+            let days = match wt.path.to_str().unwrap() {
+                "/repo" => 90,
+                "/ws/a" => 10,
+                "/ws/b" => 40,
+                "/ws/c" => 3,
+                "/ws/d" => 25,
+                _ => return None,
+            };
+            Some(now - Duration::from_secs(days * DAY))
+        };
+        let pick = |selector| {
+            let mut worktrees = all();
+            select_by_age(&mut worktrees, Path::new("/repo"), selector, activity);
+            worktrees
+                .into_iter()
+                .map(|wt| wt.path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pick(AgeSelector::Oldest(2)), ["/ws/b", "/ws/d"]);
+        assert_eq!(pick(AgeSelector::Newest(1)), ["/ws/c"]);
+        assert_eq!(pick(AgeSelector::Oldest(10)).len(), 4);
+        assert_eq!(
+            AgeSelector::from_flags(None, Some(3)),
+            Some(AgeSelector::Newest(3))
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Free-form tags on worktrees (e.g. `reviewing`, `flaky-ci`), kept in `.wtm/tags.json`.
+//!
+//! Tags are keyed by worktree path. [`Tags::reconcile`] drops the entries of
+//! worktrees that no longer exist, so a removed worktree's tags do not come
+//! back if a new one is created at the same path later.
+
+use crate::{
+    lock::{WtmLock, LOCK_TIMEOUT},
+    wtm_paths::atomic_write_json,
+};
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// File inside `.wtm/` holding every worktree's tags.
+pub const TAGS_FILE: &str = "tags.json";
+
+/// Tags per worktree path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tags {
+    by_path: BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+impl Tags {
+    /// The saved tags, or none when the file does not exist yet.
+    pub fn load(wtm_dir: &Path) -> Result<Self> {
+        let path = wtm_dir.join(TAGS_FILE);
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()))
+            }
+        };
+        let by_path = serde_json::from_str(&data)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        Ok(Self { by_path })
+    }
+
+    /// Write the tags back, removing the file once nothing is tagged.
+    pub fn save(&self, wtm_dir: &Path) -> Result<()> {
+        let path = wtm_dir.join(TAGS_FILE);
+        if self.by_path.is_empty() {
+            return match fs::remove_file(&path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                    Err(err).with_context(|| format!("failed to remove {}", path.display()))
+                }
+                _ => Ok(()),
+            };
+        }
+        atomic_write_json(&path, &self.by_path)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Tags on the worktree at `path`, sorted.
+    pub fn get(&self, path: &Path) -> Vec<&str> {
+        self.by_path
+            .get(path)
+            .map(|tags| tags.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn has(&self, path: &Path, tag: &str) -> bool {
+        self.by_path
+            .get(path)
+            .is_some_and(|tags| tags.contains(tag))
+    }
+
+    /// Tag `path`; returns whether the tag was new.
+    pub fn add(&mut self, path: &Path, tag: &str) -> bool {
+        self.by_path
+            .entry(path.to_path_buf())
+            .or_default()
+            .insert(tag.to_string())
+    }
+
+    /// Untag `path`; returns whether it had the tag.
+    pub fn remove(&mut self, path: &Path, tag: &str) -> bool {
+        let Some(tags) = self.by_path.get_mut(path) else {
+            return false;
+        };
+        let removed = tags.remove(tag);
+        if tags.is_empty() {
+            self.by_path.remove(path);
+        }
+        removed
+    }
+
+    /// Carry the tags of the worktree at `from` over to `to` after a move;
+    /// returns whether it had any.
+    pub fn rename(&mut self, from: &Path, to: &Path) -> bool {
+        match self.by_path.remove(from) {
+            Some(tags) => {
+                self.by_path.insert(to.to_path_buf(), tags);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget the tags of worktrees not in `existing`; returns whether any were dropped.
+    pub fn reconcile<'a>(&mut self, existing: impl IntoIterator<Item = &'a Path>) -> bool {
+        let existing: BTreeSet<&Path> = existing.into_iter().collect();
+        let before = self.by_path.len();
+        self.by_path
+            .retain(|path, _| existing.contains(path.as_path()));
+        self.by_path.len() != before
+    }
+}
+
+/// Check that `tag` is a single word that reads well after `#`, e.g. `flaky-ci`.
+pub fn parse_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("tags must not be empty".into());
+    }
+    if let Some(bad) = tag
+        .chars()
+        .find(|&c| c.is_whitespace() || c.is_control() || c == ',' || c == '#')
+    {
+        return Err(format!("`{tag}` must not contain {bad:?}"));
+    }
+    Ok(tag.to_string())
+}
+
+/// `#tag` labels separated by spaces, for listings.
+pub fn label(tags: &[&str]) -> String {
+    tags.iter()
+        .map(|tag| format!("#{tag}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Add and remove tags on `path` and save them under the `.wtm` lock; fails
+/// if `path` is not tagged with a tag being removed.
+pub fn update(wtm_dir: &Path, path: &Path, add: &[String], remove: &[String]) -> Result<Tags> {
+    let _lock = WtmLock::acquire_with_timeout(wtm_dir, LOCK_TIMEOUT)?;
+    let mut tags = Tags::load(wtm_dir)?;
+    for tag in remove {
+        if !tags.remove(path, tag) {
+            bail!("{} is not tagged `{tag}`", path.display());
+        }
+    }
+    for tag in add {
+        tags.add(path, tag);
+    }
+    tags.save(wtm_dir)?;
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn tags_are_added_removed_and_listed_sorted() {
+        let wtm = tempdir().unwrap();
+        let path = Path::new("/repo/.wtm/workspaces/feature");
+        let tags = update(
+            wtm.path(),
+            path,
+            &["reviewing".into(), "flaky-ci".into()],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(tags.get(path), ["flaky-ci", "reviewing"]);
+        assert_eq!(label(&tags.get(path)), "#flaky-ci #reviewing");
+
+        let tags = update(wtm.path(), path, &[], &["reviewing".into()]).unwrap();
+        assert!(!tags.has(path, "reviewing"));
+        assert_eq!(Tags::load(wtm.path()).unwrap(), tags);
+        let err = update(wtm.path(), path, &[], &["reviewing".into()]).unwrap_err();
+        assert!(err.to_string().contains("is not tagged `reviewing`"));
+
+        update(wtm.path(), path, &[], &["flaky-ci".into()]).unwrap();
+        assert!(!wtm.path().join(TAGS_FILE).exists());
+
+        assert!(parse_tag("two words").is_err());
+        assert_eq!(parse_tag(" ok ").unwrap(), "ok");
+    }
+
+    #[test]
+    fn reconcile_drops_removed_worktrees_and_persists_the_rest() {
+        let wtm = tempdir().unwrap();
+        let kept = Path::new("/ws/kept");
+        let removed = Path::new("/ws/removed");
+        let mut tags = Tags::default();
+        tags.add(kept, "reviewing");
+        tags.add(removed, "stale");
+        tags.save(wtm.path()).unwrap();
+
+        let mut loaded = Tags::load(wtm.path()).unwrap();
+        assert!(loaded.reconcile([kept]));
+        assert!(!loaded.reconcile([kept]));
+        loaded.save(wtm.path()).unwrap();
+
+        let reloaded = Tags::load(wtm.path()).unwrap();
+        assert_eq!(reloaded.get(kept), ["reviewing"]);
+        assert!(reloaded.get(removed).is_empty());
+    }
+
+    #[test]
+    fn tags_follow_a_moved_worktree() {
+        let (from, to) = (Path::new("/ws/old"), Path::new("/ws/archive/old"));
+        let mut tags = Tags::default();
+        tags.add(from, "reviewing");
+        assert!(tags.rename(from, to));
+        assert_eq!(tags.get(to), ["reviewing"]);
+        assert!(tags.get(from).is_empty());
+        assert!(!tags.rename(from, to));
+    }
+}
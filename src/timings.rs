@@ -0,0 +1,128 @@
+//! Phase timings printed to stderr by `--timings` for performance debugging.
+//!
+//! Call sites wrap their work in [`measure`], which costs one atomic load when
+//! the flag is off. Repeated phases (e.g. the TUI refreshing its listing) are
+//! summed under one label.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PHASES: Mutex<Vec<Phase>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Phase {
+    label: String,
+    count: u32,
+    total: Duration,
+}
+
+/// Start recording phases for the rest of the process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Run `work`, adding its duration to `label` when timings are enabled.
+pub fn measure<T>(label: impl FnOnce() -> String, work: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return work();
+    }
+    let start = Instant::now();
+    let result = work();
+    let elapsed = start.elapsed();
+    let mut phases = PHASES.lock().unwrap_or_else(|poison| poison.into_inner());
+    record(&mut phases, label(), elapsed);
+    result
+}
+
+fn record(phases: &mut Vec<Phase>, label: String, elapsed: Duration) {
+    match phases.iter_mut().find(|phase| phase.label == label) {
+        Some(phase) => {
+            phase.count += 1;
+            phase.total += elapsed;
+        }
+        None => phases.push(Phase {
+            label,
+            count: 1,
+            total: elapsed,
+        }),
+    }
+}
+
+/// Print every recorded phase, in the order first seen, and the overall time.
+pub fn report(total: Duration) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let phases = PHASES.lock().unwrap_or_else(|poison| poison.into_inner());
+    for line in summary_lines(&phases, total) {
+        eprintln!("{line}");
+    }
+}
+
+fn summary_lines(phases: &[Phase], total: Duration) -> Vec<String> {
+    let width = phases
+        .iter()
+        .map(|phase| phase.label.chars().count())
+        .chain(["total".len()])
+        .max()
+        .unwrap_or(0);
+    phases
+        .iter()
+        .map(|phase| {
+            let repeats = if phase.count > 1 {
+                format!(" ({}×)", phase.count)
+            } else {
+                String::new()
+            };
+            format!(
+                "timing: {:<width$}  {}{repeats}",
+                phase.label,
+                format_duration(phase.total)
+            )
+        })
+        .chain([format!(
+            "timing: {:<width$}  {}",
+            "total",
+            format_duration(total)
+        )])
+        .collect()
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_phases_are_summed_under_one_line() {
+        let mut phases = Vec::new();
+        record(
+            &mut phases,
+            "list_worktrees".into(),
+            Duration::from_millis(2),
+        );
+        record(&mut phases, "status a".into(), Duration::from_micros(1500));
+        record(
+            &mut phases,
+            "list_worktrees".into(),
+            Duration::from_millis(3),
+        );
+        assert_eq!(
+            summary_lines(&phases, Duration::from_millis(10)),
+            [
+                "timing: list_worktrees  5.0ms (2×)",
+                "timing: status a        1.5ms",
+                "timing: total           10.0ms",
+            ]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Soft-deleted worktrees kept under `.wtm/trash/` until restored or emptied.
+//!
+//! Each entry is a directory named `<unix-seconds>-<worktree name>` holding the
+//! worktree's files in `worktree/` and what is needed to re-register it in
+//! `entry.json`. Git no longer knows about trashed worktrees.
+
+use crate::bulk::BulkResult;
+use crate::wtm_paths::atomic_write_json;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Directory inside `.wtm/` holding trashed worktrees.
+pub const TRASH_DIR: &str = "trash";
+const ENTRY_FILE: &str = "entry.json";
+const CONTENTS_DIR: &str = "worktree";
+
+/// A worktree moved to the trash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    /// Directory name under `.wtm/trash/`, used to restore the entry.
+    #[serde(skip)]
+    pub id: String,
+    pub original_path: PathBuf,
+    /// Branch that was checked out; `None` for a detached worktree.
+    pub branch: Option<String>,
+    /// Commit checked out when the worktree was trashed.
+    pub head: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub trashed_at: u64,
+}
+
+/// `.wtm/trash` for the repository at `repo_root`.
+pub fn trash_root(repo_root: &Path) -> PathBuf {
+    repo_root.join(".wtm").join(TRASH_DIR)
+}
+
+/// Unused entry directory for `worktree_path` trashed at `trashed_at`.
+pub fn entry_path(trash_root: &Path, trashed_at: u64, worktree_path: &Path) -> PathBuf {
+    let name = worktree_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "worktree".into());
+    crate::wtm_paths::next_available_workspace_path(trash_root, &format!("{trashed_at}-{name}"))
+}
+
+/// Where an entry's worktree files are kept.
+pub fn contents_path(repo_root: &Path, entry: &TrashEntry) -> PathBuf {
+    trash_root(repo_root).join(&entry.id).join(CONTENTS_DIR)
+}
+
+/// Move `entry.original_path` into a new trash entry and record it.
+///
+/// `entry.id` is ignored and filled in from the chosen directory.
+pub fn move_to_trash(repo_root: &Path, mut entry: TrashEntry) -> io::Result<TrashEntry> {
+    let root = trash_root(repo_root);
+    fs::create_dir_all(&root)?;
+    let dir = entry_path(&root, entry.trashed_at, &entry.original_path);
+    fs::create_dir(&dir)?;
+    if let Err(err) = move_path(&entry.original_path, &dir.join(CONTENTS_DIR)) {
+        let _ = fs::remove_dir(&dir);
+        return Err(err);
+    }
+    entry.id = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    atomic_write_json(&dir.join(ENTRY_FILE), &entry)?;
+    Ok(entry)
+}
+
+/// Rename `from` to `to`, copying and then deleting when they are on
+/// different filesystems (e.g. `.wtm` on another mount than the worktree).
+pub(crate) fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    move_path_with(from, to, |from, to| fs::rename(from, to))
+}
+
+fn move_path_with(
+    from: &Path,
+    to: &Path,
+    rename: impl Fn(&Path, &Path) -> io::Result<()>,
+) -> io::Result<()> {
+    match rename(from, to) {
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(err) = copy_tree(from, to) {
+                let _ = remove_tree(to);
+                return Err(err);
+            }
+            remove_tree(from)
+        }
+        result => result,
+    }
+}
+
+/// Copy `from` to `to` recursively, keeping symlinks as links and permissions.
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+    if file_type.is_symlink() {
+        let target = fs::read_link(from)?;
+        #[cfg(unix)]
+        return std::os::unix::fs::symlink(target, to);
+        #[cfg(windows)]
+        return if from.is_dir() {
+            std::os::windows::fs::symlink_dir(target, to)
+        } else {
+            std::os::windows::fs::symlink_file(target, to)
+        };
+    }
+    if !file_type.is_dir() {
+        return fs::copy(from, to).map(drop);
+    }
+    fs::create_dir(to)?;
+    for item in fs::read_dir(from)? {
+        let item = item?;
+        copy_tree(&item.path(), &to.join(item.file_name()))?;
+    }
+    fs::set_permissions(to, fs::metadata(from)?.permissions())
+}
+
+fn remove_tree(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Every trash entry, most recently trashed first.
+pub fn list(repo_root: &Path) -> Result<Vec<TrashEntry>> {
+    let root = trash_root(repo_root);
+    let dir = match fs::read_dir(&root) {
+        Ok(dir) => dir,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("failed to read {}", root.display())),
+    };
+    let mut entries = Vec::new();
+    for item in dir {
+        let item = item.with_context(|| format!("failed to read {}", root.display()))?;
+        let id = item.file_name().to_string_lossy().into_owned();
+        match read_entry(&root, &id) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => log::warn!("skipping trash entry {id}: {err:#}"),
+        }
+    }
+    entries.sort_by(|a, b| b.trashed_at.cmp(&a.trashed_at).then(a.id.cmp(&b.id)));
+    Ok(entries)
+}
+
+/// The entry named `id`, if there is one.
+pub fn find(repo_root: &Path, id: &str) -> Result<Option<TrashEntry>> {
+    let root = trash_root(repo_root);
+    if id.is_empty() || id.contains(['/', '\\']) || !root.join(id).is_dir() {
+        return Ok(None);
+    }
+    read_entry(&root, id).map(Some)
+}
+
+fn read_entry(root: &Path, id: &str) -> Result<TrashEntry> {
+    let path = root.join(id).join(ENTRY_FILE);
+    let data =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut entry: TrashEntry = serde_json::from_str(&data)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    entry.id = id.to_string();
+    Ok(entry)
+}
+
+/// Delete an entry's directory, e.g. after it was restored.
+pub fn discard(repo_root: &Path, entry: &TrashEntry) -> io::Result<()> {
+    fs::remove_dir_all(trash_root(repo_root).join(&entry.id))
+}
+
+/// Permanently delete every trash entry, carrying on past failures.
+pub fn empty(repo_root: &Path) -> Result<BulkResult> {
+    let mut result = BulkResult::new();
+    for entry in list(repo_root)? {
+        result.record_result(entry.id.clone(), discard(repo_root, &entry));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(original_path: PathBuf, trashed_at: u64) -> TrashEntry {
+        TrashEntry {
+            id: String::new(),
+            original_path,
+            branch: Some("feature/x".into()),
+            head: Some("abc123".into()),
+            trashed_at,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn move_path_copies_across_filesystems() {
+        let root = tempdir().unwrap();
+        let from = root.path().join("feature-x");
+        fs::create_dir_all(from.join("src")).unwrap();
+        fs::write(from.join("src/main.rs"), "fn main() {}").unwrap();
+        std::os::unix::fs::symlink("src/main.rs", from.join("link")).unwrap();
+        let to = root.path().join("trashed");
+
+        move_path_with(&from, &to, |_, _| {
+            Err(io::Error::from(io::ErrorKind::CrossesDevices))
+        })
+        .unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(
+            fs::read_to_string(to.join("src/main.rs")).unwrap(),
+            "fn main() {}"
+        );
+        assert_eq!(
+            fs::read_link(to.join("link")).unwrap(),
+            Path::new("src/main.rs")
+        );
+    }
+
+    #[test]
+    fn entry_path_is_timestamped_and_unique() {
+        let root = tempdir().unwrap();
+        let worktree = Path::new("/ws/feature-x");
+        assert_eq!(
+            entry_path(root.path(), 1700000000, worktree),
+            root.path().join("1700000000-feature-x")
+        );
+        fs::create_dir(root.path().join("1700000000-feature-x")).unwrap();
+        assert_eq!(
+            entry_path(root.path(), 1700000000, worktree),
+            root.path().join("1700000000-feature-x-1")
+        );
+    }
+
+    #[test]
+    fn trashed_worktrees_are_listed_newest_first() {
+        let repo = tempdir().unwrap();
+        for (name, at) in [("old", 100), ("new", 200)] {
+            let worktree = repo.path().join(name);
+            fs::create_dir(&worktree).unwrap();
+            fs::write(worktree.join("notes.txt"), name).unwrap();
+            move_to_trash(repo.path(), entry(worktree.clone(), at)).unwrap();
+            assert!(!worktree.exists());
+        }
+
+        let entries = list(repo.path()).unwrap();
+        let ids: Vec<_> = entries.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, ["200-new", "100-old"]);
+        assert_eq!(
+            find(repo.path(), "100-old").unwrap().as_ref(),
+            Some(&entries[1])
+        );
+        let kept = contents_path(repo.path(), &entries[1]).join("notes.txt");
+        assert_eq!(fs::read_to_string(kept).unwrap(), "old");
+
+        assert_eq!(find(repo.path(), "../old").unwrap(), None);
+        assert_eq!(
+            empty(repo.path()).unwrap().render_summary(false),
+            "2 succeeded, 0 failed, 0 skipped"
+        );
+        assert!(list(repo.path()).unwrap().is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     git,
     jira::{self, JiraTicket},
-    wtm_paths::{branch_dir_name, next_available_workspace_path},
+    wtm_paths::{branch_slug, reserve_target_path, resolve_target_path, PathLayout},
 };
 use anyhow::Result;
 use std::{
@@ -43,6 +43,34 @@ impl Suggestion {
     }
 }
 
+/// Which kinds of suggestion the add overlay lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum SuggestionFilter {
+    #[default]
+    All,
+    Jira,
+    Branches,
+}
+
+impl SuggestionFilter {
+    fn admits(self, suggestion: &Suggestion) -> bool {
+        match self {
+            SuggestionFilter::All => true,
+            SuggestionFilter::Jira => matches!(suggestion, Suggestion::Ticket(_)),
+            SuggestionFilter::Branches => !matches!(suggestion, Suggestion::Ticket(_)),
+        }
+    }
+
+    /// Shown in the overlay title while the list is narrowed.
+    pub(super) fn label(self) -> Option<&'static str> {
+        match self {
+            SuggestionFilter::All => None,
+            SuggestionFilter::Jira => Some("Jira only"),
+            SuggestionFilter::Branches => Some("branches only"),
+        }
+    }
+}
+
 fn split_remote_branch(reference: &str) -> Option<(String, String)> {
     let mut parts = reference.splitn(2, '/');
     let remote = parts.next().unwrap_or_default();
@@ -62,26 +90,31 @@ pub(super) struct AddWorktreeState {
     remote_branches: Vec<String>,
     suggestions: Vec<Suggestion>,
     filtered: Vec<usize>,
+    filter: SuggestionFilter,
     selection: Option<usize>,
     show_overlay: bool,
     existing_branches: HashSet<String>,
     branch_exists: bool,
     branch_upstream: Option<String>,
+    /// The remote branch of the typed name that the user is being asked to
+    /// track instead of starting an unrelated branch from `HEAD`.
+    remote_prompt: Option<String>,
+    /// The user chose a new branch over the remote one for the typed name.
+    remote_declined: bool,
+    /// Why no Jira tickets are listed, shown in the overlay instead of a status message.
+    jira_hint: Option<String>,
 }
 
 impl AddWorktreeState {
     pub(super) fn new(repo_root: &Path) -> Result<(Self, Option<String>)> {
         let mut warnings = Vec::new();
 
-        let tickets = match jira::cached_tickets(repo_root) {
-            Ok(tickets) => tickets,
-            Err(err) => {
-                warnings.push(format!("Failed to load Jira cache: {err}"));
-                Vec::new()
-            }
+        let (tickets, jira_hint) = match jira::cached_tickets(repo_root) {
+            Ok(tickets) => (tickets, None),
+            Err(err) => (Vec::new(), Some(jira::unavailable_hint(&err))),
         };
 
-        let local_branches = match git::list_branches(repo_root) {
+        let local_branches = match git::list_branches(repo_root, None) {
             Ok(branches) => branches,
             Err(err) => {
                 warnings.push(format!("Failed to list git branches: {err}"));
@@ -89,7 +122,7 @@ impl AddWorktreeState {
             }
         };
 
-        let remote_branches = match git::list_remote_branches(repo_root) {
+        let remote_branches = match git::list_remote_branches(repo_root, None) {
             Ok(branches) => branches,
             Err(err) => {
                 warnings.push(format!("Failed to list remote branches: {err}"));
@@ -106,11 +139,15 @@ impl AddWorktreeState {
             remote_branches,
             suggestions: Vec::new(),
             filtered: Vec::new(),
+            filter: SuggestionFilter::All,
             selection: None,
             show_overlay: true,
             existing_branches,
             branch_exists: false,
             branch_upstream: None,
+            remote_prompt: None,
+            remote_declined: false,
+            jira_hint,
         };
         state.rebuild_suggestions();
         state.recompute_filters();
@@ -122,18 +159,27 @@ impl AddWorktreeState {
         Ok((state, warning))
     }
 
-    pub(super) fn refresh_data(&mut self, repo_root: &Path) -> Result<usize> {
-        let tickets = jira::refresh_cache(repo_root)?;
-        let local_branches = git::list_branches(repo_root)?;
-        let remote_branches = git::list_remote_branches(repo_root)?;
-        self.tickets = tickets;
+    /// Reload branches, then refetch Jira tickets.
+    ///
+    /// Branch suggestions are refreshed even when Jira is unavailable, which
+    /// returns `Ok(None)` and leaves the reason in [`Self::jira_hint`].
+    pub(super) fn refresh_data(&mut self, repo_root: &Path) -> Result<Option<usize>> {
+        let local_branches = git::list_branches(repo_root, None)?;
+        let remote_branches = git::list_remote_branches(repo_root, None)?;
+        let tickets = jira::refresh_cache(repo_root);
+        self.jira_hint = tickets.as_ref().err().map(jira::unavailable_hint);
+        self.tickets = tickets.as_ref().cloned().unwrap_or_default();
         self.local_branches = local_branches;
         self.remote_branches = remote_branches;
         self.existing_branches = self.local_branches.iter().cloned().collect();
         self.show_overlay = true;
         self.rebuild_suggestions();
         self.recompute_filters();
-        Ok(self.tickets.len())
+        Ok(tickets.ok().map(|tickets| tickets.len()))
+    }
+
+    pub(super) fn jira_hint(&self) -> Option<&str> {
+        self.jira_hint.as_deref()
     }
 
     pub(super) fn clear_cache(&mut self, repo_root: &Path) -> Result<()> {
@@ -162,9 +208,14 @@ impl AddWorktreeState {
         self.branch_exists
     }
 
-    pub(super) fn status_line(&self, workspace_root: &Path) -> String {
+    pub(super) fn status_line(&self, workspace_root: &Path, layout: PathLayout<'_>) -> String {
         let branch = self.branch_display();
-        let target = self.target_preview(workspace_root);
+        let target = self.target_path(workspace_root, layout);
+        if let Some(remote) = &self.remote_prompt {
+            return format!(
+                "[ADD] {branch} exists as {remote} • Enter/y: track it • n: new branch from HEAD • Esc: back"
+            );
+        }
         let mut status = format!("[ADD] Branch: {branch} ⇒ {}", target.display());
         if self.branch_exists() {
             status.push_str(" • existing branch");
@@ -182,16 +233,23 @@ impl AddWorktreeState {
         if self.branch_exists {
             self.branch_trimmed().to_string()
         } else {
-            branch_dir_name(self.branch_trimmed())
+            branch_slug(self.branch_trimmed())
         }
     }
 
-    pub(super) fn workspace_dir_name(&self) -> String {
-        branch_dir_name(self.branch_trimmed())
+    /// Where the worktree would be created, honouring `workspacePathTemplate` and
+    /// `maxSlugLength`.
+    pub(super) fn target_path(&self, workspace_root: &Path, layout: PathLayout<'_>) -> PathBuf {
+        resolve_target_path(workspace_root, self.branch_trimmed(), layout)
     }
 
-    pub(super) fn target_preview(&self, workspace_root: &Path) -> PathBuf {
-        next_available_workspace_path(workspace_root, &self.workspace_dir_name())
+    /// Like [`Self::target_path`], but create the directory to claim it.
+    pub(super) fn reserve_target_path(
+        &self,
+        workspace_root: &Path,
+        layout: PathLayout<'_>,
+    ) -> std::io::Result<PathBuf> {
+        reserve_target_path(workspace_root, self.branch_trimmed(), layout)
     }
 
     pub(super) fn overlay_visible(&self) -> bool {
@@ -237,7 +295,7 @@ impl AddWorktreeState {
                     Suggestion::LocalBranch(branch) => (branch.clone(), None),
                     Suggestion::RemoteBranch {
                         branch, upstream, ..
-                    } => (branch_dir_name(branch), Some(upstream.clone())),
+                    } => (branch_slug(branch), Some(upstream.clone())),
                 })
         else {
             return false;
@@ -250,6 +308,23 @@ impl AddWorktreeState {
         true
     }
 
+    /// The selected suggestion as typed at a shell prompt: the branch name, or
+    /// the slug a ticket's worktree would get.
+    pub(super) fn selected_text(&self) -> Option<String> {
+        self.selected_suggestion()
+            .map(|suggestion| match suggestion {
+                Suggestion::Ticket(ticket) => ticket.slug(),
+                Suggestion::LocalBranch(branch) | Suggestion::RemoteBranch { branch, .. } => {
+                    branch.clone()
+                }
+            })
+    }
+
+    /// The text typed to filter the suggestions.
+    pub(super) fn query(&self) -> &str {
+        &self.branch
+    }
+
     pub(super) fn backspace(&mut self) {
         self.branch_upstream = None;
         self.branch.pop();
@@ -263,6 +338,17 @@ impl AddWorktreeState {
         self.recompute_filters();
     }
 
+    pub(super) fn filter(&self) -> SuggestionFilter {
+        self.filter
+    }
+
+    /// List only the suggestions `filter` admits, showing the overlay again.
+    pub(super) fn set_filter(&mut self, filter: SuggestionFilter) {
+        self.filter = filter;
+        self.show_overlay = true;
+        self.recompute_filters();
+    }
+
     pub(super) fn toggle_overlay(&mut self) {
         if self.filtered.is_empty() {
             self.show_overlay = false;
@@ -275,6 +361,41 @@ impl AddWorktreeState {
         self.branch_upstream.as_deref()
     }
 
+    /// Before creating a branch that is not local, ask whether to track a remote
+    /// branch that would get the same name (`origin` first). Returns whether
+    /// the question is now being asked.
+    pub(super) fn offer_remote_branch(&mut self) -> bool {
+        if self.branch_exists || self.branch_upstream.is_some() || self.remote_declined {
+            return false;
+        }
+        let wanted = self.normalized_branch();
+        let mut matches = self.remote_branches.iter().filter(|remote| {
+            split_remote_branch(remote).is_some_and(|(_, branch)| branch_slug(&branch) == wanted)
+        });
+        let first = matches.next();
+        let origin = first
+            .into_iter()
+            .chain(matches)
+            .find(|remote| remote.starts_with("origin/"));
+        self.remote_prompt = origin.or(first).cloned();
+        self.remote_prompt.is_some()
+    }
+
+    pub(super) fn is_offering_remote_branch(&self) -> bool {
+        self.remote_prompt.is_some()
+    }
+
+    /// Answer [`Self::offer_remote_branch`]: `Some(true)` tracks the remote
+    /// branch, `Some(false)` creates a new one, `None` goes back to editing.
+    pub(super) fn answer_remote_offer(&mut self, track: Option<bool>) {
+        let remote = self.remote_prompt.take();
+        match track {
+            Some(true) => self.branch_upstream = remote,
+            Some(false) => self.remote_declined = true,
+            None => {}
+        }
+    }
+
     fn rebuild_suggestions(&mut self) {
         self.suggestions.clear();
         self.suggestions
@@ -287,6 +408,10 @@ impl AddWorktreeState {
         );
         for remote in &self.remote_branches {
             if let Some((remote_name, branch_name)) = split_remote_branch(remote) {
+                // A local branch of the same name already covers this remote one.
+                if self.existing_branches.contains(&branch_name) {
+                    continue;
+                }
                 self.suggestions.push(Suggestion::RemoteBranch {
                     remote: remote_name,
                     branch: branch_name,
@@ -303,19 +428,20 @@ impl AddWorktreeState {
     }
 
     fn recompute_filters(&mut self) {
+        self.remote_declined = false;
         let trimmed = self.branch.trim();
         self.branch_exists = !trimmed.is_empty() && self.existing_branches.contains(trimmed);
-        if trimmed.is_empty() {
-            self.filtered = (0..self.suggestions.len()).collect();
-        } else {
-            let query = trimmed.to_lowercase();
-            self.filtered = self
-                .suggestions
-                .iter()
-                .enumerate()
-                .filter_map(|(idx, suggestion)| suggestion.matches(&query).then_some(idx))
-                .collect();
-        }
+        let query = trimmed.to_lowercase();
+        let filter = self.filter;
+        self.filtered = self
+            .suggestions
+            .iter()
+            .enumerate()
+            .filter(|(_, suggestion)| filter.admits(suggestion))
+            .filter_map(|(idx, suggestion)| {
+                (query.is_empty() || suggestion.matches(&query)).then_some(idx)
+            })
+            .collect();
         if self.filtered.is_empty() {
             self.selection = None;
         } else {
@@ -325,15 +451,30 @@ impl AddWorktreeState {
     }
 }
 
+fn sanitize_branch_input(value: &str) -> String {
+    let mut slug: String = value
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '-',
+        })
+        .collect();
+    while slug.contains("--") {
+        slug = slug.replace("--", "-");
+    }
+    slug.trim_matches('-').to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::wtm_paths::branch_dir_name;
+    use crate::wtm_paths::branch_slug;
 
     fn sample_state() -> AddWorktreeState {
         let tickets = vec![JiraTicket {
             key: "PROJ-1".into(),
             summary: "Implement feature".into(),
+            status: None,
         }];
         let local_branches = vec!["feature/local".into()];
         let remote_branches = vec!["origin/feature/widget".into()];
@@ -346,11 +487,15 @@ mod tests {
             remote_branches,
             suggestions: Vec::new(),
             filtered: Vec::new(),
+            filter: SuggestionFilter::All,
             selection: None,
             show_overlay: true,
             existing_branches,
             branch_exists: false,
             branch_upstream: None,
+            remote_prompt: None,
+            remote_declined: false,
+            jira_hint: None,
         };
         state.rebuild_suggestions();
         state.recompute_filters();
@@ -374,6 +519,7 @@ mod tests {
         let ticket = JiraTicket {
             key: "ABC-42".into(),
             summary: "Improve performance".into(),
+            status: None,
         };
         let suggestion = Suggestion::Ticket(ticket);
         assert!(suggestion.matches("abc"));
@@ -401,16 +547,72 @@ mod tests {
         assert_eq!(state.branch_upstream(), Some("origin/feature/widget"));
     }
 
+    #[test]
+    fn accepted_remote_suggestion_is_ready_to_create_from_upstream() {
+        let mut state = sample_state();
+        state.branch = "widget".into();
+        state.recompute_filters();
+        assert!(state.accept_selection());
+
+        // These are exactly what the confirm path reads to pick `add_worktree_from_upstream`.
+        assert_eq!(state.normalized_branch(), "feature-widget");
+        assert!(!state.branch_exists());
+        assert_eq!(state.branch_upstream(), Some("origin/feature/widget"));
+    }
+
+    #[test]
+    fn typing_a_remote_branch_name_offers_to_track_it() {
+        let mut state = sample_state();
+        state.remote_branches.push("fork/feature/widget".into());
+        state.branch = "feature-widget".into();
+        state.recompute_filters();
+        assert!(state.offer_remote_branch());
+        assert!(state
+            .status_line(Path::new("/ws"), PathLayout::default())
+            .contains("exists as origin/feature/widget"));
+        state.answer_remote_offer(Some(true));
+        assert!(!state.is_offering_remote_branch());
+        assert_eq!(state.branch_upstream(), Some("origin/feature/widget"));
+        assert!(!state.offer_remote_branch());
+
+        state.backspace();
+        state.push_char('t');
+        assert!(state.offer_remote_branch());
+        state.answer_remote_offer(Some(false));
+        assert_eq!(state.branch_upstream(), None);
+        assert!(!state.offer_remote_branch(), "declining is remembered");
+
+        state.push_char('s');
+        assert!(
+            !state.offer_remote_branch(),
+            "no remote branch of that name"
+        );
+    }
+
     #[test]
     fn accept_selection_for_ticket_generates_slug() {
         let mut state = sample_state();
         state.selection = Some(0);
         assert!(state.accept_selection());
-        let expected = branch_dir_name("PROJ-1 Implement feature");
+        let expected = branch_slug("PROJ-1 Implement feature");
         assert_eq!(state.branch_trimmed(), expected);
         assert_eq!(state.branch_upstream(), None);
     }
 
+    #[test]
+    fn remote_branch_with_local_counterpart_is_suggested_once() {
+        let mut state = sample_state();
+        state.local_branches.push("feature/widget".into());
+        state.existing_branches.insert("feature/widget".into());
+        state.rebuild_suggestions();
+        state.branch = "widget".into();
+        state.recompute_filters();
+
+        let matches: Vec<&Suggestion> = state.filtered_suggestions().collect();
+        assert_eq!(matches.len(), 1);
+        assert!(matches!(matches[0], Suggestion::LocalBranch(name) if name == "feature/widget"));
+    }
+
     #[test]
     fn recompute_filters_filters_by_query() {
         let mut state = sample_state();
@@ -456,6 +658,36 @@ mod tests {
         assert_eq!(state.selected_filtered_index(), Some(3));
     }
 
+    #[test]
+    fn suggestion_filter_restricts_filtered_to_the_chosen_kinds() {
+        let mut state = sample_state();
+        assert_eq!(state.filtered_suggestions().count(), 3);
+
+        state.set_filter(SuggestionFilter::Jira);
+        assert!(state
+            .filtered_suggestions()
+            .all(|suggestion| matches!(suggestion, Suggestion::Ticket(_))));
+        assert_eq!(state.filtered_suggestions().count(), 1);
+
+        state.set_filter(SuggestionFilter::Branches);
+        assert!(state.filtered_suggestions().all(|suggestion| matches!(
+            suggestion,
+            Suggestion::LocalBranch(_) | Suggestion::RemoteBranch { .. }
+        )));
+        assert_eq!(state.filtered_suggestions().count(), 2);
+
+        state.branch = "widget".into();
+        state.recompute_filters();
+        assert_eq!(state.filtered_suggestions().count(), 1);
+        state.set_filter(SuggestionFilter::Jira);
+        assert_eq!(state.filtered_suggestions().count(), 0);
+        assert_eq!(state.selected_filtered_index(), None);
+
+        state.set_filter(SuggestionFilter::All);
+        assert_eq!(state.filter().label(), None);
+        assert_eq!(state.filtered_suggestions().count(), 1);
+    }
+
     #[test]
     fn branch_exists_detects_local_match() {
         let mut state = sample_state();
@@ -464,16 +696,3 @@ mod tests {
         assert!(state.branch_exists());
     }
 }
-fn sanitize_branch_input(value: &str) -> String {
-    let mut slug: String = value
-        .chars()
-        .map(|c| match c {
-            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
-            _ => '-',
-        })
-        .collect();
-    while slug.contains("--") {
-        slug = slug.replace("--", "-");
-    }
-    slug.trim_matches('-').to_string()
-}
@@ -0,0 +1,133 @@
+//! Detects edits to the `.wtm` and global config files so the dashboard can
+//! reload them live.
+
+use crate::config;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How often the config file's modification time is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Identifies one version of a config layer: which file is in use and when it was written.
+type LayerStamp = Option<(PathBuf, SystemTime)>;
+
+/// The global layer's stamp, then the repository's.
+type ConfigStamp = [LayerStamp; 2];
+
+/// Polls the active config files' mtimes and reports when one changes.
+///
+/// Only the top-level files are watched; edits to files they include are
+/// picked up the next time a top-level file changes.
+pub(super) struct ConfigWatcher {
+    global_dir: Option<PathBuf>,
+    stamp: ConfigStamp,
+    last_poll: Instant,
+}
+
+impl ConfigWatcher {
+    pub(super) fn new(wtm_dir: &Path) -> Self {
+        Self::with_global_dir(wtm_dir, config::global_config_dir())
+    }
+
+    fn with_global_dir(wtm_dir: &Path, global_dir: Option<PathBuf>) -> Self {
+        Self {
+            stamp: current_stamp(global_dir.as_deref(), wtm_dir),
+            global_dir,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Whether the config changed since the last call, checking at most once per interval.
+    pub(super) fn poll(&mut self, wtm_dir: &Path, now: Instant) -> bool {
+        if now.duration_since(self.last_poll) < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = now;
+        self.observe(current_stamp(self.global_dir.as_deref(), wtm_dir))
+    }
+
+    /// Record `stamp`, returning `true` when it differs from the previous one.
+    fn observe(&mut self, stamp: ConfigStamp) -> bool {
+        if stamp == self.stamp {
+            return false;
+        }
+        self.stamp = stamp;
+        true
+    }
+}
+
+fn current_stamp(global_dir: Option<&Path>, wtm_dir: &Path) -> ConfigStamp {
+    [global_dir.and_then(layer_stamp), layer_stamp(wtm_dir)]
+}
+
+fn layer_stamp(dir: &Path) -> LayerStamp {
+    let path = config::config_path(dir)?;
+    let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+    Some((path, modified))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn set_mtime(path: &Path, secs: u64) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn poll_reports_mtime_changes_once() {
+        let dir = tempdir().unwrap();
+        let config = dir.path().join("config.json");
+        fs::write(&config, "{}").unwrap();
+        set_mtime(&config, 1_000);
+
+        let mut watcher = ConfigWatcher::with_global_dir(dir.path(), None);
+        let start = Instant::now();
+        let later = start + POLL_INTERVAL;
+        assert!(!watcher.poll(dir.path(), later));
+
+        set_mtime(&config, 2_000);
+        assert!(!watcher.poll(dir.path(), later), "polls are throttled");
+        assert!(watcher.poll(dir.path(), later + POLL_INTERVAL));
+        assert!(!watcher.poll(dir.path(), later + POLL_INTERVAL * 2));
+    }
+
+    #[test]
+    fn poll_notices_config_created_or_removed() {
+        let dir = tempdir().unwrap();
+        let mut watcher = ConfigWatcher::with_global_dir(dir.path(), None);
+        let start = Instant::now();
+
+        fs::write(dir.path().join("config.toml"), "").unwrap();
+        assert!(watcher.poll(dir.path(), start + POLL_INTERVAL));
+
+        fs::remove_file(dir.path().join("config.toml")).unwrap();
+        assert!(watcher.poll(dir.path(), start + POLL_INTERVAL * 2));
+    }
+
+    #[test]
+    fn poll_notices_global_config_edits() {
+        let repo = tempdir().unwrap();
+        let global = tempdir().unwrap();
+        let global_config = global.path().join("config.json");
+        fs::write(&global_config, "{}").unwrap();
+        set_mtime(&global_config, 1_000);
+
+        let mut watcher =
+            ConfigWatcher::with_global_dir(repo.path(), Some(global.path().to_path_buf()));
+        let start = Instant::now();
+        assert!(!watcher.poll(repo.path(), start + POLL_INTERVAL));
+
+        set_mtime(&global_config, 2_000);
+        assert!(watcher.poll(repo.path(), start + POLL_INTERVAL * 2));
+    }
+}
@@ -1,13 +1,22 @@
 use crate::{
     docker,
-    git::{status, WorktreeInfo},
+    git::{self, status, AheadBehind, WorktreeInfo},
+    jira::{self, JiraTicket},
 };
 use status::GitStatusSummary;
 
 #[derive(Debug, Default, Clone)]
 pub(super) struct WorkspaceContext {
+    /// First line of the worktree's note, if it has one.
+    pub(super) note: Option<String>,
+    /// The Jira ticket named in the branch, as found in the ticket cache.
+    pub(super) jira: Option<String>,
     pub(super) git: Vec<String>,
     pub(super) docker: Vec<String>,
+    /// Compose service names, for the `l` logs picker.
+    pub(super) services: Vec<String>,
+    /// The compose CLI that listed `services`, to follow their logs with.
+    pub(super) compose_backend: Option<docker::ComposeBackend>,
     pub(super) errors: Vec<String>,
 }
 
@@ -17,7 +26,33 @@ impl WorkspaceContext {
     }
 }
 
-pub(super) fn gather_workspace_context(info: &WorktreeInfo) -> WorkspaceContext {
+/// Picking one of the context panel's compose services to follow its logs.
+#[derive(Debug)]
+pub(super) struct ServicePicker {
+    pub(super) services: Vec<String>,
+    pub(super) selected: usize,
+}
+
+impl ServicePicker {
+    pub(super) fn move_up(&mut self) {
+        let len = self.services.len();
+        if len > 0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+
+    pub(super) fn move_down(&mut self) {
+        let len = self.services.len();
+        if len > 0 {
+            self.selected = (self.selected + 1) % len;
+        }
+    }
+}
+
+pub(super) fn gather_workspace_context(
+    info: &WorktreeInfo,
+    docker_options: &docker::DockerOptions,
+) -> WorkspaceContext {
     let mut context = WorkspaceContext::default();
     context.git.push(format!("Path: {}", info.path.display()));
 
@@ -46,18 +81,36 @@ pub(super) fn gather_workspace_context(info: &WorktreeInfo) -> WorkspaceContext
     }
 
     match status::status(info.path()) {
-        Ok(summary) => append_git_status(&mut context, &summary),
+        Ok(summary) => {
+            let detail = if summary.upstream.is_some() && !summary.upstream_gone {
+                git::ahead_behind_detail(info.path())
+                    .map_err(|err| log::debug!("ahead/behind check failed: {err:#}"))
+                    .ok()
+            } else {
+                None
+            };
+            append_git_status(&mut context, &summary, detail.as_ref());
+            if let Some(detail) = &detail {
+                append_divergence(&mut context, detail);
+            }
+        }
         Err(err) => context.add_error(format!("git status unavailable: {err}")),
     }
 
-    match docker::compose_ps(info.path()) {
-        Ok(containers) => {
+    match docker::compose_ps_backend(info.path(), docker_options) {
+        Ok((backend, containers)) => {
+            context.compose_backend = Some(backend);
             if containers.is_empty() {
                 context
                     .docker
                     .push("No docker compose services detected.".into());
             } else {
                 for container in containers {
+                    if !container.service.is_empty()
+                        && !context.services.contains(&container.service)
+                    {
+                        context.services.push(container.service.clone());
+                    }
                     let label = if container.service.is_empty() {
                         container.name.clone()
                     } else if container.name.is_empty() || container.service == container.name {
@@ -80,12 +133,39 @@ pub(super) fn gather_workspace_context(info: &WorktreeInfo) -> WorkspaceContext
     context
 }
 
-fn append_git_status(context: &mut WorkspaceContext, summary: &GitStatusSummary) {
+/// `PROJ-123 — Summary (Status)` for the ticket named in `branch`, from the
+/// cached `tickets` only, or a hint to refresh the cache when it is not there.
+pub(super) fn jira_line(branch: Option<&str>, tickets: Option<&[JiraTicket]>) -> Option<String> {
+    let key = jira::ticket_key_in_any_case(branch?)?;
+    let ticket = tickets
+        .unwrap_or_default()
+        .iter()
+        .find(|ticket| ticket.key.eq_ignore_ascii_case(&key));
+    Some(match ticket {
+        Some(ticket) => ticket.headline(),
+        None => format!("{key} — not in the ticket cache (a, then Ctrl+R to refresh)"),
+    })
+}
+
+/// Most commits drawn per side of the graph under the ahead/behind sentence.
+const GRAPH_MAX_COMMITS: u32 = 8;
+
+fn append_git_status(
+    context: &mut WorkspaceContext,
+    summary: &GitStatusSummary,
+    detail: Option<&AheadBehind>,
+) {
     if let Some(upstream) = summary.upstream.as_deref() {
-        context.git.push(format!("Upstream: {upstream}"));
+        if summary.upstream_gone {
+            context.git.push(format!("Upstream: {upstream} (gone)"));
+        } else {
+            context.git.push(format!("Upstream: {upstream}"));
+        }
     }
 
-    if summary.ahead > 0 || summary.behind > 0 {
+    if let Some(detail) = detail {
+        context.git.extend(ahead_behind_lines(detail));
+    } else if summary.ahead > 0 || summary.behind > 0 {
         context.git.push(format!(
             "Ahead {} • Behind {}",
             summary.ahead, summary.behind
@@ -99,3 +179,114 @@ fn append_git_status(context: &mut WorkspaceContext, summary: &GitStatusSummary)
         summary.staged, summary.unstaged, summary.untracked, summary.conflicts
     ));
 }
+
+/// A sentence naming the counts and the common base, then a small graph of
+/// both sides branching off it. Lines start unindented because the panel trims
+/// leading whitespace when wrapping.
+fn ahead_behind_lines(detail: &AheadBehind) -> Vec<String> {
+    let base: String = detail.merge_base.chars().take(7).collect();
+    let upstream = &detail.upstream;
+    let sentence = match (detail.ahead, detail.behind) {
+        (0, 0) => return vec![format!("In sync with {upstream}")],
+        (ahead, 0) => format!("Your branch is {} ahead of {upstream}", commits(ahead)),
+        (0, behind) => format!("Your branch is {} behind {upstream}", commits(behind)),
+        (ahead, behind) => format!(
+            "Your branch is {} ahead of and {behind} behind {upstream}",
+            commits(ahead)
+        ),
+    };
+    vec![
+        format!("{sentence}; common base {base}"),
+        format!("◆ {base} (base)"),
+        format!("├─{} HEAD", graph_dots(detail.ahead)),
+        format!("└─{} {upstream}", graph_dots(detail.behind)),
+    ]
+}
+
+fn commits(count: u32) -> String {
+    if count == 1 {
+        "1 commit".into()
+    } else {
+        format!("{count} commits")
+    }
+}
+
+fn graph_dots(count: u32) -> String {
+    let mut dots = "●".repeat(count.min(GRAPH_MAX_COMMITS) as usize);
+    if count > GRAPH_MAX_COMMITS {
+        dots.push('…');
+    }
+    dots
+}
+
+/// Warn when local and upstream both have commits the other lacks, which
+/// usually means the upstream was rewritten (rebased and force-pushed).
+fn append_divergence(context: &mut WorkspaceContext, detail: &AheadBehind) {
+    if detail.ahead > 0 && detail.behind > 0 {
+        context.git.push(format!(
+            "⚠ Diverged from upstream: {} local and {} upstream commits since the merge base (rewritten upstream?)",
+            detail.ahead, detail.behind
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detail(ahead: u32, behind: u32) -> AheadBehind {
+        AheadBehind {
+            upstream: "origin/main".into(),
+            ahead,
+            behind,
+            merge_base: "4b825dc642cb6eb9a060e54bf8d69288fbee4904".into(),
+        }
+    }
+
+    #[test]
+    fn jira_line_comes_from_the_cache() {
+        let tickets = [JiraTicket {
+            key: "PROJ-123".into(),
+            summary: "Fix login".into(),
+            status: Some("In Progress".into()),
+        }];
+        assert_eq!(
+            jira_line(Some("proj-123-fix-login"), Some(&tickets)).as_deref(),
+            Some("PROJ-123 — Fix login (In Progress)")
+        );
+        assert_eq!(
+            jira_line(Some("PROJ-7-other"), Some(&tickets)).as_deref(),
+            Some("PROJ-7 — not in the ticket cache (a, then Ctrl+R to refresh)")
+        );
+        assert!(jira_line(Some("PROJ-123"), None)
+            .unwrap()
+            .contains("not in the ticket cache"));
+        assert_eq!(jira_line(Some("fix-login"), Some(&tickets)), None);
+        assert_eq!(jira_line(None, Some(&tickets)), None);
+    }
+
+    #[test]
+    fn ahead_behind_is_described_and_drawn() {
+        assert_eq!(
+            ahead_behind_lines(&detail(3, 2)),
+            [
+                "Your branch is 3 commits ahead of and 2 behind origin/main; common base 4b825dc",
+                "◆ 4b825dc (base)",
+                "├─●●● HEAD",
+                "└─●● origin/main",
+            ]
+        );
+        assert_eq!(
+            ahead_behind_lines(&detail(1, 0))[0],
+            "Your branch is 1 commit ahead of origin/main; common base 4b825dc"
+        );
+        assert_eq!(
+            ahead_behind_lines(&detail(0, 12))[3],
+            "└─●●●●●●●●… origin/main"
+        );
+        assert_eq!(
+            ahead_behind_lines(&detail(0, 0)),
+            ["In sync with origin/main"]
+        );
+    }
+}
@@ -29,7 +29,7 @@ impl FxController {
     /// Capture the duration since the previous frame so effects can advance smoothly.
     pub fn begin_frame(&mut self) {
         let now = Instant::now();
-        self.frame_delta = now.saturating_duration_since(self.last_frame).into();
+        self.frame_delta = now.saturating_duration_since(self.last_frame);
         self.last_frame = now;
     }
 
@@ -46,6 +46,11 @@ impl FxController {
         self.status_effect = Some(status_flash_effect());
     }
 
+    /// Whether an effect still needs frames to finish.
+    pub fn is_animating(&self) -> bool {
+        self.context_effect.is_some() || self.status_effect.is_some()
+    }
+
     pub fn render_context(&mut self, frame: &mut Frame<'_>, area: Rect) {
         if !self.context_visible {
             return;
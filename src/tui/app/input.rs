@@ -1,27 +1,376 @@
-use super::{add_worktree::AddWorktreeState, workspace::QuickActionState, App, Mode};
+use super::{
+    add_worktree::{AddWorktreeState, SuggestionFilter},
+    context::ServicePicker,
+    log_view::{LogState, LOG_LIMIT},
+    notes_view::NotesState,
+    palette::{Action, PaletteState},
+    recent::RecentPicker,
+    terminal_search::{self, TerminalSearch},
+    workspace::{QuickActionState, RemoveWorktreeState},
+    App, Mode,
+};
 use crate::{
-    git,
-    wtm_paths::{ensure_workspace_root, next_available_workspace_path},
+    clipboard,
+    config::QuickAction,
+    docker, git,
+    lock::WtmLock,
+    notes,
+    runner::shell_join,
+    workspace::{create_workspace, delete_workspace, plan_clone_current, Upstream},
+    wtm_paths::{branch_dir_name, ensure_workspace_root},
 };
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::layout::Rect;
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 const SCROLL_LINES_PER_TICK: isize = 3;
 
+/// Directory inside `.wtm/` that saved terminal output goes to.
+const LOGS_DIR: &str = "logs";
+
 pub(super) fn handle_key(app: &mut App, key: KeyEvent) -> Result<()> {
     match app.mode {
         Mode::Navigation => handle_navigation_key(app, key),
         Mode::TerminalInput => handle_terminal_key(app, key),
+        Mode::InsertBranch => handle_insert_branch_key(app, key),
         Mode::Adding => handle_add_worktree_key(app, key),
         Mode::Removing => handle_remove_worktree_key(app, key),
         Mode::QuickActions => handle_quick_actions_key(app, key),
+        Mode::Palette => handle_palette_key(app, key),
         Mode::Help => {
             if matches!(key.code, KeyCode::Esc | KeyCode::Char('?')) {
                 app.mode = Mode::Navigation;
             }
             Ok(())
         }
+        Mode::Log => {
+            handle_log_key(app, key);
+            Ok(())
+        }
+        Mode::Notes => {
+            handle_notes_key(app, key);
+            Ok(())
+        }
+        Mode::Recent => {
+            handle_recent_key(app, key);
+            Ok(())
+        }
+        Mode::TerminalSearch => {
+            handle_terminal_search_key(app, key);
+            Ok(())
+        }
+        Mode::ServiceLogs => handle_service_logs_key(app, key),
+    }
+}
+
+/// Type a query and Enter to jump to the newest match; then n/N step to older
+/// and newer matches and `/` starts over. Esc leaves the view where it is.
+fn handle_terminal_search_key(app: &mut App, key: KeyEvent) {
+    let Some(search) = app.terminal_search.as_mut() else {
+        app.mode = Mode::Navigation;
+        return;
+    };
+    if search.is_editing() {
+        match key.code {
+            KeyCode::Esc => {}
+            KeyCode::Enter if search.query().is_empty() => {}
+            KeyCode::Enter => return step_terminal_search(app, true),
+            KeyCode::Backspace => {
+                search.backspace();
+                let prompt = format!("/{}", search.query());
+                return app.set_status(prompt);
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                search.push_char(c);
+                let prompt = format!("/{}", search.query());
+                return app.set_status(prompt);
+            }
+            _ => return,
+        }
+    } else {
+        match key.code {
+            KeyCode::Char('n') => return step_terminal_search(app, true),
+            KeyCode::Char('N') => return step_terminal_search(app, false),
+            KeyCode::Char('/') => {
+                search.restart();
+                return app.set_status("/");
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {}
+            _ => return,
+        }
+    }
+    app.terminal_search = None;
+    app.mode = Mode::Navigation;
+    app.clear_status();
+}
+
+/// Scroll the active tab to the next older (or newer) match.
+fn step_terminal_search(app: &mut App, older: bool) {
+    let Some(search) = app.terminal_search.as_mut() else {
+        return;
+    };
+    let Some(tab) = app
+        .workspaces
+        .get_mut(app.selected_workspace)
+        .and_then(|ws| ws.active_tab_mut())
+    else {
+        return app.set_status("No terminal to search.");
+    };
+    let Some((lines, history_len)) = tab.text_lines() else {
+        return app.set_status("Terminal unavailable.");
+    };
+    let query = search.query().to_string();
+    let total = terminal_search::find_matches(&lines, &query).len();
+    let Some((found, number)) = search.step(&lines, older) else {
+        return app.set_status(format!(
+            "No matches for \"{query}\" • /: search again • Esc: done"
+        ));
+    };
+    let rows = usize::from(tab.size().rows);
+    tab.set_scrollback(terminal_search::scrollback_offset(
+        found.line,
+        history_len,
+        rows,
+    ));
+    app.set_status(format!(
+        "Match {number}/{total} for \"{query}\" • n: older • N: newer • /: search again • Esc: done"
+    ));
+}
+
+/// ↑/↓ and Enter, or a digit, pick a worktree from the recent list.
+fn handle_recent_key(app: &mut App, key: KeyEvent) {
+    let indices = app.recent_indices();
+    let picker = app.recent_picker.get_or_insert_with(RecentPicker::default);
+    let chosen = match key.code {
+        KeyCode::Esc | KeyCode::Char('r') => None,
+        KeyCode::Up => {
+            picker.move_up(indices.len());
+            return;
+        }
+        KeyCode::Down => {
+            picker.move_down(indices.len());
+            return;
+        }
+        KeyCode::Enter => indices.get(picker.selected).copied(),
+        KeyCode::Char(c @ '1'..='9') => match indices.get(c as usize - '1' as usize) {
+            Some(&index) => Some(index),
+            None => return,
+        },
+        _ => return,
+    };
+    if let Some(index) = chosen {
+        app.set_selected_workspace(index);
+    }
+    app.recent_picker = None;
+    app.mode = Mode::Navigation;
+}
+
+/// ↑/↓ and Enter pick the compose service whose logs open in a new tab.
+fn handle_service_logs_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    let Some(picker) = app.service_picker.as_mut() else {
+        app.mode = Mode::Navigation;
+        return Ok(());
+    };
+    let service = match key.code {
+        KeyCode::Up => {
+            picker.move_up();
+            return Ok(());
+        }
+        KeyCode::Down => {
+            picker.move_down();
+            return Ok(());
+        }
+        KeyCode::Enter => picker.services.get(picker.selected).cloned(),
+        KeyCode::Esc | KeyCode::Char('l') => None,
+        _ => return Ok(()),
+    };
+    app.service_picker = None;
+    app.mode = Mode::Navigation;
+    match service {
+        Some(service) => follow_service_logs(app, &service),
+        None => Ok(()),
+    }
+}
+
+/// List the selected worktree's compose services, loading its context first
+/// if the panel has not yet.
+fn open_service_picker(app: &mut App) {
+    let Some(path) = app
+        .workspaces
+        .get(app.selected_workspace)
+        .map(|ws| ws.path().to_path_buf())
+    else {
+        app.set_status("No workspace selected.");
+        return;
+    };
+    if !app.workspace_contexts.contains_key(&path) {
+        app.refresh_context_for_selected();
+    }
+    let services = app
+        .workspace_contexts
+        .get(&path)
+        .map(|context| context.services.clone())
+        .unwrap_or_default();
+    if services.is_empty() {
+        app.set_status("No docker compose services in this worktree.");
+        return;
+    }
+    app.service_picker = Some(ServicePicker {
+        services,
+        selected: 0,
+    });
+    app.mode = Mode::ServiceLogs;
+    app.clear_status();
+}
+
+/// Open a tab running `docker compose logs --follow <service>` in the worktree.
+/// Uses the compose CLI that listed the services, `docker compose` if unknown.
+fn follow_service_logs(app: &mut App, service: &str) -> Result<()> {
+    let backend = app
+        .workspaces
+        .get(app.selected_workspace)
+        .and_then(|ws| app.workspace_contexts.get(ws.path()))
+        .and_then(|context| context.compose_backend)
+        .unwrap_or(docker::ComposeBackend::Plugin);
+    let action = QuickAction {
+        label: format!("logs {service}"),
+        command: shell_join(&docker::logs_command(backend, &app.docker, service, true)),
+    };
+    if let Some(ws) = app.workspaces.get_mut(app.selected_workspace) {
+        let size = app.terminal_view_size.unwrap_or(app.terminal_size);
+        ws.spawn_quick_action_tab(&mut app.next_tab_id, size, &action)?;
+        app.clear_status();
+    }
+    Ok(())
+}
+
+/// Edit the note; Esc saves it and closes the overlay.
+fn handle_notes_key(app: &mut App, key: KeyEvent) {
+    if key.code == KeyCode::Esc {
+        close_notes(app);
+        return;
+    }
+    let Some(state) = app.notes_state.as_mut() else {
+        app.mode = Mode::Navigation;
+        return;
+    };
+    match key.code {
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => state.insert(c),
+        KeyCode::Enter => state.newline(),
+        KeyCode::Backspace => state.backspace(),
+        KeyCode::Left => state.move_left(),
+        KeyCode::Right => state.move_right(),
+        KeyCode::Up => state.move_vertical(-1),
+        KeyCode::Down => state.move_vertical(1),
+        _ => {}
+    }
+}
+
+/// Copy the selected worktree's path with `set`, or show it in the status line
+/// when the clipboard is unavailable.
+pub(super) fn copy_selected_path(app: &mut App, set: impl FnOnce(&str) -> Result<()>) {
+    let Some(ws) = app.workspaces.get(app.selected_workspace) else {
+        app.set_status("No workspace selected.");
+        return;
+    };
+    let path = ws.path().display().to_string();
+    match set(&path) {
+        Ok(()) => app.set_status(format!("Copied {path}")),
+        Err(err) => app.set_status(format!("{path} (not copied: {err:#})")),
+    }
+}
+
+/// Save the active tab's scrollback and screen to
+/// `.wtm/logs/<worktree>-<unix-seconds>.log`.
+fn export_active_tab(app: &mut App) {
+    let logs_dir = app.wtm_dir().join(LOGS_DIR);
+    let max_slug_length = app.max_slug_length;
+    let Some(ws) = app.workspaces.get_mut(app.selected_workspace) else {
+        return app.set_status("No workspace selected.");
+    };
+    let name = branch_dir_name(&ws.info().name(), max_slug_length);
+    let Some(tab) = ws.active_tab_mut() else {
+        return app.set_status("No terminal to save.");
+    };
+    let contents = tab.dump_contents();
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let path = logs_dir.join(format!("{name}-{secs}.log"));
+    let saved = fs::create_dir_all(&logs_dir).and_then(|()| fs::write(&path, contents));
+    match saved {
+        Ok(()) => app.set_status(format!("Saved terminal output to {}", path.display())),
+        Err(err) => app.set_status(format!("Failed to save {}: {err}", path.display())),
+    }
+}
+
+/// Open the notes overlay for the selected worktree.
+fn show_selected_notes(app: &mut App) {
+    let Some(ws) = app.workspaces.get(app.selected_workspace) else {
+        app.set_status("No workspace selected.");
+        return;
+    };
+    let path = notes::note_path(&app.repo_root, ws.path());
+    match notes::load(&path) {
+        Ok(text) => {
+            app.notes_state = Some(NotesState::new(ws.info().name(), path, &text));
+            app.mode = Mode::Notes;
+            app.clear_status();
+        }
+        Err(err) => app.set_status(format!("Failed to read note: {err:#}")),
+    }
+}
+
+fn close_notes(app: &mut App) {
+    app.mode = Mode::Navigation;
+    let Some(state) = app.notes_state.take() else {
+        return;
+    };
+    match notes::save(state.path(), &state.text()) {
+        Ok(()) => {
+            app.set_status(format!("Saved note for {}", state.title()));
+            app.refresh_context_for_selected();
+        }
+        Err(err) => app.set_status(format!("Failed to save note: {err:#}")),
+    }
+}
+
+fn handle_log_key(app: &mut App, key: KeyEvent) {
+    let delta = match key.code {
+        KeyCode::Esc | KeyCode::Char('g') | KeyCode::Char('q') => {
+            app.log_state = None;
+            app.mode = Mode::Navigation;
+            return;
+        }
+        KeyCode::Up | KeyCode::Char('k') => -1,
+        KeyCode::Down | KeyCode::Char('j') => 1,
+        KeyCode::PageUp => -10,
+        KeyCode::PageDown => 10,
+        _ => return,
+    };
+    if let Some(state) = app.log_state.as_mut() {
+        state.scroll_by(delta);
+    }
+}
+
+/// Open the log overlay with the selected worktree's recent commits.
+fn show_selected_log(app: &mut App) {
+    let Some(ws) = app.workspaces.get(app.selected_workspace) else {
+        app.set_status("No workspace selected.");
+        return;
+    };
+    let title = ws.info().name();
+    match git::recent_commits(ws.path(), LOG_LIMIT) {
+        Ok(commits) => {
+            app.log_state = Some(LogState::new(title, commits));
+            app.mode = Mode::Log;
+            app.clear_status();
+        }
+        Err(err) => app.set_status(format!("Failed to read git log: {err}")),
     }
 }
 
@@ -152,7 +501,7 @@ fn point_in_rect(rect: Rect, column: u16, row: u16) -> bool {
 
 fn set_add_status(app: &mut App, extra: Option<String>) {
     if let Some(state) = app.add_state.as_ref() {
-        let mut status = state.status_line(&app.workspace_root);
+        let mut status = state.status_line(&app.workspace_root, app.path_layout());
         if let Some(extra) = extra {
             if !extra.is_empty() {
                 status = format!("{extra} | {status}");
@@ -163,53 +512,96 @@ fn set_add_status(app: &mut App, extra: Option<String>) {
 }
 
 fn handle_navigation_key(app: &mut App, key: KeyEvent) -> Result<()> {
-    match key.code {
-        KeyCode::Char('q') => app.should_quit = true,
-        KeyCode::Up => {
-            if !app.workspaces.is_empty() {
-                let len = app.workspaces.len();
-                let new_index = if app.selected_workspace == 0 {
-                    len - 1
-                } else {
-                    app.selected_workspace - 1
-                };
-                app.set_selected_workspace(new_index);
-            }
+    match navigation_action(key) {
+        Some(action) => perform_action(app, action),
+        None => Ok(()),
+    }
+}
+
+/// Map a navigation-mode key press to the action it triggers.
+fn navigation_action(key: KeyEvent) -> Option<Action> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return match key.code {
+            KeyCode::Char('p') | KeyCode::Char('P') => Some(Action::CommandPalette),
+            _ => None,
+        };
+    }
+    let action = match key.code {
+        KeyCode::Char('q') => Action::Quit,
+        KeyCode::Up => Action::PrevWorkspace,
+        KeyCode::Down => Action::NextWorkspace,
+        KeyCode::Left => Action::PrevTab,
+        KeyCode::Right => Action::NextTab,
+        KeyCode::Char('n') => Action::NewTab,
+        KeyCode::Char('x') => Action::CloseTab,
+        KeyCode::Char('i') => Action::ToggleContext,
+        KeyCode::Enter => Action::FocusTerminal,
+        KeyCode::Char('a') => Action::AddWorktree,
+        KeyCode::Char('C') => Action::CloneWorktree,
+        KeyCode::Char('p') => Action::PruneWorktree,
+        KeyCode::Char('g') => Action::ShowLog,
+        KeyCode::Char('m') => Action::EditNotes,
+        KeyCode::Char('y') => Action::CopyPath,
+        KeyCode::Char('h') => Action::JumpHere,
+        KeyCode::Tab => Action::LastWorkspace,
+        KeyCode::Char('r') => Action::RecentWorkspaces,
+        KeyCode::Char('?') => Action::Help,
+        KeyCode::Char('c') => Action::QuickActions,
+        KeyCode::Char(':') => Action::CommandPalette,
+        KeyCode::Char('/') => Action::SearchTerminal,
+        KeyCode::Char('e') => Action::ExportScrollback,
+        KeyCode::Char('l') => Action::FollowServiceLogs,
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// Execute an action; shared by key bindings and the command palette.
+pub(super) fn perform_action(app: &mut App, action: Action) -> Result<()> {
+    match action {
+        Action::Quit => app.should_quit = true,
+        Action::PrevWorkspace if !app.workspaces.is_empty() => {
+            let len = app.workspaces.len();
+            let new_index = if app.selected_workspace == 0 {
+                len - 1
+            } else {
+                app.selected_workspace - 1
+            };
+            app.set_selected_workspace(new_index);
         }
-        KeyCode::Down => {
-            if !app.workspaces.is_empty() {
-                let len = app.workspaces.len();
-                let new_index = (app.selected_workspace + 1) % len;
-                app.set_selected_workspace(new_index);
-            }
+        Action::NextWorkspace if !app.workspaces.is_empty() => {
+            let len = app.workspaces.len();
+            let new_index = (app.selected_workspace + 1) % len;
+            app.set_selected_workspace(new_index);
         }
-        KeyCode::Left => {
+        Action::PrevWorkspace | Action::NextWorkspace => {}
+        Action::PrevTab => {
             if let Some(ws) = app.workspaces.get_mut(app.selected_workspace) {
                 ws.select_prev_tab();
             }
         }
-        KeyCode::Right => {
+        Action::NextTab => {
             if let Some(ws) = app.workspaces.get_mut(app.selected_workspace) {
                 ws.select_next_tab();
             }
         }
-        KeyCode::Char('n') => {
+        Action::NewTab => {
             if let Some(ws) = app.workspaces.get_mut(app.selected_workspace) {
                 let size = app.terminal_view_size.unwrap_or(app.terminal_size);
                 ws.spawn_tab(&mut app.next_tab_id, size)?;
                 app.clear_status();
             }
         }
-        KeyCode::Char('x') => {
+        Action::CloseTab => {
             if let Some(ws) = app.workspaces.get_mut(app.selected_workspace) {
                 ws.close_active_tab()?;
                 app.clear_status();
             }
         }
-        KeyCode::Char('i') => {
+        Action::ToggleContext => {
             app.toggle_context_panel();
         }
-        KeyCode::Enter => {
+        Action::FocusTerminal => {
             if let Some(ws) = app.workspaces.get(app.selected_workspace) {
                 if ws.has_tabs() {
                     app.mode = Mode::TerminalInput;
@@ -217,7 +609,21 @@ fn handle_navigation_key(app: &mut App, key: KeyEvent) -> Result<()> {
                 }
             }
         }
-        KeyCode::Char('a') => match AddWorktreeState::new(&app.repo_root) {
+        Action::SearchTerminal => {
+            let has_tabs = app
+                .workspaces
+                .get(app.selected_workspace)
+                .is_some_and(|ws| ws.has_tabs());
+            if has_tabs {
+                app.terminal_search = Some(TerminalSearch::new());
+                app.mode = Mode::TerminalSearch;
+                app.set_status("/");
+            } else {
+                app.set_status("No terminal to search.");
+            }
+        }
+        Action::ExportScrollback => export_active_tab(app),
+        Action::AddWorktree => match AddWorktreeState::new(&app.repo_root) {
             Ok((state, warning)) => {
                 app.mode = Mode::Adding;
                 app.add_state = Some(state);
@@ -227,22 +633,45 @@ fn handle_navigation_key(app: &mut App, key: KeyEvent) -> Result<()> {
                 app.set_status(format!("Failed to prepare add workflow: {err}"));
             }
         },
-        KeyCode::Char('p') => {
+        Action::CloneWorktree => clone_selected_worktree(app)?,
+        Action::ShowLog => show_selected_log(app),
+        Action::EditNotes => show_selected_notes(app),
+        Action::CopyPath => copy_selected_path(app, clipboard::set),
+        Action::JumpHere => match app.here_index() {
+            Some(index) => {
+                app.set_selected_workspace(index);
+                app.clear_status();
+            }
+            None => app.set_status("wtm was not launched from inside a worktree."),
+        },
+        Action::LastWorkspace => app.switch_to_previous_workspace(),
+        Action::FollowServiceLogs => open_service_picker(app),
+        Action::RecentWorkspaces => {
+            // Start on the previous worktree, as Tab would.
+            let selected = usize::from(app.recent_indices().len() > 1);
+            app.recent_picker = Some(RecentPicker { selected });
+            app.mode = Mode::Recent;
+        }
+        Action::PruneWorktree => {
             if let Some(ws) = app.workspaces.get(app.selected_workspace) {
                 if ws.is_primary(&app.repo_root) {
                     app.set_status("Cannot prune the primary worktree.");
+                } else if ws.info().is_prunable {
+                    app.mode = Mode::Removing;
+                    app.remove_state = Some(RemoveWorktreeState::stale(ws.path()));
+                    app.clear_status();
                 } else {
                     app.mode = Mode::Removing;
-                    app.remove_state = Some(super::workspace::RemoveWorktreeState::new(ws.path()));
+                    app.remove_state = Some(RemoveWorktreeState::new(ws.path()));
                     app.clear_status();
                 }
             }
         }
-        KeyCode::Char('?') => {
+        Action::Help => {
             app.mode = Mode::Help;
             app.clear_status();
         }
-        KeyCode::Char('c') => {
+        Action::QuickActions => {
             if app.quick_actions.is_empty() {
                 app.set_status("No quick actions configured.");
             } else {
@@ -253,6 +682,100 @@ fn handle_navigation_key(app: &mut App, key: KeyEvent) -> Result<()> {
                 app.clear_status();
             }
         }
+        Action::RunQuick(idx) => run_quick_action(app, idx)?,
+        Action::RefreshWorktrees => match app.refresh_worktrees() {
+            Ok(()) => app.set_status("Refreshed worktrees."),
+            Err(err) => app.set_status(format!("Failed to refresh worktrees: {err}")),
+        },
+        Action::CommandPalette => {
+            app.palette_state = Some(PaletteState::default());
+            app.mode = Mode::Palette;
+            app.clear_status();
+        }
+    }
+    Ok(())
+}
+
+/// Create a worktree on a new branch from the selected worktree's upstream.
+fn clone_selected_worktree(app: &mut App) -> Result<()> {
+    let Some(source) = app
+        .workspaces
+        .get(app.selected_workspace)
+        .map(|ws| ws.path().to_path_buf())
+    else {
+        app.set_status("No workspace selected.");
+        return Ok(());
+    };
+    let result = plan_clone_current(&app.repo_root, &source).and_then(|plan| {
+        let upstream = Upstream {
+            reference: &plan.start_point,
+            track: app.tracking.tracking_policy(&plan.branch),
+        };
+        create_workspace(
+            &app.repo_root,
+            &plan.branch,
+            Some(upstream),
+            app.path_layout(),
+            &app.add_options,
+        )
+        .map(|path| (plan, path))
+    });
+    match result {
+        Ok((plan, worktree_path)) => {
+            app.set_status(format!(
+                "Created worktree {} for new branch {} from {}",
+                worktree_path.display(),
+                plan.branch,
+                plan.start_point
+            ));
+            app.open_new_worktree(&worktree_path, &plan.branch)?;
+        }
+        Err(err) => app.set_status(format!("Failed to clone worktree: {err}")),
+    }
+    Ok(())
+}
+
+fn run_quick_action(app: &mut App, idx: usize) -> Result<()> {
+    let Some(action) = app.quick_actions.get(idx) else {
+        return Ok(());
+    };
+    if let Some(ws) = app.workspaces.get_mut(app.selected_workspace) {
+        let size = app.terminal_view_size.unwrap_or(app.terminal_size);
+        ws.spawn_quick_action_tab(&mut app.next_tab_id, size, action)?;
+        app.clear_status();
+    } else {
+        app.set_status("No workspace selected.");
+    }
+    Ok(())
+}
+
+fn handle_palette_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    let state = app.palette_state.get_or_insert_with(PaletteState::default);
+    let len = state.entries(&app.quick_actions).len();
+    match key.code {
+        KeyCode::Esc => {
+            app.palette_state = None;
+            app.mode = Mode::Navigation;
+        }
+        KeyCode::Up => state.move_up(len),
+        KeyCode::Down => state.move_down(len),
+        KeyCode::Backspace => state.backspace(),
+        KeyCode::Enter => {
+            let action = state.selected_action(&app.quick_actions);
+            app.palette_state = None;
+            app.mode = Mode::Navigation;
+            match action {
+                Some(action) => perform_action(app, action)?,
+                None => app.set_status("No matching command."),
+            }
+        }
+        KeyCode::Char(c)
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER) =>
+        {
+            state.push_char(c);
+        }
         _ => {}
     }
     Ok(())
@@ -269,17 +792,125 @@ fn handle_terminal_key(app: &mut App, key: KeyEvent) -> Result<()> {
         return Ok(());
     }
 
+    if key.code == KeyCode::Char('i') && key.modifiers.contains(KeyModifiers::ALT) {
+        open_insert_branch(app);
+        return Ok(());
+    }
+
     let Some(ws) = app.workspaces.get_mut(app.selected_workspace) else {
         return Ok(());
     };
     let Some(tab) = ws.active_tab_mut() else {
         return Ok(());
     };
+    if tab.is_terminated() {
+        app.set_status("Shell has exited. Press Esc, then `n` for a new tab.");
+        return Ok(());
+    }
     tab.handle_key_event(key)?;
     Ok(())
 }
 
+/// List branches and tickets to type into the focused terminal.
+fn open_insert_branch(app: &mut App) {
+    match AddWorktreeState::new(&app.repo_root) {
+        Ok((state, warning)) => {
+            app.mode = Mode::InsertBranch;
+            app.add_state = Some(state);
+            set_insert_status(app, warning);
+        }
+        Err(err) => app.set_status(format!("Failed to list branches: {err}")),
+    }
+}
+
+fn set_insert_status(app: &mut App, extra: Option<String>) {
+    if let Some(state) = app.add_state.as_ref() {
+        let mut status = format!(
+            "Insert branch: {} (Enter: type it • Esc: cancel)",
+            state.query()
+        );
+        if let Some(extra) = extra.filter(|extra| !extra.is_empty()) {
+            status = format!("{extra} | {status}");
+        }
+        app.set_status(status);
+    }
+}
+
+/// Filter like the add overlay; Enter or Tab types the selection at the prompt
+/// without running anything.
+fn handle_insert_branch_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    let Some(state) = app.add_state.as_mut() else {
+        app.mode = Mode::TerminalInput;
+        return Ok(());
+    };
+    match key.code {
+        KeyCode::Esc => {
+            app.add_state = None;
+            app.mode = Mode::TerminalInput;
+            app.clear_status();
+        }
+        KeyCode::Enter | KeyCode::Tab => {
+            let Some(text) = state.selected_text() else {
+                set_insert_status(app, Some("No suggestion selected.".into()));
+                return Ok(());
+            };
+            app.add_state = None;
+            app.mode = Mode::TerminalInput;
+            app.clear_status();
+            if let Some(tab) = app
+                .workspaces
+                .get_mut(app.selected_workspace)
+                .and_then(|ws| ws.active_tab_mut())
+            {
+                tab.send_text(&text)?;
+            }
+        }
+        KeyCode::Up => state.move_selection_up(),
+        KeyCode::Down => state.move_selection_down(),
+        KeyCode::Char(c @ ('j' | 'b' | 'a')) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            state.set_filter(match c {
+                'j' => SuggestionFilter::Jira,
+                'b' => SuggestionFilter::Branches,
+                _ => SuggestionFilter::All,
+            });
+        }
+        KeyCode::Backspace => {
+            state.backspace();
+            set_insert_status(app, None);
+        }
+        KeyCode::Char(c)
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER) =>
+        {
+            state.push_char(c);
+            set_insert_status(app, None);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 fn handle_add_worktree_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    if let Some(state) = app
+        .add_state
+        .as_mut()
+        .filter(|state| state.is_offering_remote_branch())
+    {
+        let answer = match key.code {
+            KeyCode::Enter | KeyCode::Char('y') => Some(true),
+            KeyCode::Char('n') => Some(false),
+            KeyCode::Esc => None,
+            _ => return Ok(()),
+        };
+        state.answer_remote_offer(answer);
+        if answer.is_some() {
+            confirm_add_worktree(app)?;
+        } else {
+            set_add_status(app, None);
+        }
+        return Ok(());
+    }
     if key.modifiers.contains(KeyModifiers::CONTROL) {
         match key.code {
             KeyCode::Char('r') | KeyCode::Char('R') => {
@@ -293,16 +924,15 @@ fn handle_add_worktree_key(app: &mut App, key: KeyEvent) -> Result<()> {
                             ),
                         }
                     } else {
-                        match state.refresh_data(&app.repo_root) {
-                            Ok(count) => set_add_status(
-                                app,
-                                Some(format!("Refreshed Jira tickets ({count})")),
+                        let message = match state.refresh_data(&app.repo_root) {
+                            Ok(Some(count)) => format!("Refreshed Jira tickets ({count})"),
+                            Ok(None) => format!(
+                                "Refreshed branches; {}",
+                                state.jira_hint().unwrap_or("Jira unavailable")
                             ),
-                            Err(err) => set_add_status(
-                                app,
-                                Some(format!("Failed to refresh Jira tickets: {err}")),
-                            ),
-                        }
+                            Err(err) => format!("Failed to refresh branches: {err}"),
+                        };
+                        set_add_status(app, Some(message));
                     }
                 }
                 return Ok(());
@@ -314,6 +944,17 @@ fn handle_add_worktree_key(app: &mut App, key: KeyEvent) -> Result<()> {
                 }
                 return Ok(());
             }
+            KeyCode::Char(c @ ('j' | 'b' | 'a')) => {
+                if let Some(state) = app.add_state.as_mut() {
+                    state.set_filter(match c {
+                        'j' => SuggestionFilter::Jira,
+                        'b' => SuggestionFilter::Branches,
+                        _ => SuggestionFilter::All,
+                    });
+                    set_add_status(app, None);
+                }
+                return Ok(());
+            }
             _ => {}
         }
     }
@@ -323,60 +964,23 @@ fn handle_add_worktree_key(app: &mut App, key: KeyEvent) -> Result<()> {
             app.add_state = None;
             app.mode = Mode::Navigation;
         }
-        KeyCode::Enter => {
-            let Some(state) = app.add_state.take() else {
-                app.mode = Mode::Navigation;
-                return Ok(());
-            };
-            let branch_name = state.normalized_branch();
-            if branch_name.is_empty() {
-                set_add_status(app, Some("Branch name is required.".into()));
-                app.add_state = Some(state);
-                return Ok(());
-            }
-            app.workspace_root = ensure_workspace_root(&app.repo_root)?;
-            let dir_name = state.workspace_dir_name();
-            let worktree_path = next_available_workspace_path(&app.workspace_root, &dir_name);
-            let branch_exists = state.branch_exists();
-            let branch_upstream = state.branch_upstream().map(str::to_owned);
-            let result = if branch_exists {
-                git::add_worktree_for_branch(&app.repo_root, &worktree_path, branch_name.as_str())
-            } else if let Some(ref upstream) = branch_upstream {
-                git::add_worktree_from_upstream(
-                    &app.repo_root,
-                    &worktree_path,
-                    branch_name.as_str(),
-                    upstream,
-                )
+        // Ctrl+Enter needs keyboard enhancement support; Alt+Enter works everywhere.
+        KeyCode::Enter
+            if key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            let accepted = app
+                .add_state
+                .as_mut()
+                .is_some_and(|state| state.accept_selection());
+            if accepted {
+                confirm_add_worktree(app)?;
             } else {
-                git::add_worktree(&app.repo_root, &worktree_path, Some(branch_name.as_str()))
-            };
-            match result {
-                Ok(_) => {
-                    if branch_exists {
-                        app.set_status(format!(
-                            "Added worktree {} for existing branch {}",
-                            worktree_path.display(),
-                            branch_name
-                        ));
-                    } else {
-                        app.set_status(format!(
-                            "Created worktree {} for new branch {}",
-                            worktree_path.display(),
-                            branch_name
-                        ));
-                    }
-                    app.refresh_worktrees()?;
-                    if let Some(idx) = app.index_of_path(&worktree_path) {
-                        app.set_selected_workspace(idx);
-                    }
-                }
-                Err(err) => {
-                    app.set_status(format!("Failed to create worktree: {err}"));
-                }
+                set_add_status(app, Some("No suggestion selected.".into()));
             }
-            app.mode = Mode::Navigation;
         }
+        KeyCode::Enter => confirm_add_worktree(app)?,
         KeyCode::Up => {
             if let Some(state) = app.add_state.as_mut() {
                 state.move_selection_up();
@@ -401,15 +1005,14 @@ fn handle_add_worktree_key(app: &mut App, key: KeyEvent) -> Result<()> {
                 set_add_status(app, None);
             }
         }
-        KeyCode::Char(c) => {
+        KeyCode::Char(c)
             if !key
                 .modifiers
-                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER)
-            {
-                if let Some(state) = app.add_state.as_mut() {
-                    state.push_char(c);
-                    set_add_status(app, None);
-                }
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER) =>
+        {
+            if let Some(state) = app.add_state.as_mut() {
+                state.push_char(c);
+                set_add_status(app, None);
             }
         }
         _ => {}
@@ -417,38 +1020,211 @@ fn handle_add_worktree_key(app: &mut App, key: KeyEvent) -> Result<()> {
     Ok(())
 }
 
+/// Create the worktree described by the add overlay and return to navigation.
+fn confirm_add_worktree(app: &mut App) -> Result<()> {
+    let Some(mut state) = app.add_state.take() else {
+        app.mode = Mode::Navigation;
+        return Ok(());
+    };
+    let branch_name = state.normalized_branch();
+    if branch_name.is_empty() {
+        set_add_status(app, Some("Branch name is required.".into()));
+        app.add_state = Some(state);
+        return Ok(());
+    }
+    if state.offer_remote_branch() {
+        app.add_state = Some(state);
+        set_add_status(app, None);
+        return Ok(());
+    }
+    app.workspace_root = ensure_workspace_root(&app.repo_root)?;
+    let branch_exists = state.branch_exists();
+    let branch_upstream = state.branch_upstream().map(str::to_owned);
+    if let (false, Some(upstream)) = (branch_exists, branch_upstream.as_deref()) {
+        // A stale branch list would otherwise surface git's raw "invalid reference" error.
+        if let Ok(false) = git::ref_exists(&app.repo_root, upstream) {
+            app.add_state = Some(state);
+            set_add_status(
+                app,
+                Some(format!(
+                    "Upstream {upstream} no longer exists; press Ctrl+R to refresh."
+                )),
+            );
+            return Ok(());
+        }
+    }
+    let lock = match WtmLock::acquire(&app.repo_root) {
+        Ok(lock) => lock,
+        Err(err) => {
+            app.add_state = Some(state);
+            set_add_status(app, Some(err.to_string()));
+            return Ok(());
+        }
+    };
+    let worktree_path = match state.reserve_target_path(&app.workspace_root, app.path_layout()) {
+        Ok(path) => path,
+        Err(err) => {
+            app.add_state = Some(state);
+            set_add_status(
+                app,
+                Some(format!("Failed to reserve worktree directory: {err}")),
+            );
+            return Ok(());
+        }
+    };
+    let options = app.add_options.clone();
+    let default_start = match (&branch_upstream, app.default_upstream.as_deref()) {
+        (None, Some(configured)) if !branch_exists => {
+            git::default_start_point(&app.repo_root, configured)
+        }
+        _ => None,
+    };
+    let result = if branch_exists {
+        git::add_worktree_for_branch(
+            &app.repo_root,
+            &worktree_path,
+            branch_name.as_str(),
+            &options,
+        )
+    } else if let Some(ref upstream) = branch_upstream {
+        git::add_worktree_from_upstream(
+            &app.repo_root,
+            &worktree_path,
+            branch_name.as_str(),
+            upstream,
+            app.tracking.tracking_policy(&branch_name),
+            &options,
+        )
+    } else if let Some(ref start) = default_start {
+        git::add_worktree_from_upstream(
+            &app.repo_root,
+            &worktree_path,
+            branch_name.as_str(),
+            start,
+            app.tracking.tracking_policy(&branch_name).or(Some(false)),
+            &options,
+        )
+    } else {
+        git::add_worktree_new_branch(
+            &app.repo_root,
+            &worktree_path,
+            branch_name.as_str(),
+            &options,
+        )
+    };
+    if result.is_err() {
+        // Give the reservation back; it is still empty.
+        let _ = std::fs::remove_dir(&worktree_path);
+    }
+    drop(lock);
+    match result {
+        Ok(_) => {
+            if branch_exists {
+                app.set_status(format!(
+                    "Added worktree {} for existing branch {}",
+                    worktree_path.display(),
+                    branch_name
+                ));
+            } else if let Some(start) = &default_start {
+                app.set_status(format!(
+                    "Created worktree {} for new branch {} from {start}",
+                    worktree_path.display(),
+                    branch_name
+                ));
+            } else {
+                app.set_status(format!(
+                    "Created worktree {} for new branch {}",
+                    worktree_path.display(),
+                    branch_name
+                ));
+            }
+            app.mode = Mode::Navigation;
+            app.open_new_worktree(&worktree_path, &branch_name)?;
+        }
+        Err(err) => {
+            app.set_status(format!("Failed to create worktree: {err}"));
+            app.mode = Mode::Navigation;
+        }
+    }
+    Ok(())
+}
+
 fn handle_remove_worktree_key(app: &mut App, key: KeyEvent) -> Result<()> {
+    let Some(state) = app.remove_state.as_mut() else {
+        app.mode = Mode::Navigation;
+        return Ok(());
+    };
+    if state.typed_name().is_some() {
+        match key.code {
+            KeyCode::Esc => {
+                app.remove_state = None;
+                app.mode = Mode::Navigation;
+            }
+            KeyCode::Backspace => state.pop_name_char(),
+            KeyCode::Char(c) => state.push_name_char(c),
+            KeyCode::Enter if state.typed_name_matches() => remove_confirmed_worktree(app)?,
+            KeyCode::Enter => app.set_status("Name does not match; removal not confirmed."),
+            _ => {}
+        }
+        return Ok(());
+    }
     match key.code {
         KeyCode::Esc | KeyCode::Char('n') => {
             app.remove_state = None;
             app.mode = Mode::Navigation;
         }
-        KeyCode::Char('f') => {
-            if let Some(state) = app.remove_state.as_mut() {
-                state.toggle_force();
-            }
-        }
+        KeyCode::Char('f') if !state.is_stale() => state.toggle_force(),
         KeyCode::Char('y') => {
-            let Some(state) = app.remove_state.take() else {
-                app.mode = Mode::Navigation;
-                return Ok(());
-            };
-            match git::remove_worktree(&app.repo_root, state.target(), state.force()) {
-                Ok(_) => {
-                    app.set_status(format!("Removed worktree {}", state.target().display()));
-                    app.refresh_worktrees()?;
-                }
-                Err(err) => {
-                    app.set_status(format!("Failed to remove worktree: {err}"));
-                }
+            if state.accept(app.confirm_force_remove) {
+                remove_confirmed_worktree(app)?;
+            } else {
+                let name = state.name();
+                app.set_status(format!("Type `{name}` and press Enter to force-remove."));
             }
-            app.mode = Mode::Navigation;
         }
         _ => {}
     }
     Ok(())
 }
 
+fn remove_confirmed_worktree(app: &mut App) -> Result<()> {
+    app.mode = Mode::Navigation;
+    let Some(state) = app.remove_state.take() else {
+        return Ok(());
+    };
+    if state.is_stale() {
+        let pruned = WtmLock::acquire(&app.repo_root)
+            .map_err(anyhow::Error::from)
+            .and_then(|_lock| git::prune_worktrees(&app.repo_root));
+        match pruned {
+            Ok(()) => app.set_status(format!(
+                "Pruned stale worktree {}",
+                state.target().display()
+            )),
+            Err(err) => app.set_status(format!("Failed to prune worktrees: {err:#}")),
+        }
+        return app.refresh_worktrees();
+    }
+    // The pre-remove hook may take a while, so the removal runs on a worker.
+    let repo_root = app.repo_root.clone();
+    let target = state.target().to_path_buf();
+    let force = state.force();
+    app.set_status(format!("Removing worktree {}…", target.display()));
+    app.spawn_job(move || {
+        let removed = delete_workspace(&repo_root, &target, force, true);
+        Box::new(move |app: &mut App| match removed {
+            Ok(_) => {
+                app.set_status(format!("Removed worktree {}", target.display()));
+                if let Err(err) = app.refresh_worktrees() {
+                    app.set_status(format!("Failed to refresh worktrees: {err:#}"));
+                }
+            }
+            Err(err) => app.set_status(format!("Failed to remove worktree: {err}")),
+        })
+    });
+    Ok(())
+}
+
 fn handle_quick_actions_key(app: &mut App, key: KeyEvent) -> Result<()> {
     let len = app.quick_actions.len();
     if len == 0 {
@@ -474,14 +1250,7 @@ fn handle_quick_actions_key(app: &mut App, key: KeyEvent) -> Result<()> {
         }
         KeyCode::Enter => {
             let idx = state.selected.min(len - 1);
-            let action = &app.quick_actions[idx];
-            if let Some(ws) = app.workspaces.get_mut(app.selected_workspace) {
-                let size = app.terminal_view_size.unwrap_or(app.terminal_size);
-                ws.spawn_quick_action_tab(&mut app.next_tab_id, size, action)?;
-                app.clear_status();
-            } else {
-                app.set_status("No workspace selected.");
-            }
+            run_quick_action(app, idx)?;
             app.mode = Mode::Navigation;
         }
         _ => {}
@@ -0,0 +1,69 @@
+use crate::git::CommitLine;
+
+/// How many commits the log overlay loads.
+pub(super) const LOG_LIMIT: usize = 20;
+
+/// Read-only list of recent commits shown over the dashboard.
+#[derive(Debug)]
+pub(super) struct LogState {
+    title: String,
+    commits: Vec<CommitLine>,
+    scroll: usize,
+}
+
+impl LogState {
+    pub(super) fn new(title: String, commits: Vec<CommitLine>) -> Self {
+        Self {
+            title,
+            commits,
+            scroll: 0,
+        }
+    }
+
+    pub(super) fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub(super) fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// `<hash> <subject>` per commit, or a placeholder when there are none.
+    pub(super) fn lines(&self) -> Vec<String> {
+        if self.commits.is_empty() {
+            return vec!["No commits yet.".into()];
+        }
+        self.commits
+            .iter()
+            .map(|commit| format!("{} {}", commit.hash, commit.subject))
+            .collect()
+    }
+
+    /// Move the first visible line by `delta`, staying within the list.
+    pub(super) fn scroll_by(&mut self, delta: isize) {
+        let max = self.commits.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(n: usize) -> CommitLine {
+        CommitLine {
+            hash: format!("{n:07}"),
+            subject: format!("commit {n}"),
+        }
+    }
+
+    #[test]
+    fn scroll_is_clamped_to_the_commit_list() {
+        let mut state = LogState::new("main".into(), (0..3).map(commit).collect());
+        state.scroll_by(-1);
+        assert_eq!(state.scroll(), 0);
+        state.scroll_by(10);
+        assert_eq!(state.scroll(), 2);
+        assert_eq!(state.lines()[2], "0000002 commit 2");
+    }
+}
@@ -1,41 +1,81 @@
 mod add_worktree;
+mod config_watch;
 mod context;
 #[cfg(feature = "fx")]
 mod effects;
 mod input;
+mod log_view;
+mod notes_view;
+mod palette;
+mod recent;
+mod session;
+mod status_line;
+mod terminal_search;
 mod ui;
 mod workspace;
 
 use add_worktree::AddWorktreeState;
-use context::WorkspaceContext;
+use config_watch::ConfigWatcher;
+use context::{ServicePicker, WorkspaceContext};
 use input::{handle_key, handle_mouse};
+use log_view::LogState;
+use notes_view::NotesState;
+use palette::PaletteState;
+use recent::{RecentPicker, RecentWorkspaces};
+use status_line::{StatusCache, StatusLineValues};
+use terminal_search::TerminalSearch;
 use workspace::{QuickActionState, RemoveWorktreeState, WorkspaceState};
 
 #[cfg(feature = "fx")]
 use effects::FxController;
 
-use super::size::TerminalSize;
+use super::{pty_tab::usable_session, size::TerminalSize};
 use crate::{
-    config::QuickAction,
-    git::{self, WorktreeInfo},
-    wtm_paths::ensure_workspace_root,
+    config::{
+        self, Config, PathDisplay, PrimaryPosition, QuickAction, TerminalSession, TrackingRules,
+    },
+    docker::DockerOptions,
+    git::{self, AddOptions, WorktreeInfo},
+    hooks::{self, Hook, HookContext, HookOutcome},
+    ignore::IgnoreList,
+    jira, notes, parallel,
+    pins::Pins,
+    tags::Tags,
+    wtm_paths::{ensure_workspace_root, PathLayout},
 };
 use anyhow::Result;
 use crossterm::event::{Event, KeyEventKind};
 use ratatui::{layout::Rect, Frame};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
+    sync::mpsc::{Receiver, TryRecvError},
+    time::{Duration, Instant, SystemTime},
 };
 
+/// What a finished background job does to the app, e.g. report a hook's
+/// outcome in the status bar.
+type JobResult = Box<dyn FnOnce(&mut App) + Send>;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum Mode {
     Navigation,
     TerminalInput,
+    /// Picking a branch to type into the focused terminal.
+    InsertBranch,
     Adding,
     Removing,
     QuickActions,
+    Palette,
     Help,
+    Log,
+    Notes,
+    /// Picking one of the most recently focused worktrees.
+    Recent,
+    /// Searching the active tab's scrollback (`/`, then n/N).
+    TerminalSearch,
+    /// Picking a compose service whose logs to follow in a new tab.
+    ServiceLogs,
 }
 
 pub(super) struct App {
@@ -43,16 +83,61 @@ pub(super) struct App {
     workspace_root: PathBuf,
     workspaces: Vec<WorkspaceState>,
     selected_workspace: usize,
+    /// Worktrees by when they were last selected, for Tab and the `r` picker.
+    recent: RecentWorkspaces,
+    recent_picker: Option<RecentPicker>,
     mode: Mode,
     add_state: Option<AddWorktreeState>,
     remove_state: Option<RemoveWorktreeState>,
     quick_actions: Vec<QuickAction>,
     quick_action_state: Option<QuickActionState>,
+    palette_state: Option<PaletteState>,
+    log_state: Option<LogState>,
+    notes_state: Option<NotesState>,
+    terminal_search: Option<TerminalSearch>,
+    service_picker: Option<ServicePicker>,
+    auto_close_finished_tabs_after: Option<Duration>,
+    primary_position: PrimaryPosition,
+    path_display: PathDisplay,
+    ignore: IgnoreList,
+    tracking: TrackingRules,
+    post_create_command: Option<String>,
+    hooks_path: Option<String>,
+    confirm_force_remove: bool,
+    workspace_path_template: Option<String>,
+    /// `maxSlugLength`, resolved to its default when unset.
+    max_slug_length: usize,
+    terminal_env: BTreeMap<String, String>,
+    /// `session` from the config, or a plain shell when tmux is missing.
+    session: TerminalSession,
+    /// `git worktree add` flags for every worktree the TUI creates.
+    add_options: AddOptions,
+    /// `.wtm/tags.json`, reloaded with the worktree list.
+    tags: Tags,
+    /// `.wtm/pins.json`, reloaded with the worktree list.
+    pins: Pins,
+    /// The repository is bare, so no worktree is the primary one.
+    bare: bool,
+    /// Directory wtm was started from, used to mark the `(here)` worktree.
+    launch_dir: Option<PathBuf>,
+    config_watch: ConfigWatcher,
     next_tab_id: usize,
     should_quit: bool,
     terminal_size: TerminalSize,
     terminal_view_size: Option<TerminalSize>,
     status_message: Option<String>,
+    /// `statusLine` from the config; the fixed key hint when unset.
+    status_line: Option<String>,
+    status_line_cache: StatusCache,
+    /// Hooks and removals running off the event loop, collected by
+    /// [`App::finish_jobs`].
+    jobs: Vec<Receiver<JobResult>>,
+    /// Seconds east of UTC for `{time}`, looked up once at startup.
+    utc_offset: i64,
+    /// `docker` from the config, for the context panel's compose services.
+    docker: DockerOptions,
+    /// `defaultUpstream` from the config, for new branches in the add overlay.
+    default_upstream: Option<String>,
     sidebar_area: Option<Rect>,
     context_area: Option<Rect>,
     tabs_area: Option<Rect>,
@@ -64,35 +149,98 @@ pub(super) struct App {
     fx: FxController,
 }
 
+/// A first status line for repositories with nothing to switch between yet.
+fn startup_hint(worktrees: &[WorktreeInfo]) -> Option<&'static str> {
+    let unborn = |wt: &WorktreeInfo| {
+        wt.head
+            .as_deref()
+            .is_none_or(|head| head.bytes().all(|byte| byte == b'0'))
+    };
+    match worktrees {
+        [] => None,
+        _ if worktrees.iter().all(unborn) => {
+            Some("This repository has no commits yet; commit once, then press a to add a worktree.")
+        }
+        [_] => Some("Only the primary worktree exists so far; press a to add one."),
+        _ => None,
+    }
+}
+
 impl App {
     pub fn new(
         repo_root: PathBuf,
         worktrees: Vec<WorktreeInfo>,
-        quick_actions: Vec<QuickAction>,
+        config: Config,
         size: TerminalSize,
     ) -> Result<Self> {
         let workspace_root = ensure_workspace_root(&repo_root)?;
+        let (session, session_warning) = usable_session(config.session);
+        let hint = session_warning.or_else(|| startup_hint(&worktrees).map(String::from));
+        let config_watch = ConfigWatcher::new(&repo_root.join(".wtm"));
         let mut next_tab_id = 1;
         let mut workspace_states = Vec::with_capacity(worktrees.len());
         for info in worktrees {
-            workspace_states.push(WorkspaceState::new(info, size, &mut next_tab_id)?);
+            workspace_states.push(WorkspaceState::new(
+                info,
+                &repo_root,
+                size,
+                &mut next_tab_id,
+                &config.terminal_env,
+                session,
+            )?);
         }
 
+        let add_options = AddOptions::for_repo(&repo_root, config.relative_worktree_paths);
+        let max_slug_length = config.path_layout().max_slug_length;
+        let tags = load_tags(&repo_root.join(".wtm"));
+        let pins = load_pins(&repo_root.join(".wtm"));
+        let bare = git::is_bare_repository(&repo_root).unwrap_or(false);
         let mut app = Self {
             repo_root,
             workspace_root,
             workspaces: workspace_states,
             selected_workspace: 0,
+            recent: RecentWorkspaces::default(),
+            recent_picker: None,
             mode: Mode::Navigation,
             add_state: None,
             remove_state: None,
-            quick_actions,
+            quick_actions: config.quick_actions,
             quick_action_state: None,
+            palette_state: None,
+            log_state: None,
+            notes_state: None,
+            terminal_search: None,
+            service_picker: None,
+            auto_close_finished_tabs_after: config.auto_close_finished_tabs_after,
+            primary_position: config.primary_position,
+            path_display: config.path_display,
+            ignore: config.ignore,
+            tracking: config.tracking,
+            post_create_command: config.post_create_command,
+            hooks_path: config.hooks_path,
+            confirm_force_remove: config.confirm_force_remove,
+            workspace_path_template: config.workspace_path_template,
+            max_slug_length,
+            terminal_env: config.terminal_env,
+            session,
+            add_options,
+            tags,
+            pins,
+            bare,
+            launch_dir: std::env::current_dir().ok(),
+            config_watch,
             next_tab_id,
             should_quit: false,
             terminal_size: size,
             terminal_view_size: None,
-            status_message: None,
+            status_message: hint,
+            status_line: config.status_line,
+            status_line_cache: StatusCache::default(),
+            jobs: Vec::new(),
+            utc_offset: status_line::local_utc_offset(),
+            docker: config.docker,
+            default_upstream: config.default_upstream,
             sidebar_area: None,
             context_area: None,
             tabs_area: None,
@@ -104,7 +252,14 @@ impl App {
             fx: FxController::new(false),
         };
 
-        if !app.workspaces.is_empty() {
+        let saved = session::load_last_workspace(&app.wtm_dir());
+        app.selected_workspace = session::restored_selection(
+            app.workspaces.iter().map(|ws| ws.path()),
+            saved.as_deref(),
+        );
+
+        if let Some(workspace) = app.workspaces.get(app.selected_workspace) {
+            app.recent.focus(workspace.path());
             app.refresh_context_for_selected();
         }
 
@@ -129,19 +284,168 @@ impl App {
         Ok(())
     }
 
+    /// Whether effects are running and need a frame every tick.
+    #[cfg(feature = "fx")]
+    pub fn is_animating(&self) -> bool {
+        self.fx.is_animating()
+    }
+
+    #[cfg(not(feature = "fx"))]
+    pub fn is_animating(&self) -> bool {
+        false
+    }
+
     pub fn should_quit(&self) -> bool {
         self.should_quit
     }
 
     pub fn reap_finished_children(&mut self) {
+        let now = Instant::now();
+        for workspace in &mut self.workspaces {
+            workspace.reap_finished_children(now, self.auto_close_finished_tabs_after);
+        }
+    }
+
+    /// Run `job` on a worker thread; what it returns is applied to the app once
+    /// [`Self::finish_jobs`] sees it finished.
+    pub(super) fn spawn_job(&mut self, job: impl FnOnce() -> JobResult + Send + 'static) {
+        self.jobs.push(parallel::background(job));
+    }
+
+    /// Apply the results of background jobs that have finished; returns
+    /// whether any did, so the screen can be redrawn.
+    pub fn finish_jobs(&mut self) -> bool {
+        let mut finished = Vec::new();
+        let mut panicked = false;
+        self.jobs.retain(|job| match job.try_recv() {
+            Ok(result) => {
+                finished.push(result);
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => {
+                panicked = true;
+                false
+            }
+        });
+        if panicked {
+            self.set_status("A background task failed unexpectedly; see the log.");
+        }
+        let any = panicked || !finished.is_empty();
+        for result in finished {
+            result(self);
+        }
+        any
+    }
+
+    /// Keep the git status behind the `statusLine` template current for the
+    /// selected worktree, off the render thread; returns whether a new one
+    /// arrived and the bar needs drawing.
+    pub fn refresh_status_line(&mut self) -> bool {
+        let Some(template) = self.status_line.as_deref() else {
+            return false;
+        };
+        if !status_line::needs_status(template) {
+            return false;
+        }
+        let Some(workspace) = self.workspaces.get(self.selected_workspace) else {
+            return false;
+        };
+        self.status_line_cache
+            .refresh(workspace.info().path(), Instant::now())
+    }
+
+    /// Reload quick actions when the config file has been edited since the last check.
+    pub fn reload_config_if_changed(&mut self) {
+        let wtm_dir = self.wtm_dir();
+        if self.config_watch.poll(&wtm_dir, Instant::now()) {
+            self.reload_quick_actions(config::load_config(&wtm_dir));
+        }
+    }
+
+    /// Apply a freshly loaded config, keeping the current actions if it failed to load.
+    fn reload_quick_actions(&mut self, loaded: Result<Config>) {
+        match loaded {
+            Ok(config) => {
+                self.quick_actions = config.quick_actions;
+                self.status_line = config.status_line;
+                self.docker = config.docker;
+                self.default_upstream = config.default_upstream;
+                if let Some(state) = self.quick_action_state.as_mut() {
+                    state.clamp(self.quick_actions.len());
+                }
+                self.set_status(format!(
+                    "Reloaded config ({} quick actions)",
+                    self.quick_actions.len()
+                ));
+            }
+            Err(err) => {
+                self.set_status(format!("Config not reloaded: {err:#}"));
+            }
+        }
+    }
+
+    /// The bottom bar's text when no status message is showing: the
+    /// `statusLine` template filled in for the selected worktree, or the key hint.
+    fn status_line_text(&self) -> String {
+        let Some(template) = self.status_line.as_deref() else {
+            return status_line::DEFAULT_HINT.to_string();
+        };
+        let Some(workspace) = self.workspaces.get(self.selected_workspace) else {
+            return status_line::DEFAULT_HINT.to_string();
+        };
+        let info = workspace.info();
+        let status = if status_line::needs_status(template) {
+            self.status_line_cache.get(info.path())
+        } else {
+            None
+        };
+        let workspace = info.name();
+        let values = StatusLineValues {
+            workspace: &workspace,
+            branch: info.branch.as_deref(),
+            status,
+            time: status_line::clock(SystemTime::now(), self.utc_offset),
+        };
+        status_line::render_status_line(template, &values)
+    }
+
+    /// Persist the selected worktree so the next start restores it.
+    pub fn save_session(&self) {
+        let Some(workspace) = self.workspaces.get(self.selected_workspace) else {
+            return;
+        };
+        if let Err(err) = session::save_last_workspace(&self.wtm_dir(), workspace.path()) {
+            log::warn!("failed to save session: {err:#}");
+        }
+    }
+
+    fn wtm_dir(&self) -> PathBuf {
+        self.repo_root.join(".wtm")
+    }
+
+    /// Where new worktrees go, per `workspacePathTemplate` and `maxSlugLength`.
+    fn path_layout(&self) -> PathLayout<'_> {
+        PathLayout {
+            template: self.workspace_path_template.as_deref(),
+            max_slug_length: self.max_slug_length,
+        }
+    }
+
+    /// Terminate every PTY child across all workspaces.
+    pub fn shutdown_all(&mut self) {
         for workspace in &mut self.workspaces {
-            workspace.reap_finished_children();
+            workspace.terminate_tabs();
         }
     }
 
     pub(super) fn refresh_worktrees(&mut self) -> Result<()> {
         self.workspace_root = ensure_workspace_root(&self.repo_root)?;
-        let updated = git::list_worktrees(&self.repo_root)?;
+        // Refreshing is how the dashboard notices outside changes, so skip the cache.
+        git::invalidate_worktree_cache(&self.repo_root);
+        let mut updated = git::list_worktrees_cached(&self.repo_root)?;
+        self.ignore.retain_visible(&mut updated, &self.repo_root);
+        self.primary_position.apply(&mut updated, &self.repo_root);
         let mut existing: HashMap<PathBuf, WorkspaceState> = self
             .workspaces
             .drain(..)
@@ -157,15 +461,23 @@ impl App {
             } else {
                 rebuilt.push(WorkspaceState::new(
                     info,
+                    &self.repo_root,
                     self.terminal_size,
                     &mut self.next_tab_id,
+                    &self.terminal_env,
+                    self.session,
                 )?);
             }
         }
 
         self.workspaces = rebuilt;
+        self.tags = load_tags(&self.wtm_dir());
+        self.pins = load_pins(&self.wtm_dir());
         self.workspace_contexts
             .retain(|path, _| self.workspaces.iter().any(|ws| ws.path() == path));
+        let workspaces = &self.workspaces;
+        self.recent
+            .retain(|path| workspaces.iter().any(|ws| ws.path() == path));
         if self.workspaces.is_empty() {
             self.selected_workspace = 0;
             self.workspace_contexts.clear();
@@ -175,9 +487,72 @@ impl App {
         } else {
             self.refresh_context_for_selected();
         }
+        if let Some(workspace) = self.workspaces.get(self.selected_workspace) {
+            self.recent.focus(workspace.path());
+        }
+        Ok(())
+    }
+
+    /// Set `hooksPath`, select the new worktree, and start the post-create
+    /// hook on a worker; `postCreateCommand` follows once the hook is done.
+    pub(super) fn open_new_worktree(&mut self, path: &Path, branch: &str) -> Result<()> {
+        if let Some(hooks_path) = self.hooks_path.as_deref() {
+            if let Err(err) = hooks::install_hooks_path(&self.repo_root, path, hooks_path) {
+                self.set_status(format!("Failed to set core.hooksPath: {err:#}"));
+            }
+        }
+        self.refresh_worktrees()?;
+        if let Some(idx) = self.index_of_path(path) {
+            self.set_selected_workspace(idx);
+        }
+        let repo_root = self.repo_root.clone();
+        let path = path.to_path_buf();
+        let branch = branch.to_string();
+        self.spawn_job(move || {
+            let context = HookContext {
+                repo_root: &repo_root,
+                worktree_path: &path,
+                branch: &branch,
+            };
+            let outcome = hooks::run_hook(Hook::PostCreate, context);
+            Box::new(move |app: &mut App| app.post_create_hook_finished(&path, outcome))
+        });
         Ok(())
     }
 
+    /// Report the post-create hook, then start `postCreateCommand` in a tab,
+    /// focused when the new worktree is still the one selected.
+    fn post_create_hook_finished(&mut self, path: &Path, outcome: Result<Option<HookOutcome>>) {
+        match outcome {
+            Ok(Some(outcome)) => {
+                let status = match self.status_message.take() {
+                    Some(status) => format!("{status} | {}", outcome.summary()),
+                    None => outcome.summary(),
+                };
+                self.set_status(status);
+            }
+            Ok(None) => {}
+            Err(err) => self.set_status(format!("Failed to run post-create hook: {err}")),
+        }
+        let (Some(idx), Some(command)) =
+            (self.index_of_path(path), self.post_create_command.clone())
+        else {
+            return;
+        };
+        let size = self.terminal_view_size.unwrap_or(self.terminal_size);
+        let action = QuickAction {
+            label: "post-create".into(),
+            command,
+        };
+        match self.workspaces[idx].spawn_quick_action_tab(&mut self.next_tab_id, size, &action) {
+            Ok(()) if idx == self.selected_workspace && self.mode == Mode::Navigation => {
+                self.mode = Mode::TerminalInput;
+            }
+            Ok(()) => {}
+            Err(err) => self.set_status(format!("Failed to start postCreateCommand: {err:#}")),
+        }
+    }
+
     pub(super) fn index_of_path(&self, path: &Path) -> Option<usize> {
         self.workspaces.iter().position(|ws| ws.path() == path)
     }
@@ -207,22 +582,58 @@ impl App {
     pub(super) fn refresh_context_for_selected(&mut self) {
         if let Some(workspace) = self.workspaces.get(self.selected_workspace) {
             let info = workspace.info().clone();
-            let context = context::gather_workspace_context(&info);
+            let mut context = context::gather_workspace_context(&info, &self.docker);
+            context.note = notes::load(&notes::note_path(&self.repo_root, &info.path))
+                .map(|text| notes::preview(&text).map(str::to_string))
+                .unwrap_or_else(|err| Some(format!("(unreadable: {err:#})")));
+            let tickets = jira::cached_tickets_offline(&self.repo_root)
+                .map_err(|err| log::debug!("Jira cache unreadable: {err:#}"))
+                .ok()
+                .flatten();
+            context.jira = context::jira_line(info.branch.as_deref(), tickets.as_deref());
             self.workspace_contexts
                 .insert(workspace.path().to_path_buf(), context);
         }
     }
 
+    /// Index of the worktree containing the launch directory, if any.
+    pub(super) fn here_index(&self) -> Option<usize> {
+        let launch_dir = self.launch_dir.as_deref()?;
+        session::containing_worktree(self.workspaces.iter().map(|ws| ws.path()), launch_dir)
+    }
+
     pub(super) fn set_selected_workspace(&mut self, index: usize) {
         if self.workspaces.is_empty() || index >= self.workspaces.len() {
             return;
         }
         if self.selected_workspace != index {
             self.selected_workspace = index;
+            self.recent.focus(self.workspaces[index].path());
             self.refresh_context_for_selected();
         }
     }
 
+    /// Select the worktree that was selected before the current one.
+    pub(super) fn switch_to_previous_workspace(&mut self) {
+        let Some(index) = self
+            .recent
+            .previous()
+            .and_then(|path| self.index_of_path(path))
+        else {
+            return;
+        };
+        self.set_selected_workspace(index);
+    }
+
+    /// Sidebar indices of the recently selected worktrees, most recent first.
+    pub(super) fn recent_indices(&self) -> Vec<usize> {
+        self.recent
+            .paths()
+            .iter()
+            .filter_map(|path| self.index_of_path(path))
+            .collect()
+    }
+
     pub(super) fn is_context_panel_visible(&self) -> bool {
         self.context_panel_visible
     }
@@ -237,3 +648,234 @@ impl App {
         self.fx.render_status(frame, area);
     }
 }
+
+/// Worktree tags for the sidebar; an unreadable file just hides them.
+fn load_tags(wtm_dir: &Path) -> Tags {
+    Tags::load(wtm_dir).unwrap_or_else(|err| {
+        log::warn!("could not read worktree tags: {err:#}");
+        Tags::default()
+    })
+}
+
+/// Pinned worktrees for the sidebar; an unreadable file just hides the markers.
+fn load_pins(wtm_dir: &Path) -> Pins {
+    Pins::load(wtm_dir).unwrap_or_else(|err| {
+        log::warn!("could not read worktree pins: {err:#}");
+        Pins::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use tempfile::tempdir;
+
+    fn press(app: &mut App, code: KeyCode) {
+        handle_key(app, KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+    }
+
+    #[test]
+    fn shutdown_all_terminates_every_tab() {
+        let repo = tempdir().unwrap();
+        let linked = tempdir().unwrap();
+        let worktrees = [repo.path(), linked.path()]
+            .into_iter()
+            .map(WorktreeInfo::at)
+            .collect();
+        let size = TerminalSize::new(24, 80);
+        let mut app = App::new(
+            repo.path().to_path_buf(),
+            worktrees,
+            Config::default(),
+            size,
+        )
+        .unwrap();
+        app.workspaces[0]
+            .spawn_tab(&mut app.next_tab_id, size)
+            .unwrap();
+        assert_eq!(app.workspaces[0].tabs_len(), 2);
+        assert_eq!(app.workspaces[1].tabs_len(), 1);
+
+        app.shutdown_all();
+
+        assert!(app.workspaces.iter().all(|ws| !ws.has_tabs()));
+    }
+
+    #[test]
+    fn pruning_a_prunable_worktree_offers_to_drop_its_record() {
+        let repo = tempdir().unwrap();
+        let linked = repo.path().join(".wtm/workspaces/stale");
+        std::fs::create_dir_all(&linked).unwrap();
+        let mut app = App::new(
+            repo.path().to_path_buf(),
+            vec![
+                WorktreeInfo::at(repo.path()),
+                WorktreeInfo::at(&linked).prunable(),
+            ],
+            Config::default(),
+            TerminalSize::new(24, 80),
+        )
+        .unwrap();
+        app.set_selected_workspace(1);
+
+        press(&mut app, KeyCode::Char('p'));
+        assert!(matches!(app.mode, Mode::Removing));
+        let state = app.remove_state.as_ref().unwrap();
+        assert!(state.is_stale());
+        assert_eq!(state.target(), linked);
+
+        press(&mut app, KeyCode::Char('n'));
+        assert!(matches!(app.mode, Mode::Navigation));
+        app.workspaces[1].update_info(WorktreeInfo::at(&linked));
+        press(&mut app, KeyCode::Char('p'));
+        assert!(!app.remove_state.as_ref().unwrap().is_stale());
+
+        app.shutdown_all();
+    }
+
+    #[test]
+    fn startup_hint_tells_fresh_and_single_worktree_repos_apart() {
+        let worktree = |path: &str, head: &str| WorktreeInfo::at(path).on("main").with_head(head);
+        let zeros = "0".repeat(40);
+        let fresh = [worktree("/repo", &zeros)];
+        let primary_only = [worktree("/repo", "a1b2c3d")];
+        let several = [
+            worktree("/repo", "a1b2c3d"),
+            worktree("/repo/.wtm/workspaces/x", "a1b2c3d"),
+        ];
+
+        assert!(startup_hint(&fresh).is_some_and(|hint| hint.contains("no commits yet")));
+        assert!(startup_hint(&primary_only)
+            .is_some_and(|hint| hint.contains("Only the primary worktree")));
+        assert_eq!(startup_hint(&several), None);
+        assert_eq!(startup_hint(&[]), None);
+    }
+
+    #[test]
+    fn copy_path_reports_the_copied_path_or_shows_it_instead() {
+        let repo = tempdir().unwrap();
+        let worktrees = vec![WorktreeInfo::at(repo.path())];
+        let mut app = App::new(
+            repo.path().to_path_buf(),
+            worktrees,
+            Config::default(),
+            TerminalSize::new(24, 80),
+        )
+        .unwrap();
+        let path = repo.path().display().to_string();
+
+        let mut copied = None;
+        input::copy_selected_path(&mut app, |text| {
+            copied = Some(text.to_string());
+            Ok(())
+        });
+        assert_eq!(copied.as_deref(), Some(path.as_str()));
+        assert_eq!(app.status_message, Some(format!("Copied {path}")));
+
+        input::copy_selected_path(&mut app, |_| Err(anyhow::anyhow!("no display")));
+        assert_eq!(
+            app.status_message,
+            Some(format!("{path} (not copied: no display)"))
+        );
+        app.shutdown_all();
+    }
+
+    #[test]
+    fn palette_runs_the_selected_action() {
+        let repo = tempdir().unwrap();
+        let mut app = App::new(
+            repo.path().to_path_buf(),
+            Vec::new(),
+            Config::default(),
+            TerminalSize::new(24, 80),
+        )
+        .unwrap();
+
+        press(&mut app, KeyCode::Char(':'));
+        assert_eq!(app.mode, Mode::Palette);
+        for c in "context".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, Mode::Navigation);
+        assert!(app.is_context_panel_visible());
+        assert!(app.palette_state.is_none());
+
+        handle_key(
+            &mut app,
+            KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+        )
+        .unwrap();
+        for c in "help".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.mode, Mode::Help);
+    }
+
+    #[test]
+    fn accept_and_create_requires_a_selected_suggestion() {
+        let repo = tempdir().unwrap();
+        let mut app = App::new(
+            repo.path().to_path_buf(),
+            Vec::new(),
+            Config::default(),
+            TerminalSize::new(24, 80),
+        )
+        .unwrap();
+
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(app.mode, Mode::Adding);
+        for c in "nothing-matches".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        handle_key(&mut app, KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)).unwrap();
+
+        assert_eq!(app.mode, Mode::Adding);
+        assert!(app.add_state.is_some());
+        assert!(app
+            .status_message
+            .as_deref()
+            .is_some_and(|status| status.contains("No suggestion selected.")));
+    }
+
+    #[test]
+    fn failed_config_reload_keeps_previous_quick_actions() {
+        let repo = tempdir().unwrap();
+        let quick = |label: &str| QuickAction {
+            label: label.into(),
+            command: "true".into(),
+        };
+        let config = Config {
+            quick_actions: vec![quick("Build")],
+            ..Config::default()
+        };
+        let mut app = App::new(
+            repo.path().to_path_buf(),
+            Vec::new(),
+            config,
+            TerminalSize::new(24, 80),
+        )
+        .unwrap();
+
+        app.reload_quick_actions(Err(anyhow::anyhow!("expected value at line 1")));
+        assert_eq!(app.quick_actions.len(), 1);
+        assert!(app
+            .status_message
+            .as_deref()
+            .unwrap()
+            .contains("expected value at line 1"));
+
+        app.reload_quick_actions(Ok(Config {
+            quick_actions: vec![quick("Test"), quick("Lint")],
+            ..Config::default()
+        }));
+        let labels: Vec<&str> = app.quick_actions.iter().map(|a| a.label.as_str()).collect();
+        assert_eq!(labels, ["Test", "Lint"]);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Reloaded config (2 quick actions)")
+        );
+    }
+}
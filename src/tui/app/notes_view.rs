@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+/// Multi-line editor for a worktree's note, shown over the dashboard.
+#[derive(Debug)]
+pub(super) struct NotesState {
+    title: String,
+    path: PathBuf,
+    lines: Vec<String>,
+    /// Cursor line and character (not byte) column.
+    row: usize,
+    col: usize,
+}
+
+impl NotesState {
+    pub(super) fn new(title: String, path: PathBuf, text: &str) -> Self {
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        Self {
+            title,
+            path,
+            lines,
+            row,
+            col,
+        }
+    }
+
+    pub(super) fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub(super) fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub(super) fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Cursor position as (line, character column).
+    pub(super) fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// The note as saved: lines joined with a trailing newline.
+    pub(super) fn text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        text
+    }
+
+    pub(super) fn insert(&mut self, c: char) {
+        let at = self.byte_index();
+        self.lines[self.row].insert(at, c);
+        self.col += 1;
+    }
+
+    pub(super) fn newline(&mut self) {
+        let at = self.byte_index();
+        let rest = self.lines[self.row].split_off(at);
+        self.row += 1;
+        self.lines.insert(self.row, rest);
+        self.col = 0;
+    }
+
+    /// Delete the character before the cursor, joining lines at the start of one.
+    pub(super) fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let at = self.byte_index();
+            self.lines[self.row].remove(at);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.lines[self.row].chars().count();
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    pub(super) fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len();
+        }
+    }
+
+    pub(super) fn move_right(&mut self) {
+        if self.col < self.line_len() {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    /// Move the cursor `delta` lines, keeping the column where the line allows.
+    pub(super) fn move_vertical(&mut self, delta: isize) {
+        self.row = self
+            .row
+            .saturating_add_signed(delta)
+            .min(self.lines.len() - 1);
+        self.col = self.col.min(self.line_len());
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        self.lines[self.row]
+            .char_indices()
+            .nth(self.col)
+            .map_or(self.lines[self.row].len(), |(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editing_splits_and_joins_lines() {
+        let mut state = NotesState::new("x".into(), PathBuf::from("x.md"), "héllo\n");
+        assert_eq!(state.cursor(), (0, 5));
+        state.move_left();
+        state.move_left();
+        state.newline();
+        assert_eq!(state.lines(), ["hél", "lo"]);
+        state.insert('!');
+        state.move_vertical(-1);
+        assert_eq!(state.cursor(), (0, 1));
+        assert_eq!(state.text(), "hél\n!lo\n");
+        state.move_vertical(1);
+        state.backspace();
+        state.backspace();
+        assert_eq!(state.lines(), ["héllo"]);
+        assert_eq!(state.cursor(), (0, 3));
+    }
+}
@@ -0,0 +1,260 @@
+use crate::config::QuickAction;
+
+/// Every dashboard action that can be triggered from a key or the command palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Action {
+    PrevWorkspace,
+    NextWorkspace,
+    PrevTab,
+    NextTab,
+    NewTab,
+    CloseTab,
+    FocusTerminal,
+    SearchTerminal,
+    ExportScrollback,
+    FollowServiceLogs,
+    ToggleContext,
+    AddWorktree,
+    CloneWorktree,
+    PruneWorktree,
+    ShowLog,
+    EditNotes,
+    CopyPath,
+    JumpHere,
+    LastWorkspace,
+    RecentWorkspaces,
+    QuickActions,
+    RunQuick(usize),
+    RefreshWorktrees,
+    CommandPalette,
+    Help,
+    Quit,
+}
+
+/// Actions listed in the palette, in display order (quick actions are appended).
+const PALETTE_ACTIONS: &[Action] = &[
+    Action::AddWorktree,
+    Action::CloneWorktree,
+    Action::PruneWorktree,
+    Action::ShowLog,
+    Action::EditNotes,
+    Action::CopyPath,
+    Action::JumpHere,
+    Action::LastWorkspace,
+    Action::RecentWorkspaces,
+    Action::NewTab,
+    Action::CloseTab,
+    Action::FocusTerminal,
+    Action::SearchTerminal,
+    Action::ExportScrollback,
+    Action::FollowServiceLogs,
+    Action::ToggleContext,
+    Action::QuickActions,
+    Action::RefreshWorktrees,
+    Action::Help,
+    Action::Quit,
+];
+
+impl Action {
+    pub(super) fn label(self) -> &'static str {
+        match self {
+            Action::PrevWorkspace => "Previous worktree",
+            Action::NextWorkspace => "Next worktree",
+            Action::PrevTab => "Previous tab",
+            Action::NextTab => "Next tab",
+            Action::NewTab => "New tab",
+            Action::CloseTab => "Close tab",
+            Action::FocusTerminal => "Focus terminal",
+            Action::SearchTerminal => "Search terminal output",
+            Action::ExportScrollback => "Save terminal output to a log file",
+            Action::FollowServiceLogs => "Follow docker compose service logs",
+            Action::ToggleContext => "Toggle context panel",
+            Action::AddWorktree => "Add worktree",
+            Action::CloneWorktree => "Clone branch into new worktree",
+            Action::PruneWorktree => "Prune worktree",
+            Action::ShowLog => "Show recent commits",
+            Action::EditNotes => "Edit notes",
+            Action::CopyPath => "Copy path",
+            Action::JumpHere => "Jump to launch worktree",
+            Action::LastWorkspace => "Switch to last worktree",
+            Action::RecentWorkspaces => "Recent worktrees",
+            Action::QuickActions => "Quick actions",
+            Action::RunQuick(_) => "Run quick action",
+            Action::RefreshWorktrees => "Refresh worktrees",
+            Action::CommandPalette => "Command palette",
+            Action::Help => "Help",
+            Action::Quit => "Quit",
+        }
+    }
+
+    /// Key that triggers the action in navigation mode, if any.
+    pub(super) fn key_hint(self) -> Option<&'static str> {
+        match self {
+            Action::PrevWorkspace => Some("↑"),
+            Action::NextWorkspace => Some("↓"),
+            Action::PrevTab => Some("←"),
+            Action::NextTab => Some("→"),
+            Action::NewTab => Some("n"),
+            Action::CloseTab => Some("x"),
+            Action::FocusTerminal => Some("Enter"),
+            Action::SearchTerminal => Some("/"),
+            Action::ExportScrollback => Some("e"),
+            Action::FollowServiceLogs => Some("l"),
+            Action::ToggleContext => Some("i"),
+            Action::AddWorktree => Some("a"),
+            Action::CloneWorktree => Some("C"),
+            Action::PruneWorktree => Some("p"),
+            Action::ShowLog => Some("g"),
+            Action::EditNotes => Some("m"),
+            Action::CopyPath => Some("y"),
+            Action::JumpHere => Some("h"),
+            Action::LastWorkspace => Some("Tab"),
+            Action::RecentWorkspaces => Some("r"),
+            Action::QuickActions => Some("c"),
+            Action::CommandPalette => Some(":"),
+            Action::Help => Some("?"),
+            Action::Quit => Some("q"),
+            Action::RunQuick(_) | Action::RefreshWorktrees => None,
+        }
+    }
+}
+
+/// A palette row: the action plus the text shown and matched against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct PaletteEntry {
+    pub(super) action: Action,
+    pub(super) label: String,
+    pub(super) key_hint: Option<&'static str>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct PaletteState {
+    query: String,
+    selected: usize,
+}
+
+impl PaletteState {
+    pub(super) fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub(super) fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub(super) fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    pub(super) fn backspace(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    pub(super) fn move_up(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        self.selected = if self.selected == 0 {
+            len - 1
+        } else {
+            self.selected - 1
+        };
+    }
+
+    pub(super) fn move_down(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected + 1) % len;
+    }
+
+    /// Entries whose label contains the query (case-insensitive).
+    pub(super) fn entries(&self, quick_actions: &[QuickAction]) -> Vec<PaletteEntry> {
+        let query = self.query.trim().to_lowercase();
+        all_entries(quick_actions)
+            .into_iter()
+            .filter(|entry| query.is_empty() || entry.label.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// The highlighted action after filtering, if anything matches.
+    pub(super) fn selected_action(&self, quick_actions: &[QuickAction]) -> Option<Action> {
+        let entries = self.entries(quick_actions);
+        entries
+            .get(self.selected.min(entries.len().saturating_sub(1)))
+            .map(|entry| entry.action)
+    }
+}
+
+fn all_entries(quick_actions: &[QuickAction]) -> Vec<PaletteEntry> {
+    let builtin = PALETTE_ACTIONS.iter().map(|&action| PaletteEntry {
+        action,
+        label: action.label().to_string(),
+        key_hint: action.key_hint(),
+    });
+    let quick = quick_actions
+        .iter()
+        .enumerate()
+        .map(|(idx, quick)| PaletteEntry {
+            action: Action::RunQuick(idx),
+            label: format!("{}: {}", Action::RunQuick(idx).label(), quick.label),
+            key_hint: None,
+        });
+    builtin.chain(quick).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quick(label: &str) -> QuickAction {
+        QuickAction {
+            label: label.into(),
+            command: "true".into(),
+        }
+    }
+
+    #[test]
+    fn entries_filter_case_insensitively() {
+        let mut state = PaletteState::default();
+        for c in "WORKTREE".chars() {
+            state.push_char(c);
+        }
+        let actions: Vec<Action> = state.entries(&[]).iter().map(|e| e.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                Action::AddWorktree,
+                Action::CloneWorktree,
+                Action::PruneWorktree,
+                Action::JumpHere,
+                Action::LastWorkspace,
+                Action::RecentWorkspaces,
+                Action::RefreshWorktrees
+            ]
+        );
+    }
+
+    #[test]
+    fn entries_include_configured_quick_actions() {
+        let mut state = PaletteState::default();
+        for c in "lint".chars() {
+            state.push_char(c);
+        }
+        let entries = state.entries(&[quick("Build"), quick("Lint")]);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, Action::RunQuick(1));
+        assert_eq!(entries[0].label, "Run quick action: Lint");
+    }
+
+    #[test]
+    fn selected_action_is_clamped_to_matches() {
+        let mut state = PaletteState::default();
+        state.move_up(PALETTE_ACTIONS.len());
+        assert_eq!(state.selected_action(&[]), Some(Action::Quit));
+        state.push_char('z');
+        assert_eq!(state.selected_action(&[]), None);
+    }
+}
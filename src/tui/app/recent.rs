@@ -0,0 +1,97 @@
+//! Worktrees in the order they were last focused, for jumping back and forth
+//! (Tab) or picking one of the latest from a list (r).
+//!
+//! Entries are paths rather than sidebar indices so they survive refreshes that
+//! add, remove, or reorder worktrees.
+
+use std::path::{Path, PathBuf};
+
+/// How many worktrees the list remembers; also the digits `1`–`9` in the picker.
+pub(super) const RECENT_LIMIT: usize = 9;
+
+#[derive(Debug, Default)]
+pub(super) struct RecentWorkspaces {
+    /// Most recently focused first.
+    paths: Vec<PathBuf>,
+}
+
+impl RecentWorkspaces {
+    /// Move `path` to the front.
+    pub(super) fn focus(&mut self, path: &Path) {
+        self.paths.retain(|recent| recent != path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(RECENT_LIMIT);
+    }
+
+    /// The worktree focused before the current one.
+    pub(super) fn previous(&self) -> Option<&Path> {
+        self.paths.get(1).map(PathBuf::as_path)
+    }
+
+    pub(super) fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Forget worktrees for which `exists` is false, e.g. after a removal.
+    pub(super) fn retain(&mut self, exists: impl Fn(&Path) -> bool) {
+        self.paths.retain(|path| exists(path));
+    }
+}
+
+/// Highlighted row of the recent-worktrees picker.
+#[derive(Debug, Default)]
+pub(super) struct RecentPicker {
+    pub(super) selected: usize,
+}
+
+impl RecentPicker {
+    pub(super) fn move_up(&mut self, len: usize) {
+        if len > 0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+
+    pub(super) fn move_down(&mut self, len: usize) {
+        if len > 0 {
+            self.selected = (self.selected + 1) % len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggling_returns_to_the_previous_worktree() {
+        let (a, b, c) = (Path::new("/ws/a"), Path::new("/ws/b"), Path::new("/ws/c"));
+        let mut recent = RecentWorkspaces::default();
+        recent.focus(a);
+        recent.focus(b);
+        assert_eq!(recent.previous(), Some(a));
+
+        // Toggling focuses the previous worktree, which swaps the two.
+        let previous = recent.previous().unwrap().to_path_buf();
+        recent.focus(&previous);
+        assert_eq!(recent.paths(), [a, b]);
+        assert_eq!(recent.previous(), Some(b));
+
+        recent.focus(c);
+        assert_eq!(recent.paths(), [c, a, b]);
+        recent.focus(b);
+        assert_eq!(recent.paths(), [b, c, a]);
+
+        recent.retain(|path| path != c);
+        assert_eq!(recent.paths(), [b, a]);
+    }
+
+    #[test]
+    fn the_list_keeps_only_the_latest_worktrees() {
+        let mut recent = RecentWorkspaces::default();
+        for n in 0..RECENT_LIMIT + 3 {
+            recent.focus(&PathBuf::from(format!("/ws/{n}")));
+        }
+        assert_eq!(recent.paths().len(), RECENT_LIMIT);
+        assert_eq!(recent.paths()[0], Path::new("/ws/11"));
+    }
+}
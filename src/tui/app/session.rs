@@ -0,0 +1,109 @@
+//! Dashboard state remembered between runs (currently the last selected worktree).
+
+use crate::wtm_paths::atomic_write_json;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// File inside `.wtm/` holding the persisted session.
+const SESSION_FILE: &str = "session.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionFile {
+    #[serde(default, rename = "lastWorkspace")]
+    last_workspace: Option<PathBuf>,
+}
+
+/// Read the worktree that was selected when the dashboard last exited.
+///
+/// Missing or unreadable files are treated as "no saved selection".
+pub(super) fn load_last_workspace(wtm_dir: &Path) -> Option<PathBuf> {
+    let path = wtm_dir.join(SESSION_FILE);
+    let data = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<SessionFile>(&data) {
+        Ok(session) => session.last_workspace,
+        Err(err) => {
+            log::warn!("ignoring unreadable {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Remember `workspace` as the selection to restore on the next start.
+pub(super) fn save_last_workspace(wtm_dir: &Path, workspace: &Path) -> Result<()> {
+    let path = wtm_dir.join(SESSION_FILE);
+    let session = SessionFile {
+        last_workspace: Some(workspace.to_path_buf()),
+    };
+    atomic_write_json(&path, &session)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Index of `saved` among `paths`, defaulting to the first worktree.
+pub(super) fn restored_selection<'a>(
+    paths: impl IntoIterator<Item = &'a Path>,
+    saved: Option<&Path>,
+) -> usize {
+    saved
+        .and_then(|saved| paths.into_iter().position(|path| path == saved))
+        .unwrap_or(0)
+}
+
+/// Index of the worktree containing `cwd`; the deepest match wins so that linked
+/// worktrees nested under the primary one are told apart from it.
+pub(super) fn containing_worktree<'a>(
+    paths: impl IntoIterator<Item = &'a Path>,
+    cwd: &Path,
+) -> Option<usize> {
+    paths
+        .into_iter()
+        .enumerate()
+        .filter(|(_, path)| cwd.starts_with(path))
+        .max_by_key(|(_, path)| path.components().count())
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn restored_selection_matches_saved_path() {
+        let paths = [Path::new("/repo"), Path::new("/repo/.wtm/workspaces/a")];
+        assert_eq!(
+            restored_selection(paths, Some(Path::new("/repo/.wtm/workspaces/a"))),
+            1
+        );
+        assert_eq!(restored_selection(paths, Some(Path::new("/gone"))), 0);
+        assert_eq!(restored_selection(paths, None), 0);
+    }
+
+    #[test]
+    fn containing_worktree_prefers_the_deepest_match() {
+        let paths = [
+            Path::new("/repo"),
+            Path::new("/repo/.wtm/workspaces/a"),
+            Path::new("/repo/.wtm/workspaces/ab"),
+        ];
+        let here = |cwd: &str| containing_worktree(paths, Path::new(cwd));
+        assert_eq!(here("/repo/.wtm/workspaces/a/src/bin"), Some(1));
+        assert_eq!(here("/repo/.wtm/workspaces/ab"), Some(2));
+        assert_eq!(here("/repo/src"), Some(0));
+        assert_eq!(here("/elsewhere"), None);
+    }
+
+    #[test]
+    fn last_workspace_round_trips() {
+        let dir = tempdir().unwrap();
+        assert_eq!(load_last_workspace(dir.path()), None);
+        save_last_workspace(dir.path(), Path::new("/repo/feature")).unwrap();
+        assert_eq!(
+            load_last_workspace(dir.path()),
+            Some(PathBuf::from("/repo/feature"))
+        );
+    }
+}
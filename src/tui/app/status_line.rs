@@ -0,0 +1,265 @@
+//! The `statusLine` template shown in the bottom bar when there is no status
+//! message, e.g. `{workspace} [{branch}] ↑{ahead}↓{behind} {dirty} {time}`.
+//!
+//! `{ahead}`, `{behind}`, and `{dirty}` need `git status`, which runs on a
+//! worker thread from the event loop and is reused for a few seconds; drawing
+//! only reads the last result.
+
+use crate::{
+    git::status::{self, GitStatusSummary},
+    parallel,
+};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        mpsc::{Receiver, TryRecvError},
+        OnceLock,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// What the bar shows when `statusLine` is not configured.
+pub(super) const DEFAULT_HINT: &str =
+    "q: quit • a: add • p: prune • i: context • :: commands • ?: help";
+
+/// Tokens filled in from `git status`.
+const STATUS_TOKENS: [&str; 3] = ["{ahead}", "{behind}", "{dirty}"];
+
+/// How long a worktree's status is reused before it is read again.
+const STATUS_TTL: Duration = Duration::from_secs(5);
+
+/// The current values for the template's tokens.
+#[derive(Debug, Default)]
+pub(super) struct StatusLineValues<'a> {
+    pub(super) workspace: &'a str,
+    pub(super) branch: Option<&'a str>,
+    /// `None` when git status was not needed or failed; its tokens show `?`.
+    pub(super) status: Option<&'a GitStatusSummary>,
+    pub(super) time: String,
+}
+
+/// Fill in `{workspace}`, `{branch}`, `{ahead}`, `{behind}`, `{dirty}`, and
+/// `{time}`. Anything else in braces is kept as written.
+pub(super) fn render_status_line(template: &str, values: &StatusLineValues) -> String {
+    let value = |token: &str| -> Option<String> {
+        let status = values.status;
+        Some(match token {
+            "workspace" => values.workspace.to_string(),
+            "branch" => values.branch.unwrap_or("(detached)").to_string(),
+            "ahead" => status.map_or("?".into(), |s| s.ahead.to_string()),
+            "behind" => status.map_or("?".into(), |s| s.behind.to_string()),
+            "dirty" => match status {
+                None => "?".into(),
+                Some(s) if s.staged + s.unstaged + s.untracked + s.conflicts > 0 => "dirty".into(),
+                Some(_) => String::new(),
+            },
+            "time" => values.time.clone(),
+            _ => return None,
+        })
+    };
+
+    let mut line = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(len) = rest[open..].find('}') else {
+            break;
+        };
+        line.push_str(&rest[..open]);
+        let placeholder = &rest[open..=open + len];
+        match value(&placeholder[1..placeholder.len() - 1]) {
+            Some(value) => line.push_str(&value),
+            None => line.push_str(placeholder),
+        }
+        rest = &rest[open + len + 1..];
+    }
+    line.push_str(rest);
+    line
+}
+
+/// Whether `template` uses any token that needs git status.
+pub(super) fn needs_status(template: &str) -> bool {
+    STATUS_TOKENS.iter().any(|token| template.contains(token))
+}
+
+/// The selected worktree's git status, read in the background and reused for
+/// [`STATUS_TTL`].
+#[derive(Debug, Default)]
+pub(super) struct StatusCache {
+    entry: Option<(PathBuf, Instant, Option<GitStatusSummary>)>,
+    /// The read in flight, if any, for the worktree at the path.
+    pending: Option<(PathBuf, Receiver<Option<GitStatusSummary>>)>,
+}
+
+impl StatusCache {
+    /// The last status read for `path`, without running git.
+    pub(super) fn get(&self, path: &Path) -> Option<&GitStatusSummary> {
+        match &self.entry {
+            Some((cached, _, summary)) if cached == path => summary.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Take a finished read, and start a new one for `path` once its status is
+    /// older than [`STATUS_TTL`]; returns whether a result arrived.
+    pub(super) fn refresh(&mut self, path: &Path, now: Instant) -> bool {
+        self.refresh_with(path, now, |path| {
+            status::status(path)
+                .map_err(|err| log::debug!("status line: git status failed: {err:#}"))
+                .ok()
+        })
+    }
+
+    fn refresh_with(
+        &mut self,
+        path: &Path,
+        now: Instant,
+        read: impl FnOnce(&Path) -> Option<GitStatusSummary> + Send + 'static,
+    ) -> bool {
+        let mut arrived = false;
+        if let Some((pending_path, receiver)) = &self.pending {
+            match receiver.try_recv() {
+                Ok(summary) => {
+                    self.entry = Some((pending_path.clone(), now, summary));
+                    self.pending = None;
+                    arrived = true;
+                }
+                Err(TryRecvError::Disconnected) => self.pending = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+        let fresh = matches!(&self.entry, Some((cached, read_at, _))
+            if cached == path && now.saturating_duration_since(*read_at) < STATUS_TTL);
+        let reading = matches!(&self.pending, Some((pending, _)) if pending == path);
+        if !fresh && !reading {
+            let worktree = path.to_path_buf();
+            let receiver = parallel::background(move || read(&worktree));
+            self.pending = Some((path.to_path_buf(), receiver));
+        }
+        arrived
+    }
+}
+
+/// `HH:MM` wall-clock time for `now`, `utc_offset` seconds east of UTC.
+pub(super) fn clock(now: SystemTime, utc_offset: i64) -> String {
+    let secs = now
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() as i64);
+    let minutes = (secs + utc_offset).rem_euclid(24 * 60 * 60) / 60;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// The local offset from UTC in seconds, asked of `date +%z` once; UTC where
+/// that is unavailable (e.g. on Windows).
+pub(super) fn local_utc_offset() -> i64 {
+    static OFFSET: OnceLock<i64> = OnceLock::new();
+    *OFFSET.get_or_init(|| {
+        Command::new("date")
+            .arg("+%z")
+            .output()
+            .ok()
+            .and_then(|output| parse_utc_offset(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or(0)
+    })
+}
+
+/// Seconds east of UTC for a `+hhmm`/`-hhmm` offset.
+fn parse_utc_offset(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (sign, digits) = match text.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_filled_in_and_unknown_ones_kept() {
+        let status = GitStatusSummary {
+            ahead: 2,
+            behind: 1,
+            unstaged: 3,
+            ..GitStatusSummary::default()
+        };
+        let values = StatusLineValues {
+            workspace: "feature-x",
+            branch: Some("feature/x"),
+            status: Some(&status),
+            time: "09:41".into(),
+        };
+        assert_eq!(
+            render_status_line(
+                "{workspace} [{branch}] ↑{ahead}↓{behind} {dirty} {time} {nope}",
+                &values
+            ),
+            "feature-x [feature/x] ↑2↓1 dirty 09:41 {nope}"
+        );
+        assert_eq!(render_status_line("no tokens", &values), "no tokens");
+        assert_eq!(render_status_line("open { brace", &values), "open { brace");
+
+        let missing = StatusLineValues {
+            workspace: "repo",
+            ..StatusLineValues::default()
+        };
+        assert_eq!(
+            render_status_line("{workspace} {branch} {ahead}/{behind} {dirty}", &missing),
+            "repo (detached) ?/? ?"
+        );
+        let clean = GitStatusSummary::default();
+        let values = StatusLineValues {
+            status: Some(&clean),
+            ..missing
+        };
+        assert_eq!(render_status_line("[{dirty}]", &values), "[]");
+
+        assert!(needs_status("{dirty}"));
+        assert!(!needs_status("{workspace} {time}"));
+    }
+
+    #[test]
+    fn status_is_read_in_the_background_and_reused() {
+        let path = Path::new("/ws/feature-x");
+        let start = Instant::now();
+        let mut cache = StatusCache::default();
+        let read = |_: &Path| {
+            Some(GitStatusSummary {
+                ahead: 1,
+                ..GitStatusSummary::default()
+            })
+        };
+        assert!(!cache.refresh_with(path, start, read));
+        assert_eq!(cache.get(path), None, "nothing until the worker reports");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !cache.refresh_with(path, start, |_| panic!("already reading")) {
+            assert!(Instant::now() < deadline, "worker never reported");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(cache.get(path).map(|s| s.ahead), Some(1));
+        assert_eq!(cache.get(Path::new("/ws/other")), None);
+
+        assert!(!cache.refresh_with(path, start, |_| panic!("still fresh")));
+        assert!(cache.pending.is_none());
+        assert!(!cache.refresh_with(path, start + STATUS_TTL, |_| None));
+        assert!(cache.pending.is_some(), "stale status is read again");
+    }
+
+    #[test]
+    fn clock_applies_the_utc_offset() {
+        let at = UNIX_EPOCH + Duration::from_secs(23 * 3600 + 30 * 60);
+        assert_eq!(clock(at, 0), "23:30");
+        assert_eq!(clock(at, parse_utc_offset("+0200\n").unwrap()), "01:30");
+        assert_eq!(clock(at, parse_utc_offset("-0930").unwrap()), "14:00");
+        assert_eq!(parse_utc_offset("UTC"), None);
+    }
+}
@@ -0,0 +1,195 @@
+//! Find-in-scrollback for the active terminal tab (`/`, then n/N).
+//!
+//! Matches are case-insensitive substrings of the scrollback and screen lines,
+//! numbered from the oldest scrollback line, which keeps them in place while
+//! the tab prints more below (until the scrollback is full and drops lines).
+
+/// A hit, as a line of the scrollback+screen buffer and character columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct SearchMatch {
+    pub(super) line: usize,
+    pub(super) column: usize,
+    pub(super) len: usize,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct TerminalSearch {
+    query: String,
+    /// Typing the query; otherwise n/N step through the matches.
+    editing: bool,
+    /// The match in view.
+    current: Option<SearchMatch>,
+}
+
+impl TerminalSearch {
+    pub(super) fn new() -> Self {
+        Self {
+            editing: true,
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub(super) fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub(super) fn current(&self) -> Option<SearchMatch> {
+        self.current
+    }
+
+    pub(super) fn push_char(&mut self, c: char) {
+        self.query.push(c);
+    }
+
+    pub(super) fn backspace(&mut self) {
+        self.query.pop();
+    }
+
+    /// Start typing a new query.
+    pub(super) fn restart(&mut self) {
+        self.query.clear();
+        self.editing = true;
+        self.current = None;
+    }
+
+    /// Move to the next match above (`older`) or below the one in view,
+    /// wrapping around; the first step from a new query lands on the last
+    /// match. Returns the match and its 1-based number, or `None` if the query
+    /// does not occur in `lines`.
+    pub(super) fn step(&mut self, lines: &[String], older: bool) -> Option<(SearchMatch, usize)> {
+        self.editing = false;
+        let matches = find_matches(lines, &self.query);
+        let index = match self.current.map(|current| (current.line, current.column)) {
+            None => matches.len().checked_sub(1)?,
+            Some(at) if older => matches
+                .iter()
+                .rposition(|found| (found.line, found.column) < at)
+                .unwrap_or(matches.len().checked_sub(1)?),
+            Some(at) => matches
+                .iter()
+                .position(|found| (found.line, found.column) > at)
+                .unwrap_or(0),
+        };
+        let found = *matches.get(index)?;
+        self.current = Some(found);
+        Some((found, index + 1))
+    }
+}
+
+/// Every case-insensitive occurrence of `query` in `lines`, top to bottom.
+pub(super) fn find_matches(lines: &[String], query: &str) -> Vec<SearchMatch> {
+    let needle: Vec<char> = query.chars().map(fold).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let mut matches = Vec::new();
+    for (line, text) in lines.iter().enumerate() {
+        let haystack: Vec<char> = text.chars().map(fold).collect();
+        for (column, window) in haystack.windows(needle.len()).enumerate() {
+            if window == needle.as_slice() {
+                matches.push(SearchMatch {
+                    line,
+                    column,
+                    len: needle.len(),
+                });
+            }
+        }
+    }
+    matches
+}
+
+/// Lowercase one character, keeping columns aligned with the original.
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// The scrollback offset that brings `line` into a `rows`-high view, as near
+/// the middle as the buffer allows. Lines on the live screen need none.
+pub(super) fn scrollback_offset(line: usize, history_len: usize, rows: usize) -> usize {
+    if line >= history_len {
+        return 0;
+    }
+    (history_len + rows / 2)
+        .saturating_sub(line)
+        .min(history_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(parser: &vt100::Parser) -> Vec<String> {
+        parser.screen().all_rows(0, 40).collect()
+    }
+
+    #[test]
+    fn matches_in_scrollback_scroll_into_view() {
+        let mut parser = vt100::Parser::new(5, 40, 100);
+        for n in 0..30 {
+            let text = if n == 7 {
+                "Build ERROR in foo.rs"
+            } else {
+                "ok"
+            };
+            parser.process(format!("{n:02} {text}\r\n").as_bytes());
+        }
+        let all = lines(&parser);
+        let history_len = parser.screen().scrollback_buffer_len();
+        assert_eq!(history_len, 26);
+        assert_eq!(all.len(), history_len + 5);
+
+        assert_eq!(
+            find_matches(&all, "error"),
+            [SearchMatch {
+                line: 7,
+                column: 9,
+                len: 5
+            }]
+        );
+        assert!(find_matches(&all, "").is_empty());
+
+        let offset = scrollback_offset(7, history_len, 5);
+        parser.set_scrollback(offset);
+        let top = history_len - parser.screen().scrollback();
+        let visible: Vec<String> = parser.screen().rows(0, 40).collect();
+        assert_eq!(visible[7 - top], "07 Build ERROR in foo.rs");
+        assert_eq!(top, 5, "the match sits mid-screen");
+
+        assert_eq!(scrollback_offset(0, history_len, 5), history_len);
+        assert_eq!(scrollback_offset(history_len + 2, history_len, 5), 0);
+    }
+
+    #[test]
+    fn n_and_shift_n_cycle_from_the_newest_match() {
+        let text: Vec<String> = ["fail a", "ok", "FAIL b fail", "ok"]
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        let mut search = TerminalSearch::new();
+        for c in "fail".chars() {
+            search.push_char(c);
+        }
+        let at = |step: Option<(SearchMatch, usize)>| step.map(|(m, n)| (m.line, m.column, n));
+
+        assert_eq!(at(search.step(&text, true)), Some((2, 7, 3)));
+        assert!(!search.is_editing());
+        assert_eq!(at(search.step(&text, true)), Some((2, 0, 2)));
+        assert_eq!(at(search.step(&text, true)), Some((0, 0, 1)));
+        assert_eq!(at(search.step(&text, true)), Some((2, 7, 3)), "wraps");
+        assert_eq!(at(search.step(&text, false)), Some((0, 0, 1)), "wraps");
+
+        // Stepping carries on from the same match after more output.
+        let mut longer = text.clone();
+        longer.push("more".into());
+        assert_eq!(at(search.step(&longer, false)), Some((2, 0, 2)));
+        assert_eq!(search.current().map(|m| m.len), Some(4));
+
+        search.restart();
+        search.push_char('x');
+        assert_eq!(search.step(&text, true), None);
+    }
+}
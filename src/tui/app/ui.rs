@@ -1,7 +1,9 @@
 use super::{
     add_worktree::{AddWorktreeState, Suggestion},
+    terminal_search::SearchMatch,
     App, Mode,
 };
+use crate::{pins::PIN_MARKER, tags};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -12,7 +14,11 @@ use ratatui::{
     },
     Frame,
 };
-use tui_term::widget::{Cursor, PseudoTerminal};
+use std::sync::RwLock;
+use tui_term::{
+    vt100,
+    widget::{Cursor, PseudoTerminal},
+};
 
 pub(super) fn draw(app: &mut App, frame: &mut Frame<'_>) {
     let area = frame.area();
@@ -49,6 +55,24 @@ pub(super) fn draw(app: &mut App, frame: &mut Frame<'_>) {
     if matches!(app.mode, Mode::Help) {
         draw_help_overlay(app, frame, root[0]);
     }
+    if matches!(app.mode, Mode::Notes) {
+        draw_notes_overlay(app, frame, root[0]);
+    }
+    if matches!(app.mode, Mode::Log) {
+        draw_log_overlay(app, frame, root[0]);
+    }
+    if matches!(app.mode, Mode::Removing) {
+        draw_remove_overlay(app, frame, root[0]);
+    }
+    if matches!(app.mode, Mode::Palette) {
+        draw_palette_overlay(app, frame, root[0]);
+    }
+    if matches!(app.mode, Mode::Recent) {
+        draw_recent_overlay(app, frame, root[0]);
+    }
+    if matches!(app.mode, Mode::ServiceLogs) {
+        draw_service_logs_overlay(app, frame, root[0]);
+    }
     draw_status(app, frame, root[1]);
 }
 
@@ -58,14 +82,34 @@ fn draw_sidebar(app: &App, frame: &mut Frame<'_>, area: Rect) {
         state.select(Some(app.selected_workspace));
     }
 
+    let here = app.here_index();
     let items: Vec<ListItem> = app
         .workspaces
         .iter()
-        .map(|ws| ListItem::new(Line::from(ws.sidebar_label(&app.repo_root))))
+        .enumerate()
+        .map(|(index, ws)| {
+            let mut label =
+                ws.sidebar_label(&app.repo_root, here == Some(index), app.max_slug_length);
+            if app.pins.is_pinned(ws.path()) {
+                label.push(' ');
+                label.push_str(PIN_MARKER);
+            }
+            let tags = app.tags.get(ws.path());
+            if !tags.is_empty() {
+                label.push(' ');
+                label.push_str(&tags::label(&tags));
+            }
+            ListItem::new(Line::from(label))
+        })
         .collect();
 
+    let title = if app.bare {
+        "Worktrees (bare repo)"
+    } else {
+        "Worktrees"
+    };
     let list = List::new(items)
-        .block(Block::default().title("Worktrees").borders(Borders::ALL))
+        .block(Block::default().title(title).borders(Borders::ALL))
         .highlight_style(
             Style::default()
                 .fg(Color::Yellow)
@@ -129,7 +173,7 @@ fn draw_main(app: &mut App, frame: &mut Frame<'_>, area: Rect) {
     let tabs = Tabs::new(titles)
         .block(
             Block::default()
-                .title(workspace.display_path())
+                .title(workspace.display_path(app.path_display, &app.repo_root))
                 .borders(Borders::ALL),
         )
         .highlight_style(
@@ -173,28 +217,22 @@ fn draw_main(app: &mut App, frame: &mut Frame<'_>, area: Rect) {
         if let Some(size) = terminal_size {
             tab.resize_to(size);
             let parser = tab.parser_handle();
-            let screen_guard = parser.read().expect("terminal parser poisoned");
-            let cursor = Cursor::default().visibility(matches!(app.mode, Mode::TerminalInput));
-            let terminal_widget = PseudoTerminal::new(screen_guard.screen()).cursor(cursor);
-            frame.render_widget(terminal_widget, terminal_inner);
-
-            if let Some(area) = scrollbar_area {
-                if area.height > 0 && size.rows > 0 {
-                    let screen = screen_guard.screen();
-                    let history_len = screen.scrollback_buffer_len();
-                    let viewport = usize::from(size.rows);
-                    let total_rows = history_len + viewport;
-                    if total_rows > viewport {
-                        let offset = screen.scrollback();
-                        let max_position = total_rows.saturating_sub(viewport);
-                        let top_position = history_len.saturating_sub(offset).min(max_position);
-                        let mut scrollbar_state = ScrollbarState::new(total_rows)
-                            .position(top_position)
-                            .viewport_content_length(viewport);
-                        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight);
-                        frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
-                    }
-                }
+            let show_cursor = matches!(app.mode, Mode::TerminalInput | Mode::InsertBranch);
+            let highlight = match app.mode {
+                Mode::TerminalSearch => app.terminal_search.as_ref().and_then(|s| s.current()),
+                _ => None,
+            };
+            let rendered = render_terminal(
+                frame,
+                terminal_inner,
+                scrollbar_area,
+                size,
+                &parser,
+                show_cursor,
+                highlight,
+            );
+            if !rendered {
+                tab.mark_unavailable();
             }
         }
     } else if terminal_inner.width > 0 && terminal_inner.height > 0 {
@@ -204,17 +242,89 @@ fn draw_main(app: &mut App, frame: &mut Frame<'_>, area: Rect) {
         );
     }
 
-    if matches!(app.mode, Mode::Adding) {
+    if matches!(app.mode, Mode::Adding | Mode::InsertBranch) {
         if let Some(state) = app.add_state.as_ref() {
             if state.overlay_visible() {
                 let overlay_area = centered_rect(60, 50, chunks[1]);
                 frame.render_widget(Clear, overlay_area);
-                render_add_worktree_overlay(frame, overlay_area, state);
+                let title = match app.mode {
+                    Mode::InsertBranch => {
+                        "Insert into terminal (Enter: type it • Esc: cancel)".into()
+                    }
+                    _ => overlay_title(state),
+                };
+                render_add_worktree_overlay(frame, overlay_area, state, title);
             }
         }
     }
 }
 
+/// Render the terminal screen and its scrollbar, marking `highlight` if it
+/// is in view.
+///
+/// Returns `false` (after drawing a placeholder) when the parser lock is poisoned.
+fn render_terminal(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    scrollbar_area: Option<Rect>,
+    size: super::TerminalSize,
+    parser: &RwLock<vt100::Parser>,
+    show_cursor: bool,
+    highlight: Option<SearchMatch>,
+) -> bool {
+    let Ok(screen_guard) = parser.read() else {
+        frame.render_widget(
+            Paragraph::new("[terminal unavailable]").style(Style::default().fg(Color::Red)),
+            area,
+        );
+        return false;
+    };
+    let cursor = Cursor::default().visibility(show_cursor);
+    let terminal_widget = PseudoTerminal::new(screen_guard.screen()).cursor(cursor);
+    frame.render_widget(terminal_widget, area);
+
+    if let Some(found) = highlight {
+        let screen = screen_guard.screen();
+        let top = screen
+            .scrollback_buffer_len()
+            .saturating_sub(screen.scrollback());
+        let row = found
+            .line
+            .checked_sub(top)
+            .filter(|row| *row < usize::from(area.height));
+        if let Some(row) = row {
+            let x = area
+                .x
+                .saturating_add(u16::try_from(found.column).unwrap_or(u16::MAX));
+            let width = u16::try_from(found.len).unwrap_or(u16::MAX);
+            let marked = Rect::new(x, area.y + row as u16, width, 1).intersection(area);
+            frame
+                .buffer_mut()
+                .set_style(marked, Style::default().fg(Color::Black).bg(Color::Yellow));
+        }
+    }
+
+    if let Some(area) = scrollbar_area {
+        if area.height > 0 && size.rows > 0 {
+            let screen = screen_guard.screen();
+            let history_len = screen.scrollback_buffer_len();
+            let viewport = usize::from(size.rows);
+            let total_rows = history_len + viewport;
+            if total_rows > viewport {
+                let offset = screen.scrollback();
+                let max_position = total_rows.saturating_sub(viewport);
+                let top_position = history_len.saturating_sub(offset).min(max_position);
+                let mut scrollbar_state = ScrollbarState::new(total_rows)
+                    .position(top_position)
+                    .viewport_content_length(viewport);
+                let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight);
+                frame.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
+            }
+        }
+    }
+    true
+}
+
 fn draw_context_panel(app: &mut App, frame: &mut Frame<'_>, area: Rect) {
     let mut lines: Vec<Line> = Vec::new();
     let header_style = Style::default()
@@ -227,6 +337,16 @@ fn draw_context_panel(app: &mut App, frame: &mut Frame<'_>, area: Rect) {
         .and_then(|workspace| app.workspace_contexts.get(workspace.path()));
 
     if let Some(context) = content {
+        if let Some(note) = context.note.as_deref() {
+            lines.push(Line::from(Span::styled("Note", header_style)));
+            lines.push(Line::from(format!("  {note}  (m: edit)")));
+            lines.push(Line::from(""));
+        }
+        if let Some(ticket) = context.jira.as_deref() {
+            lines.push(Line::from(Span::styled("Jira", header_style)));
+            lines.push(Line::from(format!("  {ticket}")));
+            lines.push(Line::from(""));
+        }
         if !context.git.is_empty() {
             lines.push(Line::from(Span::styled("Git", header_style)));
             for entry in &context.git {
@@ -326,11 +446,239 @@ fn draw_help_overlay(app: &App, frame: &mut Frame<'_>, area: Rect) {
     );
 }
 
+fn draw_log_overlay(app: &App, frame: &mut Frame<'_>, area: Rect) {
+    let Some(state) = app.log_state.as_ref() else {
+        return;
+    };
+    let overlay_area = centered_rect(70, 80, area);
+    frame.render_widget(Clear, overlay_area);
+    frame.render_widget(
+        Paragraph::new(state.lines().join("\n"))
+            .scroll((u16::try_from(state.scroll()).unwrap_or(u16::MAX), 0))
+            .block(
+                Block::default()
+                    .title(format!(
+                        "Recent commits — {} (↑/↓: scroll • Esc: close)",
+                        state.title()
+                    ))
+                    .borders(Borders::ALL),
+            ),
+        overlay_area,
+    );
+}
+
+fn draw_notes_overlay(app: &App, frame: &mut Frame<'_>, area: Rect) {
+    let Some(state) = app.notes_state.as_ref() else {
+        return;
+    };
+    let overlay_area = centered_rect(70, 80, area);
+    let inner_height = usize::from(overlay_area.height.saturating_sub(2)).max(1);
+    let (row, col) = state.cursor();
+    let scroll = row.saturating_sub(inner_height - 1);
+    frame.render_widget(Clear, overlay_area);
+    frame.render_widget(
+        Paragraph::new(state.lines().join("\n"))
+            .scroll((u16::try_from(scroll).unwrap_or(u16::MAX), 0))
+            .block(
+                Block::default()
+                    .title(format!("Notes — {} (Esc: save and close)", state.title()))
+                    .borders(Borders::ALL),
+            ),
+        overlay_area,
+    );
+    let x = (overlay_area.x + 1).saturating_add(u16::try_from(col).unwrap_or(u16::MAX));
+    let y = (overlay_area.y + 1).saturating_add(u16::try_from(row - scroll).unwrap_or(u16::MAX));
+    if x < overlay_area.right().saturating_sub(1) && y < overlay_area.bottom().saturating_sub(1) {
+        frame.set_cursor_position((x, y));
+    }
+}
+
+fn draw_remove_overlay(app: &App, frame: &mut Frame<'_>, area: Rect) {
+    let Some(state) = app.remove_state.as_ref() else {
+        return;
+    };
+    if state.is_stale() {
+        let lines = [
+            format!("Prune the stale entry for {}?", state.target().display()),
+            String::new(),
+            "Its directory is gone, so only git's record of it is dropped.".into(),
+            "This runs `git worktree prune`, which clears every stale entry.".into(),
+            String::new(),
+            "y: prune • n/Esc: cancel".into(),
+        ];
+        let overlay_area = centered_rect(60, 40, area);
+        frame.render_widget(Clear, overlay_area);
+        frame.render_widget(
+            Paragraph::new(lines.join("\n"))
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::default()
+                        .title("Prune worktree")
+                        .borders(Borders::ALL),
+                ),
+            overlay_area,
+        );
+        return;
+    }
+    let mut lines = vec![
+        format!("Remove {}?", state.target().display()),
+        String::new(),
+    ];
+    lines.extend(state.preview_lines());
+    lines.push(String::new());
+    match state.typed_name() {
+        Some(typed) => {
+            lines.push(format!(
+                "Type `{}` and press Enter to force-remove (Esc: cancel):",
+                state.name()
+            ));
+            lines.push(format!("> {typed}"));
+        }
+        None => lines.push(format!(
+            "y: remove • f: force ({}) • n/Esc: cancel",
+            if state.force() { "on" } else { "off" }
+        )),
+    }
+    let overlay_area = centered_rect(60, 50, area);
+    frame.render_widget(Clear, overlay_area);
+    frame.render_widget(
+        Paragraph::new(lines.join("\n")).block(
+            Block::default()
+                .title("Prune worktree")
+                .borders(Borders::ALL),
+        ),
+        overlay_area,
+    );
+}
+
+/// Recently selected worktrees, numbered for the digit shortcuts.
+fn draw_recent_overlay(app: &App, frame: &mut Frame<'_>, area: Rect) {
+    let overlay_area = centered_rect(50, 50, area);
+    frame.render_widget(Clear, overlay_area);
+    let items: Vec<ListItem> = app
+        .recent_indices()
+        .into_iter()
+        .enumerate()
+        .map(|(position, index)| {
+            let name = app.workspaces[index].info().name();
+            let current = if index == app.selected_workspace {
+                "  (current)"
+            } else {
+                ""
+            };
+            ListItem::new(format!("{}  {name}{current}", position + 1))
+        })
+        .collect();
+    let mut state = ListState::default();
+    if !items.is_empty() {
+        let selected = app
+            .recent_picker
+            .as_ref()
+            .map_or(0, |picker| picker.selected);
+        state.select(Some(selected.min(items.len() - 1)));
+    }
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title("Recent worktrees (1-9/Enter: switch • Esc: close)")
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▸ ");
+    frame.render_stateful_widget(list, overlay_area, &mut state);
+}
+
+/// The selected worktree's compose services, for following one's logs.
+fn draw_service_logs_overlay(app: &App, frame: &mut Frame<'_>, area: Rect) {
+    let Some(picker) = app.service_picker.as_ref() else {
+        return;
+    };
+    let overlay_area = centered_rect(50, 50, area);
+    frame.render_widget(Clear, overlay_area);
+    let items: Vec<ListItem> = picker
+        .services
+        .iter()
+        .map(|service| ListItem::new(service.as_str()))
+        .collect();
+    let mut state = ListState::default();
+    state.select(Some(picker.selected));
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title("Follow service logs (Enter: open tab • Esc: close)")
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▸ ");
+    frame.render_stateful_widget(list, overlay_area, &mut state);
+}
+
+fn draw_palette_overlay(app: &App, frame: &mut Frame<'_>, area: Rect) {
+    let Some(state) = app.palette_state.as_ref() else {
+        return;
+    };
+    let overlay_area = centered_rect(60, 60, area);
+    frame.render_widget(Clear, overlay_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(overlay_area);
+
+    frame.render_widget(
+        Paragraph::new(format!("> {}", state.query())).block(
+            Block::default()
+                .title("Command palette (Enter: run • Esc: close)")
+                .borders(Borders::ALL),
+        ),
+        chunks[0],
+    );
+
+    let entries = state.entries(&app.quick_actions);
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|entry| {
+            let mut spans = vec![Span::raw(entry.label.clone())];
+            if let Some(key) = entry.key_hint {
+                spans.push(Span::raw("  "));
+                spans.push(Span::styled(
+                    format!("[{key}]"),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let mut list_state = ListState::default();
+    if !entries.is_empty() {
+        list_state.select(Some(state.selected().min(entries.len() - 1)));
+    }
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▸ ");
+    frame.render_stateful_widget(list, chunks[1], &mut list_state);
+}
+
 fn draw_status(app: &mut App, frame: &mut Frame<'_>, area: Rect) {
-    let text = app
-        .status_message
-        .as_deref()
-        .unwrap_or("q: quit • a: add • p: prune • i: context • ?: help");
+    let text = match app.status_message.clone() {
+        Some(message) => message,
+        None => app.status_line_text(),
+    };
     frame.render_widget(
         Paragraph::new(text).style(Style::default().fg(Color::Gray)),
         area,
@@ -340,7 +688,12 @@ fn draw_status(app: &mut App, frame: &mut Frame<'_>, area: Rect) {
     app.render_status_fx(frame, area);
 }
 
-fn render_add_worktree_overlay(frame: &mut Frame<'_>, area: Rect, state: &AddWorktreeState) {
+fn render_add_worktree_overlay(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    state: &AddWorktreeState,
+    title: String,
+) {
     let items: Vec<ListItem> = state
         .filtered_suggestions()
         .map(|suggestion| match suggestion {
@@ -394,36 +747,56 @@ fn render_add_worktree_overlay(frame: &mut Frame<'_>, area: Rect, state: &AddWor
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol("▸ ")
-        .block(
-            Block::default()
-                .title("Jira tickets (Tab: insert • Ctrl+R: refresh • Ctrl+Shift+R: clear)")
-                .borders(Borders::ALL),
-        );
+        .block(Block::default().title(title).borders(Borders::ALL));
 
     frame.render_stateful_widget(list, area, &mut list_state);
 }
 
+fn overlay_title(state: &AddWorktreeState) -> String {
+    let title = match state.jira_hint() {
+        Some(hint) => format!("Branches — {hint} (Tab: insert • Ctrl+R: retry)"),
+        None => "Jira tickets (Tab: insert • Ctrl+R: refresh • Ctrl+Shift+R: clear)".into(),
+    };
+    match state.filter().label() {
+        Some(filter) => format!("[{filter} • Ctrl+A: all] {title}"),
+        None => title,
+    }
+}
+
 fn help_text(app: &App) -> String {
     let mut lines = vec![
         "Navigation".to_string(),
         "  ↑/↓: switch worktree".into(),
         "  ←/→: cycle tabs".into(),
-        "  Enter: focus terminal".into(),
+        "  Enter: focus terminal (Alt+I there: type a branch name from the suggestions)".into(),
         "  n: new tab".into(),
         "  x: close tab".into(),
         "  i: toggle context panel".into(),
         "  a: add worktree".into(),
+        "  C: clone branch into new worktree".into(),
         "  p: prune worktree".into(),
+        "  g: recent commits".into(),
+        "  m: edit the worktree's notes (Esc saves)".into(),
+        "  y: copy the worktree's path".into(),
+        "  h: jump to the worktree wtm was launched from".into(),
+        "  Tab: switch to the last selected worktree".into(),
+        "  r: recent worktrees (1-9 picks one)".into(),
         "  c: quick actions".into(),
+        "  : or Ctrl+P: command palette".into(),
+        "  /: search the terminal's scrollback (n/N: older/newer match, Esc: done)".into(),
+        "  e: save the tab's output to .wtm/logs".into(),
+        "  l: follow a docker compose service's logs in a new tab".into(),
         "  q: quit".into(),
         String::new(),
         "Add worktree".into(),
         "  Type to filter tickets/branches".into(),
         "  ↑/↓: select suggestion".into(),
         "  Tab: accept selection".into(),
+        "  Ctrl+Enter/Alt+Enter: accept selection and create".into(),
         "  Ctrl+R: refresh tickets".into(),
         "  Ctrl+Shift+R: clear cache".into(),
         "  Ctrl+Space: toggle overlay".into(),
+        "  Ctrl+J / Ctrl+B / Ctrl+A: list Jira tickets only / branches only / both".into(),
         "  Esc: cancel".into(),
     ];
 
@@ -465,3 +838,44 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
 
     horizontal[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn render_terminal_shows_placeholder_for_poisoned_parser() {
+        let parser = Arc::new(RwLock::new(vt100::Parser::new(4, 30, 0)));
+        let poisoner = Arc::clone(&parser);
+        let _ = thread::spawn(move || {
+            let _guard = poisoner.write().unwrap();
+            panic!("reader thread crashed");
+        })
+        .join();
+        assert!(parser.is_poisoned());
+
+        let mut terminal = Terminal::new(TestBackend::new(30, 4)).unwrap();
+        let mut rendered = true;
+        terminal
+            .draw(|frame| {
+                rendered = render_terminal(
+                    frame,
+                    frame.area(),
+                    None,
+                    super::super::TerminalSize::new(4, 30),
+                    &parser,
+                    false,
+                    None,
+                );
+            })
+            .unwrap();
+
+        assert!(!rendered);
+        let first_row: String = (0..30)
+            .map(|x| terminal.backend().buffer()[(x, 0)].symbol().to_string())
+            .collect();
+        assert!(first_row.starts_with("[terminal unavailable]"));
+    }
+}
@@ -1,24 +1,47 @@
-use super::super::{pty_tab::PtyTab, size::TerminalSize};
-use crate::{config::QuickAction, git::WorktreeInfo};
+use super::super::{
+    pty_tab::{workspace_env, PtyTab, TabProgram},
+    size::TerminalSize,
+};
+use crate::{
+    config::{PathDisplay, QuickAction, TerminalSession},
+    git::{self, status, WorktreeInfo},
+    wtm_paths::{branch_dir_name, branch_slug, format_path, home_dir},
+};
 use anyhow::Result;
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 pub(super) struct WorkspaceState {
     info: WorktreeInfo,
+    /// Repository the worktree belongs to, which scopes its tmux session.
+    repo_root: PathBuf,
     tabs: Vec<PtyTab>,
     active_tab: usize,
+    /// Configured `terminalEnv`, applied to every tab spawned here.
+    terminal_env: BTreeMap<String, String>,
+    /// What blank tabs attach to; quick actions always get a plain shell.
+    session: TerminalSession,
 }
 
 impl WorkspaceState {
     pub(super) fn new(
         info: WorktreeInfo,
+        repo_root: &Path,
         size: TerminalSize,
         next_tab_id: &mut usize,
+        terminal_env: &BTreeMap<String, String>,
+        session: TerminalSession,
     ) -> Result<Self> {
         let mut workspace = Self {
             info,
+            repo_root: repo_root.to_path_buf(),
             tabs: Vec::new(),
             active_tab: 0,
+            terminal_env: terminal_env.clone(),
+            session,
         };
         workspace.ensure_tab(next_tab_id, size)?;
         Ok(workspace)
@@ -28,7 +51,13 @@ impl WorkspaceState {
         self.info = info;
     }
 
-    pub(super) fn sidebar_label(&self, repo_root: &Path) -> String {
+    /// Name, branch, and markers; `here` flags the worktree wtm was launched from.
+    pub(super) fn sidebar_label(
+        &self,
+        repo_root: &Path,
+        here: bool,
+        max_slug_length: usize,
+    ) -> String {
         let mut label = self.info.name();
         if let Some(branch) = self.info.branch.as_deref() {
             label.push_str(" [");
@@ -42,11 +71,17 @@ impl WorkspaceState {
         } else if self.info.is_locked {
             label.push_str(" (locked)");
         }
+        if !self.is_primary(repo_root) && !dir_matches_branch(&self.info, max_slug_length) {
+            label.push_str(" dir≠branch");
+        }
+        if here {
+            label.push_str(" (here)");
+        }
         label
     }
 
-    pub(super) fn display_path(&self) -> String {
-        self.info.path.display().to_string()
+    pub(super) fn display_path(&self, style: PathDisplay, repo_root: &Path) -> String {
+        format_path(&self.info.path, style, repo_root, home_dir().as_deref())
     }
 
     pub(super) fn info(&self) -> &WorktreeInfo {
@@ -56,7 +91,13 @@ impl WorkspaceState {
     pub(super) fn tab_titles(&self) -> Vec<String> {
         self.tabs
             .iter()
-            .map(|tab| tab.title().to_string())
+            .map(|tab| {
+                if tab.finished_at().is_some() {
+                    format!("{} (exited)", tab.title())
+                } else {
+                    tab.title()
+                }
+            })
             .collect()
     }
 
@@ -93,7 +134,8 @@ impl WorkspaceState {
         let tab_id = *next_tab_id;
         *next_tab_id += 1;
         let title = format!("Tab {tab_id}");
-        let tab = PtyTab::new(&title, &self.info.path, size)?;
+        let program = TabProgram::for_worktree(self.session, &self.repo_root, &self.info);
+        let tab = PtyTab::new(&title, &self.info.path, size, &self.env(), &program)?;
         self.tabs.push(tab);
         self.active_tab = self.tabs.len().saturating_sub(1);
         Ok(())
@@ -108,13 +150,23 @@ impl WorkspaceState {
         let tab_id = *next_tab_id;
         *next_tab_id += 1;
         let title = format!("{} ({tab_id})", action.label);
-        let tab = PtyTab::new(&title, &self.info.path, size)?;
+        let tab = PtyTab::new(
+            &title,
+            &self.info.path,
+            size,
+            &self.env(),
+            &TabProgram::Shell,
+        )?;
         tab.send_command(&action.command)?;
         self.tabs.push(tab);
         self.active_tab = self.tabs.len().saturating_sub(1);
         Ok(())
     }
 
+    fn env(&self) -> Vec<(String, String)> {
+        workspace_env(&self.info, &self.terminal_env)
+    }
+
     pub(super) fn select_prev_tab(&mut self) {
         if self.tabs.is_empty() {
             return;
@@ -146,19 +198,102 @@ impl WorkspaceState {
         Ok(())
     }
 
-    pub(super) fn reap_finished_children(&mut self) {
-        self.tabs.retain(|tab| !tab.is_terminated());
+    pub(super) fn reap_finished_children(
+        &mut self,
+        now: Instant,
+        auto_close_after: Option<Duration>,
+    ) {
+        for tab in &mut self.tabs {
+            if tab.is_terminated() {
+                tab.record_finished(now);
+            }
+        }
+        self.tabs.retain(|tab| match tab.finished_at() {
+            Some(finished_at) => !should_close_finished_tab(finished_at, now, auto_close_after),
+            None => true,
+        });
         if self.active_tab >= self.tabs.len() && !self.tabs.is_empty() {
             self.active_tab = self.tabs.len() - 1;
         }
     }
 
+    /// Drop every tab, killing and reaping its shell.
+    pub(super) fn terminate_tabs(&mut self) {
+        self.tabs.clear();
+        self.active_tab = 0;
+    }
+
     pub(super) fn path(&self) -> &Path {
         &self.info.path
     }
 
     pub(super) fn is_primary(&self, repo_root: &Path) -> bool {
-        self.info.path == repo_root
+        self.info.is_primary(repo_root)
+    }
+}
+
+/// Decide whether a tab whose shell exited at `finished_at` should be closed at `now`.
+fn should_close_finished_tab(
+    finished_at: Instant,
+    now: Instant,
+    auto_close_after: Option<Duration>,
+) -> bool {
+    match auto_close_after {
+        None => true,
+        Some(delay) if delay.is_zero() => false,
+        Some(delay) => now.saturating_duration_since(finished_at) >= delay,
+    }
+}
+
+/// Most `git diff --stat` lines shown before the rest are summarised.
+const PREVIEW_STAT_LINES: usize = 10;
+
+/// Work that removing a worktree would throw away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RemovalPreview {
+    diff_stat: String,
+    untracked: usize,
+    /// Commits on the branch that no remote has.
+    unpushed: usize,
+}
+
+impl RemovalPreview {
+    fn load(target: &Path) -> Result<Self> {
+        Ok(Self {
+            diff_stat: git::diff_stat(target)?,
+            untracked: status::status(target)?.untracked,
+            unpushed: git::unpushed_count(target)?,
+        })
+    }
+
+    pub(super) fn is_clean(&self) -> bool {
+        self.diff_stat.trim().is_empty() && self.untracked == 0 && self.unpushed == 0
+    }
+
+    /// The diff stat (truncated), untracked and unpushed counts, or a note that
+    /// nothing would be lost.
+    pub(super) fn lines(&self) -> Vec<String> {
+        if self.is_clean() {
+            return vec!["clean — safe to remove.".into()];
+        }
+        let stat: Vec<&str> = self.diff_stat.lines().collect();
+        let mut lines: Vec<String> = stat
+            .iter()
+            .take(PREVIEW_STAT_LINES)
+            .map(|line| line.to_string())
+            .collect();
+        if stat.len() > PREVIEW_STAT_LINES {
+            lines.push(format!("… {} more lines", stat.len() - PREVIEW_STAT_LINES));
+        }
+        if self.untracked > 0 {
+            let plural = if self.untracked == 1 { "" } else { "s" };
+            lines.push(format!("{} untracked file{plural}", self.untracked));
+        }
+        if self.unpushed > 0 {
+            let plural = if self.unpushed == 1 { "" } else { "s" };
+            lines.push(format!("{} unpushed commit{plural}", self.unpushed));
+        }
+        lines
     }
 }
 
@@ -166,13 +301,88 @@ impl WorkspaceState {
 pub(super) struct RemoveWorktreeState {
     target: PathBuf,
     force: bool,
+    preview: Result<RemovalPreview, String>,
+    /// Name typed so far once a force-removal needs it (`confirmForceRemove`).
+    typed_name: Option<String>,
+    /// Git lists the worktree as prunable, so confirming prunes its record
+    /// instead of removing files.
+    stale: bool,
 }
 
 impl RemoveWorktreeState {
+    /// Start confirming `git worktree prune` for the prunable worktree at
+    /// `target`; there are no changes left to lose.
+    pub(super) fn stale(target: &Path) -> Self {
+        Self {
+            target: target.to_path_buf(),
+            force: false,
+            preview: Err("the directory is gone".into()),
+            typed_name: None,
+            stale: true,
+        }
+    }
+
+    pub(super) fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Start confirming removal of `target`, computing its diff-stat preview.
     pub(super) fn new(target: &Path) -> Self {
         Self {
             target: target.to_path_buf(),
             force: false,
+            preview: RemovalPreview::load(target).map_err(|err| format!("{err:#}")),
+            typed_name: None,
+            stale: false,
+        }
+    }
+
+    /// Directory name the user must type to confirm a guarded force-removal.
+    pub(super) fn name(&self) -> String {
+        self.target
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.target.display().to_string())
+    }
+
+    /// Handle `y`: `true` when removal should go ahead, `false` when the name must be typed first.
+    ///
+    /// Only forced removals of worktrees with (or with unknown) changes are guarded.
+    pub(super) fn accept(&mut self, confirm_force_remove: bool) -> bool {
+        let dirty = !matches!(&self.preview, Ok(preview) if preview.is_clean());
+        if confirm_force_remove && self.force && dirty {
+            self.typed_name = Some(String::new());
+            return false;
+        }
+        true
+    }
+
+    /// The name typed so far, when waiting for it.
+    pub(super) fn typed_name(&self) -> Option<&str> {
+        self.typed_name.as_deref()
+    }
+
+    pub(super) fn push_name_char(&mut self, c: char) {
+        if let Some(typed) = self.typed_name.as_mut() {
+            typed.push(c);
+        }
+    }
+
+    pub(super) fn pop_name_char(&mut self) {
+        if let Some(typed) = self.typed_name.as_mut() {
+            typed.pop();
+        }
+    }
+
+    pub(super) fn typed_name_matches(&self) -> bool {
+        self.typed_name() == Some(self.name().as_str())
+    }
+
+    /// Lines describing what would be lost, for the confirmation overlay.
+    pub(super) fn preview_lines(&self) -> Vec<String> {
+        match &self.preview {
+            Ok(preview) => preview.lines(),
+            Err(err) => vec![format!("Could not inspect changes: {err}")],
         }
     }
 
@@ -182,6 +392,7 @@ impl RemoveWorktreeState {
 
     pub(super) fn toggle_force(&mut self) {
         self.force = !self.force;
+        self.typed_name = None;
     }
 
     pub(super) fn force(&self) -> bool {
@@ -222,11 +433,86 @@ impl QuickActionState {
     }
 }
 
+/// Whether the worktree's directory is still named after its branch.
+///
+/// Allows the `-<n>` suffix added on collisions and the uncapped name long
+/// branches got before [`branch_dir_name`] capped it at `max_slug_length`;
+/// detached worktrees always match.
+pub(super) fn dir_matches_branch(info: &WorktreeInfo, max_slug_length: usize) -> bool {
+    let Some(branch) = info.branch.as_deref() else {
+        return true;
+    };
+    let name = info.name();
+    [
+        branch_dir_name(branch, max_slug_length),
+        branch_slug(branch),
+    ]
+    .iter()
+    .any(|expected| match name.strip_prefix(expected.as_str()) {
+        Some("") => true,
+        Some(rest) => rest
+            .strip_prefix('-')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())),
+        None => false,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wtm_paths::DEFAULT_MAX_SLUG_LENGTH;
 
     #[test]
+    fn dir_matches_branch_detects_switched_branches() {
+        let info = |path: &str, branch: Option<&str>| match branch {
+            Some(branch) => WorktreeInfo::at(path).on(branch),
+            None => WorktreeInfo::at(path),
+        };
+        assert!(dir_matches_branch(
+            &info("/ws/feature-x", Some("feature/x")),
+            DEFAULT_MAX_SLUG_LENGTH
+        ));
+        assert!(dir_matches_branch(
+            &info("/ws/feature-x-2", Some("feature/x")),
+            DEFAULT_MAX_SLUG_LENGTH
+        ));
+        assert!(dir_matches_branch(
+            &info("/ws/anything", None),
+            DEFAULT_MAX_SLUG_LENGTH
+        ));
+        assert!(!dir_matches_branch(
+            &info("/ws/feature-x", Some("main")),
+            DEFAULT_MAX_SLUG_LENGTH
+        ));
+        assert!(!dir_matches_branch(
+            &info("/ws/feature-x-old", Some("feature/x")),
+            DEFAULT_MAX_SLUG_LENGTH
+        ));
+        assert!(!dir_matches_branch(
+            &info("/ws/feature-x-", Some("feature/x")),
+            DEFAULT_MAX_SLUG_LENGTH
+        ));
+        let long = "feature/PROJ-1234-make-the-dashboard-remember-every-filter-across-sessions";
+        let capped = format!("/ws/{}", branch_dir_name(long, DEFAULT_MAX_SLUG_LENGTH));
+        let uncapped = format!("/ws/{}", branch_slug(long));
+        let short = format!("/ws/{}", branch_dir_name(long, 24));
+        assert!(dir_matches_branch(&info(&short, Some(long)), 24));
+        assert!(!dir_matches_branch(
+            &info(&short, Some(long)),
+            DEFAULT_MAX_SLUG_LENGTH
+        ));
+        assert!(dir_matches_branch(
+            &info(&capped, Some(long)),
+            DEFAULT_MAX_SLUG_LENGTH
+        ));
+        assert!(dir_matches_branch(
+            &info(&uncapped, Some(long)),
+            DEFAULT_MAX_SLUG_LENGTH
+        ));
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn quick_action_state_wraps_navigation() {
         let mut state = QuickActionState::default();
         state.selected = 0;
@@ -238,4 +524,111 @@ mod tests {
         state.clamp(3);
         assert_eq!(state.selected, 2);
     }
+
+    #[test]
+    fn removal_preview_reports_clean_and_dirty_worktrees() {
+        let clean = RemovalPreview {
+            diff_stat: "\n".into(),
+            untracked: 0,
+            unpushed: 0,
+        };
+        assert!(clean.is_clean());
+        assert_eq!(clean.lines(), ["clean — safe to remove."]);
+
+        let stat: String = (0..12).map(|n| format!(" f{n}.rs | 1 +\n")).collect();
+        let dirty = RemovalPreview {
+            diff_stat: stat,
+            untracked: 1,
+            unpushed: 0,
+        };
+        assert!(!dirty.is_clean());
+        let lines = dirty.lines();
+        assert_eq!(lines.len(), PREVIEW_STAT_LINES + 2);
+        assert_eq!(lines[PREVIEW_STAT_LINES], "… 2 more lines");
+        assert_eq!(lines.last().unwrap(), "1 untracked file");
+
+        let untracked_only = RemovalPreview {
+            diff_stat: String::new(),
+            untracked: 3,
+            unpushed: 0,
+        };
+        assert_eq!(untracked_only.lines(), ["3 untracked files"]);
+
+        let unpushed_only = RemovalPreview {
+            diff_stat: String::new(),
+            untracked: 0,
+            unpushed: 1,
+        };
+        assert!(!unpushed_only.is_clean());
+        assert_eq!(unpushed_only.lines(), ["1 unpushed commit"]);
+    }
+
+    fn remove_state(clean: bool, force: bool) -> RemoveWorktreeState {
+        RemoveWorktreeState {
+            target: PathBuf::from("/ws/feature-x"),
+            force,
+            preview: Ok(RemovalPreview {
+                diff_stat: if clean {
+                    String::new()
+                } else {
+                    " a | 1 +".into()
+                },
+                untracked: 0,
+                unpushed: 0,
+            }),
+            typed_name: None,
+            stale: false,
+        }
+    }
+
+    #[test]
+    fn force_removal_of_dirty_worktree_requires_typed_name() {
+        let mut state = remove_state(false, true);
+        assert!(!state.accept(true));
+        assert_eq!(state.typed_name(), Some(""));
+        for c in "feature-".chars() {
+            state.push_name_char(c);
+        }
+        assert!(!state.typed_name_matches());
+        state.push_name_char('x');
+        assert!(state.typed_name_matches());
+        state.pop_name_char();
+        assert!(!state.typed_name_matches());
+
+        state.toggle_force();
+        assert_eq!(state.typed_name(), None);
+        assert!(state.accept(true), "non-forced removal is single-key");
+    }
+
+    #[test]
+    fn confirmation_is_skipped_when_clean_or_disabled() {
+        assert!(remove_state(true, true).accept(true));
+        assert!(remove_state(false, true).accept(false));
+        let mut unknown = remove_state(false, true);
+        unknown.preview = Err("not a git repository".into());
+        assert!(!unknown.accept(true), "unknown changes count as dirty");
+    }
+
+    #[test]
+    fn should_close_finished_tab_honours_delay() {
+        let finished = Instant::now();
+        let later = finished + Duration::from_secs(10);
+
+        assert!(should_close_finished_tab(finished, finished, None));
+        assert!(!should_close_finished_tab(
+            finished,
+            later,
+            Some(Duration::ZERO)
+        ));
+        assert!(!should_close_finished_tab(
+            finished,
+            later,
+            Some(Duration::from_secs(30))
+        ));
+        assert!(should_close_finished_tab(
+            finished,
+            later,
+            Some(Duration::from_secs(10))
+        ));
+    }
 }
@@ -20,7 +20,7 @@ pub fn key_event_to_bytes(key: KeyEvent) -> Option<Vec<u8>> {
         KeyCode::Char(c) => {
             if modifiers.contains(KeyModifiers::CONTROL) {
                 let upper = c.to_ascii_uppercase();
-                if ('A'..='Z').contains(&upper) {
+                if upper.is_ascii_uppercase() {
                     return Some(vec![(upper as u8) - b'@']);
                 }
             }
@@ -46,7 +46,7 @@ pub fn key_event_to_bytes(key: KeyEvent) -> Option<Vec<u8>> {
         _ => {}
     }
 
-    let _ = modifiers.remove(KeyModifiers::SHIFT);
+    modifiers.remove(KeyModifiers::SHIFT);
     None
 }
 
@@ -1,56 +1,68 @@
 mod app;
 mod keymap;
 pub(crate) mod pty_tab;
+mod redraw;
 pub(crate) mod size;
 
 use anyhow::Result;
 use crossterm::{
+    cursor::Show,
     event::{self, DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{io, path::PathBuf, time::Duration};
+use std::{
+    io, panic,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
-use crate::{config::QuickAction, git::WorktreeInfo};
+use crate::{config::Config, git::WorktreeInfo};
 use app::App;
+use redraw::RedrawGate;
 use size::TerminalSize;
 
 /// Run the Ratatui dashboard for the provided workspace directories.
-pub fn run_tui(
-    repo_root: PathBuf,
-    worktrees: Vec<WorktreeInfo>,
-    quick_actions: Vec<QuickAction>,
-) -> Result<()> {
+pub fn run_tui(repo_root: PathBuf, worktrees: Vec<WorktreeInfo>, config: Config) -> Result<()> {
+    let _panic_hook = PanicHookGuard::install();
+    let shutdown_requested = install_signal_handler();
     let mut terminal = setup_terminal()?;
     let size = terminal.size()?;
-    let mut app = App::new(
-        repo_root,
-        worktrees,
-        quick_actions,
-        TerminalSize::from_size(size),
-    )?;
+    let mut app = App::new(repo_root, worktrees, config, TerminalSize::from_size(size))?;
 
-    let tick_rate = Duration::from_millis(100);
+    let mut redraw = RedrawGate::new();
 
     let result = (|| -> Result<()> {
         loop {
-            terminal.draw(|frame| app.draw(frame))?;
+            if redraw.should_draw(pty_tab::latest_generation(), app.is_animating()) {
+                terminal.draw(|frame| app.draw(frame))?;
+            }
 
-            if app.should_quit() {
+            if app.should_quit() || shutdown_requested.load(Ordering::SeqCst) {
                 break;
             }
 
-            if event::poll(tick_rate)? {
+            if event::poll(redraw.tick())? {
                 let evt = event::read()?;
                 app.handle_event(evt)?;
+                redraw.mark_dirty();
             }
 
             app.reap_finished_children();
+            app.reload_config_if_changed();
+            if app.finish_jobs() | app.refresh_status_line() {
+                redraw.mark_dirty();
+            }
         }
         Ok(())
     })();
 
+    app.save_session();
+    app.shutdown_all();
     restore_terminal(&mut terminal)?;
     result
 }
@@ -67,15 +79,62 @@ fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
 
 fn restore_terminal<W: io::Write>(terminal: &mut Terminal<CrosstermBackend<W>>) -> Result<()> {
     terminal.show_cursor().ok();
-    execute!(
-        terminal.backend_mut(),
-        DisableMouseCapture,
-        LeaveAlternateScreen
-    )?;
+    reset_terminal_modes(terminal.backend_mut())
+}
+
+/// Leave the alternate screen, stop mouse capture, and disable raw mode.
+fn reset_terminal_modes<W: io::Write>(writer: &mut W) -> Result<()> {
+    execute!(writer, DisableMouseCapture, LeaveAlternateScreen)?;
     disable_raw_mode()?;
     Ok(())
 }
 
+/// Restore the terminal from a panic hook, where no `Terminal` handle is available.
+fn restore_after_panic<W: io::Write>(writer: &mut W) -> Result<()> {
+    execute!(writer, Show)?;
+    reset_terminal_modes(writer)
+}
+
+/// Request an orderly shutdown on SIGINT/SIGTERM/SIGHUP (Ctrl+C or console close on Windows).
+///
+/// Raw mode swallows Ctrl+C as a key press, so this mainly covers `kill` and closed
+/// terminals; the event loop polls the returned flag and tears down PTY children.
+fn install_signal_handler() -> Arc<AtomicBool> {
+    let flag = Arc::new(AtomicBool::new(false));
+    let handler_flag = Arc::clone(&flag);
+    if let Err(err) = ctrlc::set_handler(move || handler_flag.store(true, Ordering::SeqCst)) {
+        log::warn!("failed to install signal handler: {err}");
+    }
+    flag
+}
+
+type PanicHook = dyn Fn(&panic::PanicHookInfo<'_>) + Sync + Send + 'static;
+
+/// Restores the terminal before the previous hook prints the panic message, and
+/// reinstalls that hook when dropped, however `run_tui` returns.
+struct PanicHookGuard {
+    previous: Arc<Box<PanicHook>>,
+}
+
+impl PanicHookGuard {
+    fn install() -> Self {
+        let previous = Arc::new(panic::take_hook());
+        let chained = Arc::clone(&previous);
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore_after_panic(&mut io::stdout());
+            chained(info);
+        }));
+        Self { previous }
+    }
+}
+
+impl Drop for PanicHookGuard {
+    fn drop(&mut self) {
+        let previous = Arc::clone(&self.previous);
+        panic::set_hook(Box::new(move |info| previous(info)));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,4 +146,9 @@ mod tests {
         let mut terminal = Terminal::new(backend).expect("create terminal");
         restore_terminal(&mut terminal).expect("restore terminal");
     }
+
+    #[test]
+    fn restore_after_panic_with_sink_succeeds() {
+        restore_after_panic(&mut io::sink()).expect("restore after panic");
+    }
 }
@@ -1,19 +1,69 @@
-use crate::tui::{keymap::key_event_to_bytes, size::TerminalSize};
+use crate::{
+    config::TerminalSession,
+    git::WorktreeInfo,
+    tui::{keymap::key_event_to_bytes, size::TerminalSize},
+    wtm_paths::{fnv1a, sanitize_branch_name},
+};
 use anyhow::{Context, Result};
 use crossterm::event::KeyEvent;
 use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
 use std::{
+    collections::BTreeMap,
     io::{self, Read, Write},
     path::Path,
-    sync::{Arc, Mutex, RwLock},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError, RwLock,
+    },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, RefreshKind, System};
 use tui_term::vt100;
 
 const DEFAULT_SCROLLBACK_LINES: usize = 5000;
 
+/// Called from a tab's background threads after its screen or title changed.
+pub(crate) type ScreenWaker = Arc<dyn Fn() + Send + Sync>;
+
+/// Source of screen generations. It is shared by every tab, so two tabs never
+/// report the same generation and a cached rendering cannot be mistaken for
+/// another tab's.
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// The generation handed out last; it moves whenever any tab changes.
+pub(crate) fn latest_generation() -> u64 {
+    NEXT_GENERATION.load(Ordering::Acquire)
+}
+
+/// Tracks when a tab's visible state last changed, and whom to tell.
+struct ScreenActivity {
+    generation: AtomicU64,
+    waker: Mutex<Option<ScreenWaker>>,
+}
+
+impl ScreenActivity {
+    fn new() -> Self {
+        Self {
+            generation: AtomicU64::new(NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)),
+            waker: Mutex::new(None),
+        }
+    }
+
+    fn touch(&self) {
+        let next = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+        self.generation.store(next, Ordering::Release);
+        let waker = self
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(waker) = waker {
+            waker();
+        }
+    }
+}
+
 pub(crate) struct PtyTab {
     base_title: String,
     title: Arc<RwLock<String>>,
@@ -24,11 +74,20 @@ pub(crate) struct PtyTab {
     reader_handle: Option<thread::JoinHandle<()>>,
     title_monitor_handle: Option<thread::JoinHandle<()>>,
     exit_status: Arc<Mutex<Option<bool>>>,
+    activity: Arc<ScreenActivity>,
+    finished_at: Option<Instant>,
     size: TerminalSize,
 }
 
 impl PtyTab {
-    pub fn new(title: &str, cwd: &Path, size: TerminalSize) -> Result<Self> {
+    /// Start `program` in `cwd` with `env` added to its environment.
+    pub fn new(
+        title: &str,
+        cwd: &Path,
+        size: TerminalSize,
+        env: &[(String, String)],
+        program: &TabProgram,
+    ) -> Result<Self> {
         let pty_system = native_pty_system();
         let pair = pty_system.openpty(PtySize {
             rows: size.rows,
@@ -37,10 +96,7 @@ impl PtyTab {
             pixel_height: 0,
         })?;
 
-        let mut command = CommandBuilder::new(default_shell());
-        command.cwd(cwd);
-        command.env("PWD", cwd);
-
+        let command = tab_command(program, cwd, env);
         let child = pair
             .slave
             .spawn_command(command)
@@ -67,9 +123,18 @@ impl PtyTab {
         let child_handle = Arc::new(Mutex::new(child));
         let reader_child = child_handle.clone();
 
+        let activity = Arc::new(ScreenActivity::new());
         let writer_clone = writer.clone();
+        let reader_activity = Arc::clone(&activity);
         let reader_handle = thread::spawn(move || {
-            reader_loop(reader, parser_clone, exit_flag, reader_child, writer_clone);
+            reader_loop(
+                reader,
+                parser_clone,
+                exit_flag,
+                reader_child,
+                writer_clone,
+                &reader_activity,
+            );
         });
 
         let base_title = title.to_string();
@@ -79,6 +144,7 @@ impl PtyTab {
             base_title.clone(),
             Arc::clone(&title_state),
             exit_status.clone(),
+            Arc::clone(&activity),
         );
 
         Ok(Self {
@@ -91,10 +157,26 @@ impl PtyTab {
             reader_handle: Some(reader_handle),
             title_monitor_handle,
             exit_status,
+            activity,
+            finished_at: None,
             size,
         })
     }
 
+    /// Changes whenever the screen, title, or running state changes.
+    pub fn generation(&self) -> u64 {
+        self.activity.generation.load(Ordering::Acquire)
+    }
+
+    /// Call `waker` from the background threads on every later change.
+    pub fn set_waker(&self, waker: ScreenWaker) {
+        *self
+            .activity
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(waker);
+    }
+
     pub fn title(&self) -> String {
         self.title
             .read()
@@ -106,6 +188,10 @@ impl PtyTab {
         Arc::clone(&self.parser)
     }
 
+    pub fn size(&self) -> TerminalSize {
+        self.size
+    }
+
     pub fn resize_to(&mut self, size: TerminalSize) {
         if self.size == size {
             return;
@@ -114,6 +200,7 @@ impl PtyTab {
         if let Ok(mut guard) = self.parser.write() {
             guard.set_size(size.rows, size.cols);
         }
+        self.activity.touch();
         if let Ok(master) = self.master.lock() {
             let _ = master.resize(PtySize {
                 rows: size.rows,
@@ -127,7 +214,7 @@ impl PtyTab {
     pub fn handle_key_event(&self, key: KeyEvent) -> Result<()> {
         if let Some(bytes) = key_event_to_bytes(key) {
             self.reset_scrollback();
-            let mut writer = self.writer.lock().unwrap();
+            let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
             writer.write_all(&bytes)?;
             writer.flush()?;
         }
@@ -146,24 +233,83 @@ impl PtyTab {
                 current.saturating_sub((-lines) as usize)
             };
             parser.set_scrollback(new_offset);
+            if parser.screen().scrollback() != current {
+                self.activity.touch();
+            }
         }
     }
 
+    /// Scroll so that `offset` lines of history sit above the screen; `0`
+    /// shows the live screen.
+    pub fn set_scrollback(&self, offset: usize) {
+        if let Ok(mut parser) = self.parser.write() {
+            if parser.screen().scrollback() != offset {
+                parser.set_scrollback(offset);
+                self.activity.touch();
+            }
+        }
+    }
+
+    /// Every scrollback and screen line, oldest first, with how many of them
+    /// are scrollback. `None` when the parser lock is poisoned.
+    pub fn text_lines(&self) -> Option<(Vec<String>, usize)> {
+        let parser = self.parser.read().ok()?;
+        let screen = parser.screen();
+        let (_, cols) = screen.size();
+        Some((
+            screen.all_rows(0, cols).collect(),
+            screen.scrollback_buffer_len(),
+        ))
+    }
+
+    /// The scrollback followed by the screen as plain text, for saving a
+    /// tab's output.
+    pub fn dump_contents(&self) -> String {
+        let parser = self.parser.read().unwrap_or_else(PoisonError::into_inner);
+        dump_screen(parser.screen())
+    }
+
     pub fn reset_scrollback(&self) {
         if let Ok(mut parser) = self.parser.write() {
-            parser.set_scrollback(0);
+            if parser.screen().scrollback() != 0 {
+                parser.set_scrollback(0);
+                self.activity.touch();
+            }
         }
     }
 
     pub fn send_command(&self, command: &str) -> Result<()> {
         self.reset_scrollback();
-        let mut writer = self.writer.lock().unwrap();
-        writer.write_all(command.as_bytes())?;
-        writer.write_all(b"\r\n")?;
-        writer.flush()?;
+        write_input(&self.writer, command, true)?;
+        Ok(())
+    }
+
+    /// Type `text` at the prompt without pressing Enter.
+    pub fn send_text(&self, text: &str) -> Result<()> {
+        self.reset_scrollback();
+        write_input(&self.writer, text, false)?;
         Ok(())
     }
 
+    /// Flag the tab as finished so it is reaped on the next tick.
+    ///
+    /// Used when the terminal state can no longer be trusted (e.g. a poisoned parser lock).
+    pub fn mark_unavailable(&self) {
+        if let Ok(mut status) = self.exit_status.lock() {
+            status.get_or_insert(false);
+        }
+        self.activity.touch();
+    }
+
+    /// Remember when the shell was first observed as finished.
+    pub fn record_finished(&mut self, now: Instant) {
+        self.finished_at.get_or_insert(now);
+    }
+
+    pub fn finished_at(&self) -> Option<Instant> {
+        self.finished_at
+    }
+
     pub fn is_terminated(&self) -> bool {
         self.exit_status
             .lock()
@@ -203,6 +349,7 @@ fn reader_loop(
     exit_flag: Arc<Mutex<Option<bool>>>,
     child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    activity: &ScreenActivity,
 ) {
     let mut buf = [0u8; 8192];
     let mut dsr_state = 0;
@@ -227,6 +374,7 @@ fn reader_loop(
                 if let Ok(mut guard) = parser.write() {
                     guard.process(&buf[..n]);
                 }
+                activity.touch();
             }
             Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
             Err(_) => break,
@@ -244,6 +392,26 @@ fn reader_loop(
                 .map(|mut flag| *flag = Some(status.success()));
         }
     }
+    activity.touch();
+}
+
+/// Every retained line of `screen`, ending in a newline unless empty.
+fn dump_screen(screen: &vt100::Screen) -> String {
+    let mut contents = screen.all_contents();
+    if !contents.is_empty() {
+        contents.push('\n');
+    }
+    contents
+}
+
+/// Write `text` to the shell, followed by Enter when `submit` is set.
+fn write_input(writer: &Mutex<Box<dyn Write + Send>>, text: &str, submit: bool) -> io::Result<()> {
+    let mut writer = writer.lock().unwrap_or_else(PoisonError::into_inner);
+    writer.write_all(text.as_bytes())?;
+    if submit {
+        writer.write_all(b"\r\n")?;
+    }
+    writer.flush()
 }
 
 fn respond_with_cursor(
@@ -266,18 +434,14 @@ fn spawn_title_monitor(
     base_title: String,
     title: Arc<RwLock<String>>,
     exit_flag: Arc<Mutex<Option<bool>>>,
+    activity: Arc<ScreenActivity>,
 ) -> Option<thread::JoinHandle<()>> {
-    let Some(id) = process_id else {
-        return None;
-    };
-    let Some(sysinfo_pid) = to_sysinfo_pid(id) else {
-        return None;
-    };
+    let sysinfo_pid = to_sysinfo_pid(process_id?)?;
 
     thread::Builder::new()
         .name("wtm-title-monitor".into())
         .spawn(move || {
-            monitor_foreground_process(sysinfo_pid, base_title, title, exit_flag);
+            monitor_foreground_process(sysinfo_pid, base_title, title, exit_flag, &activity);
         })
         .ok()
 }
@@ -287,6 +451,7 @@ fn monitor_foreground_process(
     base_title: String,
     title: Arc<RwLock<String>>,
     exit_flag: Arc<Mutex<Option<bool>>>,
+    activity: &ScreenActivity,
 ) {
     let mut system = System::new_with_specifics(
         RefreshKind::new().with_processes(ProcessRefreshKind::everything()),
@@ -304,6 +469,7 @@ fn monitor_foreground_process(
         if next_title != last_title {
             update_title(&title, &next_title);
             last_title = next_title;
+            activity.touch();
         }
 
         thread::sleep(Duration::from_millis(500));
@@ -390,9 +556,7 @@ fn compose_title(program: &str, base_title: &str) -> String {
     let base = truncate(base_title, 40);
     if program.is_empty() {
         base
-    } else if program == base {
-        program
-    } else if base.is_empty() {
+    } else if program == base || base.is_empty() {
         program
     } else {
         format!("{program} - {base}")
@@ -421,6 +585,115 @@ fn to_sysinfo_pid(process_id: u32) -> Option<Pid> {
     Some(Pid::from_u32(process_id))
 }
 
+/// Environment for a shell opened in `info`'s worktree: the configured
+/// `terminalEnv`, then `WTM_WORKSPACE` and `WTM_BRANCH` (empty when detached).
+pub(crate) fn workspace_env(
+    info: &WorktreeInfo,
+    configured: &BTreeMap<String, String>,
+) -> Vec<(String, String)> {
+    let mut env: Vec<_> = configured
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    env.push((
+        "WTM_WORKSPACE".into(),
+        info.path.to_string_lossy().into_owned(),
+    ));
+    env.push(("WTM_BRANCH".into(), info.branch.clone().unwrap_or_default()));
+    env
+}
+
+/// What a tab runs in its pseudoterminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TabProgram {
+    /// The user's shell.
+    Shell,
+    /// Attach to the named tmux session, creating it first if needed.
+    Tmux { session: String },
+}
+
+impl TabProgram {
+    /// A blank tab in `info`'s worktree of the repository at `repo_root`,
+    /// under the configured `session` kind.
+    pub(crate) fn for_worktree(
+        session: TerminalSession,
+        repo_root: &Path,
+        info: &WorktreeInfo,
+    ) -> Self {
+        match session {
+            TerminalSession::Shell => Self::Shell,
+            TerminalSession::Tmux => Self::Tmux {
+                session: tmux_session_name(repo_root, &info.name()),
+            },
+        }
+    }
+}
+
+/// `session` if tabs can use it here; without tmux on `PATH` tabs fall back to
+/// a shell, and the returned warning says so.
+pub(crate) fn usable_session(session: TerminalSession) -> (TerminalSession, Option<String>) {
+    match session {
+        TerminalSession::Tmux if !program_on_path("tmux") => (
+            TerminalSession::Shell,
+            Some("session is \"tmux\" but tmux is not installed; tabs open a plain shell".into()),
+        ),
+        session => (session, None),
+    }
+}
+
+fn program_on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+/// `wtm-<worktree>-<repo>`, without the `.` and `:` tmux rejects in session
+/// names. `<repo>` is a short hash of the canonical repository root, so
+/// same-named worktrees of two repositories get sessions of their own.
+fn tmux_session_name(repo_root: &Path, worktree: &str) -> String {
+    let root = repo_root
+        .canonicalize()
+        .unwrap_or_else(|_| repo_root.to_path_buf());
+    let repo = fnv1a(root.as_os_str().as_encoded_bytes()) & 0xff_ffff;
+    format!(
+        "wtm-{}-{repo:06x}",
+        sanitize_branch_name(worktree).replace('/', "-")
+    )
+}
+
+/// `tmux` arguments that attach to `session`, creating it with `env` if needed.
+fn tmux_args(session: &str, env: &[(String, String)]) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "new-session".into(),
+        "-A".into(),
+        "-s".into(),
+        session.into(),
+    ];
+    // A running tmux server starts new sessions with its own environment, not
+    // the client's, so the tab's variables are passed explicitly.
+    for (key, value) in env {
+        args.push("-e".into());
+        args.push(format!("{key}={value}"));
+    }
+    args
+}
+
+fn tab_command(program: &TabProgram, cwd: &Path, env: &[(String, String)]) -> CommandBuilder {
+    let mut command = match program {
+        TabProgram::Shell => CommandBuilder::new(default_shell()),
+        TabProgram::Tmux { session } => {
+            let mut command = CommandBuilder::new("tmux");
+            command.args(tmux_args(session, env));
+            command
+        }
+    };
+    command.cwd(cwd);
+    command.env("PWD", cwd);
+    for (key, value) in env {
+        command.env(key, value);
+    }
+    command
+}
+
 pub fn default_shell() -> String {
     if cfg!(windows) {
         std::env::var("COMSPEC").unwrap_or_else(|_| "cmd.exe".to_string())
@@ -466,6 +739,66 @@ mod tests {
         assert!(!default_shell().is_empty());
     }
 
+    #[test]
+    fn shell_command_carries_workspace_env() {
+        let info = WorktreeInfo::at("/ws/feature-x").on("feature/x");
+        let configured = BTreeMap::from([
+            ("PROJECT".to_string(), "wtm".to_string()),
+            ("WTM_BRANCH".to_string(), "overridden".to_string()),
+        ]);
+        let command = tab_command(
+            &TabProgram::Shell,
+            Path::new("/ws/feature-x"),
+            &workspace_env(&info, &configured),
+        );
+        let get = |key: &str| command.get_env(key).and_then(|value| value.to_str());
+        assert_eq!(get("WTM_WORKSPACE"), Some("/ws/feature-x"));
+        assert_eq!(get("WTM_BRANCH"), Some("feature/x"));
+        assert_eq!(get("PROJECT"), Some("wtm"));
+        assert_eq!(get("PWD"), Some("/ws/feature-x"));
+    }
+
+    #[test]
+    fn tmux_tabs_attach_to_a_session_named_after_the_worktree() {
+        let info = WorktreeInfo::at("/ws/v1.2:hotfix").on("hotfix");
+        let repo = Path::new("/nonexistent/repo");
+        let program = TabProgram::for_worktree(TerminalSession::Tmux, repo, &info);
+        let TabProgram::Tmux { session } = &program else {
+            panic!("expected a tmux tab, got {program:?}");
+        };
+        assert!(session.starts_with("wtm-v1-2-hotfix-"), "{session}");
+        assert_eq!(session.len(), "wtm-v1-2-hotfix-".len() + 6);
+        assert_eq!(
+            program,
+            TabProgram::for_worktree(TerminalSession::Tmux, repo, &info),
+            "the same repository gets the same session"
+        );
+        assert_ne!(
+            program,
+            TabProgram::for_worktree(TerminalSession::Tmux, Path::new("/other/repo"), &info),
+            "another repository's worktree of the same name gets its own"
+        );
+        assert_eq!(
+            TabProgram::for_worktree(TerminalSession::Shell, repo, &info),
+            TabProgram::Shell
+        );
+
+        let env = [("WTM_BRANCH".to_string(), "hotfix".to_string())];
+        assert_eq!(
+            tmux_args("wtm-v1-2-hotfix", &env),
+            [
+                "new-session",
+                "-A",
+                "-s",
+                "wtm-v1-2-hotfix",
+                "-e",
+                "WTM_BRANCH=hotfix"
+            ]
+        );
+        let command = tab_command(&program, Path::new("/ws/v1.2:hotfix"), &env);
+        assert_eq!(command.get_argv()[0], "tmux");
+    }
+
     #[test]
     fn respond_with_cursor_writes_position_sequence() {
         let parser = Arc::new(RwLock::new(vt100::Parser::new(24, 80, 0)));
@@ -483,4 +816,36 @@ mod tests {
         let recorded = buffer.lock().unwrap().clone();
         assert_eq!(recorded, b"\x1b[10;20R");
     }
+
+    #[test]
+    fn dump_includes_lines_scrolled_off_the_screen() {
+        let mut parser = vt100::Parser::new(3, 20, 100);
+        for n in 1..=6 {
+            parser.process(format!("line {n}\r\n").as_bytes());
+        }
+        parser.process(b"a long line that wraps around");
+        assert!(!parser.screen().contents().contains("line 1"));
+
+        parser.set_scrollback(2);
+        assert_eq!(
+            dump_screen(parser.screen()),
+            "line 1\nline 2\nline 3\nline 4\nline 5\nline 6\na long line that wraps around\n"
+        );
+        assert_eq!(dump_screen(vt100::Parser::new(3, 20, 0).screen()), "");
+    }
+
+    #[test]
+    fn typed_text_is_not_submitted() {
+        let (writer_impl, buffer) = RecordingWriter::new();
+        let writer: Mutex<Box<dyn Write + Send>> = Mutex::new(Box::new(writer_impl));
+
+        write_input(&writer, "feature/login", false).unwrap();
+        assert_eq!(buffer.lock().unwrap().as_slice(), b"feature/login");
+
+        write_input(&writer, " && git status", true).unwrap();
+        assert_eq!(
+            buffer.lock().unwrap().as_slice(),
+            b"feature/login && git status\r\n"
+        );
+    }
 }
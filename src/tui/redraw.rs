@@ -0,0 +1,97 @@
+//! Deciding when the dashboard loop draws and how long it waits for input.
+//!
+//! Drawing every tick keeps an idle dashboard busy, so a frame is drawn only
+//! after input, terminal output, or while an effect animates. Without any of
+//! those the wait for input doubles up to [`MAX_IDLE_TICK`], and a frame is still
+//! drawn at that pace so changes nobody reported (a reaped tab, a reloaded
+//! config) show up eventually.
+
+use std::time::Duration;
+
+/// How long to wait for input while something is happening.
+pub(super) const ACTIVE_TICK: Duration = Duration::from_millis(50);
+
+/// The longest wait for input, and the slowest redraw pace, when idle.
+pub(super) const MAX_IDLE_TICK: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
+pub(super) struct RedrawGate {
+    dirty: bool,
+    /// Terminal output generation as of the last check.
+    seen_output: Option<u64>,
+    tick: Duration,
+}
+
+impl RedrawGate {
+    pub(super) fn new() -> Self {
+        Self {
+            dirty: true,
+            seen_output: None,
+            tick: ACTIVE_TICK,
+        }
+    }
+
+    /// Record a change made by the loop itself, e.g. a handled input event.
+    pub(super) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether to draw now, given the latest terminal `output` generation, and
+    /// adjust [`Self::tick`] to match.
+    pub(super) fn should_draw(&mut self, output: u64, animating: bool) -> bool {
+        let active = self.dirty || animating || self.seen_output != Some(output);
+        self.dirty = false;
+        self.seen_output = Some(output);
+        if active {
+            self.tick = ACTIVE_TICK;
+            return true;
+        }
+        let waited_longest = self.tick == MAX_IDLE_TICK;
+        self.tick = (self.tick * 2).min(MAX_IDLE_TICK);
+        waited_longest
+    }
+
+    /// How long the next wait for input may take.
+    pub(super) fn tick(&self) -> Duration {
+        self.tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_only_after_changes_and_backs_off_while_idle() {
+        let mut gate = RedrawGate::new();
+        assert!(
+            gate.should_draw(4, false),
+            "the first frame is always drawn"
+        );
+
+        let idle: Vec<(bool, Duration)> = (0..6)
+            .map(|_| (gate.should_draw(4, false), gate.tick()))
+            .collect();
+        let ms = Duration::from_millis;
+        assert_eq!(
+            idle,
+            [
+                (false, ms(100)),
+                (false, ms(200)),
+                (false, ms(400)),
+                (false, MAX_IDLE_TICK),
+                (true, MAX_IDLE_TICK),
+                (true, MAX_IDLE_TICK),
+            ]
+        );
+
+        assert!(gate.should_draw(9, false), "terminal output");
+        assert_eq!(gate.tick(), ACTIVE_TICK);
+        assert!(!gate.should_draw(9, false));
+
+        gate.mark_dirty();
+        assert!(gate.should_draw(9, false), "input");
+        assert!(gate.should_draw(9, true), "a running effect");
+        assert_eq!(gate.tick(), ACTIVE_TICK);
+    }
+}
@@ -0,0 +1,2178 @@
+//! Workspace (managed worktree) operations shared by the CLI commands.
+//!
+//! Failures are reported as [`WorkspaceError`] so callers can distinguish
+//! user mistakes from git failures instead of matching on message text.
+
+use crate::bulk::{BulkResult, Outcome};
+use crate::cancel::CancellationToken;
+use crate::git::{self, status::GitStatusSummary, AddOptions, WorktreeInfo};
+use crate::hooks::{self, Hook, HookContext};
+use crate::last_delete::{self, LastDelete};
+use crate::lock::{LockError, WtmLock};
+use crate::notes;
+use crate::pins::Pins;
+use crate::runner::{CommandRunner, SystemRunner};
+use crate::tags::Tags;
+use crate::trash::{self, TrashEntry};
+use crate::wtm_paths::{
+    ensure_workspace_root, normalize_lexically, reserve_target_path, sanitize_branch_name,
+    target_relative_path, PathLayout,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+/// Errors produced by workspace operations.
+#[derive(Debug, Error)]
+pub enum WorkspaceError {
+    #[error("Branch name is required.")]
+    BranchRequired,
+    #[error("Workspace directory already exists at {}", .0.display())]
+    DirectoryExists(PathBuf),
+    #[error("Cannot adopt {}: it is not empty (contains {})", path.display(), entries.join(", "))]
+    NotAdoptable { path: PathBuf, entries: Vec<String> },
+    #[error("No worktree found at {}", .0.display())]
+    NoMatch(PathBuf),
+    #[error(
+        "`{name}` matches several worktrees; pass one of their paths instead: {}",
+        candidates.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", ")
+    )]
+    Ambiguous {
+        name: String,
+        candidates: Vec<PathBuf>,
+    },
+    #[error("Refusing to remove the primary worktree at {}", .0.display())]
+    PrimaryProtected(PathBuf),
+    #[error("The primary worktree at {} cannot be moved", .0.display())]
+    PrimaryImmovable(PathBuf),
+    #[error(
+        "{} is outside .wtm/workspaces (use --allow-external to move it there)",
+        .0.display()
+    )]
+    ExternalDestination(PathBuf),
+    #[error("No branch is checked out at {}", .0.display())]
+    DetachedHead(PathBuf),
+    #[error(
+        "Branch {0} does not exist locally (use --create-if-missing to start it from a remote branch or HEAD)"
+    )]
+    BranchNotFound(String),
+    #[error("Branch {0} exists neither locally nor on any remote")]
+    BranchNowhere(String),
+    #[error(
+        "Branch {branch} exists on several remotes ({}); use `worktree add {branch} --from <remote>/{branch}`",
+        remotes.join(", ")
+    )]
+    AmbiguousRemote {
+        branch: String,
+        remotes: Vec<String>,
+    },
+    #[error(
+        "Refusing to remove {}: {} (use --force to remove anyway)",
+        path.display(),
+        blockers.join(", ")
+    )]
+    Unsafe {
+        path: PathBuf,
+        blockers: Vec<String>,
+    },
+    #[error(
+        "Refusing to remove {}: it is the directory you are standing in, which would vanish from under you (use --force to remove anyway)",
+        .0.display()
+    )]
+    CurrentDirectory(PathBuf),
+    #[error("Nothing to undo: no worktree removal has been recorded")]
+    NothingToUndo,
+    #[error("Cannot undo removing {}: {reason}", path.display())]
+    UndoImpossible { path: PathBuf, reason: String },
+    #[error("No trash entry named `{0}` (see `wtm worktree trash list`)")]
+    NotInTrash(String),
+    #[error("pre-remove hook rejected removing {}: {output}", path.display())]
+    HookRejected { path: PathBuf, output: String },
+    #[error("failed to prepare {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Locked(#[from] LockError),
+    #[error(transparent)]
+    Git(#[from] anyhow::Error),
+}
+
+/// Remote reference a new branch starts from, and whether it should track it.
+#[derive(Debug, Clone, Copy)]
+pub struct Upstream<'a> {
+    pub reference: &'a str,
+    pub track: Option<bool>,
+}
+
+/// Create a worktree for `branch` under `.wtm/workspaces`, returning its path.
+///
+/// Without an `upstream` the new branch starts from the current `HEAD`;
+/// `layout` names the new directory per `workspacePathTemplate` and
+/// `maxSlugLength`.
+pub fn create_workspace(
+    repo_root: &Path,
+    branch: &str,
+    upstream: Option<Upstream<'_>>,
+    layout: PathLayout<'_>,
+    options: &AddOptions,
+) -> Result<PathBuf, WorkspaceError> {
+    let branch = sanitize_branch_name(branch);
+    if branch.is_empty() {
+        return Err(WorkspaceError::BranchRequired);
+    }
+    let _lock = WtmLock::acquire(repo_root)?;
+    let workspace_root = ensure_workspace_root(repo_root).map_err(|source| WorkspaceError::Io {
+        path: crate::wtm_paths::workspace_root(repo_root),
+        source,
+    })?;
+    reserve_and_add(&workspace_root, &branch, layout, |path| {
+        add_worktree_at(repo_root, path, &branch, upstream, options)
+    })
+}
+
+/// Reserve the directory for `branch` and let `add` register the worktree there.
+fn reserve_and_add(
+    workspace_root: &Path,
+    branch: &str,
+    layout: PathLayout<'_>,
+    add: impl FnOnce(&Path) -> Result<(), WorkspaceError>,
+) -> Result<PathBuf, WorkspaceError> {
+    let worktree_path = reserve_target_path(workspace_root, branch, layout).map_err(|source| {
+        WorkspaceError::Io {
+            path: workspace_root.join(target_relative_path(branch, layout)),
+            source,
+        }
+    })?;
+    if let Err(err) = add(&worktree_path) {
+        // Give the reservation back; it is still empty.
+        let _ = fs::remove_dir(&worktree_path);
+        return Err(err);
+    }
+    Ok(worktree_path)
+}
+
+/// Where [`attach_workspace`] found the branch it checked out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttachSource {
+    /// The local branch already existed.
+    Local,
+    /// The branch was created from this remote-tracking branch.
+    Remote(String),
+    /// Nothing matched, so the branch was created from `HEAD`.
+    Created,
+}
+
+/// Create a worktree under `.wtm/workspaces` for the existing branch `branch`.
+///
+/// With `create_if_missing`, a branch that only exists on a remote is created
+/// from it (tracking per `track`), and one that exists nowhere from `HEAD`.
+pub fn attach_workspace(
+    repo_root: &Path,
+    branch: &str,
+    create_if_missing: bool,
+    track: Option<bool>,
+    layout: PathLayout<'_>,
+    options: &AddOptions,
+) -> Result<(PathBuf, AttachSource), WorkspaceError> {
+    let branch = branch.trim();
+    if branch.is_empty() {
+        return Err(WorkspaceError::BranchRequired);
+    }
+    let _lock = WtmLock::acquire(repo_root)?;
+    let source = attach_source_with(&SystemRunner, repo_root, branch, create_if_missing)?;
+    let path = add_for_source(repo_root, branch, &source, track, layout, options)?;
+    Ok((path, source))
+}
+
+/// Create a worktree for `branch` the way `git checkout <branch>` would: the local
+/// branch if there is one, else a new branch tracking the remote branch of that name.
+///
+/// `origin` wins when several remotes have the branch; otherwise that is an error.
+pub fn checkout_workspace(
+    repo_root: &Path,
+    branch: &str,
+    layout: PathLayout<'_>,
+    options: &AddOptions,
+) -> Result<(PathBuf, AttachSource), WorkspaceError> {
+    let branch = branch.trim();
+    if branch.is_empty() {
+        return Err(WorkspaceError::BranchRequired);
+    }
+    let _lock = WtmLock::acquire(repo_root)?;
+    let source = checkout_source_with(&SystemRunner, repo_root, branch)?;
+    let path = add_for_source(repo_root, branch, &source, Some(true), layout, options)?;
+    Ok((path, source))
+}
+
+/// Reserve a directory for `branch` and check it out as `source` says.
+fn add_for_source(
+    repo_root: &Path,
+    branch: &str,
+    source: &AttachSource,
+    track: Option<bool>,
+    layout: PathLayout<'_>,
+    options: &AddOptions,
+) -> Result<PathBuf, WorkspaceError> {
+    let workspace_root = ensure_workspace_root(repo_root).map_err(|source| WorkspaceError::Io {
+        path: crate::wtm_paths::workspace_root(repo_root),
+        source,
+    })?;
+    reserve_and_add(&workspace_root, branch, layout, |path| {
+        match source {
+            AttachSource::Local => git::add_worktree_for_branch(repo_root, path, branch, options)?,
+            AttachSource::Remote(upstream) => {
+                git::add_worktree_from_upstream(repo_root, path, branch, upstream, track, options)?
+            }
+            AttachSource::Created => {
+                git::add_worktree_new_branch(repo_root, path, branch, options)?
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Decide how to check out `branch`, preferring a branch on `origin` among remotes.
+fn attach_source_with(
+    runner: &dyn CommandRunner,
+    repo_root: &Path,
+    branch: &str,
+    create_if_missing: bool,
+) -> Result<AttachSource, WorkspaceError> {
+    if git::branch_exists_with(runner, repo_root, branch)? {
+        return Ok(AttachSource::Local);
+    }
+    if !create_if_missing {
+        return Err(WorkspaceError::BranchNotFound(branch.to_string()));
+    }
+    let remotes = remote_branches_named(runner, repo_root, branch)?;
+    let upstream = remotes
+        .iter()
+        .find(|reference| reference.starts_with("origin/"))
+        .or(remotes.first());
+    Ok(match upstream {
+        Some(upstream) => AttachSource::Remote(upstream.clone()),
+        None => AttachSource::Created,
+    })
+}
+
+/// Like [`attach_source_with`] with `create_if_missing`, but never start from
+/// `HEAD` and refuse to guess between remotes other than `origin`.
+fn checkout_source_with(
+    runner: &dyn CommandRunner,
+    repo_root: &Path,
+    branch: &str,
+) -> Result<AttachSource, WorkspaceError> {
+    if git::branch_exists_with(runner, repo_root, branch)? {
+        return Ok(AttachSource::Local);
+    }
+    let mut remotes = remote_branches_named(runner, repo_root, branch)?;
+    if let Some(origin) = remotes
+        .iter()
+        .position(|reference| reference.starts_with("origin/"))
+    {
+        return Ok(AttachSource::Remote(remotes.swap_remove(origin)));
+    }
+    match remotes.len() {
+        0 => Err(WorkspaceError::BranchNowhere(branch.to_string())),
+        1 => Ok(AttachSource::Remote(remotes.remove(0))),
+        _ => {
+            remotes.sort();
+            Err(WorkspaceError::AmbiguousRemote {
+                branch: branch.to_string(),
+                remotes,
+            })
+        }
+    }
+}
+
+/// Remote-tracking branches `<remote>/<branch>`, newest commit first.
+fn remote_branches_named(
+    runner: &dyn CommandRunner,
+    repo_root: &Path,
+    branch: &str,
+) -> Result<Vec<String>, WorkspaceError> {
+    Ok(git::list_remote_branches_with(runner, repo_root, None)?
+        .into_iter()
+        .filter(|reference| {
+            reference
+                .split_once('/')
+                .is_some_and(|(_, name)| name == branch)
+        })
+        .collect())
+}
+
+/// Files that may sit in a directory being adopted; they are deleted first.
+const ADOPTABLE_LEFTOVERS: &[&str] = &[".DS_Store", "Thumbs.db", "desktop.ini"];
+
+/// Like [`create_workspace`], but reuse the target directory if it already exists.
+///
+/// The directory must be empty apart from [`ADOPTABLE_LEFTOVERS`], since
+/// `git worktree add` only populates empty directories.
+pub fn adopt_workspace(
+    repo_root: &Path,
+    branch: &str,
+    upstream: Option<Upstream<'_>>,
+    layout: PathLayout<'_>,
+    options: &AddOptions,
+) -> Result<PathBuf, WorkspaceError> {
+    let branch = sanitize_branch_name(branch);
+    if branch.is_empty() {
+        return Err(WorkspaceError::BranchRequired);
+    }
+    let _lock = WtmLock::acquire(repo_root)?;
+    let workspace_root = ensure_workspace_root(repo_root).map_err(|source| WorkspaceError::Io {
+        path: crate::wtm_paths::workspace_root(repo_root),
+        source,
+    })?;
+    let worktree_path = workspace_root.join(target_relative_path(&branch, layout));
+    if worktree_path.exists() {
+        prepare_adoption(&worktree_path)?;
+    }
+    add_worktree_at(repo_root, &worktree_path, &branch, upstream, options)?;
+    Ok(worktree_path)
+}
+
+/// Check that `dir` is empty apart from leftovers, and delete those.
+fn prepare_adoption(dir: &Path) -> Result<(), WorkspaceError> {
+    let io_error = |source| WorkspaceError::Io {
+        path: dir.to_path_buf(),
+        source,
+    };
+    let mut leftovers = Vec::new();
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if ADOPTABLE_LEFTOVERS.contains(&name.as_str()) {
+            leftovers.push(entry.path());
+        } else {
+            entries.push(name);
+        }
+    }
+    if !entries.is_empty() {
+        entries.sort();
+        return Err(WorkspaceError::NotAdoptable {
+            path: dir.to_path_buf(),
+            entries,
+        });
+    }
+    for leftover in leftovers {
+        fs::remove_file(&leftover).map_err(io_error)?;
+    }
+    Ok(())
+}
+
+fn add_worktree_at(
+    repo_root: &Path,
+    worktree_path: &Path,
+    branch: &str,
+    upstream: Option<Upstream<'_>>,
+    options: &AddOptions,
+) -> Result<(), WorkspaceError> {
+    match upstream {
+        Some(upstream) => git::add_worktree_from_upstream(
+            repo_root,
+            worktree_path,
+            branch,
+            upstream.reference,
+            upstream.track,
+            options,
+        )?,
+        None => git::add_worktree_new_branch(repo_root, worktree_path, branch, options)?,
+    }
+    Ok(())
+}
+
+/// New branch (and its start point) for a fresh worktree of the branch checked out in a worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClonePlan {
+    /// Unused `<branch>-wt-<n>` name for the new branch.
+    pub branch: String,
+    /// The current branch's upstream, or the branch itself when it has none.
+    pub start_point: String,
+}
+
+/// Plan a worktree that starts from the latest state of the branch checked out at `source`.
+pub fn plan_clone_current(repo_root: &Path, source: &Path) -> Result<ClonePlan, WorkspaceError> {
+    let status = git::status::status(source)?;
+    let existing = git::list_branches(repo_root, None)?;
+    clone_plan(&status, &existing, source)
+}
+
+fn clone_plan(
+    status: &GitStatusSummary,
+    existing: &[String],
+    source: &Path,
+) -> Result<ClonePlan, WorkspaceError> {
+    let branch = status
+        .branch
+        .as_deref()
+        .filter(|branch| *branch != "(detached)")
+        .ok_or_else(|| WorkspaceError::DetachedHead(source.to_path_buf()))?;
+    Ok(ClonePlan {
+        branch: numbered_branch_name(branch, "wt", existing),
+        start_point: status.upstream.as_deref().unwrap_or(branch).to_string(),
+    })
+}
+
+/// New branch and start commit for a second checkout of a worktree's branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicatePlan {
+    pub source: PathBuf,
+    /// Unused `<branch>-copy-<n>` name for the new branch.
+    pub branch: String,
+    /// Commit checked out at `source`, where the new branch starts.
+    pub head: String,
+}
+
+/// Plan a duplicate of the worktree at `path` (relative paths resolve against `.wtm/workspaces`).
+///
+/// git checks a branch out in one worktree at a time, so the duplicate gets its
+/// own branch starting at the source's `HEAD`, local commits included.
+pub fn plan_duplicate(repo_root: &Path, path: &Path) -> Result<DuplicatePlan, WorkspaceError> {
+    let worktree = find_workspace(repo_root, path)?;
+    let source = worktree.path;
+    let branch = worktree
+        .branch
+        .as_deref()
+        .ok_or_else(|| WorkspaceError::DetachedHead(source.clone()))?;
+    let existing = git::list_branches(repo_root, None)?;
+    Ok(DuplicatePlan {
+        branch: numbered_branch_name(branch, "copy", &existing),
+        head: git::head_commit(&source)?,
+        source,
+    })
+}
+
+/// The registered worktree at `path` (relative paths are under `.wtm/workspaces`).
+pub fn find_workspace(repo_root: &Path, path: &Path) -> Result<WorktreeInfo, WorkspaceError> {
+    let full_path = resolve_worktree_path(repo_root, path)?;
+    git::list_worktrees_cached(repo_root)?
+        .into_iter()
+        .find(|wt| same_path(&wt.path, &full_path))
+        .ok_or(WorkspaceError::NoMatch(full_path))
+}
+
+/// The worktree whose directory is named `name`, falling back to [`find_workspace`]
+/// only when none is.
+///
+/// Categories and path templates let two worktrees share a directory name;
+/// rather than guess, that is [`WorkspaceError::Ambiguous`].
+pub fn lookup_workspace(repo_root: &Path, name: &str) -> Result<WorktreeInfo, WorkspaceError> {
+    let mut by_name: Vec<_> = git::list_worktrees_cached(repo_root)?
+        .into_iter()
+        .filter(|wt| wt.name() == name)
+        .collect();
+    match by_name.len() {
+        0 => find_workspace(repo_root, Path::new(name)),
+        1 => Ok(by_name.remove(0)),
+        _ => Err(WorkspaceError::Ambiguous {
+            name: name.to_string(),
+            candidates: by_name.into_iter().map(|wt| wt.path).collect(),
+        }),
+    }
+}
+
+/// First `<branch>-<label>-<n>` (counting from 1) that is not already a branch.
+fn numbered_branch_name(branch: &str, label: &str, existing: &[String]) -> String {
+    (1..)
+        .map(|n| format!("{branch}-{label}-{n}"))
+        .find(|candidate| !existing.iter().any(|name| name == candidate))
+        .expect("an unused branch name exists")
+}
+
+/// Remove the worktree at `path` (relative paths resolve against `.wtm/workspaces`).
+///
+/// Without `force`, worktrees with uncommitted changes or unpushed commits are
+/// refused. `.wtm/hooks/pre-remove` runs first unless `run_hooks` is false and
+/// can veto the removal by exiting non-zero.
+pub fn delete_workspace(
+    repo_root: &Path,
+    path: &Path,
+    force: bool,
+    run_hooks: bool,
+) -> Result<PathBuf, WorkspaceError> {
+    let _lock = WtmLock::acquire(repo_root)?;
+    let overrides = Overrides {
+        unsafe_work: force,
+        current_directory: force,
+    };
+    let (full_path, worktree) = prepare_removal(repo_root, path, overrides, run_hooks)?;
+    git::remove_worktree(repo_root, &full_path, force)?;
+    let record = LastDelete {
+        branch: worktree.branch,
+        path: full_path.clone(),
+        force,
+    };
+    if let Err(err) = last_delete::save(&repo_root.join(".wtm"), &record) {
+        log::warn!("could not record the removal for undo: {err:#}");
+    }
+    forget_removed_metadata(repo_root);
+    Ok(full_path)
+}
+
+/// Drop the tags and pins of worktrees that no longer exist; failing only
+/// leaves stale entries.
+fn forget_removed_metadata(repo_root: &Path) {
+    let wtm_dir = repo_root.join(".wtm");
+    let worktrees = match git::list_worktrees_cached(repo_root) {
+        Ok(worktrees) => worktrees,
+        Err(err) => {
+            log::warn!("could not update worktree tags, pins, and notes: {err:#}");
+            return;
+        }
+    };
+    let existing = || worktrees.iter().map(|wt| wt.path.as_path());
+    let tags = Tags::load(&wtm_dir).and_then(|mut tags| {
+        if tags.reconcile(existing()) {
+            tags.save(&wtm_dir)?;
+        }
+        Ok(())
+    });
+    if let Err(err) = tags {
+        log::warn!("could not update worktree tags: {err:#}");
+    }
+    let pins = Pins::load(&wtm_dir).and_then(|mut pins| {
+        if pins.reconcile(existing()) {
+            pins.save(&wtm_dir)?;
+        }
+        Ok(())
+    });
+    if let Err(err) = pins {
+        log::warn!("could not update worktree pins: {err:#}");
+    }
+    if let Err(err) = notes::reconcile(repo_root, existing()) {
+        log::warn!("could not update worktree notes: {err:#}");
+    }
+}
+
+/// Move the worktree at `path` into `.wtm/trash/` and detach it from git.
+///
+/// Uncommitted and untracked files go to the trash with it, so they never
+/// block it; trashing the worktree wtm runs in still needs `from_inside`. The
+/// pre-remove hook runs and can veto.
+pub fn trash_workspace(
+    repo_root: &Path,
+    path: &Path,
+    from_inside: bool,
+    run_hooks: bool,
+) -> Result<TrashEntry, WorkspaceError> {
+    let _lock = WtmLock::acquire(repo_root)?;
+    let overrides = Overrides {
+        unsafe_work: true,
+        current_directory: from_inside,
+    };
+    let (full_path, worktree) = prepare_removal(repo_root, path, overrides, run_hooks)?;
+    let trashed_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let entry = TrashEntry {
+        id: String::new(),
+        original_path: full_path.clone(),
+        branch: worktree.branch,
+        head: worktree.head,
+        trashed_at,
+    };
+    let entry = trash::move_to_trash(repo_root, entry).map_err(|source| WorkspaceError::Io {
+        path: full_path.clone(),
+        source,
+    })?;
+    // The directory is gone, so this only drops git's record of the worktree.
+    git::remove_worktree(repo_root, &full_path, true)?;
+    forget_removed_metadata(repo_root);
+    Ok(entry)
+}
+
+/// Put the trash entry `id` back at its original path and register it with git again.
+///
+/// The branch is checked out again if it still exists, otherwise the worktree is
+/// detached at the commit it had. Staged changes come back as unstaged ones.
+pub fn restore_trashed(repo_root: &Path, id: &str) -> Result<TrashEntry, WorkspaceError> {
+    let _lock = WtmLock::acquire(repo_root)?;
+    let entry =
+        trash::find(repo_root, id)?.ok_or_else(|| WorkspaceError::NotInTrash(id.to_string()))?;
+    if entry.original_path.exists() {
+        return Err(WorkspaceError::DirectoryExists(entry.original_path));
+    }
+    let branch = match entry.branch.as_deref() {
+        Some(branch) if git::branch_exists(repo_root, branch)? => Some(branch),
+        _ => None,
+    };
+    match (branch, entry.head.as_deref()) {
+        (Some(branch), _) => {
+            git::add_worktree_without_checkout(repo_root, &entry.original_path, branch, false)?
+        }
+        (None, Some(head)) => {
+            git::add_worktree_without_checkout(repo_root, &entry.original_path, head, true)?
+        }
+        (None, None) => {
+            return Err(WorkspaceError::UndoImpossible {
+                path: entry.original_path,
+                reason: "the trash entry records neither a branch nor a commit".into(),
+            })
+        }
+    }
+    let contents = trash::contents_path(repo_root, &entry);
+    let io_error = |source| WorkspaceError::Io {
+        path: contents.clone(),
+        source,
+    };
+    for item in fs::read_dir(&contents).map_err(io_error)? {
+        let item = item.map_err(io_error)?;
+        // The old `.git` file points at metadata git has already dropped.
+        if item.file_name() == ".git" {
+            continue;
+        }
+        trash::move_path(&item.path(), &entry.original_path.join(item.file_name()))
+            .map_err(io_error)?;
+    }
+    git::reset_index(&entry.original_path)?;
+    trash::discard(repo_root, &entry).map_err(io_error)?;
+    Ok(entry)
+}
+
+/// `path` itself when absolute, otherwise relative to `.wtm/workspaces`.
+fn resolve_worktree_path(repo_root: &Path, path: &Path) -> Result<PathBuf, WorkspaceError> {
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    let workspace_root = ensure_workspace_root(repo_root).map_err(|source| WorkspaceError::Io {
+        path: crate::wtm_paths::workspace_root(repo_root),
+        source,
+    })?;
+    Ok(workspace_root.join(path))
+}
+
+/// Which of [`prepare_removal`]'s refusals to skip.
+#[derive(Debug, Clone, Copy)]
+struct Overrides {
+    /// Uncommitted changes, untracked files, or unpushed commits.
+    unsafe_work: bool,
+    /// wtm was started in the worktree or below it.
+    current_directory: bool,
+}
+
+/// Resolve `path`, check it may be removed, and run the pre-remove hook.
+fn prepare_removal(
+    repo_root: &Path,
+    path: &Path,
+    overrides: Overrides,
+    run_hooks: bool,
+) -> Result<(PathBuf, WorktreeInfo), WorkspaceError> {
+    let full_path = resolve_worktree_path(repo_root, path)?;
+    let worktrees = git::list_worktrees_cached(repo_root)?;
+    check_removable(repo_root, &worktrees, &full_path)?;
+    if !overrides.current_directory
+        && std::env::current_dir().is_ok_and(|cwd| holds_worktree(&full_path, &cwd))
+    {
+        return Err(WorkspaceError::CurrentDirectory(full_path));
+    }
+    // With the directory already gone there is nothing left to lose, and git
+    // only prunes its record of the worktree.
+    if !overrides.unsafe_work && full_path.exists() {
+        let blockers = removal_blockers(
+            &git::status::status(&full_path)?,
+            git::unpushed_count(&full_path)?,
+        );
+        if !blockers.is_empty() {
+            return Err(WorkspaceError::Unsafe {
+                path: full_path,
+                blockers,
+            });
+        }
+    }
+    let worktree = worktrees
+        .into_iter()
+        .find(|wt| same_path(&wt.path, &full_path))
+        .ok_or_else(|| WorkspaceError::NoMatch(full_path.clone()))?;
+    if run_hooks {
+        let context = HookContext {
+            repo_root,
+            worktree_path: &full_path,
+            branch: worktree.branch.as_deref().unwrap_or_default(),
+        };
+        if let Some(outcome) = hooks::run_hook(Hook::PreRemove, context)? {
+            if !outcome.success {
+                return Err(WorkspaceError::HookRejected {
+                    path: full_path,
+                    output: outcome.output,
+                });
+            }
+        }
+    }
+    Ok((full_path, worktree))
+}
+
+/// Recreate the worktree last removed by [`delete_workspace`] at its old path,
+/// registering it with `options` like any other added worktree.
+///
+/// Only the checkout comes back: uncommitted work discarded by a forced removal
+/// is gone. The record is cleared once the worktree exists again.
+pub fn undo_delete(repo_root: &Path, options: &AddOptions) -> Result<LastDelete, WorkspaceError> {
+    undo_delete_with(&SystemRunner, repo_root, options)
+}
+
+fn undo_delete_with(
+    runner: &dyn CommandRunner,
+    repo_root: &Path,
+    options: &AddOptions,
+) -> Result<LastDelete, WorkspaceError> {
+    let _lock = WtmLock::acquire(repo_root)?;
+    let wtm_dir = repo_root.join(".wtm");
+    let record = last_delete::load(&wtm_dir)?.ok_or(WorkspaceError::NothingToUndo)?;
+    let impossible = |reason: String| WorkspaceError::UndoImpossible {
+        path: record.path.clone(),
+        reason,
+    };
+    let Some(branch) = record.branch.as_deref() else {
+        return Err(impossible("it had no branch checked out".into()));
+    };
+    if !git::branch_exists_with(runner, repo_root, branch)? {
+        return Err(impossible(format!("branch {branch} no longer exists")));
+    }
+    if record.path.exists() {
+        return Err(WorkspaceError::DirectoryExists(record.path));
+    }
+    git::add_worktree_for_branch_with(runner, repo_root, &record.path, branch, options)?;
+    last_delete::clear(&wtm_dir)?;
+    Ok(record)
+}
+
+/// Work that removing a worktree would lose, one description per kind;
+/// `unpushed` counts the commits on no remote.
+fn removal_blockers(status: &GitStatusSummary, unpushed: usize) -> Vec<String> {
+    let plural =
+        |count: usize, noun: &str| format!("{count} {noun}{}", if count == 1 { "" } else { "s" });
+    let mut blockers = Vec::new();
+    let changes = status.staged + status.unstaged + status.conflicts;
+    if changes > 0 {
+        blockers.push(plural(changes, "uncommitted change"));
+    }
+    if status.untracked > 0 {
+        blockers.push(plural(status.untracked, "untracked file"));
+    }
+    if unpushed > 0 {
+        let upstream = status.upstream.as_deref().unwrap_or("any remote");
+        blockers.push(format!(
+            "{} not pushed to {upstream}",
+            plural(unpushed, "commit")
+        ));
+    }
+    blockers
+}
+
+fn check_removable(
+    repo_root: &Path,
+    worktrees: &[WorktreeInfo],
+    path: &Path,
+) -> Result<(), WorkspaceError> {
+    if same_path(path, repo_root) {
+        return Err(WorkspaceError::PrimaryProtected(path.to_path_buf()));
+    }
+    if !worktrees.iter().any(|wt| same_path(&wt.path, path)) {
+        return Err(WorkspaceError::NoMatch(path.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// Where a worktree move would go, as `git worktree move` will resolve it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovePlan {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    /// Nothing exists at `destination` yet, so git can move the worktree there.
+    pub destination_free: bool,
+}
+
+/// Resolve a move of the worktree at `path` to `to` without touching anything.
+///
+/// Relative paths resolve against `.wtm/workspaces`; see [`move_plan`] for `to`.
+pub fn plan_move(
+    repo_root: &Path,
+    path: &Path,
+    to: &Path,
+    allow_external: bool,
+) -> Result<MovePlan, WorkspaceError> {
+    let root = crate::wtm_paths::workspace_root(repo_root);
+    let source = normalize_lexically(&root.join(path));
+    if same_path(&source, repo_root) {
+        return Err(WorkspaceError::PrimaryImmovable(source));
+    }
+    let worktrees = git::list_worktrees_cached(repo_root)?;
+    if !worktrees.iter().any(|wt| same_path(&wt.path, &source)) {
+        return Err(WorkspaceError::NoMatch(source));
+    }
+    move_plan(&root, source, to, allow_external)
+}
+
+/// A `to` that ends in a separator or names an existing directory is where
+/// the worktree goes, keeping its name (`archive/` → `archive/feature-a`);
+/// any other `to` is the worktree's new path (`archive/old-a`).
+///
+/// `to` may be absolute, but a destination outside `workspace_root` (including
+/// a relative one that climbs out with `..`) needs `allow_external`.
+fn move_plan(
+    workspace_root: &Path,
+    source: PathBuf,
+    to: &Path,
+    allow_external: bool,
+) -> Result<MovePlan, WorkspaceError> {
+    let mut destination = normalize_lexically(&workspace_root.join(to));
+    if !allow_external && !destination.starts_with(normalize_lexically(workspace_root)) {
+        return Err(WorkspaceError::ExternalDestination(destination));
+    }
+    let into_directory = to
+        .as_os_str()
+        .as_encoded_bytes()
+        .last()
+        .is_some_and(|byte| std::path::is_separator(char::from(*byte)));
+    if into_directory || destination.is_dir() {
+        if let Some(name) = source.file_name() {
+            destination.push(name);
+        }
+    }
+    Ok(MovePlan {
+        destination_free: !destination.exists(),
+        source,
+        destination,
+    })
+}
+
+/// Carry out a [`MovePlan`] with `git worktree move`, creating the destination's parent.
+///
+/// `force` is how many times to pass `--force`, as with git.
+pub fn move_workspace(repo_root: &Path, plan: &MovePlan, force: u8) -> Result<(), WorkspaceError> {
+    let _lock = WtmLock::acquire(repo_root)?;
+    if !plan.destination_free {
+        return Err(WorkspaceError::DirectoryExists(plan.destination.clone()));
+    }
+    if let Some(parent) = plan.destination.parent() {
+        fs::create_dir_all(parent).map_err(|source| WorkspaceError::Io {
+            path: parent.to_path_buf(),
+            source,
+        })?;
+    }
+    git::move_worktree(repo_root, &plan.source, &plan.destination, force)?;
+    move_metadata(repo_root, &plan.source, &plan.destination);
+    Ok(())
+}
+
+/// Re-key what `.wtm` keeps by worktree path to the worktree's new path;
+/// failing only loses the entries. The caller holds the `.wtm` lock.
+fn move_metadata(repo_root: &Path, from: &Path, to: &Path) {
+    let wtm_dir = repo_root.join(".wtm");
+    let pins = Pins::load(&wtm_dir).and_then(|mut pins| {
+        if pins.rename(from, to) {
+            pins.save(&wtm_dir)?;
+        }
+        Ok(())
+    });
+    if let Err(err) = pins {
+        log::warn!("could not move the worktree's pin: {err:#}");
+    }
+    let tags = Tags::load(&wtm_dir).and_then(|mut tags| {
+        if tags.rename(from, to) {
+            tags.save(&wtm_dir)?;
+        }
+        Ok(())
+    });
+    if let Err(err) = tags {
+        log::warn!("could not move the worktree's tags: {err:#}");
+    }
+    if let Err(err) = notes::rename(repo_root, from, to) {
+        log::warn!("could not move the worktree's note: {err:#}");
+    }
+}
+
+/// Drop the primary worktree, for commands run with `--no-primary`.
+pub fn retain_linked(worktrees: &mut Vec<WorktreeInfo>, repo_root: &Path) {
+    worktrees.retain(|wt| !wt.is_primary(repo_root));
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    canonical(a) == canonical(b)
+}
+
+/// Whether the worktree at `path` has nothing but its `.git` file, as after
+/// `worktree add --no-checkout`.
+pub fn is_unpopulated(path: &Path) -> bool {
+    fs::read_dir(path).is_ok_and(|entries| {
+        entries
+            .filter_map(|entry| entry.ok())
+            .all(|entry| entry.file_name() == ".git")
+    })
+}
+
+/// Whether `worktree` is `dir` or nested inside it (e.g. via `workspacePathTemplate`).
+fn holds_worktree(dir: &Path, worktree: &Path) -> bool {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    canonical(worktree).starts_with(canonical(dir))
+}
+
+/// Differences between `.wtm/workspaces` on disk and `git worktree list`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AuditReport {
+    /// Directories under the managed root that git does not know about.
+    pub orphan_directories: Vec<PathBuf>,
+    /// Checkouts under the managed root that belong to another repository,
+    /// e.g. copied in by hand. Kept out of `orphan_directories` so `--fix`
+    /// never deletes them.
+    pub foreign_worktrees: Vec<PathBuf>,
+    /// Linked worktrees that live outside the managed root.
+    pub external_worktrees: Vec<PathBuf>,
+    /// Worktrees git reports as prunable.
+    pub prunable_worktrees: Vec<PathBuf>,
+    /// Branches checked out in more than one worktree, which git normally forbids.
+    pub shared_branches: Vec<SharedBranch>,
+}
+
+/// A branch checked out in several worktrees, in `git worktree list` order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SharedBranch {
+    pub branch: String,
+    pub worktrees: Vec<PathBuf>,
+}
+
+impl AuditReport {
+    pub fn is_clean(&self) -> bool {
+        self.orphan_directories.is_empty()
+            && self.foreign_worktrees.is_empty()
+            && self.external_worktrees.is_empty()
+            && self.prunable_worktrees.is_empty()
+            && self.shared_branches.is_empty()
+    }
+
+    /// The orphaned directories `--fix` deletes: every one but the `default`
+    /// directory `wtm init` used to create.
+    pub fn removable_orphans(&self) -> impl Iterator<Item = &PathBuf> {
+        self.orphan_directories
+            .iter()
+            .filter(|dir| !is_init_placeholder(dir))
+    }
+}
+
+/// Cross-reference the managed workspace root with git's worktree list.
+pub fn audit_workspaces(repo_root: &Path) -> Result<AuditReport, WorkspaceError> {
+    let root = crate::wtm_paths::workspace_root(repo_root);
+    let worktrees = git::list_worktrees_cached(repo_root)?;
+    let directories = workspace_directories(&root)?;
+    let mut report = compare_workspaces(repo_root, &root, &directories, &worktrees);
+    let checkouts: Vec<PathBuf> = report
+        .orphan_directories
+        .iter()
+        .filter(|dir| dir.join(".git").exists())
+        .cloned()
+        .collect();
+    report.foreign_worktrees = foreign_checkouts(repo_root, &checkouts, git::common_dir)?;
+    report
+        .orphan_directories
+        .retain(|dir| !report.foreign_worktrees.contains(dir));
+    Ok(report)
+}
+
+/// The `checkouts` whose git common dir is not `repo_root`'s; one git cannot
+/// read counts as foreign too, since it is not this repository's either.
+fn foreign_checkouts(
+    repo_root: &Path,
+    checkouts: &[PathBuf],
+    common_dir: impl Fn(&Path) -> anyhow::Result<PathBuf>,
+) -> Result<Vec<PathBuf>, WorkspaceError> {
+    if checkouts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let ours = common_dir(repo_root)?;
+    Ok(checkouts
+        .iter()
+        .filter(|dir| common_dir(dir).map_or(true, |theirs| !same_path(&theirs, &ours)))
+        .cloned()
+        .collect())
+}
+
+/// The directories directly under the managed root; none if it does not exist.
+fn workspace_directories(root: &Path) -> Result<Vec<PathBuf>, WorkspaceError> {
+    match fs::read_dir(root) {
+        Ok(entries) => Ok(entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|ty| ty.is_dir()))
+            .map(|entry| entry.path())
+            .collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(source) => Err(WorkspaceError::Io {
+            path: root.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+/// The `directories` that neither are a worktree nor hold one, looking inside
+/// those that only hold worktrees further down (e.g. via
+/// `workspacePathTemplate`) for orphans next to them.
+fn orphans(directories: &[PathBuf], worktrees: &[WorktreeInfo]) -> Vec<PathBuf> {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let worktrees: Vec<PathBuf> = worktrees.iter().map(|wt| canonical(&wt.path)).collect();
+    let mut found = Vec::new();
+    let mut pending = directories.to_vec();
+    while let Some(dir) = pending.pop() {
+        let resolved = canonical(&dir);
+        if worktrees.contains(&resolved) {
+            continue;
+        }
+        if worktrees.iter().any(|wt| wt.starts_with(&resolved)) {
+            pending.extend(workspace_directories(&dir).unwrap_or_default());
+        } else {
+            found.push(dir);
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Warn when `.wtm/workspaces` holds directories that are not worktrees, which
+/// `wtm worktree audit` can list and clean up.
+pub fn stray_directories_warning(repo_root: &Path) -> Option<String> {
+    let root = crate::wtm_paths::workspace_root(repo_root);
+    let directories = workspace_directories(&root).ok()?;
+    if directories.is_empty() {
+        return None;
+    }
+    let worktrees = git::list_worktrees_cached(repo_root).ok()?;
+    let stray = stray_directories(&directories, &worktrees);
+    if stray.is_empty() {
+        return None;
+    }
+    let names: Vec<String> = stray
+        .iter()
+        .filter_map(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    Some(format!(
+        "{} not a git worktree: {} (run `wtm worktree audit` to review)",
+        if names.len() == 1 {
+            "1 directory in .wtm/workspaces is".to_string()
+        } else {
+            format!("{} directories in .wtm/workspaces are", names.len())
+        },
+        names.join(", ")
+    ))
+}
+
+/// The `default` directory older versions of `wtm init` created under the
+/// managed root.
+fn is_init_placeholder(dir: &Path) -> bool {
+    dir.file_name().is_some_and(|name| name == "default")
+}
+
+/// `dir` is the init placeholder and nothing was put in it.
+fn is_empty_placeholder(dir: &Path) -> bool {
+    is_init_placeholder(dir) && fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none())
+}
+
+/// Orphaned `directories`, except the empty `default` placeholder.
+fn stray_directories(directories: &[PathBuf], worktrees: &[WorktreeInfo]) -> Vec<PathBuf> {
+    let candidates: Vec<PathBuf> = directories
+        .iter()
+        .filter(|dir| !is_empty_placeholder(dir))
+        .cloned()
+        .collect();
+    orphans(&candidates, worktrees)
+}
+
+fn compare_workspaces(
+    repo_root: &Path,
+    workspace_root: &Path,
+    directories: &[PathBuf],
+    worktrees: &[WorktreeInfo],
+) -> AuditReport {
+    AuditReport {
+        foreign_worktrees: Vec::new(),
+        orphan_directories: stray_directories(directories, worktrees),
+        external_worktrees: worktrees
+            .iter()
+            .filter(|wt| !same_path(&wt.path, repo_root) && !wt.path.starts_with(workspace_root))
+            .map(|wt| wt.path.clone())
+            .collect(),
+        prunable_worktrees: worktrees
+            .iter()
+            .filter(|wt| wt.is_prunable)
+            .map(|wt| wt.path.clone())
+            .collect(),
+        shared_branches: shared_branches(worktrees),
+    }
+}
+
+/// Group worktrees by branch and keep the branches checked out more than once.
+fn shared_branches(worktrees: &[WorktreeInfo]) -> Vec<SharedBranch> {
+    let mut by_branch: BTreeMap<&str, Vec<PathBuf>> = BTreeMap::new();
+    for wt in worktrees {
+        if let Some(branch) = wt.branch.as_deref() {
+            by_branch.entry(branch).or_default().push(wt.path.clone());
+        }
+    }
+    by_branch
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(branch, worktrees)| SharedBranch {
+            branch: branch.to_string(),
+            worktrees,
+        })
+        .collect()
+}
+
+/// Detach `HEAD` in every worktree but the first of each shared branch, so the
+/// primary checkout (listed first by git) keeps its branch. Returns the
+/// detached worktrees.
+pub fn detach_shared_branches(
+    repo_root: &Path,
+    report: &AuditReport,
+) -> Result<Vec<PathBuf>, WorkspaceError> {
+    let _lock = WtmLock::acquire(repo_root)?;
+    let mut detached = Vec::new();
+    for shared in &report.shared_branches {
+        for path in shared.worktrees.iter().skip(1) {
+            git::detach_head(repo_root, path)?;
+            detached.push(path.clone());
+        }
+    }
+    Ok(detached)
+}
+
+/// Delete the [removable](AuditReport::removable_orphans) orphaned directories
+/// found by [`audit_workspaces`], carrying on past failures.
+///
+/// The report may be stale by the time the user confirms, so the audit is
+/// re-run under the wtm lock and only directories that are still orphaned are
+/// deleted; ones that are gone or were registered since are skipped.
+pub fn remove_orphans(
+    repo_root: &Path,
+    report: &AuditReport,
+) -> Result<BulkResult, WorkspaceError> {
+    let _lock = WtmLock::acquire(repo_root)?;
+    git::invalidate_worktree_cache(repo_root);
+    let current = audit_workspaces(repo_root)?;
+    let still_orphaned: BTreeSet<&PathBuf> = current.removable_orphans().collect();
+    let mut result = BulkResult::new();
+    for dir in report.removable_orphans() {
+        let name = dir.display().to_string();
+        if !dir.exists() || !still_orphaned.contains(dir) {
+            result.record(name, Outcome::Skipped);
+            continue;
+        }
+        result.record_result(name, fs::remove_dir_all(dir));
+    }
+    Ok(result)
+}
+
+/// Disk usage of a single worktree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkspaceUsage {
+    pub name: String,
+    pub path: PathBuf,
+    pub bytes: u64,
+    /// The directory is gone (or git marks the worktree prunable), so it was
+    /// not measured and counts as zero.
+    pub missing: bool,
+}
+
+/// Combined disk usage across worktrees, largest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiskUsage {
+    pub total_bytes: u64,
+    pub workspaces: Vec<WorkspaceUsage>,
+    /// The walk was cancelled, so the sizes are only what it counted so far.
+    pub cancelled: bool,
+}
+
+/// What [`directory_size`] counted, and whether it stopped early.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirectorySize {
+    pub bytes: u64,
+    pub cancelled: bool,
+}
+
+/// Files and bytes counted so far by a disk walk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalkProgress {
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// How many files a walk counts between progress reports.
+const PROGRESS_INTERVAL: u64 = 512;
+
+/// Measure every worktree in parallel, passing the combined progress of all
+/// walks to `on_progress`.
+///
+/// Worktrees nested inside another (e.g. `.wtm/workspaces/*` under the primary
+/// checkout) are only counted once, against their own entry. Soon after
+/// `cancel` is set every walk stops, and the result holds the sizes counted so
+/// far with [`DiskUsage::cancelled`] set.
+pub fn disk_usage_with(
+    worktrees: &[WorktreeInfo],
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(WalkProgress) + Sync),
+) -> io::Result<DiskUsage> {
+    let roots: Vec<&Path> = worktrees.iter().map(|wt| wt.path.as_path()).collect();
+    let bytes = AtomicU64::new(0);
+    let files = AtomicU64::new(0);
+    let sizes: Vec<Option<io::Result<DirectorySize>>> = crate::timings::measure(
+        || "disk walk".into(),
+        || {
+            thread::scope(|scope| {
+                let handles: Vec<_> = worktrees
+                    .iter()
+                    .map(|worktree| {
+                        if is_missing(worktree) {
+                            return None;
+                        }
+                        let root = worktree.path.as_path();
+                        let (roots, bytes, files) = (&roots, &bytes, &files);
+                        Some(scope.spawn(move || {
+                            let nested: Vec<&Path> = roots
+                                .iter()
+                                .copied()
+                                .filter(|other| *other != root && other.starts_with(root))
+                                .collect();
+                            let mut reported = WalkProgress::default();
+                            directory_size(root, &nested, cancel, &mut |progress| {
+                                let new_bytes = progress.bytes - reported.bytes;
+                                let new_files = progress.files - reported.files;
+                                reported = progress;
+                                on_progress(WalkProgress {
+                                    bytes: bytes.fetch_add(new_bytes, Ordering::Relaxed)
+                                        + new_bytes,
+                                    files: files.fetch_add(new_files, Ordering::Relaxed)
+                                        + new_files,
+                                });
+                            })
+                        }))
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .zip(&roots)
+                    .map(|(handle, root)| {
+                        handle.map(|handle| {
+                            handle.join().unwrap_or_else(|_| {
+                                Err(io::Error::other(format!(
+                                    "measuring {} failed unexpectedly",
+                                    root.display()
+                                )))
+                            })
+                        })
+                    })
+                    .collect()
+            })
+        },
+    );
+
+    let mut workspaces = Vec::with_capacity(worktrees.len());
+    let mut cancelled = false;
+    for (worktree, size) in worktrees.iter().zip(sizes) {
+        let size = size.transpose()?;
+        cancelled |= size.is_some_and(|size| size.cancelled);
+        workspaces.push(WorkspaceUsage {
+            name: worktree.name(),
+            path: worktree.path.clone(),
+            bytes: size.map_or(0, |size| size.bytes),
+            missing: size.is_none(),
+        });
+    }
+    workspaces.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    Ok(DiskUsage {
+        total_bytes: workspaces.iter().map(|ws| ws.bytes).sum(),
+        workspaces,
+        cancelled,
+    })
+}
+
+/// A worktree whose directory is gone has nothing to measure.
+fn is_missing(worktree: &WorktreeInfo) -> bool {
+    worktree.is_prunable || !worktree.path.is_dir()
+}
+
+/// `err` with the path it happened at, which io errors leave out.
+fn io_error_at(path: &Path, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+}
+
+/// Total size of regular files under `root`, skipping `excluded` subtrees and symlinks.
+///
+/// Progress is reported every [`PROGRESS_INTERVAL`] files and once at the end;
+/// once `cancel` is set the walk stops before the next directory and returns
+/// the bytes counted so far. It keeps its own stack of directories, so deep
+/// trees cannot overflow the thread's stack.
+pub fn directory_size(
+    root: &Path,
+    excluded: &[&Path],
+    cancel: &CancellationToken,
+    on_progress: &mut dyn FnMut(WalkProgress),
+) -> io::Result<DirectorySize> {
+    let mut progress = WalkProgress::default();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if cancel.is_cancelled() {
+            return Ok(DirectorySize {
+                bytes: progress.bytes,
+                cancelled: true,
+            });
+        }
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            // Deleted while we walked, e.g. by a build cleaning up.
+            Err(err) if err.kind() == io::ErrorKind::NotFound && dir != root => continue,
+            Err(err) => return Err(io_error_at(&dir, err)),
+        };
+        for entry in entries {
+            let entry = entry.map_err(|err| io_error_at(&dir, err))?;
+            let path = entry.path();
+            let file_type = entry.file_type().map_err(|err| io_error_at(&path, err))?;
+            if file_type.is_dir() {
+                if !excluded.iter().any(|skip| *skip == path) {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() {
+                progress.bytes += match entry.metadata() {
+                    Ok(metadata) => metadata.len(),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(io_error_at(&path, err)),
+                };
+                progress.files += 1;
+                if progress.files % PROGRESS_INTERVAL == 0 {
+                    on_progress(progress);
+                }
+            }
+        }
+    }
+    on_progress(progress);
+    Ok(DirectorySize {
+        bytes: progress.bytes,
+        cancelled: false,
+    })
+}
+
+/// Human-readable size using binary units (e.g. `1.5 MiB`).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removal_blockers_report_dirty_and_unpushed_work() {
+        let clean = GitStatusSummary {
+            branch: Some("feature/x".into()),
+            upstream: Some("origin/feature/x".into()),
+            behind: 4,
+            ..GitStatusSummary::default()
+        };
+        assert!(removal_blockers(&clean, 0).is_empty());
+
+        let dirty = GitStatusSummary {
+            staged: 1,
+            unstaged: 1,
+            untracked: 1,
+            ..clean
+        };
+        assert_eq!(
+            removal_blockers(&dirty, 3),
+            [
+                "2 uncommitted changes",
+                "1 untracked file",
+                "3 commits not pushed to origin/feature/x"
+            ]
+        );
+        let local = GitStatusSummary {
+            upstream: None,
+            ..GitStatusSummary::default()
+        };
+        assert_eq!(
+            removal_blockers(&local, 1),
+            ["1 commit not pushed to any remote"]
+        );
+    }
+
+    #[test]
+    fn numbered_branch_name_skips_existing_branches() {
+        let existing = vec!["main".to_string(), "main-wt-1".to_string()];
+        assert_eq!(numbered_branch_name("main", "wt", &existing), "main-wt-2");
+        assert_eq!(
+            numbered_branch_name("feature/x", "wt", &existing),
+            "feature/x-wt-1"
+        );
+        let existing = vec!["main-copy-1".to_string(), "main-copy-3".to_string()];
+        assert_eq!(
+            numbered_branch_name("main", "copy", &existing),
+            "main-copy-2"
+        );
+    }
+
+    #[test]
+    fn clone_plan_prefers_upstream_as_start_point() {
+        let source = Path::new("/repo");
+        let mut status = GitStatusSummary {
+            branch: Some("feature/x".into()),
+            upstream: Some("origin/feature/x".into()),
+            ..GitStatusSummary::default()
+        };
+        let plan = clone_plan(&status, &[], source).unwrap();
+        assert_eq!(plan.branch, "feature/x-wt-1");
+        assert_eq!(plan.start_point, "origin/feature/x");
+
+        status.upstream = None;
+        assert_eq!(
+            clone_plan(&status, &[], source).unwrap().start_point,
+            "feature/x"
+        );
+
+        status.branch = Some("(detached)".into());
+        assert!(matches!(
+            clone_plan(&status, &[], source),
+            Err(WorkspaceError::DetachedHead(_))
+        ));
+    }
+
+    #[test]
+    fn remove_orphans_skips_directories_registered_after_the_audit() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(repo)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+        let reserved = repo.join(".wtm/workspaces/reserved");
+        let leftover = repo.join(".wtm/workspaces/leftover");
+        fs::create_dir_all(&reserved).unwrap();
+        fs::create_dir_all(&leftover).unwrap();
+
+        let report = audit_workspaces(repo).unwrap();
+        assert_eq!(report.removable_orphans().count(), 2);
+        git(&[
+            "worktree",
+            "add",
+            "-q",
+            "-b",
+            "reserved",
+            &reserved.to_string_lossy(),
+        ]);
+
+        let result = remove_orphans(repo, &report).unwrap();
+        assert_eq!(
+            result.render_summary(false),
+            "1 succeeded, 0 failed, 1 skipped"
+        );
+        assert!(reserved.join(".git").exists());
+        assert!(!leftover.exists());
+    }
+
+    #[test]
+    fn lookup_workspace_refuses_a_name_shared_across_categories() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(repo)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?}");
+        };
+        git(&["init", "-q"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+        for (branch, dir) in [
+            ("hot/fix", "a/hot-fix"),
+            ("hot-fix", "b/hot-fix"),
+            ("docs", "docs"),
+        ] {
+            let path = repo.join(".wtm/workspaces").join(dir);
+            git(&[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                branch,
+                &path.to_string_lossy(),
+            ]);
+        }
+
+        match lookup_workspace(repo, "hot-fix") {
+            Err(WorkspaceError::Ambiguous { name, candidates }) => {
+                assert_eq!(name, "hot-fix");
+                let mut names: Vec<_> = candidates
+                    .iter()
+                    .map(|path| path.strip_prefix(repo.canonicalize().unwrap()).unwrap())
+                    .collect();
+                names.sort();
+                assert_eq!(
+                    names,
+                    [
+                        Path::new(".wtm/workspaces/a/hot-fix"),
+                        Path::new(".wtm/workspaces/b/hot-fix")
+                    ]
+                );
+            }
+            other => panic!("expected an ambiguity error, got {other:?}"),
+        }
+        assert_eq!(
+            lookup_workspace(repo, "docs").unwrap().branch.as_deref(),
+            Some("docs")
+        );
+        assert_eq!(
+            lookup_workspace(repo, "b/hot-fix")
+                .unwrap()
+                .branch
+                .as_deref(),
+            Some("hot-fix")
+        );
+    }
+
+    #[test]
+    fn check_removable_protects_primary_worktree() {
+        let root = Path::new("/repo");
+        let worktrees = [
+            WorktreeInfo::at(root),
+            WorktreeInfo::at(Path::new("/repo/.wtm/workspaces/a")),
+        ];
+        let err = check_removable(root, &worktrees, root).unwrap_err();
+        assert!(matches!(err, WorkspaceError::PrimaryProtected(_)));
+        assert!(check_removable(root, &worktrees, Path::new("/repo/.wtm/workspaces/a")).is_ok());
+
+        // A bare repository's root is no checkout, so even the first worktree
+        // git lists may go.
+        let bare_root = Path::new("/project");
+        let worktrees = [
+            WorktreeInfo::at(Path::new("/project/main")),
+            WorktreeInfo::at(Path::new("/project/b")),
+        ];
+        assert!(check_removable(bare_root, &worktrees, Path::new("/project/main")).is_ok());
+        let err = check_removable(bare_root, &worktrees, Path::new("/project/.bare")).unwrap_err();
+        assert!(matches!(err, WorkspaceError::NoMatch(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn retain_linked_drops_the_primary_even_through_a_symlink() {
+        let temp = tempfile::tempdir().unwrap();
+        let primary = temp.path().join("repo");
+        let linked = primary.join(".wtm/workspaces/a");
+        fs::create_dir_all(&linked).unwrap();
+        let alias = temp.path().join("alias");
+        std::os::unix::fs::symlink(&primary, &alias).unwrap();
+
+        for root in [&primary, &alias] {
+            let mut worktrees = vec![WorktreeInfo::at(&primary), WorktreeInfo::at(&linked)];
+            retain_linked(&mut worktrees, root);
+            let paths: Vec<&Path> = worktrees.iter().map(|wt| wt.path.as_path()).collect();
+            assert_eq!(paths, [linked.as_path()], "{}", root.display());
+        }
+    }
+
+    #[test]
+    fn move_plan_resolves_relative_destinations() {
+        let root = tempfile::tempdir().unwrap();
+        let source = root.path().join("feature-a");
+        let plan = move_plan(
+            root.path(),
+            source.clone(),
+            Path::new("team/./x/../feature-b"),
+            false,
+        )
+        .unwrap();
+        assert_eq!(plan.source, source);
+        assert_eq!(plan.destination, root.path().join("team/feature-b"));
+        assert!(plan.destination_free);
+
+        fs::create_dir_all(root.path().join("archive/feature-a")).unwrap();
+        let plan = move_plan(root.path(), source, Path::new("archive"), false).unwrap();
+        assert_eq!(plan.destination, root.path().join("archive/feature-a"));
+        assert!(!plan.destination_free);
+    }
+
+    #[test]
+    fn launch_directory_inside_the_target_is_detected() {
+        let root = tempfile::tempdir().unwrap();
+        let worktree = root.path().join("feature-a");
+        let nested = worktree.join("src/bin");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir(root.path().join("feature-ab")).unwrap();
+
+        assert!(holds_worktree(&worktree, &worktree));
+        assert!(holds_worktree(&worktree, &nested));
+        assert!(!holds_worktree(&worktree, root.path()));
+        assert!(!holds_worktree(&worktree, &root.path().join("feature-ab")));
+    }
+
+    #[test]
+    fn move_plan_tells_a_target_directory_from_a_new_name() {
+        let root = tempfile::tempdir().unwrap();
+        let source = root.path().join("feature-a");
+        let destination = |to: &str| {
+            move_plan(root.path(), source.clone(), Path::new(to), false)
+                .unwrap()
+                .destination
+        };
+        assert_eq!(destination("old/"), root.path().join("old/feature-a"));
+        assert_eq!(destination("old"), root.path().join("old"));
+        assert_eq!(destination("old/a"), root.path().join("old/a"));
+
+        fs::create_dir(root.path().join("old")).unwrap();
+        assert_eq!(destination("old"), root.path().join("old/feature-a"));
+        assert_eq!(destination("old/"), root.path().join("old/feature-a"));
+    }
+
+    #[test]
+    fn move_plan_gates_destinations_outside_the_root() {
+        let root = Path::new("/nonexistent/repo/.wtm/workspaces");
+        let source = root.join("feature-a");
+        let internal = root.join("team/feature-a");
+        let plan = move_plan(root, source.clone(), &internal, false).unwrap();
+        assert_eq!(plan.destination, internal);
+
+        for external in ["/nonexistent/elsewhere/feature-a", "../../feature-a"] {
+            let err = move_plan(root, source.clone(), Path::new(external), false).unwrap_err();
+            assert!(
+                matches!(err, WorkspaceError::ExternalDestination(_)),
+                "{external}: {err}"
+            );
+        }
+        let plan = move_plan(
+            root,
+            source,
+            Path::new("/nonexistent/elsewhere/feature-a"),
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.destination,
+            Path::new("/nonexistent/elsewhere/feature-a")
+        );
+    }
+
+    #[test]
+    fn check_removable_rejects_unknown_paths() {
+        let root = Path::new("/repo");
+        let err =
+            check_removable(root, &[WorktreeInfo::at(root)], Path::new("/elsewhere")).unwrap_err();
+        assert!(matches!(err, WorkspaceError::NoMatch(path) if path == Path::new("/elsewhere")));
+    }
+
+    #[test]
+    fn compare_workspaces_reports_each_mismatch() {
+        let root = Path::new("/repo");
+        let managed = Path::new("/repo/.wtm/workspaces");
+        let directories = [
+            PathBuf::from("/repo/.wtm/workspaces/tracked"),
+            PathBuf::from("/repo/.wtm/workspaces/orphan"),
+            PathBuf::from("/repo/.wtm/workspaces/PROJ-7"),
+        ];
+        let worktrees = [
+            WorktreeInfo::at(root),
+            WorktreeInfo::at(Path::new("/repo/.wtm/workspaces/tracked")),
+            WorktreeInfo::at(Path::new("/repo/.wtm/workspaces/PROJ-7/PROJ-7-login")),
+            WorktreeInfo::at(Path::new("/elsewhere/hotfix")),
+            WorktreeInfo::at(Path::new("/repo/.wtm/workspaces/stale")).prunable(),
+        ];
+
+        let report = compare_workspaces(root, managed, &directories, &worktrees);
+
+        assert_eq!(
+            report.orphan_directories,
+            [PathBuf::from("/repo/.wtm/workspaces/orphan")]
+        );
+        assert_eq!(
+            report.external_worktrees,
+            [PathBuf::from("/elsewhere/hotfix")]
+        );
+        assert_eq!(
+            report.prunable_worktrees,
+            [PathBuf::from("/repo/.wtm/workspaces/stale")]
+        );
+        assert!(!report.is_clean());
+        assert!(compare_workspaces(root, managed, &[], &worktrees[..2]).is_clean());
+    }
+
+    #[test]
+    fn checkouts_of_another_repository_are_foreign() {
+        use crate::runner::testing::ScriptedRunner;
+        let runner = ScriptedRunner::default()
+            .succeed("/repo/.git\n")
+            .succeed("/repo/.git\n")
+            .succeed(".git\n")
+            .fail("fatal: not a git repository");
+        let managed = Path::new("/repo/.wtm/workspaces");
+        let checkouts = [
+            managed.join("ours"),
+            managed.join("copied"),
+            managed.join("broken"),
+        ];
+        let common_dir = |path: &Path| git::common_dir_with(&runner, path);
+        let foreign = foreign_checkouts(Path::new("/repo"), &checkouts, common_dir).unwrap();
+        assert_eq!(foreign, [managed.join("copied"), managed.join("broken")]);
+        assert_eq!(runner.calls()[2].args, ["rev-parse", "--git-common-dir"]);
+        assert_eq!(
+            runner.calls()[2].cwd.as_deref(),
+            Some(checkouts[1].as_path())
+        );
+
+        let idle = ScriptedRunner::default();
+        let common_dir = |path: &Path| git::common_dir_with(&idle, path);
+        assert!(foreign_checkouts(Path::new("/repo"), &[], common_dir)
+            .unwrap()
+            .is_empty());
+        assert!(idle.calls().is_empty());
+    }
+
+    #[test]
+    fn stray_directories_skip_worktrees_and_the_empty_default() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = |name: &str| {
+            let path = root.path().join(name);
+            fs::create_dir_all(&path).unwrap();
+            path
+        };
+        let tracked = dir("tracked");
+        let nested = dir("PROJ-7");
+        let login = dir("PROJ-7/PROJ-7-login/src");
+        let leftover = dir("PROJ-7/PROJ-7-old");
+        let placeholder = dir("default");
+        let manual = dir("scratch");
+        let worktrees = [
+            WorktreeInfo::at(&tracked),
+            WorktreeInfo::at(login.parent().unwrap()),
+        ];
+        let directories = [tracked, nested, placeholder.clone(), manual.clone()];
+
+        assert_eq!(
+            stray_directories(&directories, &worktrees),
+            [leftover.clone(), manual.clone()]
+        );
+
+        fs::write(placeholder.join("notes.txt"), "kept by hand").unwrap();
+        assert_eq!(
+            stray_directories(&directories, &worktrees),
+            [leftover, placeholder, manual]
+        );
+    }
+
+    #[test]
+    fn shared_branches_groups_worktrees_by_branch() {
+        let worktrees = [
+            WorktreeInfo::at("/repo").on("main"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/a").on("feature"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/b"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/c").on("main"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/d"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/e").on("feature"),
+            WorktreeInfo::at("/repo/.wtm/workspaces/f").on("other"),
+        ];
+
+        assert_eq!(
+            shared_branches(&worktrees),
+            [
+                SharedBranch {
+                    branch: "feature".into(),
+                    worktrees: vec![
+                        PathBuf::from("/repo/.wtm/workspaces/a"),
+                        PathBuf::from("/repo/.wtm/workspaces/e"),
+                    ],
+                },
+                SharedBranch {
+                    branch: "main".into(),
+                    worktrees: vec![
+                        PathBuf::from("/repo"),
+                        PathBuf::from("/repo/.wtm/workspaces/c"),
+                    ],
+                },
+            ]
+        );
+        assert!(shared_branches(&worktrees[..3]).is_empty());
+    }
+
+    #[test]
+    fn prepare_adoption_accepts_only_empty_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".DS_Store"), "").unwrap();
+        prepare_adoption(dir.path()).unwrap();
+        assert!(
+            !dir.path().join(".DS_Store").exists(),
+            "leftovers are cleared"
+        );
+
+        fs::write(dir.path().join("notes.txt"), "keep me").unwrap();
+        fs::create_dir(dir.path().join("build")).unwrap();
+        match prepare_adoption(dir.path()).unwrap_err() {
+            WorkspaceError::NotAdoptable { entries, .. } => {
+                assert_eq!(entries, ["build", "notes.txt"])
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert!(dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn attach_source_prefers_local_then_remote_then_new() {
+        use crate::runner::testing::ScriptedRunner;
+
+        let repo = Path::new("/repo");
+        let local = ScriptedRunner::default().succeed("abc123\n");
+        assert_eq!(
+            attach_source_with(&local, repo, "feature/x", false).unwrap(),
+            AttachSource::Local
+        );
+
+        let missing = ScriptedRunner::default().fail("");
+        let err = attach_source_with(&missing, repo, "feature/x", false).unwrap_err();
+        assert!(matches!(err, WorkspaceError::BranchNotFound(_)), "{err}");
+
+        let remote_only = ScriptedRunner::default().fail("").succeed(
+            "1700000002\tupstream/feature/x\n1700000001\torigin/feature/x\n1700000000\torigin/main\n",
+        );
+        assert_eq!(
+            attach_source_with(&remote_only, repo, "feature/x", true).unwrap(),
+            AttachSource::Remote("origin/feature/x".into())
+        );
+        assert_eq!(remote_only.calls()[1].args[0], "for-each-ref");
+
+        let absent = ScriptedRunner::default()
+            .fail("")
+            .succeed("1700000000\torigin/main\n");
+        assert_eq!(
+            attach_source_with(&absent, repo, "feature/x", true).unwrap(),
+            AttachSource::Created
+        );
+    }
+
+    #[test]
+    fn checkout_source_needs_a_local_branch_or_one_clear_remote() {
+        use crate::runner::testing::ScriptedRunner;
+
+        let repo = Path::new("/repo");
+        let local = ScriptedRunner::default().succeed("abc123\n");
+        assert_eq!(
+            checkout_source_with(&local, repo, "feature/x").unwrap(),
+            AttachSource::Local
+        );
+        assert_eq!(local.calls().len(), 1);
+
+        let single = ScriptedRunner::default()
+            .fail("")
+            .succeed("1700000001\tfork/feature/x\n1700000000\tfork/main\n");
+        assert_eq!(
+            checkout_source_with(&single, repo, "feature/x").unwrap(),
+            AttachSource::Remote("fork/feature/x".into())
+        );
+
+        let with_origin = ScriptedRunner::default()
+            .fail("")
+            .succeed("1700000002\tfork/feature/x\n1700000001\torigin/feature/x\n");
+        assert_eq!(
+            checkout_source_with(&with_origin, repo, "feature/x").unwrap(),
+            AttachSource::Remote("origin/feature/x".into())
+        );
+
+        let ambiguous = ScriptedRunner::default()
+            .fail("")
+            .succeed("1700000002\tupstream/feature/x\n1700000001\tfork/feature/x\n");
+        let err = checkout_source_with(&ambiguous, repo, "feature/x").unwrap_err();
+        assert!(
+            matches!(&err, WorkspaceError::AmbiguousRemote { remotes, .. }
+                if remotes == &["fork/feature/x", "upstream/feature/x"]),
+            "{err}"
+        );
+
+        let nowhere = ScriptedRunner::default().fail("").succeed("");
+        let err = checkout_source_with(&nowhere, repo, "feature/x").unwrap_err();
+        assert!(matches!(err, WorkspaceError::BranchNowhere(_)), "{err}");
+    }
+
+    #[test]
+    fn create_workspace_requires_a_branch() {
+        let err = create_workspace(
+            Path::new("/repo"),
+            "  ",
+            None,
+            PathLayout::default(),
+            &AddOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, WorkspaceError::BranchRequired));
+        assert_eq!(err.to_string(), "Branch name is required.");
+    }
+
+    #[test]
+    fn undo_restores_the_recorded_worktree_once() {
+        use crate::runner::testing::ScriptedRunner;
+
+        let repo = tempfile::tempdir().unwrap();
+        let wtm_dir = repo.path().join(".wtm");
+        let removed = wtm_dir.join("workspaces").join("feature-x");
+        let record = LastDelete {
+            branch: Some("feature/x".into()),
+            path: removed.clone(),
+            force: false,
+        };
+        fs::create_dir_all(&wtm_dir).unwrap();
+        last_delete::save(&wtm_dir, &record).unwrap();
+
+        let runner = ScriptedRunner::default().succeed("abc123\n").succeed("");
+        let relative = AddOptions {
+            relative_paths: true,
+            ..AddOptions::default()
+        };
+        assert_eq!(
+            undo_delete_with(&runner, repo.path(), &relative).unwrap(),
+            record
+        );
+        let calls = runner.calls();
+        assert_eq!(calls[0].args[3], "refs/heads/feature/x");
+        assert_eq!(
+            calls[1].args,
+            [
+                "worktree",
+                "add",
+                "--relative-paths",
+                &removed.to_string_lossy(),
+                "feature/x"
+            ]
+        );
+        assert_eq!(last_delete::load(&wtm_dir).unwrap(), None);
+
+        let err = undo_delete_with(&ScriptedRunner::default(), repo.path(), &relative).unwrap_err();
+        assert!(matches!(err, WorkspaceError::NothingToUndo));
+    }
+
+    #[test]
+    fn undo_reports_a_deleted_branch() {
+        use crate::runner::testing::ScriptedRunner;
+
+        let repo = tempfile::tempdir().unwrap();
+        let wtm_dir = repo.path().join(".wtm");
+        fs::create_dir_all(&wtm_dir).unwrap();
+        let record = LastDelete {
+            branch: Some("feature/x".into()),
+            path: wtm_dir.join("workspaces").join("feature-x"),
+            force: true,
+        };
+        last_delete::save(&wtm_dir, &record).unwrap();
+
+        let runner = ScriptedRunner::default().fail("");
+        let err = undo_delete_with(&runner, repo.path(), &AddOptions::default()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("branch feature/x no longer exists"),
+            "{err}"
+        );
+        assert_eq!(runner.calls().len(), 1, "nothing is recreated");
+        assert_eq!(last_delete::load(&wtm_dir).unwrap(), Some(record));
+    }
+
+    #[test]
+    fn disk_usage_total_is_sum_of_workspaces_without_double_counting() {
+        let temp = tempfile::tempdir().unwrap();
+        let primary = temp.path().join("repo");
+        let nested = primary.join(".wtm/workspaces/feature");
+        fs::create_dir_all(primary.join("src")).unwrap();
+        fs::create_dir_all(nested.join("docs")).unwrap();
+        fs::write(primary.join("README"), vec![0u8; 100]).unwrap();
+        fs::write(primary.join("src/main.rs"), vec![0u8; 50]).unwrap();
+        fs::write(nested.join("docs/guide.md"), vec![0u8; 300]).unwrap();
+
+        let usage = disk_usage_with(
+            &[WorktreeInfo::at(&primary), WorktreeInfo::at(&nested)],
+            &CancellationToken::new(),
+            &|_| {},
+        )
+        .unwrap();
+
+        assert_eq!(usage.workspaces[0].path, nested);
+        assert_eq!(usage.workspaces[0].bytes, 300);
+        assert_eq!(usage.workspaces[1].bytes, 150);
+        assert_eq!(
+            usage.total_bytes,
+            usage.workspaces.iter().map(|ws| ws.bytes).sum::<u64>()
+        );
+        assert_eq!(usage.total_bytes, 450);
+        assert!(!usage.cancelled);
+    }
+
+    #[test]
+    fn disk_usage_marks_missing_worktrees_and_measures_the_rest() {
+        let temp = tempfile::tempdir().unwrap();
+        let present = temp.path().join("present");
+        fs::create_dir(&present).unwrap();
+        fs::write(present.join("file"), vec![0u8; 10]).unwrap();
+
+        let usage = disk_usage_with(
+            &[
+                WorktreeInfo::at(temp.path().join("gone")),
+                WorktreeInfo::at(temp.path().join("prunable")).prunable(),
+                WorktreeInfo::at(&present),
+            ],
+            &CancellationToken::new(),
+            &|_| {},
+        )
+        .unwrap();
+        let rows: Vec<(&str, u64, bool)> = usage
+            .workspaces
+            .iter()
+            .map(|ws| (ws.name.as_str(), ws.bytes, ws.missing))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("present", 10, false),
+                ("gone", 0, true),
+                ("prunable", 0, true)
+            ]
+        );
+        assert_eq!(usage.total_bytes, 10);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn directory_walk_errors_name_the_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let locked = temp.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        let result = directory_size(temp.path(), &[], &CancellationToken::new(), &mut |_| {});
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        if let Err(err) = result {
+            assert!(
+                err.to_string().starts_with(&locked.display().to_string()),
+                "{err}"
+            );
+        } // Running as root, the directory stays readable.
+    }
+
+    #[test]
+    fn directory_walk_reports_progress_and_matches_a_recursive_sum() {
+        fn recursive_size(dir: &Path) -> u64 {
+            fs::read_dir(dir)
+                .unwrap()
+                .map(|entry| {
+                    let entry = entry.unwrap();
+                    if entry.file_type().unwrap().is_dir() {
+                        recursive_size(&entry.path())
+                    } else {
+                        entry.metadata().unwrap().len()
+                    }
+                })
+                .sum()
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let mut dir = temp.path().to_path_buf();
+        for depth in 0..20 {
+            dir.push(format!("d{depth}"));
+            fs::create_dir(&dir).unwrap();
+            for n in 0..60 {
+                fs::write(dir.join(format!("f{n}")), vec![0u8; depth * n]).unwrap();
+            }
+        }
+
+        let mut reports = Vec::new();
+        let total = directory_size(temp.path(), &[], &CancellationToken::new(), &mut |p| {
+            reports.push(p)
+        })
+        .unwrap()
+        .bytes;
+        assert_eq!(total, recursive_size(temp.path()));
+        assert_eq!(reports.len(), 1200 / PROGRESS_INTERVAL as usize + 1);
+        assert!(reports.windows(2).all(|pair| pair[0].files < pair[1].files));
+        assert_eq!(
+            reports.last(),
+            Some(&WalkProgress {
+                bytes: total,
+                files: 1200
+            })
+        );
+    }
+
+    #[test]
+    fn cancelling_mid_walk_keeps_the_bytes_counted_so_far() {
+        let temp = tempfile::tempdir().unwrap();
+        let files = PROGRESS_INTERVAL + 1;
+        for dir in ["a", "b", "c"] {
+            let dir = temp.path().join(dir);
+            fs::create_dir(&dir).unwrap();
+            for n in 0..files {
+                fs::write(dir.join(format!("f{n}")), [0u8; 2]).unwrap();
+            }
+        }
+
+        let cancel = CancellationToken::new();
+        let size = directory_size(temp.path(), &[], &cancel, &mut |_| cancel.cancel()).unwrap();
+        assert_eq!(
+            size,
+            DirectorySize {
+                bytes: 2 * files,
+                cancelled: true
+            },
+            "the walk stops after the directory it was reading"
+        );
+
+        let size = directory_size(temp.path(), &[], &cancel, &mut |_| {}).unwrap();
+        assert_eq!(size.bytes, 0, "an already cancelled walk reads nothing");
+    }
+
+    #[test]
+    fn directory_size_handles_very_deep_trees() {
+        let temp = tempfile::tempdir().unwrap();
+        // Single-letter names keep the deepest path well under PATH_MAX.
+        let depth = 1500;
+        let mut deepest = temp.path().to_path_buf();
+        for _ in 0..depth {
+            deepest.push("d");
+        }
+        fs::create_dir_all(&deepest).unwrap();
+        fs::write(deepest.join("f"), [0u8; 7]).unwrap();
+        fs::write(temp.path().join("d").join("f"), [0u8; 5]).unwrap();
+
+        let size =
+            directory_size(temp.path(), &[], &CancellationToken::new(), &mut |_| {}).unwrap();
+        assert_eq!(size.bytes, 12);
+    }
+
+    #[test]
+    fn format_bytes_uses_binary_units() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
@@ -1,5 +1,9 @@
+use crate::{config::PathDisplay, jira::ticket_key_in};
+use serde::Serialize;
 use std::{
+    ffi::OsString,
     fs,
+    io::{self, Write},
     path::{Path, PathBuf},
 };
 
@@ -16,35 +20,143 @@ pub fn ensure_workspace_root(repo_root: &Path) -> std::io::Result<PathBuf> {
 }
 
 /// Normalise a raw branch/workspace name into a safe slug.
+///
+/// Accented Latin letters are transliterated (`é` → `e`, `ß` → `ss`) first, so
+/// they survive as readable ASCII instead of collapsing into `-`.
 pub fn sanitize_branch_name(branch: &str) -> String {
-    let mut slug: String = branch
-        .trim()
-        .chars()
-        .map(|c| match c {
-            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '/' => c,
-            _ => '-',
-        })
-        .collect();
+    let mut slug = String::with_capacity(branch.len());
+    for c in branch.trim().chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '/' => slug.push(c),
+            _ => slug.push_str(transliterate(c).unwrap_or("-")),
+        }
+    }
     while slug.contains("--") {
         slug = slug.replace("--", "-");
     }
     slug.trim_matches('-').to_string()
 }
 
-/// Generate a filesystem-safe directory name for the provided branch.
-pub fn branch_dir_name(branch: &str) -> String {
+/// ASCII spelling of a common accented Latin letter.
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'ç' | 'ć' | 'č' => "c",
+        'Ç' | 'Ć' | 'Č' => "C",
+        'ď' | 'đ' | 'ð' => "d",
+        'Ď' | 'Đ' | 'Ð' => "D",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'ğ' => "g",
+        'Ğ' => "G",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' | 'Į' | 'İ' => "I",
+        'ł' => "l",
+        'Ł' => "L",
+        'ñ' | 'ń' | 'ň' => "n",
+        'Ñ' | 'Ń' | 'Ň' => "N",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ő' => "O",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ř' => "r",
+        'Ř' => "R",
+        'ś' | 'š' | 'ş' => "s",
+        'Ś' | 'Š' | 'Ş' => "S",
+        'ß' => "ss",
+        'ť' | 'ţ' => "t",
+        'Ť' | 'Ţ' => "T",
+        'þ' => "th",
+        'Þ' => "TH",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ū' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ý' | 'ÿ' => "y",
+        'Ý' | 'Ÿ' => "Y",
+        'ź' | 'ż' | 'ž' => "z",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        _ => return None,
+    })
+}
+
+/// Default for `maxSlugLength`, the longest directory name [`branch_dir_name`]
+/// produces.
+pub const DEFAULT_MAX_SLUG_LENGTH: usize = 60;
+
+/// Smallest `maxSlugLength` accepted; shorter limits could not even hold the
+/// hash suffix. [`branch_dir_name`] raises smaller limits to this.
+pub const MIN_SLUG_LENGTH: usize = 16;
+
+/// How new worktree directories are named under `.wtm/workspaces`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathLayout<'a> {
+    /// The configured `workspacePathTemplate`, if any.
+    pub template: Option<&'a str>,
+    /// Longest directory name a branch is turned into (`maxSlugLength`).
+    pub max_slug_length: usize,
+}
+
+impl Default for PathLayout<'_> {
+    fn default() -> Self {
+        Self {
+            template: None,
+            max_slug_length: DEFAULT_MAX_SLUG_LENGTH,
+        }
+    }
+}
+
+/// Filesystem-safe slug for `branch` at any length: the name of branches
+/// created from free text, and of worktree directories made before
+/// [`branch_dir_name`] capped their length.
+pub fn branch_slug(branch: &str) -> String {
     let mut slug = sanitize_branch_name(branch).replace('/', "-");
     while slug.contains("--") {
         slug = slug.replace("--", "-");
     }
     let slug = slug.trim_matches('-').to_string();
     if slug.is_empty() {
-        "worktree".to_string()
+        return "worktree".to_string();
+    }
+    slug
+}
+
+/// Filesystem-safe directory name for `branch`: its [`branch_slug`],
+/// shortened to at most `max_len` bytes (but no fewer than [`MIN_SLUG_LENGTH`]).
+///
+/// Long slugs are cut at a `-` where possible and end in `-<hash>` of the
+/// full branch name, so two long branches sharing a prefix stay distinct.
+pub fn branch_dir_name(branch: &str, max_len: usize) -> String {
+    let max_len = max_len.max(MIN_SLUG_LENGTH);
+    let slug = branch_slug(branch);
+    if slug.len() <= max_len {
+        return slug;
+    }
+    let hash = format!("{:08x}", fnv1a(branch.as_bytes()));
+    let keep = max_len - (hash.len() + 1);
+    // The slug is ASCII, so any byte offset is a char boundary.
+    let head = &slug[..keep];
+    let mid_word = slug.as_bytes()[keep] != b'-';
+    let head = match head.rfind('-') {
+        Some(cut) if mid_word && cut >= keep / 2 => &head[..cut],
+        _ => head,
+    };
+    let head = head.trim_end_matches('-');
+    if head.is_empty() {
+        hash
     } else {
-        slug
+        format!("{head}-{hash}")
     }
 }
 
+/// 32-bit FNV-1a; stable across builds, unlike `std`'s `DefaultHasher`.
+pub fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
 /// Find the first available workspace path (appending numeric suffixes if needed).
 pub fn next_available_workspace_path(root: &Path, base_name: &str) -> PathBuf {
     let candidate = root.join(base_name);
@@ -61,19 +173,392 @@ pub fn next_available_workspace_path(root: &Path, base_name: &str) -> PathBuf {
     }
 }
 
+/// How many suffixes [`reserve_workspace_path`] tries before giving up.
+const MAX_RESERVE_ATTEMPTS: usize = 1000;
+
+/// Like [`next_available_workspace_path`], but create the directory so that a
+/// concurrent creator cannot claim the same path before `git worktree add` runs.
+///
+/// The result is an empty directory, which `git worktree add` populates.
+pub fn reserve_workspace_path(root: &Path, base_name: &str) -> io::Result<PathBuf> {
+    let first = root.join(base_name);
+    if let Some(parent) = first.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let candidates = std::iter::once(first)
+        .chain((1..MAX_RESERVE_ATTEMPTS).map(|n| root.join(format!("{base_name}-{n}"))));
+    for candidate in candidates {
+        match fs::create_dir(&candidate) {
+            Ok(()) => return Ok(candidate),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!(
+            "no free directory for {} after {MAX_RESERVE_ATTEMPTS} attempts",
+            root.join(base_name).display()
+        ),
+    ))
+}
+
+/// Placeholders understood by `workspacePathTemplate`.
+pub const PATH_TEMPLATE_PLACEHOLDERS: &[&str] = &["branch", "ticket"];
+
+/// Check that a `workspacePathTemplate` is relative and only uses known placeholders.
+pub fn validate_path_template(template: &str) -> Result<(), String> {
+    if template.starts_with('/') {
+        return Err(format!("`{template}` must be relative to .wtm/workspaces"));
+    }
+    let rendered = fill_path_template(template, |_| "x".into())?;
+    if rendered.as_os_str().is_empty() {
+        return Err(format!("`{template}` does not name a directory"));
+    }
+    Ok(())
+}
+
+/// Check that a `--category` is a single directory name that stays inside
+/// `.wtm/workspaces`.
+pub fn parse_category(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(format!("`{name}` is not a directory name"));
+    }
+    if let Some(bad) = name
+        .chars()
+        .find(|&c| matches!(c, '/' | '\\' | '{' | '}') || c.is_control())
+    {
+        return Err(format!(
+            "`{name}` must be a single directory name (found `{bad}`)"
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// `template` (or the branch's directory name) nested under `category`.
+///
+/// `category` must have passed [`parse_category`].
+pub fn categorized_template(category: &str, template: Option<&str>) -> String {
+    format!("{category}/{}", template.unwrap_or("{branch}"))
+}
+
+/// Where a new worktree for `branch` goes: the layout's template rendered under
+/// `workspace_root`, or the branch's directory name, made unique with a numeric
+/// suffix.
+///
+/// `{branch}` is the branch's directory name and `{ticket}` the Jira key it starts
+/// with, falling back to the branch's directory name.
+pub fn resolve_target_path(workspace_root: &Path, branch: &str, layout: PathLayout<'_>) -> PathBuf {
+    let relative = target_relative_path(branch, layout);
+    next_available_workspace_path(workspace_root, &relative.to_string_lossy())
+}
+
+/// [`resolve_target_path`], reserved with [`reserve_workspace_path`].
+pub fn reserve_target_path(
+    workspace_root: &Path,
+    branch: &str,
+    layout: PathLayout<'_>,
+) -> io::Result<PathBuf> {
+    let relative = target_relative_path(branch, layout);
+    reserve_workspace_path(workspace_root, &relative.to_string_lossy())
+}
+
+/// Path of a new worktree for `branch` relative to `.wtm/workspaces`, before any suffix.
+pub fn target_relative_path(branch: &str, layout: PathLayout<'_>) -> PathBuf {
+    let dir_name = branch_dir_name(branch, layout.max_slug_length);
+    layout
+        .template
+        .and_then(|template| {
+            fill_path_template(template, |placeholder| match placeholder {
+                "ticket" => ticket_key_in(branch).unwrap_or_else(|| dir_name.clone()),
+                _ => dir_name.clone(),
+            })
+            .ok()
+        })
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .unwrap_or_else(|| PathBuf::from(&dir_name))
+}
+
+/// Replace `{placeholder}`s per `/`-separated segment, dropping empty, `.` and `..` segments.
+fn fill_path_template(template: &str, value: impl Fn(&str) -> String) -> Result<PathBuf, String> {
+    let mut path = PathBuf::new();
+    for segment in template.split('/') {
+        let mut filled = String::new();
+        let mut rest = segment;
+        while let Some(start) = rest.find('{') {
+            filled.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unclosed `{{` in `{template}`"))?;
+            let placeholder = &rest[start + 1..start + end];
+            if !PATH_TEMPLATE_PLACEHOLDERS.contains(&placeholder) {
+                return Err(format!(
+                    "unknown placeholder `{{{placeholder}}}` in `{template}` (use {{branch}} or {{ticket}})"
+                ));
+            }
+            filled.push_str(&value(placeholder));
+            rest = &rest[start + end + 1..];
+        }
+        filled.push_str(rest);
+        match filled.trim() {
+            "" | "." => {}
+            ".." => return Err(format!("`{template}` must not contain `..`")),
+            segment => path.push(segment),
+        }
+    }
+    Ok(path)
+}
+
+/// Resolve `.` and `..` components without requiring the path to exist.
+pub fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// The current user's home directory, from `$HOME`.
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Write `value` to `path` as pretty JSON without ever leaving it half
+/// written: the data goes to a temporary file beside it, which is synced and
+/// then renamed over `path`. A crash part-way keeps the previous contents.
+pub fn atomic_write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> io::Result<()> {
+    let data = serde_json::to_string_pretty(value).map_err(io::Error::other)? + "\n";
+    let Some(file_name) = path.file_name() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file path", path.display()),
+        ));
+    };
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp = path.with_file_name(temp_name);
+
+    let written = fs::File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(data.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+/// Render `path` for display according to `style`.
+///
+/// `Relative` falls back to `Tilde` for paths outside the repository's parent
+/// directory, and `Tilde` falls back to the absolute path outside `home`.
+pub fn format_path(
+    path: &Path,
+    style: PathDisplay,
+    repo_root: &Path,
+    home: Option<&Path>,
+) -> String {
+    match style {
+        PathDisplay::Absolute => path.display().to_string(),
+        PathDisplay::Relative => match repo_root
+            .parent()
+            .and_then(|parent| path.strip_prefix(parent).ok())
+        {
+            Some(relative) if !relative.as_os_str().is_empty() => relative.display().to_string(),
+            _ => format_path(path, PathDisplay::Tilde, repo_root, home),
+        },
+        PathDisplay::Tilde => match home.and_then(|home| path.strip_prefix(home).ok()) {
+            Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+            Some(rest) => format!("~/{}", rest.display()),
+            None => path.display().to_string(),
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn accented_branches_get_readable_slugs() {
+        assert_eq!(
+            branch_dir_name("féature-über", DEFAULT_MAX_SLUG_LENGTH),
+            "feature-uber"
+        );
+        assert_eq!(
+            branch_dir_name("Straße/Ærø", DEFAULT_MAX_SLUG_LENGTH),
+            "Strasse-AEro"
+        );
+        assert_eq!(
+            branch_dir_name("fix/łódź crème", DEFAULT_MAX_SLUG_LENGTH),
+            "fix-lodz-creme"
+        );
+        assert_eq!(sanitize_branch_name("naïve/café"), "naive/cafe");
+        for branch in ["féature-über", "fix/łódź crème", "日本"] {
+            let slug = branch_dir_name(branch, DEFAULT_MAX_SLUG_LENGTH);
+            assert_eq!(
+                branch_dir_name(&slug, DEFAULT_MAX_SLUG_LENGTH),
+                slug,
+                "idempotent for {branch}"
+            );
+        }
+    }
+
+    #[test]
+    fn long_branches_are_truncated_at_a_word_with_a_hash() {
+        let branch = "feature/PROJ-1234-make-the-dashboard-remember-every-filter-across-sessions";
+        let slug = branch_dir_name(branch, DEFAULT_MAX_SLUG_LENGTH);
+        assert!(slug.len() <= DEFAULT_MAX_SLUG_LENGTH, "{slug}");
+        let (head, hash) = slug.rsplit_once('-').unwrap();
+        assert_eq!(head, "feature-PROJ-1234-make-the-dashboard-remember-every");
+        assert_eq!(hash.len(), 8);
+        assert_eq!(
+            branch_dir_name(branch, DEFAULT_MAX_SLUG_LENGTH),
+            slug,
+            "deterministic"
+        );
+        assert_eq!(
+            branch_dir_name(&slug, DEFAULT_MAX_SLUG_LENGTH),
+            slug,
+            "idempotent"
+        );
+
+        let sibling = "feature/PROJ-1234-make-the-dashboard-remember-every-filter-across-tabs";
+        assert_ne!(branch_dir_name(sibling, DEFAULT_MAX_SLUG_LENGTH), slug);
+        assert_eq!(
+            branch_dir_name("short/branch", DEFAULT_MAX_SLUG_LENGTH),
+            "short-branch"
+        );
+        assert_eq!(
+            branch_dir_name("abcdefghijklmnopqrst", 16),
+            format!("abcdefg-{:08x}", fnv1a(b"abcdefghijklmnopqrst"))
+        );
+        assert!(branch_dir_name(branch, 30).len() <= 30);
+        assert!(branch_dir_name(branch, 3).len() <= MIN_SLUG_LENGTH);
+        assert_eq!(
+            branch_slug(branch),
+            "feature-PROJ-1234-make-the-dashboard-remember-every-filter-across-sessions"
+        );
+    }
+
+    #[test]
+    fn untransliterable_branches_fall_back_to_worktree() {
+        assert_eq!(
+            branch_dir_name("日本語", DEFAULT_MAX_SLUG_LENGTH),
+            "worktree"
+        );
+        assert_eq!(
+            branch_dir_name("🚀/✨", DEFAULT_MAX_SLUG_LENGTH),
+            "worktree"
+        );
+        assert_eq!(branch_dir_name("日本-é", DEFAULT_MAX_SLUG_LENGTH), "e");
+    }
+
+    #[test]
+    fn reserving_skips_directories_created_in_the_meantime() {
+        let root = tempfile::tempdir().unwrap();
+        // Another creator got there between our check and our claim.
+        fs::create_dir_all(root.path().join("team/feature-x")).unwrap();
+        fs::create_dir(root.path().join("team/feature-x-1")).unwrap();
+
+        let reserved = reserve_workspace_path(root.path(), "team/feature-x").unwrap();
+        assert_eq!(reserved, root.path().join("team/feature-x-2"));
+        assert!(reserved.is_dir());
+        assert_eq!(
+            reserve_workspace_path(root.path(), "team/feature-x").unwrap(),
+            root.path().join("team/feature-x-3")
+        );
+    }
+
+    #[test]
+    fn format_path_abbreviates_home() {
+        let home = Some(Path::new("/home/me"));
+        let repo = Path::new("/home/me/src/app");
+        let ws = Path::new("/home/me/src/app/.wtm/workspaces/feat");
+        assert_eq!(
+            format_path(ws, PathDisplay::Tilde, repo, home),
+            "~/src/app/.wtm/workspaces/feat"
+        );
+        assert_eq!(
+            format_path(Path::new("/home/me"), PathDisplay::Tilde, repo, home),
+            "~"
+        );
+        assert_eq!(
+            format_path(ws, PathDisplay::Absolute, repo, home),
+            ws.display().to_string()
+        );
+    }
+
+    #[test]
+    fn format_path_relative_to_repo_parent() {
+        let home = Some(Path::new("/home/me"));
+        let repo = Path::new("/home/me/src/app");
+        assert_eq!(
+            format_path(
+                Path::new("/home/me/src/app/.wtm/workspaces/feat"),
+                PathDisplay::Relative,
+                repo,
+                home
+            ),
+            "app/.wtm/workspaces/feat"
+        );
+        assert_eq!(
+            format_path(
+                Path::new("/home/me/other"),
+                PathDisplay::Relative,
+                repo,
+                home
+            ),
+            "~/other"
+        );
+    }
+
+    #[test]
+    fn format_path_outside_home_and_repo_stays_absolute() {
+        let home = Some(Path::new("/home/me"));
+        let repo = Path::new("/home/me/src/app");
+        for style in [PathDisplay::Tilde, PathDisplay::Relative] {
+            assert_eq!(
+                format_path(Path::new("/srv/worktrees/x"), style, repo, home),
+                "/srv/worktrees/x"
+            );
+        }
+        assert_eq!(
+            format_path(Path::new("/home/me/x"), PathDisplay::Tilde, repo, None),
+            "/home/me/x"
+        );
+    }
+
     #[test]
     fn branch_dir_name_preserves_hyphen_and_underscore() {
-        assert_eq!(branch_dir_name("feature-branch"), "feature-branch");
-        assert_eq!(branch_dir_name("feature_branch"), "feature_branch");
+        assert_eq!(
+            branch_dir_name("feature-branch", DEFAULT_MAX_SLUG_LENGTH),
+            "feature-branch"
+        );
+        assert_eq!(
+            branch_dir_name("feature_branch", DEFAULT_MAX_SLUG_LENGTH),
+            "feature_branch"
+        );
     }
 
     #[test]
     fn branch_dir_name_replaces_slashes() {
-        assert_eq!(branch_dir_name("feature/branch"), "feature-branch");
+        assert_eq!(
+            branch_dir_name("feature/branch", DEFAULT_MAX_SLUG_LENGTH),
+            "feature-branch"
+        );
     }
 
     #[test]
@@ -85,6 +570,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn path_template_nests_worktrees_by_ticket() {
+        let root = Path::new("/nonexistent/.wtm/workspaces");
+        let template = PathLayout {
+            template: Some("{ticket}/{branch}"),
+            ..PathLayout::default()
+        };
+        assert_eq!(
+            resolve_target_path(root, "PROJ-42-fix-login", template),
+            root.join("PROJ-42/PROJ-42-fix-login")
+        );
+        assert_eq!(
+            resolve_target_path(root, "feature/fix login", template),
+            root.join("feature-fix-login/feature-fix-login")
+        );
+        assert_eq!(
+            resolve_target_path(
+                root,
+                "feature/x",
+                PathLayout {
+                    template: Some("teams/{branch}"),
+                    ..PathLayout::default()
+                }
+            ),
+            root.join("teams/feature-x")
+        );
+        assert_eq!(
+            resolve_target_path(root, "feature/x", PathLayout::default()),
+            root.join("feature-x")
+        );
+    }
+
+    #[test]
+    fn categories_nest_worktrees_one_level_down() {
+        assert_eq!(parse_category(" features ").unwrap(), "features");
+        for bad in ["", "..", "a/b", "a\\b", "{branch}"] {
+            assert!(parse_category(bad).is_err(), "{bad:?}");
+        }
+
+        let root = tempfile::tempdir().unwrap();
+        let template = categorized_template("features", None);
+        let first = reserve_target_path(
+            root.path(),
+            "feat/x",
+            PathLayout {
+                template: Some(&template),
+                ..PathLayout::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(first, root.path().join("features").join("feat-x"));
+        assert!(first.is_dir());
+        let ticketed = categorized_template("bugs", Some("{ticket}/{branch}"));
+        assert_eq!(
+            target_relative_path(
+                "ABC-1-fix",
+                PathLayout {
+                    template: Some(&ticketed),
+                    ..PathLayout::default()
+                }
+            ),
+            Path::new("bugs/ABC-1/ABC-1-fix")
+        );
+    }
+
+    #[test]
+    fn validate_path_template_rejects_escapes_and_typos() {
+        assert!(validate_path_template("{ticket}/{branch}").is_ok());
+        assert!(validate_path_template("/abs/{branch}")
+            .unwrap_err()
+            .contains("relative"));
+        assert!(validate_path_template("../{branch}")
+            .unwrap_err()
+            .contains(".."));
+        assert!(validate_path_template("{tiket}")
+            .unwrap_err()
+            .contains("unknown placeholder `{tiket}`"));
+        assert!(validate_path_template("{branch")
+            .unwrap_err()
+            .contains("unclosed"));
+        assert!(validate_path_template("./").is_err());
+    }
+
+    #[test]
+    fn atomic_json_writes_replace_the_file_and_leave_no_temp_behind() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("tags.json");
+        atomic_write_json(&path, &serde_json::json!({ "a": ["wip"] })).unwrap();
+        atomic_write_json(&path, &serde_json::json!({ "b": [] })).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, serde_json::json!({ "b": [] }));
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["tags.json"]);
+
+        let missing_dir = dir.path().join("gone/tags.json");
+        assert!(atomic_write_json(&missing_dir, &1).is_err());
+        assert!(!missing_dir.exists());
+    }
+
     #[test]
     fn sanitize_branch_name_preserves_slashes() {
         assert_eq!(sanitize_branch_name("feature/branch"), "feature/branch");
@@ -92,7 +681,13 @@ mod tests {
 
     #[test]
     fn branch_dir_name_replaces_spaces_with_single_hyphen() {
-        assert_eq!(branch_dir_name("feature branch"), "feature-branch");
-        assert_eq!(branch_dir_name("feature  branch"), "feature-branch");
+        assert_eq!(
+            branch_dir_name("feature branch", DEFAULT_MAX_SLUG_LENGTH),
+            "feature-branch"
+        );
+        assert_eq!(
+            branch_dir_name("feature  branch", DEFAULT_MAX_SLUG_LENGTH),
+            "feature-branch"
+        );
     }
 }
@@ -1,7 +1,11 @@
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
 use serde_json::Value;
-use std::{fs, path::Path, process::Command};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
 use tempfile::TempDir;
 
 #[test]
@@ -24,10 +28,67 @@ fn init_creates_expected_structure() -> Result<(), Box<dyn std::error::Error>> {
 
     let terminals: Value = read_json(&wtm_dir.join("terminals.json"))?;
     assert!(terminals["workspaces"].as_object().unwrap().is_empty());
+    assert_eq!(fs::read_dir(wtm_dir.join("workspaces"))?.count(), 0);
 
     Ok(())
 }
 
+#[test]
+fn interactive_init_without_a_terminal_writes_the_defaults(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .env("EDITOR", "nano")
+        .env_remove("VISUAL")
+        .args(["init", "--interactive"])
+        .stdin(std::process::Stdio::null());
+    cmd.assert().success();
+
+    let config: Value = read_json(&temp.path().join(".wtm/config.json"))?;
+    assert_eq!(config["quickAccess"][0]["quickCommand"], "nano .");
+    assert!(config.get("session").is_none());
+    Ok(())
+}
+
+#[test]
+fn init_with_worktree_creates_a_first_worktree() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["init", "--with-worktree", "feature/first"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Initialised .wtm workspace scaffold",
+        ))
+        .stdout(predicate::str::contains(
+            "Created worktree for branch feature/first",
+        ));
+    assert!(temp
+        .path()
+        .join(".wtm/workspaces/feature-first/.git")
+        .exists());
+
+    let mut list = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    list.current_dir(temp.path()).args(["worktree", "list"]);
+    list.assert()
+        .success()
+        .stdout(predicate::str::contains("branch: feature/first"))
+        .stderr(predicate::str::contains("not a git worktree").not());
+
+    let elsewhere = TempDir::new()?;
+    let mut outside = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    outside
+        .current_dir(elsewhere.path())
+        .args(["init", "--with-worktree", "x"]);
+    outside.assert().failure().code(3);
+    assert!(!elsewhere.path().join(".wtm").exists());
+    Ok(())
+}
+
 #[test]
 fn init_fails_when_directory_exists() -> Result<(), Box<dyn std::error::Error>> {
     let temp = TempDir::new()?;
@@ -55,6 +116,7 @@ fn running_without_wtm_directory_errors() -> Result<(), Box<dyn std::error::Erro
     cmd.current_dir(temp.path());
     cmd.assert()
         .failure()
+        .code(4)
         .stderr(predicate::str::contains("No .wtm directory found"));
     Ok(())
 }
@@ -69,7 +131,146 @@ fn running_with_empty_workspaces_errors() -> Result<(), Box<dyn std::error::Erro
     cmd.current_dir(temp.path());
     cmd.assert()
         .failure()
-        .stderr(predicate::str::contains("git command failed"));
+        .code(3)
+        .stderr(predicate::str::contains(
+            "is not inside a git repository; run `git init` there",
+        ));
+    Ok(())
+}
+
+#[test]
+fn dashboard_refuses_to_start_without_a_terminal() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm/workspaces"))?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).env_remove("CI");
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "not starting the dashboard: stdin is not a terminal",
+        ))
+        .stderr(predicate::str::contains("wtm worktree list"));
+
+    let mut ci = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    ci.current_dir(temp.path()).env("CI", "true").arg("gui");
+    ci.assert().failure().stderr(predicate::str::contains(
+        "the CI environment variable is set",
+    ));
+    Ok(())
+}
+
+#[test]
+fn dashboard_finds_wtm_from_a_nested_subdirectory() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm/workspaces"))?;
+    let nested = temp.path().join("src/deep");
+    fs::create_dir_all(&nested)?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(&nested).env_remove("CI");
+    cmd.assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "not starting the dashboard: stdin is not a terminal",
+        ));
+    Ok(())
+}
+
+#[test]
+fn commands_in_a_linked_worktree_use_the_main_checkouts_wtm(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let wtm = |cwd: &Path, args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(cwd).args(args);
+        cmd
+    };
+    wtm(temp.path(), &["worktree", "add", "inner"])
+        .assert()
+        .success();
+    let inner = temp.path().join(".wtm/workspaces/inner");
+
+    wtm(&inner, &["worktree", "pin", "inner"])
+        .assert()
+        .success();
+    assert!(temp.path().join(".wtm/pins.json").exists());
+    assert!(!inner.join(".wtm").exists());
+
+    let mut dashboard = wtm(&inner, &[]);
+    dashboard
+        .env_remove("CI")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "not starting the dashboard: stdin is not a terminal",
+        ));
+    Ok(())
+}
+
+#[test]
+fn worktree_remove_unknown_path_exits_with_no_match() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "remove", "missing"]);
+    cmd.assert()
+        .failure()
+        .code(5)
+        .stderr(predicate::str::contains("No worktree found"));
+    Ok(())
+}
+
+#[test]
+fn worktree_path_shared_name_exits_with_ambiguous() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let wtm = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args(args);
+        cmd
+    };
+    wtm(&["worktree", "add", "hot/fix", "--category", "a"])
+        .assert()
+        .success();
+    wtm(&["worktree", "add", "hot-fix", "--category", "b"])
+        .assert()
+        .success();
+
+    wtm(&["worktree", "path", "hot-fix"])
+        .assert()
+        .failure()
+        .code(7)
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("matches several worktrees"));
+    Ok(())
+}
+
+#[test]
+fn worktree_remove_prunes_a_worktree_whose_directory_is_gone(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let wtm = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args(args);
+        cmd
+    };
+    wtm(&["worktree", "add", "gone"]).assert().success();
+    fs::remove_dir_all(temp.path().join(".wtm/workspaces/gone"))?;
+
+    wtm(&["worktree", "remove", "gone"]).assert().success();
+    wtm(&["worktree", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("gone").not());
     Ok(())
 }
 
@@ -86,6 +287,21 @@ fn worktree_list_outputs_primary() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn verbose_flag_logs_git_invocations() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .env_remove("RUST_LOG")
+        .args(["--verbose", "worktree", "list"]);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("running git"));
+    Ok(())
+}
+
 #[test]
 fn worktree_add_and_remove_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
     let temp = TempDir::new()?;
@@ -113,6 +329,373 @@ fn worktree_add_and_remove_roundtrip() -> Result<(), Box<dyn std::error::Error>>
     ]);
     remove.assert().success();
     assert!(!expected_dir.exists());
+
+    let mut undo = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    undo.current_dir(temp.path()).args(["worktree", "undo"]);
+    undo.assert()
+        .success()
+        .stdout(predicate::str::contains("for branch feature/test"));
+    assert!(expected_dir.exists());
+
+    let mut again = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    again.current_dir(temp.path()).args(["worktree", "undo"]);
+    again
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Nothing to undo"));
+    Ok(())
+}
+
+#[test]
+fn worktree_remove_trash_and_restore() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let worktree = temp.path().join(".wtm/workspaces/feature-trash");
+    let wtm = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args(args);
+        cmd
+    };
+
+    wtm(&["worktree", "add", "feature/trash"])
+        .assert()
+        .success();
+    fs::write(worktree.join("notes.txt"), "keep me")?;
+    wtm(&["worktree", "remove", "feature-trash", "--trash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("to trash as"));
+    assert!(!worktree.exists());
+
+    let output = wtm(&["worktree", "trash", "list", "--json"]).output()?;
+    let entries: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(entries[0]["branch"], "feature/trash");
+    let id = entries[0]["id"].as_str().unwrap().to_string();
+    assert!(id.ends_with("-feature-trash"), "{id}");
+
+    wtm(&["worktree", "trash", "restore", &id])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(worktree.join("notes.txt"))?, "keep me");
+    wtm(&["worktree", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("branch: feature/trash"));
+    wtm(&["worktree", "trash", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Trash is empty."));
+    wtm(&["worktree", "trash", "restore", &id]).assert().code(5);
+    Ok(())
+}
+
+#[test]
+fn worktree_remove_refuses_the_directory_it_runs_in() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let worktree = temp.path().join(".wtm/workspaces/feature-here");
+    let wtm = |cwd: &Path, args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(cwd).arg("-C").arg(temp.path()).args(args);
+        cmd
+    };
+
+    wtm(temp.path(), &["worktree", "add", "feature/here"])
+        .assert()
+        .success();
+    wtm(&worktree, &["worktree", "remove", "feature-here"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the directory you are standing in",
+        ));
+    assert!(worktree.exists());
+    wtm(
+        &worktree,
+        &["worktree", "remove", "feature-here", "--trash"],
+    )
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains(
+        "the directory you are standing in",
+    ));
+    assert!(worktree.exists());
+
+    wtm(
+        &worktree,
+        &["worktree", "remove", "feature-here", "--force"],
+    )
+    .assert()
+    .success();
+    assert!(!worktree.exists());
+    Ok(())
+}
+
+#[test]
+fn glob_remove_skips_pinned_worktrees_unless_included() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let workspaces = temp.path().join(".wtm/workspaces");
+    let wtm = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args(args);
+        cmd
+    };
+    for branch in ["feature/a", "feature/b", "other"] {
+        wtm(&["worktree", "add", branch]).assert().success();
+    }
+
+    wtm(&["worktree", "pin", "feature-a"]).assert().success();
+    wtm(&["worktree", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("📌 pinned"));
+    wtm(&["worktree", "remove", "feature-*"])
+        .stdin(std::process::Stdio::null())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("feature-a (pinned, kept)"))
+        .stderr(predicate::str::contains("Remove these 1 worktree(s)?"))
+        .stdout(predicate::str::contains("Left the worktrees in place."));
+    assert!(workspaces.join("feature-b").exists());
+    wtm(&["worktree", "remove", "feature-*", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skipped pinned worktree feature-a",
+        ))
+        .stderr(predicate::str::contains("1 succeeded, 0 failed, 1 skipped"));
+    assert!(workspaces.join("feature-a").exists());
+    assert!(!workspaces.join("feature-b").exists());
+    assert!(workspaces.join("other").exists());
+
+    wtm(&[
+        "worktree",
+        "remove",
+        "feature-*",
+        "--include-pinned",
+        "--yes",
+    ])
+    .assert()
+    .success()
+    .stderr(predicate::str::contains("1 succeeded, 0 failed, 0 skipped"));
+    assert!(!workspaces.join("feature-a").exists());
+    assert!(
+        !temp.path().join(".wtm/pins.json").exists(),
+        "the removed worktree's pin is forgotten"
+    );
+    wtm(&["worktree", "remove", "feature-*"]).assert().code(5);
+    Ok(())
+}
+
+#[test]
+fn reset_refuses_a_diverged_branch_before_discarding_anything(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let wtm = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args(args);
+        cmd
+    };
+    wtm(&["worktree", "add", "scratch"]).assert().success();
+    let scratch = temp.path().join(".wtm/workspaces/scratch");
+    let commit = |dir: &Path, message: &'static str| {
+        run_git_with_env(
+            dir,
+            ["commit", "--allow-empty", "-m", message].as_ref(),
+            [
+                ("GIT_AUTHOR_NAME", "Test"),
+                ("GIT_AUTHOR_EMAIL", "test@example.com"),
+                ("GIT_COMMITTER_NAME", "Test"),
+                ("GIT_COMMITTER_EMAIL", "test@example.com"),
+            ],
+        )
+    };
+    let main = String::from_utf8(
+        std::process::Command::new("git")
+            .current_dir(temp.path())
+            .args(["branch", "--show-current"])
+            .output()?
+            .stdout,
+    )?;
+    run_git(
+        &scratch,
+        ["branch", "--set-upstream-to", main.trim()].as_ref(),
+    )?;
+    commit(&scratch, "local")?;
+    commit(temp.path(), "upstream")?;
+    fs::write(scratch.join("README.md"), "edited")?;
+
+    wtm(&["worktree", "reset", "scratch", "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be fast-forwarded"));
+    assert_eq!(fs::read_to_string(scratch.join("README.md"))?, "edited");
+
+    wtm(&[
+        "worktree",
+        "reset",
+        "scratch",
+        "--to",
+        "@{upstream}",
+        "--yes",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("local"));
+    assert_eq!(fs::read_to_string(scratch.join("README.md"))?, "hello");
+
+    fs::write(scratch.join("README.md"), "edited again")?;
+    wtm(&["--quiet", "worktree", "reset", "scratch", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    assert_eq!(fs::read_to_string(scratch.join("README.md"))?, "hello");
+    Ok(())
+}
+
+#[test]
+fn remove_oldest_does_not_count_pinned_worktrees() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let workspaces = temp.path().join(".wtm/workspaces");
+    let wtm = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args(args);
+        cmd
+    };
+    for branch in ["feature/a", "feature/b"] {
+        wtm(&["worktree", "add", branch]).assert().success();
+    }
+    wtm(&["worktree", "pin", "feature-a"]).assert().success();
+
+    wtm(&["--quiet", "worktree", "remove", "--oldest", "1", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    assert!(workspaces.join("feature-a").exists());
+    assert!(!workspaces.join("feature-b").exists());
+
+    wtm(&[
+        "worktree",
+        "remove",
+        "--oldest",
+        "1",
+        "--yes",
+        "--include-pinned",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("feature-a (pinned)"));
+    assert!(!workspaces.join("feature-a").exists());
+    Ok(())
+}
+
+#[test]
+fn moved_worktrees_stay_pinned() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let workspaces = temp.path().join(".wtm/workspaces");
+    let wtm = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args(args);
+        cmd
+    };
+    wtm(&["worktree", "add", "feature/a"]).assert().success();
+    wtm(&["worktree", "pin", "feature-a"]).assert().success();
+    wtm(&["worktree", "move", "feature-a", "feature-renamed"])
+        .assert()
+        .success();
+
+    wtm(&["worktree", "remove", "feature-*", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skipped pinned worktree feature-renamed",
+        ));
+    assert!(workspaces.join("feature-renamed").exists());
+    Ok(())
+}
+
+#[test]
+fn tags_follow_moves_and_select_worktrees_to_remove() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let workspaces = temp.path().join(".wtm/workspaces");
+    let wtm = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args(args);
+        cmd
+    };
+    for branch in ["spike-a", "spike-b", "keep"] {
+        wtm(&["worktree", "add", branch]).assert().success();
+    }
+    wtm(&["worktree", "tag", "spike-a", "--add", "throwaway"])
+        .assert()
+        .success();
+    wtm(&["worktree", "tag", "spike-b", "--add", "throwaway"])
+        .assert()
+        .success();
+    wtm(&["worktree", "move", "spike-b", "spike-moved"])
+        .assert()
+        .success();
+    wtm(&["worktree", "tag", "spike-moved"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("spike-moved: #throwaway"));
+
+    wtm(&["worktree", "remove", "--tag", "throwaway"])
+        .stdin(std::process::Stdio::null())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Tagged #throwaway:"))
+        .stderr(predicate::str::contains("spike-moved"));
+    assert!(workspaces.join("spike-a").exists());
+    wtm(&[
+        "--quiet",
+        "worktree",
+        "remove",
+        "--tag",
+        "throwaway",
+        "--yes",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::is_empty());
+    assert!(!workspaces.join("spike-a").exists());
+    assert!(!workspaces.join("spike-moved").exists());
+    assert!(workspaces.join("keep").exists());
+    wtm(&["worktree", "remove", "--tag", "throwaway"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no linked worktree is tagged"));
+    Ok(())
+}
+
+#[test]
+fn worktree_duplicate_branches_off_the_source_head() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let mut add = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    add.current_dir(temp.path())
+        .args(["worktree", "add", "feature/dup"]);
+    add.assert().success();
+
+    let mut duplicate = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    duplicate
+        .current_dir(temp.path())
+        .args(["worktree", "duplicate", "feature-dup"]);
+    duplicate
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Created worktree for branch feature/dup-copy-1",
+        ));
+    assert!(temp
+        .path()
+        .join(".wtm/workspaces/feature-dup-copy-1")
+        .exists());
     Ok(())
 }
 
@@ -147,6 +730,39 @@ fn worktree_add_sanitizes_branch_name() -> Result<(), Box<dyn std::error::Error>
     Ok(())
 }
 
+#[test]
+fn bare_repo_git_roots_wtm_beside_its_sibling_worktrees() -> Result<(), Box<dyn std::error::Error>>
+{
+    let temp = TempDir::new()?;
+    let source = temp.path().join("source");
+    fs::create_dir(&source)?;
+    init_git_repo(&source)?;
+    run_git(
+        temp.path(),
+        &["clone", "--quiet", "--bare", "source", "repo.git"],
+    )?;
+    let bare = temp.path().join("repo.git");
+    run_git(&bare, &["worktree", "add", "-b", "feature", "../feature"])?;
+    let wtm = |dir: &Path, args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(dir).args(args);
+        cmd
+    };
+
+    wtm(&temp.path().join("feature"), &["worktree", "add", "other"])
+        .assert()
+        .success();
+    assert!(temp.path().join(".wtm/workspaces/other/.git").exists());
+    assert!(!bare.join(".wtm").exists());
+
+    wtm(&bare, &["worktree", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("other"))
+        .stdout(predicate::str::contains("feature"));
+    Ok(())
+}
+
 fn read_json(path: &Path) -> Result<Value, Box<dyn std::error::Error>> {
     let data = fs::read_to_string(path)?;
     Ok(serde_json::from_str(&data)?)
@@ -209,3 +825,1055 @@ fn run_git_with_env(
         Err(format!("git {:?} failed with status {:?}", args, status).into())
     }
 }
+
+#[test]
+fn version_json_reports_package_version() -> Result<(), Box<dyn std::error::Error>> {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .args(["version", "--verbose", "--json"])
+        .output()?;
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(report["version"], env!("CARGO_PKG_VERSION"));
+    assert!(report["tools"]["git"].is_string());
+    Ok(())
+}
+
+#[test]
+fn repo_flag_runs_against_another_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let repo = TempDir::new()?;
+    init_git_repo(repo.path())?;
+    let elsewhere = TempDir::new()?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(elsewhere.path())
+        .arg("-C")
+        .arg(repo.path())
+        .args(["worktree", "list"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(repo.path().to_string_lossy()));
+    Ok(())
+}
+
+#[test]
+fn completions_install_writes_bash_script() -> Result<(), Box<dyn std::error::Error>> {
+    let home = TempDir::new()?;
+    let expected = home
+        .path()
+        .join(".local/share/bash-completion/completions/wtm");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.env("HOME", home.path())
+        .env_remove("XDG_DATA_HOME")
+        .args(["completions", "install", "bash"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Installed bash completions"));
+
+    let script = fs::read_to_string(&expected)?;
+    assert!(script.contains("wtm"));
+    Ok(())
+}
+
+#[test]
+fn describe_lists_top_level_commands() -> Result<(), Box<dyn std::error::Error>> {
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .arg("__describe")
+        .output()?;
+    assert!(output.status.success());
+    let model: Value = serde_json::from_slice(&output.stdout)?;
+    let names: Vec<&str> = model["subcommands"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|cmd| cmd["name"].as_str())
+        .collect();
+    for expected in ["init", "worktree", "completions", "gui"] {
+        assert!(names.contains(&expected), "missing {expected} in {names:?}");
+    }
+    assert!(!names.contains(&"__describe"));
+    Ok(())
+}
+
+#[test]
+fn worktree_list_respects_ignore_file() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "add", "generated/one"])
+        .assert()
+        .success();
+    fs::write(temp.path().join(".wtm/ignore"), "generated/*\n")?;
+
+    let mut list = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    list.current_dir(temp.path()).args(["worktree", "list"]);
+    list.assert()
+        .success()
+        .stdout(predicate::str::contains("generated/one").not());
+
+    let mut list_all = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    list_all
+        .current_dir(temp.path())
+        .args(["worktree", "list", "--all"]);
+    list_all
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("branch: generated/one"));
+    Ok(())
+}
+
+#[test]
+fn worktree_audit_fix_removes_orphans() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let orphan = temp.path().join(".wtm/workspaces/leftover");
+    fs::create_dir_all(&orphan)?;
+    let placeholder = temp.path().join(".wtm/workspaces/default");
+    fs::create_dir_all(&placeholder)?;
+    fs::write(placeholder.join("notes.txt"), "kept by hand")?;
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "audit", "--json"])
+        .output()?;
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(report["orphan_directories"].as_array().unwrap().len(), 2);
+
+    Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "audit", "--fix", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("leftover"));
+    assert!(!orphan.exists());
+    assert!(placeholder.join("notes.txt").exists());
+    Ok(())
+}
+
+#[test]
+fn quiet_keeps_list_output_but_hides_config_warning() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+    fs::write(temp.path().join(".wtm/config.json"), "{ not json")?;
+
+    let mut noisy = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    noisy.current_dir(temp.path()).args(["worktree", "list"]);
+    noisy
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("failed to load configuration"));
+
+    let mut quiet = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    quiet
+        .current_dir(temp.path())
+        .args(["--quiet", "worktree", "list"]);
+    quiet
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(temp.path().to_string_lossy()))
+        .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn worktree_add_runs_post_create_command() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+    fs::write(
+        temp.path().join(".wtm/config.json"),
+        r#"{"postCreateCommand": "touch from-config"}"#,
+    )?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args([
+        "worktree",
+        "add",
+        "feature/run",
+        "--run",
+        "touch from-flag",
+    ]);
+    cmd.assert().success();
+
+    let worktree = temp
+        .path()
+        .join(".wtm/workspaces")
+        .join(branch_dir_name("feature/run"));
+    assert!(worktree.join("from-flag").exists());
+    assert!(!worktree.join("from-config").exists());
+
+    let mut failing = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    failing
+        .current_dir(temp.path())
+        .args(["worktree", "add", "feature/fail", "--run", "exit 3"]);
+    failing.assert().code(1).stderr(predicate::str::contains(
+        "post-create command `exit 3` failed",
+    ));
+    Ok(())
+}
+
+#[test]
+fn worktree_remove_refuses_dirty_worktree_without_force() -> Result<(), Box<dyn std::error::Error>>
+{
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+
+    let mut add = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    add.current_dir(temp.path())
+        .args(["worktree", "add", "feature/dirty"]);
+    add.assert().success();
+    let worktree = temp
+        .path()
+        .join(".wtm/workspaces")
+        .join(branch_dir_name("feature/dirty"));
+    fs::write(worktree.join("README.md"), "changed")?;
+
+    let mut remove = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    remove
+        .current_dir(temp.path())
+        .args(["worktree", "remove"])
+        .arg(&worktree);
+    remove
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("1 uncommitted change"));
+    assert!(worktree.exists());
+
+    let mut forced = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    forced
+        .current_dir(temp.path())
+        .args(["worktree", "remove", "--force"])
+        .arg(&worktree);
+    forced.assert().success();
+    assert!(!worktree.exists());
+    Ok(())
+}
+
+#[test]
+fn worktree_list_stale_filters_recent_worktrees() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+    let mut add = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    add.current_dir(temp.path())
+        .args(["worktree", "add", "feature/fresh"]);
+    add.assert().success();
+
+    let list_json = |stale: &str| -> Result<Value, Box<dyn std::error::Error>> {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+            .current_dir(temp.path())
+            .args(["worktree", "list", "--json", "--stale", stale])
+            .output()?;
+        assert!(output.status.success());
+        Ok(serde_json::from_slice(&output.stdout)?)
+    };
+    assert_eq!(list_json("1d")?, Value::Array(Vec::new()));
+    let everything = list_json("0h")?;
+    let branches: Vec<&str> = everything
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|wt| wt["branch"].as_str())
+        .collect();
+    assert_eq!(branches, ["feature/fresh"]);
+
+    let mut bad = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    bad.current_dir(temp.path())
+        .args(["worktree", "list", "--stale", "5m"]);
+    bad.assert()
+        .code(2)
+        .stderr(predicate::str::contains("ambiguous"));
+    Ok(())
+}
+
+#[test]
+fn worktree_status_compact_reports_dirty_counts() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+    fs::write(temp.path().join("README.md"), "changed")?;
+    fs::write(temp.path().join("notes.txt"), "new")?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "status", "--compact"]);
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let line = stdout.lines().next().unwrap_or_default();
+    for token in ["↑0↓0", "✱1", "?1", "‼0", "iB"] {
+        assert!(line.contains(token), "missing {token} in {line:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn worktree_status_json_fields_projects_output() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "status", "--json", "--fields=name,ahead"]);
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    let entries: Value = serde_json::from_slice(&output.stdout)?;
+    let entry = entries[0].as_object().expect("worktree object");
+    let mut keys: Vec<_> = entry.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["ahead", "name"]);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "status", "--json", "--fields=name,size"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown field `size`"))
+        .stderr(predicate::str::contains("valid fields: name, path"));
+    Ok(())
+}
+
+#[test]
+fn worktree_status_no_primary_leaves_out_the_main_checkout(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "feature-a"]);
+    cmd.assert().success();
+
+    let names = |args: &[&str]| -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+            .current_dir(temp.path())
+            .args(["worktree", "status", "--json", "--fields=name"])
+            .args(args)
+            .output()?;
+        assert!(output.status.success());
+        let entries: Vec<Value> = serde_json::from_slice(&output.stdout)?;
+        Ok(entries
+            .iter()
+            .map(|entry| entry["name"].as_str().unwrap_or_default().to_string())
+            .collect())
+    };
+    assert_eq!(names(&[])?.len(), 2);
+    assert_eq!(names(&["--no-primary"])?, ["feature-a"]);
+    Ok(())
+}
+
+#[test]
+fn worktree_add_honours_path_template() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+    fs::write(
+        temp.path().join(".wtm/config.json"),
+        r#"{"workspacePathTemplate": "{ticket}/{branch}"}"#,
+    )?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "PROJ-7-fix-login"]);
+    cmd.assert().success();
+
+    let worktree = temp.path().join(".wtm/workspaces/PROJ-7/PROJ-7-fix-login");
+    assert!(worktree.join(".git").exists());
+    Ok(())
+}
+
+#[test]
+fn worktree_add_starts_from_default_upstream_or_head() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    run_git(temp.path(), &["branch", "base"])?;
+    fs::write(temp.path().join("later.txt"), "later")?;
+    run_git(temp.path(), &["add", "."])?;
+    run_git_with_env(
+        temp.path(),
+        &["commit", "-m", "later"],
+        [
+            ("GIT_AUTHOR_NAME", "Test"),
+            ("GIT_AUTHOR_EMAIL", "test@example.com"),
+            ("GIT_COMMITTER_NAME", "Test"),
+            ("GIT_COMMITTER_EMAIL", "test@example.com"),
+        ],
+    )?;
+    let rev = |reference: &str| -> Result<String, Box<dyn std::error::Error>> {
+        let output = std::process::Command::new("git")
+            .current_dir(temp.path())
+            .args(["rev-parse", reference])
+            .output()?;
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    };
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+    let config = temp.path().join(".wtm/config.json");
+
+    fs::write(&config, r#"{"defaultUpstream": "base"}"#)?;
+    Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "add", "from-base"])
+        .assert()
+        .success();
+    assert_eq!(rev("from-base")?, rev("base")?);
+
+    fs::write(&config, r#"{"defaultUpstream": "origin/missing"}"#)?;
+    Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "add", "from-head"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "defaultUpstream `origin/missing` does not resolve",
+        ));
+    assert_eq!(rev("from-head")?, rev("HEAD")?);
+    Ok(())
+}
+
+#[test]
+fn worktree_add_track_follows_default_upstream() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    run_git(temp.path(), &["branch", "base"])?;
+    let upstream = |branch: &str| -> Result<String, Box<dyn std::error::Error>> {
+        let output = std::process::Command::new("git")
+            .current_dir(temp.path())
+            .args([
+                "rev-parse",
+                "--abbrev-ref",
+                &format!("{branch}@{{upstream}}"),
+            ])
+            .output()?;
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    };
+
+    Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "add", "--track", "no-start"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("need a start point"));
+
+    fs::create_dir_all(temp.path().join(".wtm"))?;
+    fs::write(
+        temp.path().join(".wtm/config.json"),
+        r#"{"defaultUpstream": "base"}"#,
+    )?;
+    Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "add", "--track", "tracked"])
+        .assert()
+        .success();
+    assert_eq!(upstream("tracked")?, "base");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "add", "untracked"])
+        .assert()
+        .success();
+    assert_eq!(upstream("untracked")?, "");
+    Ok(())
+}
+
+#[test]
+fn worktree_add_category_nests_and_still_resolves_by_name() -> Result<(), Box<dyn std::error::Error>>
+{
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "feat/nested", "--category", "features"]);
+    cmd.assert().success();
+    let worktree = temp.path().join(".wtm/workspaces/features/feat-nested");
+    assert!(worktree.join(".git").exists());
+
+    for selector in ["feat-nested", "features/feat-nested"] {
+        let output = Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+            .current_dir(temp.path())
+            .args(["worktree", "path", selector])
+            .output()?;
+        assert!(output.status.success(), "{selector}");
+        let printed = PathBuf::from(String::from_utf8(output.stdout)?.trim());
+        assert_eq!(printed.canonicalize()?, worktree.canonicalize()?);
+    }
+
+    // The same directory name under another category makes the bare name
+    // ambiguous; the category-qualified paths still pick one.
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "feat-nested", "--category", "spikes"]);
+    cmd.assert().success();
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "path", "feat-nested"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "`feat-nested` matches several worktrees",
+        ))
+        .stderr(predicate::str::contains("features/feat-nested"))
+        .stderr(predicate::str::contains("spikes/feat-nested"));
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "path", "spikes/feat-nested"])
+        .output()?;
+    assert!(output.status.success());
+    let printed = PathBuf::from(String::from_utf8(output.stdout)?.trim());
+    assert_eq!(
+        printed.canonicalize()?,
+        temp.path()
+            .join(".wtm/workspaces/spikes/feat-nested")
+            .canonicalize()?
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "escape", "--category", "../out"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("single directory name"));
+    Ok(())
+}
+
+#[test]
+fn worktree_add_no_checkout_registers_an_empty_worktree() -> Result<(), Box<dyn std::error::Error>>
+{
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "sparse", "--no-checkout"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Created no-checkout worktree"));
+    let worktree = temp.path().join(".wtm/workspaces/sparse");
+    let entries: Vec<_> = fs::read_dir(&worktree)?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(entries, [".git"]);
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("wtm"))
+        .current_dir(temp.path())
+        .args(["worktree", "path", "sparse"])
+        .output()?;
+    assert!(output.status.success());
+    let printed = PathBuf::from(String::from_utf8(output.stdout)?.trim());
+    assert_eq!(printed.canonicalize()?, worktree.canonicalize()?);
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args(["worktree", "list"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("no-checkout").not());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "list", "--status"]);
+    cmd.assert().success().stdout(predicate::str::is_match(
+        r"sparse +\| branch: sparse +\| HEAD: \w+ \| .*no-checkout\n",
+    )?);
+    Ok(())
+}
+
+#[test]
+fn tags_filter_the_list_and_are_dropped_with_the_worktree() -> Result<(), Box<dyn std::error::Error>>
+{
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let wtm = |args: &[&str]| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args(args);
+        cmd.assert().success()
+    };
+    wtm(&["worktree", "add", "tagged"]);
+    wtm(&["worktree", "add", "plain"]);
+    wtm(&["worktree", "tag", "tagged", "--add", "reviewing"])
+        .stdout(predicate::str::contains("tagged: #reviewing"));
+
+    let listed = wtm(&["worktree", "list", "--tag", "reviewing"])
+        .get_output()
+        .stdout
+        .clone();
+    let listed = String::from_utf8(listed)?;
+    assert_eq!(listed.lines().count(), 1, "{listed}");
+    assert!(listed.contains("tags: #reviewing"), "{listed}");
+
+    wtm(&["worktree", "remove", "tagged"]);
+    let tags = fs::read_to_string(temp.path().join(".wtm/tags.json")).unwrap_or_default();
+    assert!(!tags.contains("tagged"), "{tags}");
+    Ok(())
+}
+
+#[test]
+fn worktree_add_adopt_reuses_only_empty_directories() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let restored = temp.path().join(".wtm/workspaces/restored");
+    fs::create_dir_all(&restored)?;
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "restored", "--adopt"]);
+    cmd.assert().success();
+    assert!(restored.join(".git").exists());
+
+    let cluttered = temp.path().join(".wtm/workspaces/cluttered");
+    fs::create_dir_all(&cluttered)?;
+    fs::write(cluttered.join("old.txt"), "backup")?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "cluttered", "--adopt"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("not empty (contains old.txt)"));
+    assert!(!cluttered.join(".git").exists());
+    Ok(())
+}
+
+#[test]
+fn worktree_move_dry_run_prints_plan_only() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "feature/move"]);
+    cmd.assert().success();
+    let workspaces = temp.path().join(".wtm/workspaces");
+    let source = workspaces.join(branch_dir_name("feature/move"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args([
+        "worktree",
+        "move",
+        &branch_dir_name("feature/move"),
+        "archive/moved",
+        "--dry-run",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "destination: {} (free)",
+            workspaces.join("archive/moved").display()
+        )));
+    assert!(source.exists());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args([
+        "worktree",
+        "move",
+        &branch_dir_name("feature/move"),
+        "archive/moved",
+    ]);
+    cmd.assert().success();
+    assert!(!source.exists());
+    assert!(workspaces.join("archive/moved/.git").exists());
+    Ok(())
+}
+
+#[test]
+fn add_with_open_cd_prints_the_new_path_last() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let workspaces = temp.path().join(".wtm/workspaces");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "feature/open", "--open=cd"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let path = workspaces.join(branch_dir_name("feature/open"));
+    let stdout = String::from_utf8(output)?;
+    assert_eq!(stdout.lines().last(), Some(path.to_str().unwrap()));
+    assert!(path.is_dir());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .env_remove("VISUAL")
+        .env_remove("EDITOR")
+        .args(["worktree", "add", "feature/open-json", "--open", "--json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let result: Value = serde_json::from_slice(&output)?;
+    let path = workspaces.join(branch_dir_name("feature/open-json"));
+    assert_eq!(
+        result,
+        serde_json::json!({ "path": path, "branch": "feature/open-json", "open": "cd" })
+    );
+    Ok(())
+}
+
+#[test]
+fn completions_suggest_can_exclude_the_primary() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "feature/suggest"]);
+    cmd.assert().success();
+    let name = branch_dir_name("feature/suggest");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args([
+        "completions",
+        "suggest",
+        "worktrees",
+        "--exclude-primary",
+        "--description-style",
+        "none",
+    ]);
+    cmd.assert().success().stdout(format!("{name}\n"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args([
+        "completions",
+        "suggest",
+        "worktrees",
+        "--for",
+        "remove",
+        "--description-style",
+        "none",
+    ]);
+    cmd.assert().success().stdout(format!("{name}\n"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["completions", "suggest", "worktrees"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "{name}\tworktree on feature/suggest\n"
+        )))
+        .stdout(predicate::str::contains(format!(
+            "{}\t",
+            temp.path().file_name().unwrap().to_string_lossy()
+        )));
+    Ok(())
+}
+
+#[test]
+fn completions_suggest_null_and_json_replace_the_shell_format(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "feature/null"]);
+    cmd.assert().success();
+    let name = branch_dir_name("feature/null");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args([
+        "completions",
+        "suggest",
+        "worktrees",
+        "--exclude-primary",
+        "--null",
+    ]);
+    cmd.assert().success().stdout(format!("{name}\0"));
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args([
+        "completions",
+        "suggest",
+        "worktrees",
+        "--exclude-primary",
+        "--json",
+    ]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let suggestions: Value = serde_json::from_slice(&output)?;
+    assert_eq!(
+        suggestions,
+        serde_json::json!([{ "value": name, "source": "worktree", "detail": "on feature/null" }])
+    );
+
+    for flags in [["--json", "--shell"], ["--null", "--shell"]] {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path()).args([
+            "completions",
+            "suggest",
+            "branches",
+            flags[0],
+            flags[1],
+            "zsh",
+        ]);
+        cmd.assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+    Ok(())
+}
+
+#[test]
+fn worktree_run_propagates_the_exit_code() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "feature/run"]);
+    cmd.assert().success();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args([
+        "--timings",
+        "worktree",
+        "run",
+        &branch_dir_name("feature/run"),
+        "--",
+        "sh",
+        "-c",
+        "echo \"in $WTM_BRANCH\"; exit 7",
+    ]);
+    cmd.assert()
+        .code(7)
+        .stdout(predicate::str::contains("in feature/run"))
+        .stderr(predicate::str::contains("total"));
+    Ok(())
+}
+
+#[test]
+fn timings_flag_reports_phases_on_stderr_only() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["--timings", "worktree", "status", "--json"]);
+    let output = cmd.assert().success().get_output().clone();
+    let stderr = String::from_utf8(output.stderr)?;
+    for phase in [
+        "repo discovery",
+        "list_worktrees",
+        "status ",
+        "disk walk",
+        "total",
+    ] {
+        assert!(
+            stderr
+                .lines()
+                .any(|line| line.starts_with("timing: ") && line.contains(phase)),
+            "missing {phase}: {stderr}"
+        );
+    }
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert!(json.is_array());
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn worktree_remove_handles_non_utf8_paths() -> Result<(), Box<dyn std::error::Error>> {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let temp = TempDir::new()?;
+    let repo = temp.path().join("repo");
+    fs::create_dir(&repo)?;
+    init_git_repo(&repo)?;
+    let worktree = temp.path().join(OsStr::from_bytes(b"caf\xe9"));
+    let status = Command::new("git")
+        .current_dir(&repo)
+        .args(["worktree", "add", "-b", "cafe"])
+        .arg(&worktree)
+        .status()?;
+    assert!(status.success());
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(&repo)
+        .args(["worktree", "remove"])
+        .arg(&worktree);
+    cmd.assert().success();
+    assert!(!worktree.exists());
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn global_config_is_scaffolded_and_validated_after_editing(
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new()?;
+    let xdg = temp.path().join("xdg");
+    let global_config = xdg.join("wtm/config.json");
+    let wtm = |args: &[&str], editor: &Path| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path())
+            .env("XDG_CONFIG_HOME", &xdg)
+            .env("EDITOR", editor)
+            .env_remove("VISUAL")
+            .args(args);
+        cmd.assert()
+    };
+    let editor_writing = |name: &str, content: &str| -> std::io::Result<PathBuf> {
+        let path = temp.path().join(name);
+        fs::write(
+            &path,
+            format!("#!/bin/sh\nprintf '%s' '{content}' > \"$1\"\n"),
+        )?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        Ok(path)
+    };
+    let valid = editor_writing("valid.sh", r#"{"pathDisplay": "tilde"}"#)?;
+    let invalid = editor_writing("invalid.sh", r#"{"pathDisplay": "sideways"}"#)?;
+
+    wtm(&["init", "--global"], &valid).success();
+    assert!(fs::read_to_string(&global_config)?.contains("quickAccess"));
+    wtm(&["init", "--global"], &valid)
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+
+    fs::remove_file(&global_config)?;
+    wtm(&["config", "edit", "--global"], &valid).success();
+    assert_eq!(
+        fs::read_to_string(&global_config)?,
+        r#"{"pathDisplay": "tilde"}"#
+    );
+
+    wtm(&["config", "edit", "--global"], &invalid)
+        .failure()
+        .stderr(predicate::str::contains("is not valid"));
+    Ok(())
+}
+
+#[test]
+fn envelope_wraps_list_json_with_schema_version() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["--envelope", "worktree", "list", "--json"]);
+    let output = cmd.assert().success().get_output().clone();
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["schemaVersion"], 1);
+    assert_eq!(json["data"].as_array().map(Vec::len), Some(1));
+    Ok(())
+}
+
+/// The schema keywords `wtm json-schema` uses, checked against `value`.
+fn schema_violations(schema: &Value, value: &Value, at: &str) -> Vec<String> {
+    let mut violations = Vec::new();
+    if let Some(kinds) = schema.get("type") {
+        let kinds: Vec<&str> = match kinds {
+            Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+            kind => kind.as_str().into_iter().collect(),
+        };
+        let matches = kinds.iter().any(|kind| match *kind {
+            "array" => value.is_array(),
+            "object" => value.is_object(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_u64() || value.is_i64(),
+            "null" => value.is_null(),
+            other => panic!("unsupported schema type {other}"),
+        });
+        if !matches {
+            violations.push(format!("{at}: {value} is not {kinds:?}"));
+            return violations;
+        }
+    }
+    if let (Some(minimum), Some(number)) = (schema.get("minimum"), value.as_i64()) {
+        if number < minimum.as_i64().unwrap_or(0) {
+            violations.push(format!("{at}: {number} is below {minimum}"));
+        }
+    }
+    if let (Some(items), Some(values)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in values.iter().enumerate() {
+            violations.extend(schema_violations(items, item, &format!("{at}[{index}]")));
+        }
+    }
+    if let Some(object) = value.as_object() {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let key = required.as_str().unwrap_or_default();
+            if !object.contains_key(key) {
+                violations.push(format!("{at}: missing `{key}`"));
+            }
+        }
+        for (key, field) in object {
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(property) => {
+                    violations.extend(schema_violations(property, field, &format!("{at}.{key}")))
+                }
+                None if schema["additionalProperties"] == false => {
+                    violations.push(format!("{at}: unexpected `{key}`"))
+                }
+                None => {}
+            }
+        }
+    }
+    violations
+}
+
+#[test]
+fn json_schema_validates_real_list_output() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "add", "feature/schema"]);
+    cmd.assert().success();
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.args(["json-schema", "list"]);
+    let schema: Value = serde_json::from_slice(&cmd.assert().success().get_output().stdout)?;
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "list", "--json"]);
+    let list: Value = serde_json::from_slice(&cmd.assert().success().get_output().stdout)?;
+    assert_eq!(list.as_array().map(Vec::len), Some(2));
+    assert_eq!(schema_violations(&schema, &list, "$"), Vec::<String>::new());
+
+    let mut bad = list.clone();
+    bad[1]["is_locked"] = Value::from("no");
+    bad[0]["extra"] = Value::from(1);
+    assert_eq!(schema_violations(&schema, &bad, "$").len(), 2);
+    Ok(())
+}
+
+#[test]
+fn worktree_list_pages_with_limit_and_offset() -> Result<(), Box<dyn std::error::Error>> {
+    let temp = TempDir::new()?;
+    init_git_repo(temp.path())?;
+    for branch in ["page/a", "page/b", "page/c"] {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+        cmd.current_dir(temp.path())
+            .args(["worktree", "add", branch]);
+        cmd.assert().success();
+    }
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path())
+        .args(["worktree", "list", "--limit", "2", "--offset", "1"]);
+    let output = cmd.assert().success().get_output().clone();
+    let stdout = String::from_utf8(output.stdout)?;
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3, "{stdout}");
+    assert!(lines[0].contains("branch: page/a"), "{stdout}");
+    assert!(lines[1].contains("branch: page/b"), "{stdout}");
+    assert_eq!(lines[2], "showing 2–3 of 4");
+
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("wtm"));
+    cmd.current_dir(temp.path()).args([
+        "--envelope",
+        "worktree",
+        "list",
+        "--json",
+        "--limit",
+        "2",
+        "--offset",
+        "1",
+    ]);
+    let output = cmd.assert().success().get_output().clone();
+    let json: Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(json["data"].as_array().map(Vec::len), Some(2));
+    assert_eq!(json["pagination"]["total"], 4);
+    Ok(())
+}